@@ -10,7 +10,7 @@ fn main() -> Result<(), io::Error> {
     let mut r = RasterBuilder::<SGray8>::new().with_clear(16, 16);
     for y in 0..16 {
         for x in 0..16 {
-            if x + y & 1 != 0 {
+            if (x + y) & 1 != 0 {
                 r.set_pixel(x, y, v);
             }
         }
@@ -25,7 +25,7 @@ fn write_pgm(raster: &Raster<SGray8>, filename: &str) -> io::Result<()> {
     w.write_all(
         format!("P5\n{} {}\n255\n", raster.width(), raster.height()).as_bytes(),
     )?;
-    w.write_all(&raster.as_u8_slice())?;
+    w.write_all(raster.as_u8_slice())?;
     w.flush()?;
     Ok(())
 }
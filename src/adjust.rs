@@ -0,0 +1,436 @@
+// adjust.rs    Color adjustments.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Mode as _, Straight};
+use crate::gamma::Srgb;
+use crate::hsv::channel_from_f32;
+use crate::{
+    Ch32, Ch8, Channel, ColorModel, PixAlpha, PixModel, Pixel, Raster, Rgb,
+};
+use std::any::TypeId;
+
+/// Luma coefficients used by the color matrices
+const LUMA: [f32; 3] = [0.213, 0.715, 0.072];
+
+/// Get a hue rotation matrix
+fn hue_matrix(degrees: f32) -> [[f32; 3]; 3] {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let [lr, lg, lb] = LUMA;
+    [
+        [
+            lr + cos * (1.0 - lr) - sin * lr,
+            lg - cos * lg - sin * lg,
+            lb - cos * lb + sin * (1.0 - lb),
+        ],
+        [
+            lr - cos * lr + sin * 0.143,
+            lg + cos * (1.0 - lg) + sin * 0.140,
+            lb - cos * lb - sin * 0.283,
+        ],
+        [
+            lr - cos * lr - sin * (1.0 - lr),
+            lg - cos * lg + sin * lg,
+            lb + cos * (1.0 - lb) + sin * lb,
+        ],
+    ]
+}
+
+/// Get a saturation matrix
+fn saturate_matrix(factor: f32) -> [[f32; 3]; 3] {
+    let mut m = [[0.0; 3]; 3];
+    for (i, row) in m.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            let id = if i == j { 1.0 } else { 0.0 };
+            *v = LUMA[j] + (id - LUMA[j]) * factor;
+        }
+    }
+    m
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Adjust brightness and contrast.
+    ///
+    /// * `brightness` Amount to add to each channel, from -1.0 to 1.0.
+    /// * `contrast` Contrast factor; 1.0 is unchanged.
+    ///
+    /// Contrast is scaled around mid-gray, and then brightness is
+    /// added.  Results are clamped to the channel range.  *Alpha* is not
+    /// changed.  Premultiplied and non-RGB formats are handled as with
+    /// [apply_curve](struct.Raster.html#method.apply_curve).
+    ///
+    /// ### Increase contrast
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new()
+    ///     .with_u8_buffer(3, 1, vec![0x40, 0x80, 0xC0]);
+    /// r.adjust(0.0, 2.0);
+    /// assert_eq!(r.as_u8_slice(), &[0x00, 0x80, 0xFF]);
+    /// ```
+    pub fn adjust(&mut self, brightness: f32, contrast: f32)
+    where
+        Ch32: From<P::Chan>,
+    {
+        let mid = f32::from(Ch32::from(channel_from_f32::<P::Chan>(0.5)));
+        self.apply_curve(|c| {
+            let v = f32::from(Ch32::from(c));
+            channel_from_f32((v - mid) * contrast + mid + brightness)
+        });
+    }
+    /// Invert the color channels of each pixel in place.
+    ///
+    /// See [Pixel::invert](trait.Pixel.html#method.invert).
+    ///
+    /// ### Invert a mask
+    /// ```
+    /// # use pix::*;
+    /// let mut m = RasterBuilder::<Mask8>::new().with_clear(4, 4);
+    /// m.invert_in_place();
+    /// assert_eq!(m.pixel(2, 2), Mask8::new(0xFF));
+    /// ```
+    pub fn invert_in_place(&mut self) {
+        self.map_in_place(|p| *p = p.invert());
+    }
+    /// Apply a curve to each channel value.
+    ///
+    /// * `f` Function mapping a channel value to its new value.
+    ///
+    /// *Alpha* is not changed.  For 8-bit formats, the curve is evaluated
+    /// once for each of the 256 possible values, and applied using a
+    /// look-up table.
+    ///
+    /// Premultiplied components are divided by *alpha* before applying the
+    /// curve, and multiplied again after.  Color models other than RGB and
+    /// gray are converted to RGB (with the same gamma) and back, so hue and
+    /// chroma channels are not changed directly.  A `Mask` has no color
+    /// channels, so it is not changed.
+    ///
+    /// ### Apply a gamma curve
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgba8>::new()
+    ///     .with_color(4, 4, SRgba8::with_alpha(0x40, 0x80, 0xC0, 0x80));
+    /// r.apply_curve(|c| c.powf(0.5));
+    /// assert_eq!(r.pixel(0, 0), SRgba8::with_alpha(0x80, 0xB5, 0xDD, 0x80));
+    /// ```
+    pub fn apply_curve<F>(&mut self, f: F)
+    where
+        F: Fn(P::Chan) -> P::Chan,
+    {
+        let fmt = P::PIX_FMT;
+        if fmt.model() == PixModel::Mask {
+            return;
+        }
+        let lut: Option<Vec<P::Chan>> =
+            if TypeId::of::<P::Chan>() == TypeId::of::<Ch8>() {
+                // Channel is Ch8, which is repr(transparent) over u8
+                Some(
+                    (0..=255u8)
+                        .map(|i| f(unsafe { std::mem::transmute_copy(&i) }))
+                        .collect(),
+                )
+            } else {
+                None
+            };
+        let curve = |c: P::Chan| match &lut {
+            Some(lut) => {
+                let i: u8 = unsafe { std::mem::transmute_copy(&c) };
+                lut[usize::from(i)]
+            }
+            None => f(c),
+        };
+        let direct = match fmt.model() {
+            PixModel::Rgb | PixModel::Bgr | PixModel::Gray => {
+                fmt.alpha() != PixAlpha::Premultiplied
+            }
+            _ => false,
+        };
+        if direct {
+            let n = P::CHANNELS;
+            let colors = P::default().components().len().min(n);
+            for px in self.as_chan_slice_mut().chunks_exact_mut(n) {
+                for c in px[..colors].iter_mut() {
+                    *c = curve(*c);
+                }
+            }
+        } else {
+            self.map_in_place(|p| {
+                let mut rgba = p.to_rgba();
+                let alpha = rgba[3];
+                for c in rgba[..3].iter_mut() {
+                    let v = P::Alpha::decode(*c, alpha);
+                    *c = P::Alpha::encode(curve(v), alpha);
+                }
+                *p = P::with_rgba(rgba);
+            });
+        }
+    }
+}
+
+impl<C, A> Raster<Rgb<C, A, Straight, Srgb>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    Ch32: From<C>,
+{
+    /// Rotate the hue of all pixels, in place.
+    ///
+    /// * `degrees` Angle of rotation.
+    ///
+    /// A luminance-preserving color matrix is applied to the gamma-encoded
+    /// components, as with the CSS `hue-rotate` filter.  Gray pixels are not
+    /// changed.  Out-of-gamut results are clamped separately for each
+    /// component, which can shift the hue of saturated colors slightly.
+    ///
+    /// ### Rotate red toward green
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(2, 2, SRgb8::new(0xFF, 0x00, 0x00));
+    /// r.hue_rotate(120.0);
+    /// let p = r.pixel(0, 0);
+    /// assert!(p.green() > p.red() && p.green() > p.blue());
+    /// ```
+    pub fn hue_rotate(&mut self, degrees: f32) {
+        self.apply_matrix(hue_matrix(degrees));
+    }
+    /// Adjust the saturation of all pixels, in place.
+    ///
+    /// * `factor` Saturation factor; 0.0 is gray and 1.0 is unchanged.
+    ///
+    /// A luminance-preserving color matrix is applied to the gamma-encoded
+    /// components, as with the CSS `saturate` filter.  Out-of-gamut results
+    /// (when `factor` is greater than 1.0) are clamped separately for each
+    /// component.
+    ///
+    /// ### Desaturate
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(2, 2, SRgb8::new(0xFF, 0x00, 0x00));
+    /// r.saturate(0.0);
+    /// assert_eq!(r.pixel(0, 0), SRgb8::new(0x36, 0x36, 0x36));
+    /// ```
+    pub fn saturate(&mut self, factor: f32) {
+        if factor != 1.0 {
+            self.apply_matrix(saturate_matrix(factor));
+        }
+    }
+    /// Apply a color matrix to all non-gray pixels
+    fn apply_matrix(&mut self, m: [[f32; 3]; 3]) {
+        self.map_in_place(|p| {
+            let rgba = p.to_rgba();
+            if rgba[0] == rgba[1] && rgba[1] == rgba[2] {
+                return;
+            }
+            let v = [
+                f32::from(Ch32::from(rgba[0])),
+                f32::from(Ch32::from(rgba[1])),
+                f32::from(Ch32::from(rgba[2])),
+            ];
+            let mut out = rgba;
+            for (o, row) in out.iter_mut().zip(m.iter()) {
+                let sum = row.iter().zip(v.iter()).map(|(a, b)| a * b).sum();
+                *o = channel_from_f32(sum);
+            }
+            *p = Rgb::with_rgba(out);
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn adjust_gray8() {
+        let mut r = RasterBuilder::<SGray8>::new()
+            .with_u8_buffer(4, 1, vec![0x00, 0x40, 0x80, 0xFF]);
+        let orig = r.clone();
+        r.adjust(0.0, 1.0);
+        assert_eq!(r, orig);
+        r.adjust(0.25, 1.0);
+        assert_eq!(r.as_u8_slice(), &[0x40, 0x80, 0xC0, 0xFF]);
+        r.adjust(-2.0, 1.0);
+        assert_eq!(r.as_u8_slice(), &[0x00; 4]);
+    }
+
+    #[test]
+    fn adjust_rgb8() {
+        let mut r = RasterBuilder::<SRgb8>::new()
+            .with_color(2, 2, SRgb8::new(0x60, 0x80, 0xA0));
+        r.adjust(0.0, 0.0);
+        assert_eq!(r.pixel(1, 1), SRgb8::new(0x80, 0x80, 0x80));
+    }
+
+    #[test]
+    fn adjust_rgba16() {
+        let clr = SRgba16::with_alpha(0x4000, 0x8000, 0xF000, 0x1234);
+        let mut r = RasterBuilder::<SRgba16>::new().with_color(2, 2, clr);
+        r.adjust(0.0, 1.0);
+        assert_eq!(r.pixel(0, 0), clr);
+        r.adjust(0.0, 2.0);
+        let p = r.pixel(1, 0);
+        assert_eq!(p, SRgba16::with_alpha(0x0000, 0x8000, 0xFFFF, 0x1234));
+    }
+
+    #[test]
+    fn curve() {
+        let mut r = RasterBuilder::<SRgba16>::new()
+            .with_color(2, 2, SRgba16::with_alpha(1, 2, 3, 4));
+        r.apply_curve(|c| c + Ch16::new(10));
+        assert_eq!(r.pixel(1, 1), SRgba16::with_alpha(11, 12, 13, 4));
+        let mut m = RasterBuilder::<Mask8>::new().with_clear(2, 2);
+        m.apply_curve(|_| Ch8::MAX);
+        assert_eq!(m.pixel(0, 0), Mask8::new(0));
+        let mut g = RasterBuilder::<SGray8>::new()
+            .with_fn(256, 1, |x, _y| SGray8::new(x as u8));
+        g.apply_curve(|c| Ch8::MAX - c);
+        assert_eq!(g.pixel(0, 0), SGray8::new(0xFF));
+        assert_eq!(g.pixel(200, 0), SGray8::new(55));
+    }
+
+    #[test]
+    fn adjust_premultiplied() {
+        let mut r = RasterBuilder::<SRgba8p>::new()
+            .with_color(2, 2, SRgba8p::with_alpha(0x40, 0x20, 0x00, 0x80));
+        r.adjust(0.5, 1.0);
+        assert_eq!(r.pixel(1, 1), SRgba8p::with_alpha(0x80, 0x60, 0x40, 0x80));
+        let mut r = RasterBuilder::<Rgba16p>::new()
+            .with_color(1, 1, Rgba16p::with_alpha(0x1000, 0, 0x2000, 0x2000));
+        r.apply_curve(|c| c.powf(2.0));
+        let p = r.pixel(0, 0);
+        assert_eq!(p, Rgba16p::with_alpha(0x0800, 0, 0x2000, 0x2000));
+    }
+
+    #[test]
+    fn adjust_models() {
+        let green = SHsv8::new(0x55, 0xFF, 0xFF);
+        let mut r = RasterBuilder::<SHsv8>::new().with_color(1, 1, green);
+        r.adjust(0.0, 2.0);
+        assert_eq!(r.pixel(0, 0), green);
+        let gray = YCbCr8::new(0x40, 0x80, 0x80);
+        let mut r = RasterBuilder::<YCbCr8>::new().with_color(1, 1, gray);
+        r.apply_curve(|c| Ch8::MAX - c);
+        assert_eq!(r.pixel(0, 0), YCbCr8::new(0xBF, 0x80, 0x80));
+        let mut m = RasterBuilder::<Mask8>::new()
+            .with_color(1, 1, Mask8::new(0x40));
+        m.adjust(0.5, 1.0);
+        assert_eq!(m.pixel(0, 0), Mask8::new(0x40));
+    }
+
+    #[test]
+    fn invert() {
+        let mut r = RasterBuilder::<SRgba16>::new().with_fn(16, 16, |x, y| {
+            let v = (x * 4099 + y * 257) as u16;
+            SRgba16::with_alpha(v, v / 3, 0xFFFF - v, v / 2)
+        });
+        let orig = r.clone();
+        r.invert_in_place();
+        let p = SRgba16::with_alpha(61436, 64169, 4099, 2049);
+        assert_eq!(r.pixel(1, 0), p);
+        r.invert_in_place();
+        assert_eq!(r, orig);
+        let mut r = RasterBuilder::<Rgb32>::new().with_fn(16, 16, |x, y| {
+            let v = (x * 16 + y) as f32 / 256.0;
+            Rgb32::new(v, 1.0 - v, 0.5 + v / 2.0)
+        });
+        let orig = r.clone();
+        r.invert_in_place();
+        assert_eq!(r.pixel(0, 0), Rgb32::new(1.0, 0.0, 0.5));
+        r.invert_in_place();
+        assert_eq!(r, orig);
+        let mut m = RasterBuilder::<Mask32>::new()
+            .with_fn(4, 4, |x, _y| Mask32::new(x as f32 / 4.0));
+        m.invert_in_place();
+        assert_eq!(m.pixel(1, 0), Mask32::new(0.75));
+    }
+
+    #[test]
+    fn invert_float() {
+        let p = Rgb32::new(0.1, 0.3, 0.7);
+        let p2 = p.invert().invert();
+        assert!(p2.approx_eq(&p, f32::EPSILON / 2.0));
+        assert_eq!(p2.blue(), p.blue());
+        let m = Mask32::new(0.1);
+        assert!(m.invert().invert().approx_eq(&m, f32::EPSILON / 2.0));
+        assert_eq!(Mask32::new(0.7).invert().invert(), Mask32::new(0.7));
+        let g = Gray64::new(0.3);
+        assert!(g.invert().invert().approx_eq(&g, f32::EPSILON / 2.0));
+        assert_eq!(Gray64::new(0.9).invert().invert(), Gray64::new(0.9));
+    }
+
+    #[test]
+    fn invert_premultiplied() {
+        let p = SRgba8p::with_alpha(0x20, 0x00, 0x80, 0x80);
+        assert_eq!(p.invert(), SRgba8p::with_alpha(0x60, 0x80, 0x00, 0x80));
+        assert_eq!(p.invert().invert(), p);
+        let g = SGray8::new(0x12);
+        assert_eq!(g.invert(), SGray8::new(0xED));
+    }
+
+    #[test]
+    fn invert_models() {
+        let red = Hsv8::new(0x00, 0xFF, 0xFF);
+        let cyan: Rgb8 = red.invert().convert();
+        assert!(cyan.approx_eq(&Rgb8::new(0x00, 0xFF, 0xFF), 3.0 / 255.0));
+        let red = Hsl32::new(0.0, 1.0, 0.5);
+        let cyan: Rgb32 = red.invert().convert();
+        assert_eq!(cyan, Rgb32::new(0.0, 1.0, 1.0));
+        let red = Cmyk8::new(0x00, 0xFF, 0xFF, 0x00);
+        assert_eq!(red.invert(), Cmyk8::new(0xFF, 0x00, 0x00, 0x00));
+        let white: Rgb8 = Cmyk8::new(0, 0, 0, 0xFF).invert().convert();
+        assert_eq!(white, Rgb8::new(0xFF, 0xFF, 0xFF));
+        let gray = YCbCr8::new(0x40, 0x80, 0x80);
+        assert_eq!(gray.invert(), YCbCr8::new(0xBF, 0x80, 0x80));
+    }
+
+    #[test]
+    fn hue_rotate() {
+        let mut r = RasterBuilder::<SRgba8>::new().with_fn(4, 1, |x, _y| {
+            let v = (x * 0x40) as u8;
+            SRgba8::with_alpha(v, v, v, 0x80)
+        });
+        let orig = r.clone();
+        r.hue_rotate(77.0);
+        assert_eq!(r, orig);
+        let mut r = RasterBuilder::<SRgb8>::new()
+            .with_color(1, 1, SRgb8::new(0x20, 0x80, 0x60));
+        let orig = r.clone();
+        r.hue_rotate(0.0);
+        assert_eq!(r, orig);
+        r.hue_rotate(360.0);
+        assert_eq!(r, orig);
+        r.hue_rotate(180.0);
+        let p = r.pixel(0, 0);
+        assert!(p.red() > p.green() && p.red() > p.blue(), "{:?}", p);
+    }
+
+    #[test]
+    fn saturate() {
+        let mut r = RasterBuilder::<SRgb16>::new().with_fn(8, 8, |x, y| {
+            SRgb16::new((x * 8000) as u16, (y * 8000) as u16, 0x1234)
+        });
+        let orig = r.clone();
+        r.saturate(1.0);
+        assert_eq!(r, orig);
+        let mut r = RasterBuilder::<SRgb32>::new()
+            .with_color(1, 1, SRgb32::new(0.5, 0.25, 0.75));
+        r.saturate(0.0);
+        let p = r.pixel(0, 0);
+        assert_eq!(p.red(), p.green());
+        assert_eq!(p.green(), p.blue());
+    }
+
+    #[test]
+    fn saturate_clamped() {
+        let mut r = RasterBuilder::<SRgba8>::new()
+            .with_color(1, 1, SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x40));
+        r.saturate(2.0);
+        assert_eq!(r.pixel(0, 0), SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x40));
+        let mut r = RasterBuilder::<SRgb8>::new()
+            .with_color(1, 1, SRgb8::new(0xC0, 0x80, 0x80));
+        r.saturate(3.0);
+        assert_eq!(r.pixel(0, 0), SRgb8::new(0xFF, 0x65, 0x65));
+    }
+}
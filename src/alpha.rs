@@ -4,8 +4,8 @@
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
 //! Module for alpha channel items
-use crate::private::Sealed;
-use crate::{Ch16, Ch32, Ch8, Channel};
+use crate::private::{PlainData, Sealed};
+use crate::{Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, PixAlpha};
 use std::any::Any;
 use std::fmt::Debug;
 use std::marker::PhantomData;
@@ -17,11 +17,28 @@ use std::ops::Mul;
 ///
 /// This trait is *sealed*, and cannot be implemented outside of this crate.
 pub trait AChannel:
-    Any + Copy + Debug + Default + Mul<Output = Self> + PartialEq + Sealed
+    Any
+    + Copy
+    + Debug
+    + Default
+    + Mul<Output = Self>
+    + PartialEq
+    + Send
+    + Sync
+    + PlainData
+    + Sealed
 {
     /// `Channel` type
     type Chan: Channel;
 
+    /// Fully opaque value
+    const OPAQUE: Self;
+
+    /// Fully transparent value.
+    ///
+    /// For [Opaque](struct.Opaque.html), this is the same as `OPAQUE`.
+    const TRANSPARENT: Self;
+
     /// Get the alpha `Channel` value.
     ///
     /// [Channel::MIN](../trait.Channel.html#associatedconstant.MIN) is fully
@@ -36,14 +53,16 @@ pub trait AChannel:
 ///
 /// [Pixel](../trait.Pixel.html) formats with `Opaque` alpha channels take less
 /// memory than those with [translucent](struct.Translucent.html) ones.
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
 pub struct Opaque<C> {
     value: PhantomData<C>,
 }
 
 /// [Alpha channel](trait.AChannel.html) for translucent or transparent pixels
 /// and [Raster](../struct.Raster.html)s.
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
 pub struct Translucent<C: Channel> {
     value: C,
 }
@@ -52,8 +71,10 @@ pub struct Translucent<C: Channel> {
 ///
 /// This trait is *sealed*, and cannot be implemented outside of this crate.
 pub trait Mode:
-    Any + Copy + Clone + Debug + Default + PartialEq + Sealed
+    Any + Copy + Clone + Debug + Default + PartialEq + Send + Sync + Sealed
 {
+    /// Alpha mode, for [PixFmt](../struct.PixFmt.html)
+    const PIX_ALPHA: PixAlpha;
     /// Encode one `Channel` using the alpha mode.
     fn encode<C: Channel>(c: C, a: C) -> C;
     /// Decode one `Channel` using the alpha mode.
@@ -61,11 +82,11 @@ pub trait Mode:
 }
 
 /// Each `Channel` is "straight" (not premultiplied with alpha)
-#[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct Straight;
 
 /// Each `Channel` is premultiplied, or associated, with alpha
-#[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
 pub struct Premultiplied;
 
 impl<C, H> From<H> for Opaque<C>
@@ -87,12 +108,24 @@ impl<C: Channel> From<Opaque<C>> for Ch16 {
         Ch16::MAX
     }
 }
+impl<C: Channel> From<Opaque<C>> for Ch16f {
+    fn from(_value: Opaque<C>) -> Self {
+        Ch16f::MAX
+    }
+}
+
 impl<C: Channel> From<Opaque<C>> for Ch32 {
     fn from(_value: Opaque<C>) -> Self {
         Ch32::MAX
     }
 }
 
+impl<C: Channel> From<Opaque<C>> for Ch64 {
+    fn from(_value: Opaque<C>) -> Self {
+        Ch64::MAX
+    }
+}
+
 impl<C, A> From<Translucent<A>> for Opaque<C>
 where
     C: Channel,
@@ -114,6 +147,9 @@ impl<C: Channel> Mul<Self> for Opaque<C> {
 impl<C: Channel> AChannel for Opaque<C> {
     type Chan = C;
 
+    const OPAQUE: Self = Opaque { value: PhantomData };
+    const TRANSPARENT: Self = Opaque { value: PhantomData };
+
     /// Get the alpha `Channel` value.
     ///
     /// Always returns
@@ -144,12 +180,24 @@ impl From<u16> for Translucent<Ch16> {
         Ch16::new(value).into()
     }
 }
+impl From<f32> for Translucent<Ch16f> {
+    fn from(value: f32) -> Self {
+        Ch16f::new(value).into()
+    }
+}
+
 impl From<f32> for Translucent<Ch32> {
     fn from(value: f32) -> Self {
         Ch32::new(value).into()
     }
 }
 
+impl From<f64> for Translucent<Ch64> {
+    fn from(value: f64) -> Self {
+        Ch64::new(value).into()
+    }
+}
+
 impl<C: Channel> Mul<Self> for Translucent<C> {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self {
@@ -179,6 +227,9 @@ where
 impl<C: Channel> AChannel for Translucent<C> {
     type Chan = C;
 
+    const OPAQUE: Self = Translucent { value: C::MAX };
+    const TRANSPARENT: Self = Translucent { value: C::MIN };
+
     /// Get the alpha `Channel` value.
     ///
     /// [Channel::MIN](../trait.Channel.html#associatedconstant.MIN) is fully
@@ -191,6 +242,8 @@ impl<C: Channel> AChannel for Translucent<C> {
 }
 
 impl Mode for Straight {
+    const PIX_ALPHA: PixAlpha = PixAlpha::Straight;
+
     /// Encode one `Channel` using the alpha mode.
     fn encode<C: Channel>(c: C, _a: C) -> C {
         c
@@ -202,12 +255,19 @@ impl Mode for Straight {
 }
 
 impl Mode for Premultiplied {
+    const PIX_ALPHA: PixAlpha = PixAlpha::Premultiplied;
+
     /// Encode one `Channel` using the alpha mode.
+    ///
+    /// Integer channels are rounded to the nearest value.
     fn encode<C: Channel>(c: C, a: C) -> C {
-        c * a
+        c.scale(a)
     }
     /// Decode one `Channel` using the alpha mode.
+    ///
+    /// Integer channels are rounded to the nearest value, and zero alpha
+    /// decodes to zero.
     fn decode<C: Channel>(c: C, a: C) -> C {
-        c / a
+        c.div_scale(a)
     }
 }
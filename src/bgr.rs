@@ -0,0 +1,360 @@
+// bgr.rs       BGR channel order RGB color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{
+    self, AChannel, Opaque, Premultiplied, Straight, Translucent,
+};
+use crate::gamma::{self, Linear};
+use crate::pixel::fmt_pixel;
+use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel, Rgb};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// RGB additive [color model], stored in *blue*, *green*, *red* order.
+///
+/// This matches the memory layout used by GDI, Cairo and many video APIs.
+/// Components are stored as *blue*, *green*, *red*, then optional *[alpha]*.
+/// Converting to or from [Rgb](struct.Rgb.html) with the same channel, alpha
+/// and gamma is a lossless swizzle.
+///
+/// [alpha]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    components: [C; 3],
+    alpha: A,
+    mode: PhantomData<M>,
+    gamma: PhantomData<G>,
+}
+
+impl<C, A, M, G> ColorModel for Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components, in *blue*, *green*, *red* order
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        [self.red(), self.green(), self.blue(), self.alpha()]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        Bgr::with_alpha(rgba[0], rgba[1], rgba[2], rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let diff = |a: C, b: C| a.max(b) - a.min(b);
+        Bgr::with_alpha(
+            diff(self.red(), rhs.red()),
+            diff(self.green(), rhs.green()),
+            diff(self.blue(), rhs.blue()),
+            diff(self.alpha(), rhs.alpha()),
+        )
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.red() <= rhs.red()
+            && self.green() <= rhs.green()
+            && self.blue() <= rhs.blue()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A, M, G> fmt::Debug for Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A, M, G> Pixel for Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Bgr);
+
+    const WHITE: Self = Bgr {
+        components: [C::MAX; 3],
+        alpha: A::OPAQUE,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = Bgr {
+        components: [C::MIN; 3],
+        alpha: A::OPAQUE,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = Bgr {
+        components: [C::MIN; 3],
+        alpha: A::TRANSPARENT,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+
+    type Alpha = M;
+    type Gamma = G;
+}
+
+impl<C, A, M, G> Iterator for Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+impl<C, A, M, G> From<Rgb<C, A, M, G>> for Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    /// Swizzle an `Rgb` into `Bgr` order
+    fn from(c: Rgb<C, A, M, G>) -> Self {
+        Bgr::with_alpha(c.red(), c.green(), c.blue(), c.alpha())
+    }
+}
+
+impl<C, A, M, G> From<Bgr<C, A, M, G>> for Rgb<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    /// Swizzle a `Bgr` into `Rgb` order
+    fn from(c: Bgr<C, A, M, G>) -> Self {
+        Rgb::with_alpha(c.red(), c.green(), c.blue(), c.alpha())
+    }
+}
+
+impl<C, A, M, G> Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying *red*,
+    /// *green* and *blue* values.
+    pub fn new<H>(red: H, green: H, blue: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(red, green, blue, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying *red*, *green*, *blue* and *alpha* values.
+    pub fn with_alpha<H, B>(red: H, green: H, blue: H, alpha: B) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let components = [C::from(blue), C::from(green), C::from(red)];
+        let alpha = A::from(alpha);
+        Bgr {
+            components,
+            alpha,
+            mode: PhantomData,
+            gamma: PhantomData,
+        }
+    }
+    /// Get the red component.
+    pub const fn red(self) -> C {
+        self.components[2]
+    }
+    /// Get the green component.
+    pub const fn green(self) -> C {
+        self.components[1]
+    }
+    /// Get the blue component.
+    pub const fn blue(self) -> C {
+        self.components[0]
+    }
+    /// Set the red component.
+    pub fn set_red<H>(&mut self, red: H)
+    where
+        C: From<H>,
+    {
+        self.components[2] = C::from(red);
+    }
+    /// Set the green component.
+    pub fn set_green<H>(&mut self, green: H)
+    where
+        C: From<H>,
+    {
+        self.components[1] = C::from(green);
+    }
+    /// Set the blue component.
+    pub fn set_blue<H>(&mut self, blue: H)
+    where
+        C: From<H>,
+    {
+        self.components[0] = C::from(blue);
+    }
+    /// Get the *alpha* value.
+    pub fn alpha(self) -> C {
+        self.alpha.value()
+    }
+}
+
+/// [Bgr](struct.Bgr.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Bgr8 = Bgr<Ch8, Opaque<Ch8>, Straight, Linear>;
+/// [Bgr](struct.Bgr.html) 16-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Bgr16 = Bgr<Ch16, Opaque<Ch16>, Straight, Linear>;
+/// [Bgr](struct.Bgr.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Bgr32 = Bgr<Ch32, Opaque<Ch32>, Straight, Linear>;
+
+/// [Bgr](struct.Bgr.html) 8-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Bgra8 = Bgr<Ch8, Translucent<Ch8>, Straight, Linear>;
+/// [Bgr](struct.Bgr.html) 16-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Bgra16 = Bgr<Ch16, Translucent<Ch16>, Straight, Linear>;
+/// [Bgr](struct.Bgr.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Bgra32 = Bgr<Ch32, Translucent<Ch32>, Straight, Linear>;
+
+type SBgr<C, A> = Bgr<C, A, Straight, gamma::Srgb>;
+/// [Bgr](struct.Bgr.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgr8 = SBgr<Ch8, Opaque<Ch8>>;
+/// [Bgr](struct.Bgr.html) 16-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgr16 = SBgr<Ch16, Opaque<Ch16>>;
+/// [Bgr](struct.Bgr.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgr32 = SBgr<Ch32, Opaque<Ch32>>;
+
+/// [Bgr](struct.Bgr.html) 8-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgra8 = SBgr<Ch8, Translucent<Ch8>>;
+/// [Bgr](struct.Bgr.html) 16-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgra16 = SBgr<Ch16, Translucent<Ch16>>;
+/// [Bgr](struct.Bgr.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgra32 = SBgr<Ch32, Translucent<Ch32>>;
+
+type SBgrap<C, A> = Bgr<C, A, Premultiplied, gamma::Srgb>;
+/// [Bgr](struct.Bgr.html) 8-bit
+/// [premultiplied](alpha/struct.Premultiplied.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+///
+/// This is the layout of Cairo's `ARGB32` format on little-endian machines.
+pub type SBgra8p = SBgrap<Ch8, Translucent<Ch8>>;
+/// [Bgr](struct.Bgr.html) 16-bit
+/// [premultiplied](alpha/struct.Premultiplied.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgra16p = SBgrap<Ch16, Translucent<Ch16>>;
+/// [Bgr](struct.Bgr.html) 32-bit
+/// [premultiplied](alpha/struct.Premultiplied.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SBgra32p = SBgrap<Ch32, Translucent<Ch32>>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<SBgr8>(), 3);
+        assert_eq!(std::mem::size_of::<SBgr16>(), 6);
+        assert_eq!(std::mem::size_of::<SBgr32>(), 12);
+        assert_eq!(std::mem::size_of::<SBgra8>(), 4);
+        assert_eq!(std::mem::size_of::<SBgra16>(), 8);
+        assert_eq!(std::mem::size_of::<SBgra32>(), 16);
+    }
+
+    #[test]
+    fn swizzle() {
+        let rgb = SRgba16::with_alpha(0x1234, 0x5678, 0x9ABC, 0xDEF0);
+        let bgr = SBgra16::from(rgb);
+        assert_eq!(bgr.red(), Ch16::new(0x1234));
+        assert_eq!(bgr.blue(), Ch16::new(0x9ABC));
+        assert_eq!(SRgba16::from(bgr), rgb);
+        assert_eq!(bgr.convert::<SRgba16>(), rgb);
+        assert_eq!(rgb.convert::<SBgra16>(), bgr);
+    }
+
+    #[test]
+    fn byte_order() {
+        let mut r = RasterBuilder::<SBgra8>::new().with_clear(2, 1);
+        r.set_pixel(0, 0, SRgba8::with_alpha(0x10, 0x20, 0x30, 0x40));
+        r.set_pixel(1, 0, SBgra8::with_alpha(0x50, 0x60, 0x70, 0x80));
+        let v = [0x30,0x20,0x10,0x40, 0x70,0x60,0x50,0x80];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+
+    #[test]
+    fn with_u8_buffer() {
+        let buf = vec![0x00,0x80,0xFF, 0x40,0x20,0x10];
+        let r = RasterBuilder::<SBgr8>::new().with_u8_buffer(2, 1, buf);
+        assert_eq!(r.pixel(0, 0), SBgr8::new(0xFF, 0x80, 0x00));
+        let r = RasterBuilder::<SRgb8>::new().with_raster(&r);
+        let v = [0xFF,0x80,0x00, 0x10,0x20,0x40];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+
+    #[test]
+    fn accessors() {
+        let mut clr = SBgr8::new(0x10, 0x20, 0x30);
+        assert_eq!(clr.red(), Ch8::new(0x10));
+        assert_eq!(clr.blue(), Ch8::new(0x30));
+        clr.set_red(0xFF);
+        clr.set_blue(0);
+        let chans: Vec<u8> =
+            clr.channels().iter().map(|c| (*c).into()).collect();
+        assert_eq!(chans, [0, 0x20, 0xFF]);
+    }
+}
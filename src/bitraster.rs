@@ -0,0 +1,241 @@
+// bitraster.rs     1-bit packed mask raster.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Channel, ColorModel, Mask, Raster, RasterBuilder};
+
+/// Bit-packed mask image, with 1 bit per pixel.
+///
+/// Each row is packed 8 pixels per byte and padded to a byte boundary, so
+/// the [stride](struct.BitRaster.html#method.stride) is `(width + 7) / 8`
+/// bytes.  Within each byte, the least significant bit is the leftmost
+/// pixel, as in the XBM format.  Padding bits are always zero.
+///
+/// A `BitRaster` can be converted to and from a `Raster` of
+/// [Mask](struct.Mask.html) pixels.  When narrowing, pixels with alpha of
+/// 50% or more are set; when widening, set pixels become fully opaque.
+///
+/// This is a separate type rather than a `Mask1` pixel format for `Raster`,
+/// because a `Raster<P>` stores one `P` per pixel and lends them out by
+/// reference (`as_slice`, `rows_mut`, `pixels_mut`, ...).  A pixel smaller
+/// than a byte has no address, so a packed `Raster<Mask1>` could not support
+/// those methods.
+///
+/// ### Create a `BitRaster` from a `Mask8` raster
+/// ```
+/// # use pix::*;
+/// let mut r = RasterBuilder::<Mask8>::new().with_clear(10, 2);
+/// r.set_region((0, 0, 3, 1), Mask8::new(0xFF));
+/// let b = BitRaster::with_raster(&r);
+/// assert_eq!(b.stride(), 2);
+/// assert_eq!(b.as_u8_slice(), &[0b0000_0111, 0, 0, 0]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitRaster {
+    width: u32,
+    height: u32,
+    bits: Box<[u8]>,
+}
+
+impl From<BitRaster> for Box<[u8]> {
+    /// Get packed bit data as boxed slice.
+    fn from(raster: BitRaster) -> Self {
+        raster.bits
+    }
+}
+
+impl From<BitRaster> for Vec<u8> {
+    /// Get packed bit data as `Vec`.
+    fn from(raster: BitRaster) -> Self {
+        raster.bits.into()
+    }
+}
+
+impl BitRaster {
+    /// Create a new `BitRaster` with all pixels clear.
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = stride(width) * height as usize;
+        let bits = vec![0; len].into_boxed_slice();
+        BitRaster {
+            width,
+            height,
+            bits,
+        }
+    }
+    /// Create a `BitRaster` from a packed `u8` buffer.
+    ///
+    /// * `B` Owned buffer type (`Vec` or boxed slice).
+    /// * `width` Width of `BitRaster`.
+    /// * `height` Height of `BitRaster`.
+    /// * `buffer` Packed rows, padded to byte boundaries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` length is not equal to `stride` * `height`.
+    pub fn with_u8_buffer<B>(width: u32, height: u32, buffer: B) -> Self
+    where
+        B: Into<Box<[u8]>>,
+    {
+        let mut bits = buffer.into();
+        assert_eq!(stride(width) * height as usize, bits.len());
+        let pad = padding_mask(width);
+        if pad != 0 {
+            let stride = stride(width);
+            for row in bits.chunks_exact_mut(stride) {
+                row[stride - 1] &= !pad;
+            }
+        }
+        BitRaster {
+            width,
+            height,
+            bits,
+        }
+    }
+    /// Create a `BitRaster` from a `Raster` of mask pixels.
+    ///
+    /// Pixels with alpha of 50% or more are set.
+    pub fn with_raster<C>(src: &Raster<Mask<C>>) -> Self
+    where
+        C: Channel,
+    {
+        let half = C::from(0.5);
+        let mut r = BitRaster::new(src.width(), src.height());
+        for (y, row) in src.rows().enumerate() {
+            for (x, p) in row.iter().enumerate() {
+                r.set_pixel(x as u32, y as u32, p.alpha() >= half);
+            }
+        }
+        r
+    }
+    /// Get width of `BitRaster`.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of `BitRaster`.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get the number of bytes in each packed row.
+    pub fn stride(&self) -> usize {
+        stride(self.width)
+    }
+    /// Get one pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        let (i, bit) = self.bit_index(x, y);
+        self.bits[i] & bit != 0
+    }
+    /// Set one pixel value.
+    pub fn set_pixel(&mut self, x: u32, y: u32, v: bool) {
+        let (i, bit) = self.bit_index(x, y);
+        if v {
+            self.bits[i] |= bit;
+        } else {
+            self.bits[i] &= !bit;
+        }
+    }
+    /// Clear all pixels.
+    pub fn clear(&mut self) {
+        for b in self.bits.iter_mut() {
+            *b = 0;
+        }
+    }
+    /// Get byte index and bit of one pixel.
+    fn bit_index(&self, x: u32, y: u32) -> (usize, u8) {
+        assert!(x < self.width && y < self.height);
+        let i = y as usize * self.stride() + x as usize / 8;
+        (i, 1 << (x % 8))
+    }
+    /// Get one packed row as a `u8` slice.
+    pub fn as_u8_slice_row(&self, y: u32) -> &[u8] {
+        let stride = self.stride();
+        let start = y as usize * stride;
+        &self.bits[start..start + stride]
+    }
+    /// Get packed rows as a `u8` slice.
+    pub fn as_u8_slice(&self) -> &[u8] {
+        &self.bits
+    }
+}
+
+impl<C: Channel> RasterBuilder<Mask<C>> {
+    /// Build a `Raster` from a [BitRaster](struct.BitRaster.html).
+    ///
+    /// Set pixels are fully opaque; clear pixels are fully transparent.
+    ///
+    /// ### Expand a `BitRaster` to `Mask8`
+    /// ```
+    /// # use pix::*;
+    /// let mut b = BitRaster::new(3, 1);
+    /// b.set_pixel(1, 0, true);
+    /// let r = RasterBuilder::<Mask8>::new().with_bit_raster(&b);
+    /// assert_eq!(r.as_u8_slice(), &[0x00, 0xFF, 0x00]);
+    /// ```
+    pub fn with_bit_raster(self, src: &BitRaster) -> Raster<Mask<C>> {
+        self.with_fn(src.width(), src.height(), |x, y| {
+            if src.pixel(x, y) {
+                Mask::new(C::MAX)
+            } else {
+                Mask::new(C::MIN)
+            }
+        })
+    }
+}
+
+/// Get the number of bytes in a packed row.
+fn stride(width: u32) -> usize {
+    (width as usize).div_ceil(8)
+}
+
+/// Get mask of padding bits in the last byte of each row.
+fn padding_mask(width: u32) -> u8 {
+    match width % 8 {
+        0 => 0,
+        n => !((1 << n) - 1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn pixels() {
+        let mut b = BitRaster::new(9, 2);
+        assert_eq!(b.stride(), 2);
+        assert_eq!(b.as_u8_slice().len(), 4);
+        b.set_pixel(0, 0, true);
+        b.set_pixel(8, 0, true);
+        b.set_pixel(3, 1, true);
+        assert!(b.pixel(0, 0) && b.pixel(8, 0) && b.pixel(3, 1));
+        assert!(!b.pixel(1, 0) && !b.pixel(8, 1));
+        assert_eq!(b.as_u8_slice(), &[0x01, 0x01, 0x08, 0x00]);
+        assert_eq!(b.as_u8_slice_row(1), &[0x08, 0x00]);
+        b.set_pixel(0, 0, false);
+        assert_eq!(b.as_u8_slice(), &[0x00, 0x01, 0x08, 0x00]);
+        b.clear();
+        assert_eq!(b.as_u8_slice(), &[0; 4]);
+    }
+
+    #[test]
+    fn with_u8_buffer() {
+        let b = BitRaster::with_u8_buffer(4, 2, vec![0xFF, 0x0A]);
+        assert_eq!(b.as_u8_slice(), &[0x0F, 0x0A]);
+        assert!(b.pixel(3, 0));
+        assert!(b.pixel(1, 1) && !b.pixel(2, 1));
+    }
+
+    #[test]
+    fn mask_round_trip() {
+        let mut r = RasterBuilder::<Mask8>::new().with_clear(4, 1);
+        r.set_pixel(1, 0, Mask8::new(0x7F));
+        r.set_pixel(2, 0, Mask8::new(0x80));
+        r.set_pixel(3, 0, Mask8::new(0xFF));
+        let b = BitRaster::with_raster(&r);
+        assert_eq!(b.as_u8_slice(), &[0b1100]);
+        let r = RasterBuilder::<Mask8>::new().with_bit_raster(&b);
+        assert_eq!(r.as_u8_slice(), &[0x00, 0x00, 0xFF, 0xFF]);
+        let r = RasterBuilder::<Mask32>::new().with_bit_raster(&b);
+        assert_eq!(r.pixel(3, 0), Mask32::new(1.0));
+        assert_eq!(BitRaster::with_raster(&r), b);
+    }
+}
@@ -0,0 +1,209 @@
+// bmp.rs       Uncompressed BMP images.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Pixel, Raster, SRgb8, SRgba8};
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+/// Size of file header
+const FILE_HEADER_LEN: u32 = 14;
+
+/// Size of `BITMAPINFOHEADER`
+const INFO_HEADER_LEN: u32 = 40;
+
+/// Size of `BITMAPV4HEADER`
+const V4_HEADER_LEN: u32 = 108;
+
+/// Uncompressed RGB compression type
+const BI_RGB: u32 = 0;
+
+/// Uncompressed with channel masks compression type
+const BI_BITFIELDS: u32 = 3;
+
+/// sRGB color space type (`sRGB`)
+const LCS_SRGB: u32 = 0x7352_4742;
+
+/// Resolution, in pixels per meter (72 DPI)
+const PIXELS_PER_METER: u32 = 2835;
+
+/// Create an error for an image too large for BMP
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "image too large for BMP")
+}
+
+/// BMP header writer
+struct Header {
+    /// Width in pixels
+    width: u32,
+    /// Height in pixels
+    height: u32,
+    /// Bits per pixel
+    bpp: u16,
+}
+
+impl Header {
+    /// Get the length of one row, including padding to 4 bytes
+    fn row_len(&self) -> u64 {
+        (u64::from(self.width) * u64::from(self.bpp / 8) + 3) & !3
+    }
+    /// Write file header and info header
+    fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        let v4 = self.bpp == 32;
+        let info_len = if v4 { V4_HEADER_LEN } else { INFO_HEADER_LEN };
+        let offset = FILE_HEADER_LEN + info_len;
+        let width = i32::try_from(self.width).map_err(|_| too_large())?;
+        let height = i32::try_from(self.height).map_err(|_| too_large())?;
+        let image_len = self.row_len() * u64::from(self.height);
+        let image_len = u32::try_from(image_len).map_err(|_| too_large())?;
+        let file_len = image_len.checked_add(offset).ok_or_else(too_large)?;
+        let mut buf = Vec::with_capacity(offset as usize);
+        buf.extend_from_slice(b"BM");
+        buf.extend_from_slice(&file_len.to_le_bytes());
+        buf.extend_from_slice(&0u32.to_le_bytes());
+        buf.extend_from_slice(&offset.to_le_bytes());
+        buf.extend_from_slice(&info_len.to_le_bytes());
+        buf.extend_from_slice(&width.to_le_bytes());
+        // Positive height means rows are bottom-up
+        buf.extend_from_slice(&height.to_le_bytes());
+        buf.extend_from_slice(&1u16.to_le_bytes());
+        buf.extend_from_slice(&self.bpp.to_le_bytes());
+        let compression = if v4 { BI_BITFIELDS } else { BI_RGB };
+        buf.extend_from_slice(&compression.to_le_bytes());
+        buf.extend_from_slice(&image_len.to_le_bytes());
+        buf.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        buf.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        // Palette colors used / important
+        buf.extend_from_slice(&[0; 8]);
+        if v4 {
+            // Red, green, blue and alpha masks
+            let masks = [0x00FF_0000u32, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000];
+            for mask in masks.iter() {
+                buf.extend_from_slice(&mask.to_le_bytes());
+            }
+            buf.extend_from_slice(&LCS_SRGB.to_le_bytes());
+            // Endpoints and gamma are unused for sRGB
+            buf.extend_from_slice(&[0; 48]);
+        }
+        w.write_all(&buf)
+    }
+}
+
+impl Raster<SRgb8> {
+    /// Write an uncompressed 24-bit BMP image.
+    ///
+    /// * `w` Writer for image data.
+    ///
+    /// ### Dump a raster for debugging
+    /// ```no_run
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_color_bars(64, 48);
+    /// let file = std::fs::File::create("debug.bmp").unwrap();
+    /// r.write_bmp(std::io::BufWriter::new(file)).unwrap();
+    /// ```
+    pub fn write_bmp(&self, mut w: impl Write) -> io::Result<()> {
+        let header = Header {
+            width: self.width(),
+            height: self.height(),
+            bpp: 24,
+        };
+        header.write(&mut w)?;
+        let mut buf = Vec::with_capacity(header.row_len() as usize);
+        for row in self.rows().rev() {
+            buf.clear();
+            for c in row.iter().map(Pixel::channels) {
+                buf.extend(c.iter().rev().map(|v| u8::from(*v)));
+            }
+            buf.resize(header.row_len() as usize, 0);
+            w.write_all(&buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl Raster<SRgba8> {
+    /// Write an uncompressed 32-bit BMP image, with *alpha*.
+    ///
+    /// * `w` Writer for image data.
+    ///
+    /// The image has a `BITMAPV4HEADER` with `BI_BITFIELDS` channel masks.
+    pub fn write_bmp(&self, mut w: impl Write) -> io::Result<()> {
+        let header = Header {
+            width: self.width(),
+            height: self.height(),
+            bpp: 32,
+        };
+        header.write(&mut w)?;
+        let mut buf = Vec::with_capacity(header.row_len() as usize);
+        for row in self.rows().rev() {
+            buf.clear();
+            for c in row.iter().map(Pixel::channels) {
+                let bgr = c[..3].iter().rev();
+                buf.extend(bgr.chain(&c[3..]).map(|v| u8::from(*v)));
+            }
+            w.write_all(&buf)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    const BMP_3X2: &[u8] = &[
+        // File header
+        b'B', b'M', 78, 0, 0, 0, 0, 0, 0, 0, 54, 0, 0, 0,
+        // Info header
+        40, 0, 0, 0, 3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 24, 0,
+        0, 0, 0, 0, 24, 0, 0, 0, 0x13, 0x0B, 0, 0, 0x13, 0x0B, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0,
+        // Bottom row
+        12, 11, 10, 15, 14, 13, 18, 17, 16, 0, 0, 0,
+        // Top row
+        3, 2, 1, 6, 5, 4, 9, 8, 7, 0, 0, 0,
+    ];
+
+    #[test]
+    fn golden_24() {
+        let r = RasterBuilder::<SRgb8>::new().with_fn(3, 2, |x, y| {
+            let v = (y * 9 + x * 3) as u8;
+            SRgb8::new(v + 1, v + 2, v + 3)
+        });
+        let mut bmp = vec![];
+        r.write_bmp(&mut bmp).unwrap();
+        assert_eq!(bmp, BMP_3X2);
+    }
+
+    #[test]
+    fn alpha_32() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(3, 2, |x, y| {
+            let v = (y * 12 + x * 4) as u8;
+            SRgba8::with_alpha(v + 1, v + 2, v + 3, v + 4)
+        });
+        let mut bmp = vec![];
+        r.write_bmp(&mut bmp).unwrap();
+        assert_eq!(bmp.len(), 122 + 24);
+        assert_eq!(&bmp[2..6], &[146, 0, 0, 0]);
+        assert_eq!(&bmp[10..18], &[122, 0, 0, 0, 108, 0, 0, 0]);
+        // 32 bits per pixel, BI_BITFIELDS
+        assert_eq!(&bmp[28..34], &[32, 0, 3, 0, 0, 0]);
+        // Red, green, blue and alpha masks, then sRGB
+        assert_eq!(
+            &bmp[54..74],
+            &[
+                0, 0, 0xFF, 0, 0, 0xFF, 0, 0, 0xFF, 0, 0, 0, 0, 0, 0, 0xFF,
+                b'B', b'G', b'R', b's',
+            ][..]
+        );
+        // Bottom row first, BGRA
+        assert_eq!(&bmp[122..126], &[15, 14, 13, 16]);
+        assert_eq!(&bmp[134..138], &[3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn too_large() {
+        let r = RasterBuilder::<SRgb8>::new().with_clear(0x8000_0000, 0);
+        assert!(r.write_bmp(std::io::sink()).is_err());
+    }
+}
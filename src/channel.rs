@@ -4,6 +4,8 @@
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
 use crate::gamma::SrgbValue;
+use crate::pixfmt::PixDepth;
+use crate::private::PlainData;
 use std::any::Any;
 use std::cmp::Ordering;
 use std::fmt::Debug;
@@ -11,13 +13,15 @@ use std::ops::{Add, Div, Mul, Sub};
 
 /// A [color model] *component* which determines *bit depth*.
 ///
-/// Existing `Channel`s are [Ch8], [Ch16] and [Ch32].
+/// Existing `Channel`s are [Ch8], [Ch16], [Ch16f], [Ch32] and [Ch64].
 ///
 /// This trait is *sealed*, and cannot be implemented outside of this crate.
 ///
 /// [Ch8]: struct.Ch8.html
 /// [Ch16]: struct.Ch16.html
+/// [Ch16f]: struct.Ch16f.html
 /// [Ch32]: struct.Ch32.html
+/// [Ch64]: struct.Ch64.html
 /// [color model]: trait.ColorModel.html
 pub trait Channel:
     Any
@@ -30,6 +34,9 @@ pub trait Channel:
     + Div<Output = Self>
     + Mul<Output = Self>
     + Sub<Output = Self>
+    + Send
+    + Sync
+    + PlainData
     + SrgbValue
 {
     /// Minimum intensity (*zero*)
@@ -38,8 +45,82 @@ pub trait Channel:
     /// Maximum intensity (*one*)
     const MAX: Self;
 
+    /// Middle intensity (*one half*)
+    const MID: Self;
+
+    /// Bit depth, for [PixFmt](struct.PixFmt.html)
+    const DEPTH: PixDepth;
+
     /// Raise to given power
     fn powf(self, g: f32) -> Self;
+
+    /// Linearly interpolate between `self` (`t` = `MIN`) and `rhs` (`t` =
+    /// `MAX`).
+    ///
+    /// Integer channels are rounded to the nearest value, without floating
+    /// point math.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let c = Ch8::new(0x10).lerp(Ch8::new(0x20), Ch8::new(0x40));
+    /// assert_eq!(c, Ch8::new(0x14));
+    /// ```
+    fn lerp(self, rhs: Self, t: Self) -> Self;
+
+    /// Check if two values are approximately equal.
+    ///
+    /// * `epsilon` Maximum difference, treating values as though they range
+    ///   between 0 and 1.
+    ///
+    /// For integer channels, `epsilon` is rounded to the nearest step, so
+    /// an `epsilon` of 0 requires an exact match.  `NaN` values are never
+    /// equal.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// assert!(Ch8::new(100).approx_eq(Ch8::new(102), 2.0 / 255.0));
+    /// assert!(!Ch8::new(100).approx_eq(Ch8::new(103), 2.0 / 255.0));
+    /// assert!(Ch32::new(0.3).approx_eq(Ch32::new(0.1 + 0.2), 1e-6));
+    /// ```
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool;
+
+    /// Add, clamping to `MAX`.
+    ///
+    /// Same as the `+` operator.
+    fn saturating_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+
+    /// Subtract, clamping to `MIN`.
+    ///
+    /// Same as the `-` operator.
+    fn saturating_sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+
+    /// Multiply, treating values as though they range between 0 and 1.
+    ///
+    /// Integer channels are rounded to the nearest value, so scaling by
+    /// `MAX` returns `self` unchanged.  For floating-point channels, this is
+    /// the same as the `*` operator.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// assert_eq!(Ch8::new(0x80).scale(Ch8::MAX), Ch8::new(0x80));
+    /// assert_eq!(Ch8::new(0x80).scale(Ch8::new(0x80)), Ch8::new(0x40));
+    /// ```
+    fn scale(self, rhs: Self) -> Self {
+        self * rhs
+    }
+
+    /// Divide, treating values as though they range between 0 and 1.
+    ///
+    /// Integer channels are rounded to the nearest value.  The result is
+    /// clamped to `MAX`, and dividing by `MIN` returns `MIN`.  For
+    /// floating-point channels, this is the same as the `/` operator.
+    fn div_scale(self, rhs: Self) -> Self {
+        self / rhs
+    }
 }
 
 /// 8-bit color [Channel](trait.Channel.html).
@@ -58,7 +139,8 @@ pub trait Channel:
 /// let c: Ch32 = c.into();
 /// assert_eq!(c, Ch32::MAX);
 /// ```
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
 pub struct Ch8(u8);
 
 /// 16-bit color [Channel](trait.Channel.html).
@@ -77,7 +159,8 @@ pub struct Ch8(u8);
 /// let c: Ch32 = c.into();
 /// assert_eq!(c, Ch32::MAX);
 /// ```
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
 pub struct Ch16(u16);
 
 /// 32-bit color [Channel](trait.Channel.html).
@@ -97,8 +180,47 @@ pub struct Ch16(u16);
 /// assert_eq!(c, Ch16::MAX);
 /// ```
 #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[repr(transparent)]
 pub struct Ch32(f32);
 
+/// 64-bit color [Channel](trait.Channel.html).
+///
+/// The `Channel` is represented by an `f64`, but values are guaranteed to be
+/// between 0 and 1, inclusive.
+///
+/// ```
+/// # use pix::*;
+/// let c: Ch64 = 0.1_f64.into();
+/// assert_eq!(f64::from(c), 0.1);
+/// let c: Ch32 = c.into();
+/// assert_eq!(f32::from(c), 0.1);
+/// let c: Ch64 = Ch8::MAX.into();
+/// assert_eq!(c, Ch64::MAX);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Ch64(f64);
+
+/// 16-bit floating-point (half precision) color [Channel](trait.Channel.html).
+///
+/// The `Channel` is represented by the bits of an IEEE 754 `binary16` value,
+/// as used by RGBA16F textures.  Values are guaranteed to be between 0 and 1,
+/// inclusive: conversions round to nearest, then clamp.  `NaN` and negative
+/// values become 0, while positive infinity becomes 1.
+///
+/// ```
+/// # use pix::*;
+/// let c: Ch16f = 0.5.into();
+/// assert_eq!(c.to_bits(), 0x3800);
+/// assert_eq!(Ch16f::from_bits(0x7C00), Ch16f::MAX);
+/// assert_eq!(Ch16f::from_bits(0x7E00), Ch16f::MIN);
+/// let c: Ch8 = Ch16f::MAX.into();
+/// assert_eq!(c, Ch8::MAX);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Ch16f(u16);
+
 impl Ch8 {
     /// Create a new 8-bit `Channel` value.
     pub fn new(value: u8) -> Self {
@@ -113,11 +235,48 @@ impl Channel for Ch8 {
     /// Maximum intensity (*one*)
     const MAX: Ch8 = Ch8(0xFF);
 
+    /// Middle intensity (*one half*)
+    const MID: Ch8 = Ch8(0x80);
+
+    /// Bit depth
+    const DEPTH: PixDepth = PixDepth::U8;
+
     /// Raise to given power
     fn powf(self, g: f32) -> Self {
         let v = f32::from(Ch32::from(self)).powf(g);
         Ch32::new(v).into()
     }
+
+    /// Linearly interpolate by a channel value
+    fn lerp(self, rhs: Self, t: Self) -> Self {
+        let t = u32::from(t.0);
+        let v = u32::from(self.0) * (255 - t) + u32::from(rhs.0) * t;
+        Ch8(((v + 127) / 255) as u8)
+    }
+
+    /// Multiply, rounded to nearest
+    fn scale(self, rhs: Self) -> Self {
+        // Rounded (self * rhs / 255), without a division
+        let v = u32::from(self.0) * u32::from(rhs.0) + 0x80;
+        Ch8((((v >> 8) + v) >> 8) as u8)
+    }
+
+    /// Divide, rounded to nearest
+    fn div_scale(self, rhs: Self) -> Self {
+        if rhs.0 > 0 {
+            let ss = u32::from(self.0) * 255;
+            let rr = u32::from(rhs.0);
+            Ch8(((ss + (rr >> 1)) / rr).min(255) as u8)
+        } else {
+            Ch8(0)
+        }
+    }
+
+    /// Check if two values are approximately equal
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool {
+        let eps = Ch8::from(epsilon);
+        self.0.max(rhs.0) - self.0.min(rhs.0) <= eps.0
+    }
 }
 
 impl From<u8> for Ch8 {
@@ -209,11 +368,48 @@ impl Channel for Ch16 {
     /// Maximum intensity (*one*)
     const MAX: Ch16 = Ch16(0xFFFF);
 
+    /// Middle intensity (*one half*)
+    const MID: Ch16 = Ch16(0x8000);
+
+    /// Bit depth
+    const DEPTH: PixDepth = PixDepth::U16;
+
     /// Raise to given power
     fn powf(self, g: f32) -> Self {
         let v = f32::from(Ch32::from(self)).powf(g);
         Ch32::new(v).into()
     }
+
+    /// Linearly interpolate by a channel value
+    fn lerp(self, rhs: Self, t: Self) -> Self {
+        let t = u64::from(t.0);
+        let v = u64::from(self.0) * (65535 - t) + u64::from(rhs.0) * t;
+        Ch16(((v + 32767) / 65535) as u16)
+    }
+
+    /// Multiply, rounded to nearest
+    fn scale(self, rhs: Self) -> Self {
+        // Rounded (self * rhs / 65535), without a division
+        let v = u64::from(self.0) * u64::from(rhs.0) + 0x8000;
+        Ch16((((v >> 16) + v) >> 16) as u16)
+    }
+
+    /// Divide, rounded to nearest
+    fn div_scale(self, rhs: Self) -> Self {
+        if rhs.0 > 0 {
+            let ss = u64::from(self.0) * 65535;
+            let rr = u64::from(rhs.0);
+            Ch16(((ss + (rr >> 1)) / rr).min(65535) as u16)
+        } else {
+            Ch16(0)
+        }
+    }
+
+    /// Check if two values are approximately equal
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool {
+        let eps = Ch16::from(epsilon);
+        self.0.max(rhs.0) - self.0.min(rhs.0) <= eps.0
+    }
 }
 
 impl From<Ch8> for Ch16 {
@@ -330,10 +526,26 @@ impl Channel for Ch32 {
     /// Maximum intensity (*one*)
     const MAX: Ch32 = Ch32(1.0);
 
+    /// Middle intensity (*one half*)
+    const MID: Ch32 = Ch32(0.5);
+
+    /// Bit depth
+    const DEPTH: PixDepth = PixDepth::F32;
+
     /// Raise to given power
     fn powf(self, g: f32) -> Self {
         Ch32::new(self.0.powf(g))
     }
+
+    /// Linearly interpolate by a channel value
+    fn lerp(self, rhs: Self, t: Self) -> Self {
+        Ch32::new(self.0 + (rhs.0 - self.0) * t.0)
+    }
+
+    /// Check if two values are approximately equal
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool {
+        (self.0 - rhs.0).abs() <= epsilon
+    }
 }
 
 impl From<Ch8> for Ch32 {
@@ -357,7 +569,7 @@ impl From<Ch32> for f32 {
 impl From<Ch32> for Ch8 {
     fn from(c: Ch32) -> Self {
         let value = c.0;
-        debug_assert!(value >= 0.0 && value <= 1.0);
+        debug_assert!((0.0..=1.0).contains(&value));
         // this cast is not UB since the value is guaranteed
         // to be between 0.0 and 1.0 (see bug #10184)
         Ch8::new((value * 255.0).round() as u8)
@@ -367,7 +579,7 @@ impl From<Ch32> for Ch8 {
 impl From<Ch32> for Ch16 {
     fn from(c: Ch32) -> Self {
         let value = c.0;
-        debug_assert!(value >= 0.0 && value <= 1.0);
+        debug_assert!((0.0..=1.0).contains(&value));
         // this cast is not UB since the value is guaranteed
         // to be between 0.0 and 1.0 (see bug #10184)
         Ch16::new((value * 65535.0).round() as u16)
@@ -382,9 +594,12 @@ impl From<Ch16> for Ch32 {
 
 impl Eq for Ch32 {}
 
+// PartialOrd is derived so that comparisons with NaN return false, while
+// Ord uses the IEEE total order so that sorting NaN values does not panic
+#[allow(clippy::derive_ord_xor_partial_ord)]
 impl Ord for Ch32 {
     fn cmp(&self, other: &Ch32) -> Ordering {
-        self.partial_cmp(other).unwrap()
+        self.0.total_cmp(&other.0)
     }
 }
 
@@ -435,6 +650,388 @@ where
     }
 }
 
+impl Ch64 {
+    /// Create a new 64-bit `Channel` value.
+    ///
+    /// Returns [MIN](trait.Channel.html#associatedconstant.MIN) if value is
+    ///         less than 0.0, or `NaN`.
+    /// Returns [MAX](trait.Channel.html#associatedconstant.MAX) if value is
+    ///         greater than 1.0.
+    pub fn new(value: f64) -> Self {
+        let v = if value.is_nan() || value < 0.0 {
+            0.0
+        } else if value > 1.0 {
+            1.0
+        } else {
+            value
+        };
+        Ch64(v)
+    }
+}
+
+impl Channel for Ch64 {
+    /// Minimum intensity (*zero*)
+    const MIN: Ch64 = Ch64(0.0);
+
+    /// Maximum intensity (*one*)
+    const MAX: Ch64 = Ch64(1.0);
+
+    /// Middle intensity (*one half*)
+    const MID: Ch64 = Ch64(0.5);
+
+    /// Bit depth
+    const DEPTH: PixDepth = PixDepth::F64;
+
+    /// Raise to given power
+    fn powf(self, g: f32) -> Self {
+        Ch64::new(self.0.powf(f64::from(g)))
+    }
+
+    /// Linearly interpolate by a channel value
+    fn lerp(self, rhs: Self, t: Self) -> Self {
+        Ch64::new(self.0 + (rhs.0 - self.0) * t.0)
+    }
+
+    /// Check if two values are approximately equal
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool {
+        (self.0 - rhs.0).abs() <= f64::from(epsilon)
+    }
+}
+
+impl From<Ch8> for Ch64 {
+    fn from(c: Ch8) -> Self {
+        Ch64(f64::from(c.0) / 255.0)
+    }
+}
+
+impl From<Ch16> for Ch64 {
+    fn from(c: Ch16) -> Self {
+        Ch64(f64::from(c.0) / 65535.0)
+    }
+}
+
+impl From<Ch32> for Ch64 {
+    fn from(c: Ch32) -> Self {
+        Ch64(f64::from(c.0))
+    }
+}
+
+impl From<f32> for Ch64 {
+    fn from(value: f32) -> Self {
+        Ch64::new(f64::from(value))
+    }
+}
+
+impl From<f64> for Ch64 {
+    fn from(value: f64) -> Self {
+        Ch64::new(value)
+    }
+}
+
+impl From<Ch64> for f64 {
+    fn from(c: Ch64) -> f64 {
+        c.0
+    }
+}
+
+impl From<Ch64> for Ch8 {
+    fn from(c: Ch64) -> Self {
+        let value = c.0;
+        debug_assert!((0.0..=1.0).contains(&value));
+        // this cast is not UB since the value is guaranteed
+        // to be between 0.0 and 1.0 (see bug #10184)
+        Ch8::new((value * 255.0).round() as u8)
+    }
+}
+
+impl From<Ch64> for Ch16 {
+    fn from(c: Ch64) -> Self {
+        let value = c.0;
+        debug_assert!((0.0..=1.0).contains(&value));
+        // this cast is not UB since the value is guaranteed
+        // to be between 0.0 and 1.0 (see bug #10184)
+        Ch16::new((value * 65535.0).round() as u16)
+    }
+}
+
+impl From<Ch64> for Ch32 {
+    fn from(c: Ch64) -> Self {
+        Ch32::new(c.0 as f32)
+    }
+}
+
+impl Eq for Ch64 {}
+
+// PartialOrd is derived so that comparisons with NaN return false, while
+// Ord uses the IEEE total order so that sorting NaN values does not panic
+#[allow(clippy::derive_ord_xor_partial_ord)]
+impl Ord for Ch64 {
+    fn cmp(&self, other: &Ch64) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<R> Add<R> for Ch64
+where
+    f64: From<R>,
+{
+    type Output = Self;
+    fn add(self, rhs: R) -> Self {
+        let value = self.0 + f64::from(rhs);
+        Ch64(value.min(1.0))
+    }
+}
+
+impl<R> Sub<R> for Ch64
+where
+    f64: From<R>,
+{
+    type Output = Self;
+    fn sub(self, rhs: R) -> Self {
+        let value = self.0 - f64::from(rhs);
+        Ch64(value.max(0.0))
+    }
+}
+
+impl<R> Mul<R> for Ch64
+where
+    f64: From<R>,
+{
+    type Output = Self;
+    fn mul(self, rhs: R) -> Self {
+        Ch64(self.0 * f64::from(rhs))
+    }
+}
+
+impl<R> Div<R> for Ch64
+where
+    f64: From<R>,
+{
+    type Output = Self;
+    fn div(self, rhs: R) -> Self {
+        let v = f64::from(rhs);
+        if v > 0.0 {
+            Ch64((self.0 / v).min(1.0))
+        } else {
+            Ch64(0.0)
+        }
+    }
+}
+
+/// Bits of half-precision 1.0
+const HALF_ONE: u16 = 0x3C00;
+
+/// Bits of half-precision 0.5
+const HALF_MID: u16 = 0x3800;
+
+impl Ch16f {
+    /// Create a new half-precision `Channel` value.
+    ///
+    /// Returns [MIN](trait.Channel.html#associatedconstant.MIN) if value is
+    ///         less than 0.0, or `NaN`.
+    /// Returns [MAX](trait.Channel.html#associatedconstant.MAX) if value is
+    ///         greater than 1.0.
+    pub fn new(value: f32) -> Self {
+        let v = if value.is_nan() || value < 0.0 {
+            0.0
+        } else if value > 1.0 {
+            1.0
+        } else {
+            value
+        };
+        Ch16f(half_from_f32(v))
+    }
+    /// Create a half-precision `Channel` from raw `binary16` bits.
+    ///
+    /// Negative values and `NaN` become 0; values greater than 1.0 (including
+    /// infinity) become 1.
+    pub fn from_bits(bits: u16) -> Self {
+        let exp = (bits >> 10) & 0x1F;
+        if bits & 0x8000 != 0 || (exp == 0x1F && bits & 0x3FF != 0) {
+            Ch16f(0)
+        } else {
+            Ch16f(bits.min(HALF_ONE))
+        }
+    }
+    /// Get raw `binary16` bits.
+    pub fn to_bits(self) -> u16 {
+        self.0
+    }
+}
+
+/// Convert an `f32` between 0.0 and 1.0 to half-precision bits.
+///
+/// Rounds to nearest, with ties to even.
+fn half_from_f32(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let exp = ((bits >> 23) & 0xFF) as i32 - 127 + 15;
+    let man = bits & 0x7F_FFFF;
+    let (v, shift) = if exp >= 1 {
+        ((exp as u32) << 23 | man, 13)
+    } else {
+        // Half subnormal; make implicit leading bit explicit
+        (man | 0x80_0000, (14 - exp) as u32)
+    };
+    if shift > 24 {
+        return 0;
+    }
+    let h = v >> shift;
+    let rem = v & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let h = if rem > half || (rem == half && h & 1 != 0) {
+        h + 1
+    } else {
+        h
+    };
+    h as u16
+}
+
+/// Convert half-precision bits to an `f32`.
+fn half_to_f32(bits: u16) -> f32 {
+    let exp = u32::from(bits >> 10) & 0x1F;
+    let man = u32::from(bits) & 0x3FF;
+    if exp == 0 {
+        man as f32 / 16_777_216.0
+    } else {
+        f32::from_bits((exp + 127 - 15) << 23 | man << 13)
+    }
+}
+
+impl Channel for Ch16f {
+    /// Minimum intensity (*zero*)
+    const MIN: Ch16f = Ch16f(0);
+
+    /// Maximum intensity (*one*)
+    const MAX: Ch16f = Ch16f(HALF_ONE);
+
+    /// Middle intensity (*one half*)
+    const MID: Ch16f = Ch16f(HALF_MID);
+
+    /// Bit depth
+    const DEPTH: PixDepth = PixDepth::F16;
+
+    /// Raise to given power
+    fn powf(self, g: f32) -> Self {
+        Ch16f::new(f32::from(self).powf(g))
+    }
+
+    /// Linearly interpolate by a channel value
+    fn lerp(self, rhs: Self, t: Self) -> Self {
+        let (a, b) = (f32::from(self), f32::from(rhs));
+        Ch16f::new(a + (b - a) * f32::from(t))
+    }
+
+    /// Check if two values are approximately equal
+    fn approx_eq(self, rhs: Self, epsilon: f32) -> bool {
+        (f32::from(self) - f32::from(rhs)).abs() <= epsilon
+    }
+}
+
+impl From<Ch8> for Ch16f {
+    fn from(c: Ch8) -> Self {
+        Ch16f::new(f32::from(c.0) / 255.0)
+    }
+}
+
+impl From<Ch16> for Ch16f {
+    fn from(c: Ch16) -> Self {
+        Ch16f::new(f32::from(c.0) / 65535.0)
+    }
+}
+
+impl From<Ch32> for Ch16f {
+    fn from(c: Ch32) -> Self {
+        Ch16f::new(c.0)
+    }
+}
+
+impl From<Ch64> for Ch16f {
+    fn from(c: Ch64) -> Self {
+        Ch16f::new(c.0 as f32)
+    }
+}
+
+impl From<f32> for Ch16f {
+    fn from(value: f32) -> Self {
+        Ch16f::new(value)
+    }
+}
+
+impl From<Ch16f> for f32 {
+    fn from(c: Ch16f) -> f32 {
+        half_to_f32(c.0)
+    }
+}
+
+impl From<Ch16f> for Ch8 {
+    fn from(c: Ch16f) -> Self {
+        Ch32::from(c).into()
+    }
+}
+
+impl From<Ch16f> for Ch16 {
+    fn from(c: Ch16f) -> Self {
+        Ch32::from(c).into()
+    }
+}
+
+impl From<Ch16f> for Ch32 {
+    fn from(c: Ch16f) -> Self {
+        Ch32(f32::from(c))
+    }
+}
+
+impl From<Ch16f> for Ch64 {
+    fn from(c: Ch16f) -> Self {
+        Ch64(f64::from(f32::from(c)))
+    }
+}
+
+impl<R> Add<R> for Ch16f
+where
+    f32: From<R>,
+{
+    type Output = Self;
+    fn add(self, rhs: R) -> Self {
+        Ch16f::new(half_to_f32(self.0) + f32::from(rhs))
+    }
+}
+
+impl<R> Sub<R> for Ch16f
+where
+    f32: From<R>,
+{
+    type Output = Self;
+    fn sub(self, rhs: R) -> Self {
+        Ch16f::new(half_to_f32(self.0) - f32::from(rhs))
+    }
+}
+
+impl<R> Mul<R> for Ch16f
+where
+    f32: From<R>,
+{
+    type Output = Self;
+    fn mul(self, rhs: R) -> Self {
+        Ch16f::new(half_to_f32(self.0) * f32::from(rhs))
+    }
+}
+
+impl<R> Div<R> for Ch16f
+where
+    f32: From<R>,
+{
+    type Output = Self;
+    fn div(self, rhs: R) -> Self {
+        let v = f32::from(rhs);
+        if v > 0.0 {
+            Ch16f::new(half_to_f32(self.0) / v)
+        } else {
+            Ch16f(0)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -510,6 +1107,16 @@ mod test {
         assert_eq!(Ch16::new(2048), Ch16::new(32768) * 0.0625);
     }
     #[test]
+    fn ch16_scale() {
+        for i in (0..=65535).step_by(7) {
+            let c = Ch16::new(i);
+            assert_eq!(c, c.scale(Ch16::MAX));
+            assert_eq!(c, c.div_scale(Ch16::MAX));
+            let v = (u64::from(i) * 0x1234 + 32767) / 65535;
+            assert_eq!(Ch16::new(v as u16), c.scale(Ch16::new(0x1234)));
+        }
+    }
+    #[test]
     fn ch16_div() {
         assert_eq!(Ch16::new(65535), Ch16::new(65535) / 1.0);
         assert_eq!(Ch16::new(65535), Ch16::new(32768) / 0.5);
@@ -536,4 +1143,166 @@ mod test {
         assert_eq!(Ch32::new(0.0625), Ch32::new(0.5) * 0.125);
         assert_eq!(Ch32::new(0.03125), Ch32::new(0.5) * 0.0625);
     }
+    #[test]
+    fn ch32_nan_cmp() {
+        let nan = Ch32::MID * f32::NAN;
+        assert!(!nan.lt(&Ch32::MAX));
+        assert!(!nan.gt(&Ch32::MIN));
+        assert_eq!(nan.partial_cmp(&Ch32::MID), None);
+        assert_eq!(nan.cmp(&nan), Ordering::Equal);
+        assert_ne!(nan.cmp(&Ch32::MID), Ordering::Equal);
+        let (lo, hi) = (nan.min(Ch32::MID), nan.max(Ch32::MID));
+        assert!(lo.0.is_nan() != hi.0.is_nan());
+    }
+    #[test]
+    fn ch64_nan_cmp() {
+        let nan = Ch64::MID * f64::NAN;
+        assert!(!nan.lt(&Ch64::MAX));
+        assert!(!nan.gt(&Ch64::MIN));
+        assert_eq!(nan.partial_cmp(&Ch64::MID), None);
+        assert_eq!(nan.cmp(&nan), Ordering::Equal);
+        assert_ne!(nan.cmp(&Ch64::MID), Ordering::Equal);
+        let (lo, hi) = (nan.min(Ch64::MID), nan.max(Ch64::MID));
+        assert!(lo.0.is_nan() != hi.0.is_nan());
+    }
+    #[test]
+    fn ch64_into() {
+        assert_eq!(Ch64::new(1.0), 1.0_f64.into());
+        assert_eq!(Ch64::new(0.5), 0.5_f32.into());
+        assert_eq!(Ch64::MIN, Ch8::new(0).into());
+        assert_eq!(Ch8::new(128), Ch64::new(0.5).into());
+        assert_eq!(Ch16::new(32768), Ch64::new(0.5).into());
+        assert_eq!(Ch32::new(0.25), Ch64::new(0.25).into());
+        for i in 0..=255 {
+            let c8 = Ch8::new(i);
+            let c64: Ch64 = c8.into();
+            assert_eq!(c8, c64.into());
+        }
+        for i in (0..=65535).step_by(7) {
+            let c16 = Ch16::new(i);
+            let c64: Ch64 = c16.into();
+            assert_eq!(c16, c64.into());
+        }
+    }
+    #[test]
+    fn ch64_precision() {
+        let v = 1.0 - 1e-12;
+        assert_eq!(v as f32, 1.0);
+        let c = Ch64::new(v);
+        assert_eq!(f64::from(c * Ch64::MAX), v);
+        assert_eq!(f64::from(c / Ch64::MAX), v);
+        assert_eq!(f64::from(Ch64::new(0.1) * 0.5_f64), 0.05);
+    }
+    #[test]
+    fn ch16f_into() {
+        assert_eq!(Ch16f::new(1.0).to_bits(), 0x3C00);
+        assert_eq!(Ch16f::new(0.25).to_bits(), 0x3400);
+        assert_eq!(Ch16f::new(2.0), Ch16f::MAX);
+        assert_eq!(Ch16f::new(-1.0), Ch16f::MIN);
+        assert_eq!(Ch16f::new(f32::NAN), Ch16f::MIN);
+        assert_eq!(Ch16f::new(f32::INFINITY), Ch16f::MAX);
+        // smallest subnormal, and ties to even
+        assert_eq!(Ch16f::new(5.960_464_5e-8).to_bits(), 0x0001);
+        assert_eq!(Ch16f::new(2.980_232_2e-8).to_bits(), 0x0000);
+        assert_eq!(Ch16f::new(1.0 - 2.0_f32.powi(-12)).to_bits(), 0x3C00);
+        assert_eq!(Ch16f::new(0.5 + 2.0_f32.powi(-12)).to_bits(), 0x3800);
+        assert_eq!(Ch16f::from_bits(0x8000), Ch16f::MIN);
+        assert_eq!(Ch16f::from_bits(0xBC00), Ch16f::MIN);
+        assert_eq!(Ch16f::from_bits(0x4000), Ch16f::MAX);
+        assert_eq!(Ch16f::from_bits(0x7FFF), Ch16f::MIN);
+        for bits in 0..=0x3C00 {
+            let c = Ch16f::from_bits(bits);
+            assert_eq!(c, Ch16f::new(f32::from(c)));
+        }
+        for i in 0..=255 {
+            let c8 = Ch8::new(i);
+            let c: Ch16f = c8.into();
+            assert_eq!(c8, c.into());
+        }
+        assert_eq!(Ch16::MAX, Ch16f::MAX.into());
+        assert_eq!(Ch16f::new(0.5), Ch16::new(32768).into());
+    }
+    #[test]
+    fn ch16f_math() {
+        let half = Ch16f::new(0.5);
+        assert_eq!(half * half, Ch16f::new(0.25));
+        assert_eq!(half + half, Ch16f::MAX);
+        assert_eq!(Ch16f::MAX + half, Ch16f::MAX);
+        assert_eq!(half - Ch16f::MAX, Ch16f::MIN);
+        assert_eq!(Ch16f::new(0.25) / half, half);
+        assert_eq!(half / Ch16f::MIN, Ch16f::MIN);
+    }
+    #[test]
+    fn mid() {
+        assert_eq!(Ch8::MID, Ch8::from(0.5));
+        assert_eq!(Ch16::MID, Ch16::from(0.5));
+        assert_eq!(Ch16f::MID, Ch16f::new(0.5));
+        assert_eq!(Ch32::MID, Ch32::new(0.5));
+        assert_eq!(Ch64::MID, Ch64::new(0.5));
+        assert!(Ch8::MIN < Ch8::MID && Ch8::MID < Ch8::MAX);
+    }
+    #[test]
+    fn scale_exhaustive() {
+        for x in 0..=255u8 {
+            let cx = Ch8::new(x);
+            assert_eq!(cx.scale(Ch8::MAX), cx);
+            assert_eq!(cx.scale(Ch8::MIN), Ch8::MIN);
+            assert_eq!(cx.div_scale(Ch8::MAX), cx);
+            for y in 0..=255u8 {
+                let cy = Ch8::new(y);
+                // Rounded to nearest: (2xy + 255) / 510
+                let v = (2 * u32::from(x) * u32::from(y) + 255) / 510;
+                assert_eq!(cx.scale(cy), Ch8::new(v as u8), "{} {}", x, y);
+                assert_eq!(cx.scale(cy), cy.scale(cx));
+                let t = u32::from(y);
+                let v = u32::from(x) * (255 - t) + 255 * t;
+                let v = (2 * v + 255) / 510;
+                let c = cx.lerp(Ch8::MAX, cy);
+                assert_eq!(c, Ch8::new(v as u8), "{} {}", x, y);
+            }
+        }
+        for x in (0..=0xFFFFu16).step_by(257) {
+            let cx = Ch16::new(x);
+            assert_eq!(cx.scale(Ch16::MAX), cx);
+            assert_eq!(cx.div_scale(Ch16::MAX), cx);
+        }
+    }
+    #[test]
+    fn channel_math() {
+        let c = Ch8::new(200);
+        assert_eq!(c.saturating_add(Ch8::new(100)), Ch8::MAX);
+        assert_eq!(c.saturating_sub(Ch8::new(201)), Ch8::MIN);
+        assert_eq!(Ch8::new(0x40).div_scale(Ch8::new(0x80)), Ch8::new(0x80));
+        assert_eq!(c.div_scale(Ch8::new(100)), Ch8::MAX);
+        assert_eq!(c.div_scale(Ch8::MIN), Ch8::MIN);
+        let c = Ch16::new(0x1000).lerp(Ch16::new(0x2000), Ch16::MID);
+        assert_eq!(c, Ch16::new(0x1800));
+        let c = Ch32::new(0.75);
+        assert_eq!(c.saturating_add(c), Ch32::MAX);
+        assert_eq!(Ch32::new(0.25).saturating_sub(c), Ch32::MIN);
+        assert_eq!(c.scale(Ch32::MID), Ch32::new(0.375));
+        assert_eq!(Ch32::new(0.25).div_scale(Ch32::MID), Ch32::MID);
+        assert_eq!(c.lerp(Ch32::MIN, Ch32::MID), Ch32::new(0.375));
+        let c = Ch64::MAX.lerp(Ch64::MIN, Ch64::new(0.25));
+        assert_eq!(c, Ch64::new(0.75));
+        let c = Ch16f::MIN.lerp(Ch16f::MAX, Ch16f::MID);
+        assert_eq!(c, Ch16f::MID);
+    }
+    #[test]
+    fn approx_eq() {
+        assert!(Ch8::new(10).approx_eq(Ch8::new(10), 0.0));
+        assert!(!Ch8::new(10).approx_eq(Ch8::new(11), 0.0));
+        assert!(Ch8::new(11).approx_eq(Ch8::new(10), 1.0 / 255.0));
+        assert!(Ch8::MIN.approx_eq(Ch8::MAX, 1.0));
+        assert!(Ch16::new(1000).approx_eq(Ch16::new(1010), 0.0002));
+        assert!(!Ch16::new(1000).approx_eq(Ch16::new(1020), 0.0002));
+        assert!(Ch32::new(0.5).approx_eq(Ch32::new(0.5001), 0.001));
+        assert!(!Ch32::new(0.5).approx_eq(Ch32::new(0.51), 0.001));
+        assert!(Ch64::new(0.25).approx_eq(Ch64::new(0.2501), 0.001));
+        assert!(Ch16f::new(0.25).approx_eq(Ch16f::new(0.2502), 0.001));
+        let nan = Ch32::new(0.5) * f32::NAN;
+        assert!(!nan.approx_eq(nan, 1.0));
+        assert!(!nan.approx_eq(Ch32::MAX, 1.0));
+        assert!(!Ch32::MID.approx_eq(Ch32::MID, f32::NAN));
+    }
 }
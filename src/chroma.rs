@@ -0,0 +1,182 @@
+// chroma.rs    Chroma keying.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::Straight;
+use crate::hsv::channel_from_f32;
+use crate::{Ch32, Ch8, ColorModel, Pixel, Raster, SRgb8, SRgba8};
+
+/// Width of the soft edge, relative to the tolerance
+const EDGE: f32 = 0.5;
+
+/// Get the *blue-difference* and *red-difference* chroma of a color
+fn chroma(clr: SRgb8) -> [f32; 2] {
+    let rgba = clr.to_rgba();
+    let red = f32::from(Ch32::from(rgba[0]));
+    let green = f32::from(Ch32::from(rgba[1]));
+    let blue = f32::from(Ch32::from(rgba[2]));
+    let cb = -0.168_736 * red - 0.331_264 * green + 0.5 * blue;
+    let cr = 0.5 * red - 0.418_688 * green - 0.081_312 * blue;
+    [cb, cr]
+}
+
+/// Chroma key matte
+struct Key {
+    chroma: [f32; 2],
+    tolerance: f32,
+}
+
+impl Key {
+    /// Create a new chroma key
+    fn new(key: SRgb8, tolerance: f32) -> Self {
+        let chroma = chroma(key);
+        Key { chroma, tolerance }
+    }
+    /// Get the opacity of a color, from 0 (keyed) to 1 (not keyed)
+    fn opacity(&self, clr: SRgb8) -> f32 {
+        let [cb, cr] = chroma(clr);
+        let dcb = cb - self.chroma[0];
+        let dcr = cr - self.chroma[1];
+        let dist = (dcb * dcb + dcr * dcr).sqrt();
+        let edge = self.tolerance * EDGE;
+        if dist <= self.tolerance {
+            0.0
+        } else if dist >= self.tolerance + edge {
+            1.0
+        } else {
+            (dist - self.tolerance) / edge
+        }
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Make pixels matching a key color transparent.
+    ///
+    /// * `key` Key color, such as a green screen.
+    /// * `tolerance` Maximum chroma distance to key, from 0.0 to about 0.7.
+    ///
+    /// Distance is measured between the Y′CbCr chroma (Cb and Cr) of each
+    /// pixel and the key, ignoring luma, so shadows on the key color are
+    /// also keyed.  Pixels within `tolerance` become fully transparent.  To
+    /// avoid hard fringes, *alpha* ramps up to its original value over a
+    /// further half of `tolerance`.
+    ///
+    /// ### Key out a green screen
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(4, 4, SRgb8::new(0x10, 0xD0, 0x20));
+    /// r.set_pixel(1, 1, SRgb8::new(0xD0, 0x80, 0x60));
+    /// let k = r.chroma_key(SRgb8::new(0x00, 0xFF, 0x00), 0.2);
+    /// assert_eq!(k.pixel(0, 0).alpha(), Ch8::new(0));
+    /// assert_eq!(k.pixel(1, 1).alpha(), Ch8::new(0xFF));
+    /// ```
+    pub fn chroma_key(&self, key: SRgb8, tolerance: f32) -> Raster<SRgba8>
+    where
+        Ch8: From<P::Chan>,
+    {
+        let key = Key::new(key, tolerance);
+        self.map(|p| {
+            let mut p = p.convert::<SRgba8>();
+            let rgba = p.to_rgba();
+            let opacity = key.opacity(SRgb8::with_rgba(rgba));
+            let alpha = f32::from(Ch32::from(rgba[3])) * opacity;
+            p.set_alpha(channel_from_f32(alpha));
+            p
+        })
+    }
+}
+
+impl<P> Raster<P>
+where
+    P: Pixel<Alpha = Straight>,
+{
+    /// Make pixels matching a key color transparent, in place.
+    ///
+    /// * `key` Key color, such as a green screen.
+    /// * `tolerance` Maximum chroma distance to key, from 0.0 to about 0.7.
+    ///
+    /// See [chroma_key](struct.Raster.html#method.chroma_key).  Formats
+    /// without an *alpha* channel are not changed.
+    pub fn chroma_key_in_place(&mut self, key: SRgb8, tolerance: f32)
+    where
+        Ch8: From<P::Chan>,
+        Ch32: From<P::Chan>,
+    {
+        let key = Key::new(key, tolerance);
+        self.map_in_place(|p| {
+            let opacity = key.opacity(p.convert());
+            let alpha = f32::from(Ch32::from(p.alpha())) * opacity;
+            p.set_alpha(channel_from_f32(alpha));
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    /// Green rectangle over a warm gradient, with a blended border
+    fn green_screen() -> Raster<SRgb8> {
+        RasterBuilder::<SRgb8>::new().with_fn(32, 16, |x, y| {
+            let green = [0x20, 0xC0, 0x30];
+            let bg = [0xA0 + x, 0x60 + y * 2, 0x40 + x];
+            let (r, g, b) = match x {
+                8..=23 => (green[0], green[1], green[2]),
+                7 | 24 => (
+                    (green[0] + bg[0]) / 2,
+                    (green[1] + bg[1]) / 2,
+                    (green[2] + bg[2]) / 2,
+                ),
+                _ => (bg[0], bg[1], bg[2]),
+            };
+            SRgb8::new(r as u8, g as u8, b as u8)
+        })
+    }
+
+    #[test]
+    fn key_rectangle() {
+        let r = green_screen();
+        let k = r.chroma_key(SRgb8::new(0x20, 0xC0, 0x30), 0.16);
+        for y in 0..16 {
+            for x in 0..32 {
+                let a = u8::from(k.pixel(x, y).alpha());
+                match x {
+                    8..=23 => assert_eq!(a, 0, "{} {}", x, y),
+                    7 | 24 => assert!(a > 0 && a < 0xFF, "{} {} {}", x, y, a),
+                    _ => assert_eq!(a, 0xFF, "{} {}", x, y),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn key_in_place() {
+        let r = green_screen();
+        let key = SRgb8::new(0x20, 0xC0, 0x30);
+        let k = r.chroma_key(key, 0.16);
+        let mut a = RasterBuilder::<SRgba8>::new().with_raster(&r);
+        a.chroma_key_in_place(key, 0.16);
+        assert_eq!(a, k);
+        let mut m = RasterBuilder::<SRgba16>::new().with_raster(&r);
+        let clr = SRgba16::with_alpha(0x8000, 0x4000, 0x3000, 0x8000);
+        m.set_pixel(0, 0, clr);
+        m.chroma_key_in_place(key, 0.16);
+        assert_eq!(m.pixel(0, 0).alpha(), Ch16::new(0x8000));
+        assert_eq!(m.pixel(10, 0).alpha(), Ch16::new(0));
+    }
+
+    #[test]
+    fn soft_edge() {
+        let key = SRgb8::new(0x00, 0xFF, 0x00);
+        let r = RasterBuilder::<SRgb8>::new().with_fn(64, 1, |x, _y| {
+            let v = (x * 4) as u8;
+            SRgb8::new(v, 0xFF - v, 0)
+        });
+        let k = r.chroma_key(key, 0.2);
+        let alpha: Vec<u8> =
+            k.as_slice().iter().map(|p| u8::from(p.alpha())).collect();
+        assert!(alpha.windows(2).all(|w| w[0] <= w[1]));
+        assert!(alpha.iter().filter(|a| **a > 0 && **a < 0xFF).count() > 4);
+    }
+}
@@ -0,0 +1,283 @@
+// cmyk.rs      CMYK color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight, Translucent};
+use crate::gamma::{self, Srgb};
+use crate::pixel::fmt_pixel;
+use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// CMYK subtractive [color model], with optional [alpha channel].
+///
+/// The components are *cyan*, *magenta*, *yellow* and *key* (black).  They
+/// are converted from gamma-encoded [Rgb](struct.Rgb.html) components using
+/// naive device formulas, without color management:
+///
+/// * *key* = 1 - max(*red*, *green*, *blue*)
+/// * *cyan* = (1 - *red* - *key*) / (1 - *key*), etc.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    components: [C; 4],
+    alpha: A,
+    gamma: PhantomData<G>,
+}
+
+impl<C, A, G> Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying
+    /// *cyan*, *magenta*, *yellow* and *key* components.
+    pub fn new<H>(cyan: H, magenta: H, yellow: H, key: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(cyan, magenta, yellow, key, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying *cyan*, *magenta*, *yellow*, *key* and *alpha*.
+    pub fn with_alpha<H, B>(
+        cyan: H,
+        magenta: H,
+        yellow: H,
+        key: H,
+        alpha: B,
+    ) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let components =
+            [C::from(cyan), C::from(magenta), C::from(yellow), C::from(key)];
+        let alpha = A::from(alpha);
+        Cmyk {
+            components,
+            alpha,
+            gamma: PhantomData,
+        }
+    }
+    /// Get the *cyan* component.
+    pub fn cyan(self) -> C {
+        self.components[0]
+    }
+    /// Get the *magenta* component.
+    pub fn magenta(self) -> C {
+        self.components[1]
+    }
+    /// Get the *yellow* component.
+    pub fn yellow(self) -> C {
+        self.components[2]
+    }
+    /// Get the *key* (black) component.
+    pub fn key(self) -> C {
+        self.components[3]
+    }
+}
+
+impl<C, A, G> ColorModel for Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let k = C::MAX - self.key();
+        let red = (C::MAX - self.cyan()).scale(k);
+        let green = (C::MAX - self.magenta()).scale(k);
+        let blue = (C::MAX - self.yellow()).scale(k);
+        [red, green, blue, self.alpha()]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let max = rgba[0].max(rgba[1]).max(rgba[2]);
+        let cyan = (max - rgba[0]).div_scale(max);
+        let magenta = (max - rgba[1]).div_scale(max);
+        let yellow = (max - rgba[2]).div_scale(max);
+        let key = C::MAX - max;
+        Cmyk::with_alpha(cyan, magenta, yellow, key, rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let diff = |a: C, b: C| a.max(b) - a.min(b);
+        Cmyk::with_alpha(
+            diff(self.cyan(), rhs.cyan()),
+            diff(self.magenta(), rhs.magenta()),
+            diff(self.yellow(), rhs.yellow()),
+            diff(self.key(), rhs.key()),
+            diff(self.alpha(), rhs.alpha()),
+        )
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.cyan() <= rhs.cyan()
+            && self.magenta() <= rhs.magenta()
+            && self.yellow() <= rhs.yellow()
+            && self.key() <= rhs.key()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A, G> fmt::Debug for Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A, G> Pixel for Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Cmyk);
+
+    const WHITE: Self = Cmyk {
+        components: [C::MIN; 4],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = Cmyk {
+        components: [C::MIN, C::MIN, C::MIN, C::MAX],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = Cmyk {
+        components: [C::MIN, C::MIN, C::MIN, C::MAX],
+        alpha: A::TRANSPARENT,
+        gamma: PhantomData,
+    };
+
+    type Alpha = Straight;
+    type Gamma = G;
+}
+
+impl<C, A, G> Iterator for Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// [Cmyk](struct.Cmyk.html) 8-bit [opaque](alpha/struct.Opaque.html)
+/// (no alpha) [sRGB](gamma/struct.Srgb.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type Cmyk8 = Cmyk<Ch8, Opaque<Ch8>, Srgb>;
+/// [Cmyk](struct.Cmyk.html) 16-bit [opaque](alpha/struct.Opaque.html)
+/// (no alpha) [sRGB](gamma/struct.Srgb.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type Cmyk16 = Cmyk<Ch16, Opaque<Ch16>, Srgb>;
+/// [Cmyk](struct.Cmyk.html) 32-bit [opaque](alpha/struct.Opaque.html)
+/// (no alpha) [sRGB](gamma/struct.Srgb.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type Cmyk32 = Cmyk<Ch32, Opaque<Ch32>, Srgb>;
+
+/// [Cmyk](struct.Cmyk.html) 8-bit [straight](alpha/struct.Straight.html)
+/// alpha [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html)
+/// format.
+pub type Cmyka8 = Cmyk<Ch8, Translucent<Ch8>, Srgb>;
+/// [Cmyk](struct.Cmyk.html) 16-bit [straight](alpha/struct.Straight.html)
+/// alpha [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html)
+/// format.
+pub type Cmyka16 = Cmyk<Ch16, Translucent<Ch16>, Srgb>;
+/// [Cmyk](struct.Cmyk.html) 32-bit [straight](alpha/struct.Straight.html)
+/// alpha [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html)
+/// format.
+pub type Cmyka32 = Cmyk<Ch32, Translucent<Ch32>, Srgb>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<Cmyk8>(), 4);
+        assert_eq!(std::mem::size_of::<Cmyk16>(), 8);
+        assert_eq!(std::mem::size_of::<Cmyk32>(), 16);
+        assert_eq!(std::mem::size_of::<Cmyka8>(), 5);
+        assert_eq!(std::mem::size_of::<Cmyka16>(), 10);
+        assert_eq!(std::mem::size_of::<Cmyka32>(), 20);
+    }
+
+    #[test]
+    fn rgb_to_cmyk() {
+        let cmyk: Cmyk8 = SRgb8::new(0x00, 0x00, 0x00).convert();
+        assert_eq!(cmyk, Cmyk8::new(0x00, 0x00, 0x00, 0xFF));
+        let cmyk: Cmyk8 = SRgb8::new(0xFF, 0xFF, 0xFF).convert();
+        assert_eq!(cmyk, Cmyk8::new(0x00, 0x00, 0x00, 0x00));
+        let cmyk: Cmyk8 = SRgb8::new(0xFF, 0x00, 0x00).convert();
+        assert_eq!(cmyk, Cmyk8::new(0x00, 0xFF, 0xFF, 0x00));
+        let cmyk: Cmyk8 = SRgb8::new(0x80, 0x40, 0x00).convert();
+        assert_eq!(cmyk, Cmyk8::new(0x00, 0x80, 0xFF, 0x7F));
+        let cmyk: Cmyka16 =
+            SRgba16::with_alpha(0x0000, 0x0000, 0x0000, 0x8000).convert();
+        assert_eq!(cmyk, Cmyka16::with_alpha(0, 0, 0, 0xFFFF, 0x8000));
+        let cmyk: Cmyk32 = SRgb32::new(0.25, 0.5, 1.0).convert();
+        assert_eq!(cmyk, Cmyk32::new(0.75, 0.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn cmyk_to_rgb() {
+        let rgb: SRgb8 = Cmyk8::new(0x00, 0x00, 0x00, 0xFF).convert();
+        assert_eq!(rgb, SRgb8::new(0x00, 0x00, 0x00));
+        let rgb: SRgb8 = Cmyk8::new(0xFF, 0x00, 0x00, 0x00).convert();
+        assert_eq!(rgb, SRgb8::new(0x00, 0xFF, 0xFF));
+        let rgb: SRgb8 = Cmyk8::new(0x00, 0x80, 0xFF, 0x7F).convert();
+        assert_eq!(rgb, SRgb8::new(0x80, 0x40, 0x00));
+    }
+
+    #[test]
+    fn with_raster() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(2, 1);
+        r.set_pixel(1, 0, SRgb8::new(0x00, 0xFF, 0xFF));
+        let c = RasterBuilder::<Cmyk8>::new().with_raster(&r);
+        let v = [0x00,0x00,0x00,0xFF, 0xFF,0x00,0x00,0x00];
+        assert_eq!(c.as_u8_slice(), &v[..]);
+    }
+}
@@ -0,0 +1,548 @@
+// convolve.rs  Convolution filters.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::Straight;
+use crate::{Ch16, Ch32, Ch64, Mask16, Pixel, Raster, RasterBuilder, SGray32};
+use std::any::TypeId;
+
+/// Handling of samples beyond the edges of a [Raster](struct.Raster.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Edge {
+    /// Repeat the nearest edge pixel
+    Clamp,
+    /// Wrap around to the opposite edge
+    Wrap,
+    /// Reflect about the edge pixel, without repeating it
+    ///
+    /// `[a, b, c]` is extended as `c b | a b c | b a`.
+    Mirror,
+}
+
+/// Convolution kernel for [Raster](struct.Raster.html)`::`
+/// [convolve](struct.Raster.html#method.convolve).
+///
+/// A kernel is a matrix of weights with odd width and height, centered on
+/// the destination pixel.  The weighted sum of source values is divided by
+/// the *divisor*.
+///
+/// ### Create a 3x3 kernel
+/// ```
+/// # use pix::*;
+/// let kernel = Kernel::new(3, 3, vec![
+///     1.0, 2.0, 1.0,
+///     2.0, 4.0, 2.0,
+///     1.0, 2.0, 1.0,
+/// ], 16.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Kernel {
+    width: usize,
+    height: usize,
+    weights: Vec<f32>,
+    divisor: f32,
+}
+
+impl Edge {
+    /// Get the source coordinate for a position which may be out of bounds.
+    ///
+    /// * `v` Position.
+    /// * `len` Length of the dimension; must be non-zero.
+    fn coord(self, v: isize, len: usize) -> usize {
+        let len = len as isize;
+        let v = match self {
+            Edge::Clamp => v.clamp(0, len - 1),
+            Edge::Wrap => v.rem_euclid(len),
+            Edge::Mirror if len == 1 => 0,
+            Edge::Mirror => {
+                let period = 2 * (len - 1);
+                let v = v.rem_euclid(period);
+                if v < len {
+                    v
+                } else {
+                    period - v
+                }
+            }
+        };
+        v as usize
+    }
+}
+
+impl Kernel {
+    /// Create a new convolution kernel.
+    ///
+    /// * `width` Width of kernel matrix; must be odd.
+    /// * `height` Height of kernel matrix; must be odd.
+    /// * `weights` Weights, in row-major order.
+    /// * `divisor` Normalization factor; the weighted sum is divided by this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` is even, or if the length of `weights`
+    /// is not `width` * `height`.
+    pub fn new(
+        width: usize,
+        height: usize,
+        weights: Vec<f32>,
+        divisor: f32,
+    ) -> Self {
+        assert!(
+            width % 2 == 1 && height % 2 == 1,
+            "kernel dimensions must be odd: {}x{}",
+            width,
+            height
+        );
+        assert_eq!(weights.len(), width * height, "kernel weights length");
+        Kernel {
+            width,
+            height,
+            weights,
+            divisor,
+        }
+    }
+    /// Create a 1x1 identity kernel.
+    pub fn identity() -> Self {
+        Self::new(1, 1, vec![1.0], 1.0)
+    }
+    /// Create a box blur kernel.
+    ///
+    /// * `size` Width and height of kernel; must be odd.
+    pub fn box_blur(size: usize) -> Self {
+        let weights = vec![1.0; size * size];
+        Self::new(size, size, weights, (size * size) as f32)
+    }
+    /// Create a 3x3 sharpen kernel.
+    pub fn sharpen() -> Self {
+        let weights = vec![
+             0.0, -1.0,  0.0,
+            -1.0,  5.0, -1.0,
+             0.0, -1.0,  0.0,
+        ];
+        Self::new(3, 3, weights, 1.0)
+    }
+    /// Create a 3x3 emboss kernel, lit from the upper left.
+    pub fn emboss() -> Self {
+        let weights = vec![
+            -2.0, -1.0, 0.0,
+            -1.0,  1.0, 1.0,
+             0.0,  1.0, 2.0,
+        ];
+        Self::new(3, 3, weights, 1.0)
+    }
+    /// Get the width of the kernel matrix.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    /// Get the height of the kernel matrix.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    /// Get the weights, in row-major order.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+    /// Get the divisor (normalization factor).
+    pub fn divisor(&self) -> f32 {
+        self.divisor
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Convolve with a kernel, clamping at the edges.
+    ///
+    /// * `kernel` Convolution kernel.
+    ///
+    /// See [convolve_edge](struct.Raster.html#method.convolve_edge).
+    ///
+    /// ### Sharpen a raster
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(8, 8, SRgb8::new(0x40, 0x80, 0xC0));
+    /// let s = r.convolve(&Kernel::sharpen());
+    /// assert_eq!(r, s);
+    /// ```
+    pub fn convolve(&self, kernel: &Kernel) -> Raster<P>
+    where
+        Ch64: From<P::Chan>,
+        P::Chan: From<Ch64>,
+    {
+        self.convolve_edge(kernel, Edge::Clamp)
+    }
+    /// Convolve with a kernel.
+    ///
+    /// * `kernel` Convolution kernel.
+    /// * `edge` Handling of samples beyond the edges.
+    ///
+    /// Each channel is filtered separately, including *alpha*.  Results are
+    /// rounded and clamped to the channel range.  To avoid dark fringes
+    /// around transparent areas, premultiply alpha before filtering.
+    pub fn convolve_edge(&self, kernel: &Kernel, edge: Edge) -> Raster<P>
+    where
+        Ch64: From<P::Chan>,
+        P::Chan: From<Ch64>,
+    {
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        let n = P::CHANNELS;
+        let src: Vec<f64> = self
+            .as_chan_slice()
+            .iter()
+            .map(|c| f64::from(Ch64::from(*c)))
+            .collect();
+        let kx = (kernel.width / 2) as isize;
+        let ky = (kernel.height / 2) as isize;
+        let divisor = f64::from(kernel.divisor);
+        let mut buffer = Vec::with_capacity(src.len());
+        let mut sums = vec![0.0; n];
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                sums.iter_mut().for_each(|s| *s = 0.0);
+                let mut weights = kernel.weights.iter();
+                for dy in -ky..=ky {
+                    let sy = edge.coord(y + dy, height);
+                    for dx in -kx..=kx {
+                        let w = f64::from(*weights.next().unwrap());
+                        let sx = edge.coord(x + dx, width);
+                        let s = (sy * width + sx) * n;
+                        for (sum, v) in sums.iter_mut().zip(&src[s..s + n]) {
+                            *sum += w * v;
+                        }
+                    }
+                }
+                buffer.extend(
+                    sums.iter()
+                        .map(|s| P::Chan::from(Ch64::new(s / divisor))),
+                );
+            }
+        }
+        RasterBuilder::new()
+            .try_with_chan_buffer(self.width(), self.height(), buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// Build a normalized 1D Gaussian kernel
+fn gaussian_weights(sigma: f32) -> Vec<f64> {
+    let sigma = f64::from(sigma);
+    let radius = (sigma * 3.0).ceil() as isize;
+    let weights: Vec<f64> = (-radius..=radius)
+        .map(|i| (-(i * i) as f64 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+/// Blur one line of channel values, clamping at the edges.
+///
+/// * `dst` Destination values.
+/// * `src` Source values.
+/// * `len` Number of pixels in the line.
+/// * `step` Number of values from one pixel to the next.
+/// * `n` Number of channels per pixel.
+/// * `weights` 1D kernel.
+fn blur_line(
+    dst: &mut [f64],
+    src: &[f64],
+    len: usize,
+    step: usize,
+    n: usize,
+    weights: &[f64],
+) {
+    let radius = (weights.len() / 2) as isize;
+    for i in 0..len {
+        for c in 0..n {
+            dst[i * step + c] = weights
+                .iter()
+                .enumerate()
+                .map(|(k, w)| {
+                    let j = i as isize + k as isize - radius;
+                    w * src[Edge::Clamp.coord(j, len) * step + c]
+                })
+                .sum();
+        }
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Blur with a Gaussian kernel, clamping at the edges.
+    ///
+    /// * `sigma` Standard deviation of the Gaussian, in pixels.
+    ///
+    /// The kernel extends to 3 `sigma` on each side, and is applied to rows
+    /// and then columns.  Formats with straight *alpha* are blurred using
+    /// premultiplied values, to avoid dark halos around transparent areas.
+    /// When `sigma` is too small to affect any neighboring pixel, the result
+    /// is an exact copy.
+    ///
+    /// ### Blur a raster
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(9, 9);
+    /// r.set_pixel(4, 4, SGray8::new(0xFF));
+    /// let b = r.gaussian_blur(1.0);
+    /// assert!(b.pixel(4, 4).value() < Ch8::new(0xFF));
+    /// assert!(b.pixel(3, 4).value() > Ch8::new(0));
+    /// ```
+    pub fn gaussian_blur(&self, sigma: f32) -> Raster<P>
+    where
+        Ch64: From<P::Chan>,
+        P::Chan: From<Ch64>,
+    {
+        if sigma.is_nan() || sigma <= 0.0 || self.as_slice().is_empty() {
+            return self.clone();
+        }
+        let weights = gaussian_weights(sigma);
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        let n = P::CHANNELS;
+        let colors = P::default().components().len();
+        let straight = n > colors
+            && TypeId::of::<P::Alpha>() == TypeId::of::<Straight>();
+        let mut src: Vec<f64> = self
+            .as_chan_slice()
+            .iter()
+            .map(|c| f64::from(Ch64::from(*c)))
+            .collect();
+        if straight {
+            for px in src.chunks_exact_mut(n) {
+                let alpha = px[n - 1];
+                px[..colors].iter_mut().for_each(|v| *v *= alpha);
+            }
+        }
+        let mut tmp = vec![0.0; src.len()];
+        let row = width * n;
+        for (d, s) in tmp.chunks_exact_mut(row).zip(src.chunks_exact(row)) {
+            blur_line(d, s, width, n, n, &weights);
+        }
+        for x in 0..width {
+            let s = x * n;
+            blur_line(&mut src[s..], &tmp[s..], height, row, n, &weights);
+        }
+        if straight {
+            for px in src.chunks_exact_mut(n) {
+                let alpha = px[n - 1];
+                if alpha > 0.0 {
+                    px[..colors].iter_mut().for_each(|v| *v /= alpha);
+                }
+            }
+        }
+        let buffer = src.iter().map(|v| P::Chan::from(Ch64::new(*v))).collect();
+        RasterBuilder::new()
+            .try_with_chan_buffer(self.width(), self.height(), buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Detect edges with the Sobel operator.
+    ///
+    /// Each pixel is converted to sRGB luma, and the standard 3x3 Sobel
+    /// kernels are applied horizontally and vertically, clamping at the
+    /// edges.  The result is the gradient magnitude, scaled so that a step
+    /// from black to white is `MAX`, and clamped.  *Alpha* is ignored.
+    ///
+    /// Since the kernels span 3 pixels, a sharp step edge is detected in
+    /// the columns (or rows) on both sides of the step.
+    ///
+    /// ### Find edges of a square
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new().with_clear(16, 16);
+    /// r.fill_region((4, 4, 8, 8), SRgb8::new(0xFF, 0xFF, 0xFF));
+    /// let e = r.sobel();
+    /// assert_eq!(e.pixel(4, 8), Mask16::new(0xFFFF));
+    /// assert_eq!(e.pixel(8, 8), Mask16::new(0));
+    /// ```
+    pub fn sobel(&self) -> Raster<Mask16>
+    where
+        Ch32: From<P::Chan>,
+    {
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        let luma: Vec<f32> = self
+            .as_slice()
+            .iter()
+            .map(|p| f32::from(p.convert::<SGray32>().value()))
+            .collect();
+        let at = |x: isize, y: isize| {
+            let x = Edge::Clamp.coord(x, width);
+            let y = Edge::Clamp.coord(y, height);
+            luma[y * width + x]
+        };
+        RasterBuilder::new().with_fn(self.width(), self.height(), |x, y| {
+            let (x, y) = (x as isize, y as isize);
+            let gx = at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                - at(x - 1, y - 1)
+                - 2.0 * at(x - 1, y)
+                - at(x - 1, y + 1);
+            let gy = at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                - at(x - 1, y - 1)
+                - 2.0 * at(x, y - 1)
+                - at(x + 1, y - 1);
+            let magnitude = (gx * gx + gy * gy).sqrt() / 4.0;
+            Mask16::new(Ch16::from(magnitude))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn ramp() -> Raster<SRgba8> {
+        RasterBuilder::<SRgba8>::new().with_fn(7, 5, |x, y| {
+            let v = (x * 37 + y * 11) as u8;
+            SRgba8::with_alpha(v, 255 - v, v / 2, 0x80 + v / 2)
+        })
+    }
+
+    #[test]
+    fn identity() {
+        let r = ramp();
+        let w = vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+        let id = Kernel::new(3, 3, w, 1.0);
+        for edge in [Edge::Clamp, Edge::Wrap, Edge::Mirror].iter() {
+            let c = r.convolve_edge(&id, *edge);
+            assert_eq!(c.as_u8_slice(), r.as_u8_slice());
+        }
+        assert_eq!(r.convolve(&Kernel::identity()), r);
+        let r = RasterBuilder::<Gray64>::new()
+            .with_fn(5, 5, |x, y| Gray64::new(f64::from(x * y) / 17.0));
+        assert_eq!(r.convolve(&id), r);
+    }
+
+    #[test]
+    fn edges() {
+        let r = RasterBuilder::<Gray8>::new()
+            .with_u8_buffer(4, 1, vec![10, 20, 30, 40]);
+        let left = Kernel::new(3, 1, vec![1.0, 0.0, 0.0], 1.0);
+        let c = r.convolve_edge(&left, Edge::Clamp);
+        assert_eq!(c.as_u8_slice(), &[10, 10, 20, 30]);
+        let c = r.convolve_edge(&left, Edge::Wrap);
+        assert_eq!(c.as_u8_slice(), &[40, 10, 20, 30]);
+        let c = r.convolve_edge(&left, Edge::Mirror);
+        assert_eq!(c.as_u8_slice(), &[20, 10, 20, 30]);
+        let right = Kernel::new(3, 1, vec![0.0, 0.0, 1.0], 1.0);
+        let c = r.convolve_edge(&right, Edge::Mirror);
+        assert_eq!(c.as_u8_slice(), &[20, 30, 40, 30]);
+    }
+
+    #[test]
+    fn box_blur() {
+        let mut r = RasterBuilder::<Gray8>::new().with_clear(5, 5);
+        r.set_pixel(2, 2, Gray8::new(90));
+        let b = r.convolve(&Kernel::box_blur(3));
+        assert_eq!(b.pixel(1, 1), Gray8::new(10));
+        assert_eq!(b.pixel(3, 3), Gray8::new(10));
+        assert_eq!(b.pixel(0, 0), Gray8::new(0));
+    }
+
+    #[test]
+    fn clamped() {
+        let mut r = RasterBuilder::<Gray8>::new()
+            .with_color(3, 3, Gray8::new(200));
+        r.set_pixel(1, 1, Gray8::new(250));
+        let s = r.convolve(&Kernel::sharpen());
+        assert_eq!(s.pixel(1, 1), Gray8::new(255));
+        let e = RasterBuilder::<Gray8>::new()
+            .with_color(3, 3, Gray8::new(50))
+            .convolve(&Kernel::emboss());
+        assert_eq!(e.pixel(1, 1), Gray8::new(50));
+    }
+
+    #[test]
+    #[should_panic]
+    fn even_kernel() {
+        Kernel::new(2, 3, vec![0.0; 6], 1.0);
+    }
+
+    #[test]
+    fn blur_zero() {
+        let r = ramp();
+        assert_eq!(r.gaussian_blur(0.0), r);
+        assert_eq!(r.gaussian_blur(0.1), r);
+    }
+
+    #[test]
+    fn blur_impulse() {
+        let sigma = 4.0;
+        let mut r = RasterBuilder::<Gray64>::new().with_clear(65, 65);
+        r.set_pixel(32, 32, Gray64::new(1.0));
+        let b = r.gaussian_blur(sigma as f32);
+        let peak = 1.0 / (2.0 * std::f64::consts::PI * sigma * sigma);
+        let points = [(32, 32), (36, 32), (32, 24), (40, 40), (20, 30)];
+        for (x, y) in points.iter() {
+            let dx = f64::from(*x) - 32.0;
+            let dy = f64::from(*y) - 32.0;
+            let g = peak * (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp();
+            let v = f64::from(b.pixel(*x, *y).value());
+            assert!((v - g).abs() < g * 0.02 + 1e-5, "{} {}", v, g);
+        }
+        let total: f64 =
+            b.as_slice().iter().map(|p| f64::from(p.value())).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn blur_no_halo() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(16, 4, |x, _y| {
+            if x < 8 {
+                SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0xFF)
+            } else {
+                SRgba8::default()
+            }
+        });
+        let b = r.gaussian_blur(2.0);
+        for p in b.as_slice() {
+            if u8::from(p.alpha()) >= 0x10 {
+                assert_eq!(u8::from(p.red()), 0xFF, "{:?}", p);
+            }
+        }
+        assert!(u8::from(b.pixel(8, 0).alpha()) < 0xFF);
+    }
+
+    /// Vertical step edge between columns 3 and 4
+    fn step() -> Raster<SRgb8> {
+        RasterBuilder::<SRgb8>::new().with_fn(8, 6, |x, _y| {
+            let v = if x < 4 { 0x00 } else { 0xFF };
+            SRgb8::new(v, v, v)
+        })
+    }
+
+    /// Get the sum of edge response in each column
+    fn columns(e: &Raster<Mask16>) -> Vec<u32> {
+        (0..e.width())
+            .map(|x| {
+                (0..e.height())
+                    .map(|y| u32::from(u16::from(e.pixel(x, y).alpha())))
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sobel_step() {
+        let e = step().sobel();
+        let full = 0xFFFF * 6;
+        assert_eq!(columns(&e), vec![0, 0, 0, full, full, 0, 0, 0]);
+        // An edge centered on a pixel has one full-scale column
+        let r = RasterBuilder::<SGray32>::new().with_fn(7, 5, |x, _y| {
+            SGray32::new((x as f32 - 2.0).clamp(0.0, 2.0) / 2.0)
+        });
+        let (full, half) = (0xFFFF * 5, 0x8000 * 5);
+        assert_eq!(columns(&r.sobel()), vec![0, 0, half, full, half, 0, 0]);
+    }
+
+    #[test]
+    fn sobel_flat() {
+        let r = RasterBuilder::<SRgba16>::new().with_fn(5, 5, |x, y| {
+            SRgba16::with_alpha(0x4000, 0x8000, 0x2000, (x * y) as u16 * 999)
+        });
+        let e = r.sobel();
+        assert!(e.as_slice().iter().all(|p| *p == Mask16::new(0)));
+        let r = step().transpose();
+        let rows = columns(&r.sobel().transpose());
+        assert_eq!(rows, columns(&step().sobel()));
+        let e = RasterBuilder::<SGray8>::new().with_clear(0, 3).sobel();
+        assert_eq!((e.width(), e.height()), (0, 3));
+    }
+}
+
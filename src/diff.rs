@@ -0,0 +1,144 @@
+// diff.rs      Raster comparison.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Ch8, Channel, ColorModel, Pixel, Raster, RasterBuilder, SGray8};
+
+/// Differences between two [Raster](struct.Raster.html)s.
+///
+/// Use `Raster`::[diff](struct.Raster.html#method.diff) to create.
+///
+/// ### Check that two rasters are within 1 LSB
+/// ```
+/// # use pix::*;
+/// let clr = SRgb8::new(9, 9, 9);
+/// let a = RasterBuilder::<SRgb8>::new().with_color(4, 4, clr);
+/// let mut b = a.clone();
+/// b.set_pixel(1, 2, SRgb8::new(9, 10, 9));
+/// let d = a.diff(&b, Ch8::new(1));
+/// assert_eq!(d.count, 0);
+/// assert_eq!(d.max, Ch8::new(1));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RasterDiff<C: Channel> {
+    /// Number of pixels with a channel difference greater than the tolerance
+    pub count: usize,
+    /// Maximum absolute difference of any channel, including alpha
+    pub max: C,
+}
+
+/// Get the maximum absolute channel difference between two pixels
+pub(crate) fn max_difference<P: Pixel>(p: P, q: P) -> P::Chan {
+    let d = |a: P::Chan, b: P::Chan| a.max(b) - a.min(b);
+    p.components()
+        .iter()
+        .zip(q.components())
+        .fold(d(p.alpha(), q.alpha()), |m, (a, b)| m.max(d(*a, *b)))
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Compare with another `Raster`.
+    ///
+    /// * `other` `Raster` to compare.
+    /// * `tolerance` Largest channel difference for pixels to be considered
+    ///   the same.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    ///
+    /// ### Compare with a tolerance
+    /// ```
+    /// # use pix::*;
+    /// let a = RasterBuilder::new().with_color(2, 2, Gray32::new(0.5));
+    /// let b = RasterBuilder::new().with_color(2, 2, Gray32::new(0.51));
+    /// assert_eq!(a.diff(&b, Ch32::new(0.001)).count, 4);
+    /// assert_eq!(a.diff(&b, Ch32::new(0.02)).count, 0);
+    /// ```
+    pub fn diff(
+        &self,
+        other: &Raster<P>,
+        tolerance: P::Chan,
+    ) -> RasterDiff<P::Chan> {
+        self.assert_same_size(other);
+        let mut count = 0;
+        let mut max = P::Chan::MIN;
+        for (p, q) in self.as_slice().iter().zip(other.as_slice()) {
+            let d = max_difference(*p, *q);
+            if d > tolerance {
+                count += 1;
+            }
+            max = max.max(d);
+        }
+        RasterDiff { count, max }
+    }
+    /// Create a heatmap of differences with another `Raster`.
+    ///
+    /// * `other` `Raster` to compare.
+    ///
+    /// Each pixel of the heatmap is the maximum absolute channel difference
+    /// (including alpha), converted to 8 bits.  Identical pixels are black.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    ///
+    /// ### Find where two rasters differ
+    /// ```
+    /// # use pix::*;
+    /// let a = RasterBuilder::<SRgba16>::new().with_clear(4, 4);
+    /// let mut b = a.clone();
+    /// b.set_pixel(3, 0, SRgba16::with_alpha(0, 0, 0, 0xFFFF));
+    /// let heat = a.diff_heatmap(&b);
+    /// assert_eq!(heat.pixel(3, 0), SGray8::new(0xFF));
+    /// assert_eq!(heat.pixel(0, 0), SGray8::new(0));
+    /// ```
+    pub fn diff_heatmap(&self, other: &Raster<P>) -> Raster<SGray8>
+    where
+        Ch8: From<P::Chan>,
+    {
+        self.assert_same_size(other);
+        let pixels: Vec<_> = self
+            .as_slice()
+            .iter()
+            .zip(other.as_slice())
+            .map(|(p, q)| {
+                let v = Ch8::from(max_difference(*p, *q));
+                SGray8::with_rgba([v, v, v, Ch8::MAX])
+            })
+            .collect();
+        RasterBuilder::new().with_pixels(self.width(), self.height(), pixels)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn diff() {
+        let a = RasterBuilder::<SGrayAlpha8>::new()
+            .with_fn(5, 3, |x, y| SGrayAlpha8::new((x * 10 + y) as u8));
+        let mut b = a.clone();
+        let d = a.diff(&b, Ch8::new(0));
+        assert_eq!(d, RasterDiff { count: 0, max: Ch8::new(0) });
+        b.set_pixel(0, 0, SGrayAlpha8::with_alpha(3, 0xFF));
+        b.set_pixel(4, 2, SGrayAlpha8::with_alpha(42, 0xF0));
+        let d = a.diff(&b, Ch8::new(2));
+        assert_eq!(d.count, 2);
+        assert_eq!(d.max, Ch8::new(0x0F));
+        assert_eq!(a.diff(&b, Ch8::new(3)).count, 1);
+        let heat = a.diff_heatmap(&b);
+        assert_eq!(heat.pixel(0, 0), SGray8::new(3));
+        assert_eq!(heat.pixel(4, 2), SGray8::new(0x0F));
+        assert_eq!(heat.pixel(2, 1), SGray8::new(0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn diff_size() {
+        let a = RasterBuilder::<SRgb8>::new().with_clear(4, 4);
+        let b = RasterBuilder::<SRgb8>::new().with_clear(4, 5);
+        a.diff(&b, Ch8::new(0));
+    }
+}
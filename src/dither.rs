@@ -0,0 +1,170 @@
+// dither.rs    Error diffusion dithering.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::hsv::channel_from_f32;
+use crate::{Ch32, Pixel, Raster, RasterBuilder};
+
+/// Add a weighted error to one pixel of an error row
+fn spread(row: &mut [f32], x: usize, err: &[f32], weight: f32) {
+    let n = err.len();
+    for (e, v) in row[x * n..(x + 1) * n].iter_mut().zip(err) {
+        *e += v * weight;
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Convert to another pixel format, with Floyd-Steinberg dithering.
+    ///
+    /// * `Q` Destination pixel format.
+    ///
+    /// The quantization error of each channel is diffused to neighboring
+    /// pixels, which breaks up banding when reducing bit depth.  Rows are
+    /// scanned in alternating directions (serpentine) to avoid directional
+    /// artifacts.  Diffused values are clamped to the channel range.
+    ///
+    /// The alpha channel is converted without dithering.
+    ///
+    /// ### Reduce a 16-bit gradient to 8 bits
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray16>::new()
+    ///     .with_fn(64, 4, |x, _y| SGray16::new(0x1000 + x as u16 * 4));
+    /// let d = r.dither_to::<SGray8>();
+    /// assert_eq!(d.width(), 64);
+    /// ```
+    pub fn dither_to<Q>(&self) -> Raster<Q>
+    where
+        Q: Pixel,
+        Q::Chan: From<P::Chan>,
+        P::Chan: From<Q::Chan>,
+        Ch32: From<P::Chan>,
+    {
+        let n = P::CHANNELS;
+        let width = self.width() as usize;
+        let mut err = vec![0.0; width * n];
+        let mut next = vec![0.0; width * n];
+        let mut diff = vec![0.0; n];
+        let mut pixels = Vec::with_capacity(self.as_slice().len());
+        for (y, row) in self.rows().enumerate() {
+            let mut out = vec![Q::default(); width];
+            let reverse = y % 2 == 1;
+            for i in 0..width {
+                let x = if reverse { width - 1 - i } else { i };
+                let src = row[x];
+                let colors = src.components().len();
+                let mut want = src;
+                for (c, (v, d)) in
+                    want.channels_mut().iter_mut().zip(&mut diff).enumerate()
+                {
+                    let value = f32::from(Ch32::from(*v));
+                    let value = if c < colors {
+                        (value + err[x * n + c]).clamp(0.0, 1.0)
+                    } else {
+                        value
+                    };
+                    *v = channel_from_f32::<P::Chan>(value);
+                    *d = value;
+                }
+                let q = want.convert::<Q>();
+                let got = q.convert::<P>();
+                for (c, d) in diff.iter_mut().enumerate() {
+                    *d = if c < colors {
+                        *d - f32::from(Ch32::from(got.channels()[c]))
+                    } else {
+                        0.0
+                    };
+                }
+                out[x] = q;
+                let (fwd, back) = if reverse {
+                    (x.checked_sub(1), x.checked_add(1))
+                } else {
+                    (x.checked_add(1), x.checked_sub(1))
+                };
+                let fwd = fwd.filter(|&x| x < width);
+                let back = back.filter(|&x| x < width);
+                if let Some(f) = fwd {
+                    spread(&mut err, f, &diff, 7.0 / 16.0);
+                    spread(&mut next, f, &diff, 1.0 / 16.0);
+                }
+                if let Some(b) = back {
+                    spread(&mut next, b, &diff, 3.0 / 16.0);
+                }
+                spread(&mut next, x, &diff, 5.0 / 16.0);
+            }
+            pixels.extend(out);
+            std::mem::swap(&mut err, &mut next);
+            next.iter_mut().for_each(|e| *e = 0.0);
+        }
+        RasterBuilder::new().with_pixels(self.width(), self.height(), pixels)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    /// Count columns which contain more than one value
+    fn mixed_columns<P: Pixel>(r: &Raster<P>) -> u32 {
+        let mixed = |x| (1..r.height()).any(|y| r.pixel(x, y) != r.pixel(x, 0));
+        (0..r.width()).filter(|&x| mixed(x)).count() as u32
+    }
+
+    fn gradient() -> Raster<SGray16> {
+        RasterBuilder::<SGray16>::new()
+            .with_fn(256, 16, |x, _y| SGray16::new(0x4000 + x as u16 * 4))
+    }
+
+    #[test]
+    fn banding() {
+        let r = gradient();
+        let plain = RasterBuilder::<SGray8>::new().with_raster(&r);
+        let dither = r.dither_to::<SGray8>();
+        assert_eq!(mixed_columns(&plain), 0);
+        assert!(mixed_columns(&dither) > 192);
+        // Dithering only spreads values to neighboring levels
+        let hp = plain.histogram();
+        let hd = dither.histogram();
+        let levels = |h: &Histogram| {
+            h.channel(0).iter().filter(|c| **c > 0).count()
+        };
+        assert!(levels(&hd) >= levels(&hp));
+        assert!(levels(&hd) <= levels(&hp) + 2);
+    }
+
+    #[test]
+    fn column_mean() {
+        let r = gradient();
+        let dither = r.dither_to::<SGray8>();
+        for x in 0..256 {
+            let want = f32::from(Ch32::from(r.pixel(x, 0).value()));
+            let mean = (0..16)
+                .map(|y| f32::from(Ch32::from(dither.pixel(x, y).value())))
+                .sum::<f32>()
+                / 16.0;
+            assert!((want - mean).abs() < 1.0 / 255.0, "x: {}", x);
+        }
+    }
+
+    #[test]
+    fn clamped() {
+        let r = RasterBuilder::<Gray32>::new()
+            .with_fn(16, 16, |x, y| Gray32::new(((x + y) % 2) as f32));
+        let d = r.dither_to::<Gray8>();
+        for (a, b) in r.as_slice().iter().zip(d.as_slice()) {
+            assert_eq!(Gray8::new(Ch8::from(a.value())), *b);
+        }
+    }
+
+    #[test]
+    fn alpha_not_dithered() {
+        let r = RasterBuilder::<SRgba16>::new().with_fn(32, 8, |x, _y| {
+            SRgba16::with_alpha(0x1234, 0x5678, 0x9ABC, 0x1000 + x as u16)
+        });
+        let plain = RasterBuilder::<SRgba8>::new().with_raster(&r);
+        let dither = r.dither_to::<SRgba8>();
+        for (a, b) in plain.as_slice().iter().zip(dither.as_slice()) {
+            assert_eq!(a.alpha(), b.alpha());
+        }
+    }
+}
@@ -0,0 +1,317 @@
+// downscale.rs Area-averaging downscaling.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::Straight;
+use crate::histogram::int_scale;
+use crate::{Ch64, Pixel, Raster, RasterBuilder};
+use std::any::TypeId;
+
+/// Channel sums for one row of output pixels
+enum Sums {
+    /// Exact sums of integer channel values
+    Int(Vec<u64>, f64),
+    /// Sums of floating-point channel values
+    Float(Vec<f64>),
+}
+
+impl Sums {
+    /// Create sums for a row of channel values
+    fn new(len: usize, scale: Option<f64>) -> Self {
+        match scale {
+            Some(scale) => Sums::Int(vec![0; len], scale),
+            None => Sums::Float(vec![0.0; len]),
+        }
+    }
+    /// Add a channel value
+    fn add(&mut self, i: usize, v: f64) {
+        match self {
+            Sums::Int(sums, scale) => sums[i] += (v * *scale).round() as u64,
+            Sums::Float(sums) => sums[i] += v,
+        }
+    }
+    /// Add a channel value, premultiplied by alpha
+    fn add_weighted(&mut self, i: usize, v: f64, alpha: f64) {
+        match self {
+            Sums::Int(sums, scale) => {
+                let v = (v * *scale).round() as u64;
+                sums[i] += v * (alpha * *scale).round() as u64;
+            }
+            Sums::Float(sums) => sums[i] += v * alpha,
+        }
+    }
+    /// Get the mean of a sum, rounded for integer channels
+    fn mean(&self, i: usize, count: u64) -> f64 {
+        match self {
+            Sums::Int(sums, scale) => {
+                ((sums[i] + count / 2) / count) as f64 / scale
+            }
+            Sums::Float(sums) => sums[i] / count as f64,
+        }
+    }
+    /// Get the ratio of a premultiplied sum to an alpha sum
+    fn unpremultiply(&self, i: usize, alpha: usize) -> f64 {
+        match self {
+            Sums::Int(sums, _) if sums[alpha] == 0 => 0.0,
+            Sums::Int(sums, scale) => {
+                let a = sums[alpha];
+                ((sums[i] + a / 2) / a) as f64 / scale
+            }
+            Sums::Float(sums) if sums[alpha] > 0.0 => sums[i] / sums[alpha],
+            Sums::Float(_) => 0.0,
+        }
+    }
+    /// Reset all sums to zero
+    fn clear(&mut self) {
+        match self {
+            Sums::Int(sums, _) => sums.iter_mut().for_each(|s| *s = 0),
+            Sums::Float(sums) => sums.iter_mut().for_each(|s| *s = 0.0),
+        }
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Downscale by averaging blocks of pixels.
+    ///
+    /// * `fx` Horizontal factor; width of each block.
+    /// * `fy` Vertical factor; height of each block.
+    ///
+    /// Each `fx` by `fy` block becomes one pixel, with every channel
+    /// averaged separately (including *alpha*).  When the width or height is
+    /// not a multiple of the factor, the last column or row of blocks is
+    /// smaller, and averaged over only the pixels it contains.
+    ///
+    /// Sums of 8- and 16-bit channels are exact, and means are rounded to
+    /// nearest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fx` or `fy` is zero.
+    ///
+    /// ### Make a thumbnail
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(100, 75, SRgb8::new(0x20, 0x40, 0x80));
+    /// let t = r.downscale_by(8, 8);
+    /// assert_eq!((t.width(), t.height()), (13, 10));
+    /// assert_eq!(t.pixel(12, 9), SRgb8::new(0x20, 0x40, 0x80));
+    /// ```
+    pub fn downscale_by(&self, fx: u32, fy: u32) -> Raster<P>
+    where
+        Ch64: From<P::Chan>,
+        P::Chan: From<Ch64>,
+    {
+        assert!(fx > 0 && fy > 0, "downscale factor must be non-zero");
+        self.downscale(fx, fy, false)
+    }
+    /// Create a chain of mipmap levels.
+    ///
+    /// Each level is half the size of the previous one, rounded up.  The
+    /// first level is half the size of this raster; the raster itself is not
+    /// included.  At odd edges, pixels are averaged over a 1- or 2-pixel
+    /// footprint.
+    ///
+    /// The number of levels is `floor(log2(max(width, height)))`.  When the
+    /// larger dimension is a power of two, the last level is 1x1; otherwise,
+    /// rounding up leaves it larger (5x3 has levels of 3x2 and 2x1).  An
+    /// empty raster has no levels.  Formats with straight *alpha* are
+    /// averaged using premultiplied values, so transparent pixels do not
+    /// darken their neighbors.
+    ///
+    /// ### Create mipmaps for a texture
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgba8>::new().with_clear(256, 64);
+    /// let levels = r.mipmaps();
+    /// assert_eq!(levels.len(), 8);
+    /// assert_eq!((levels[0].width(), levels[0].height()), (128, 32));
+    /// assert_eq!((levels[7].width(), levels[7].height()), (1, 1));
+    /// ```
+    pub fn mipmaps(&self) -> Vec<Raster<P>>
+    where
+        Ch64: From<P::Chan>,
+        P::Chan: From<Ch64>,
+    {
+        let mut levels: Vec<Raster<P>> = vec![];
+        if self.width() == 0 || self.height() == 0 {
+            return levels;
+        }
+        let count = self.width().max(self.height()).ilog2();
+        for _ in 0..count {
+            let prev = levels.last().unwrap_or(self);
+            let level = prev.downscale(2, 2, true);
+            levels.push(level);
+        }
+        levels
+    }
+    /// Downscale by averaging blocks of pixels.
+    ///
+    /// * `premultiply` Average straight alpha formats as premultiplied.
+    fn downscale(&self, fx: u32, fy: u32, premultiply: bool) -> Raster<P>
+    where
+        Ch64: From<P::Chan>,
+        P::Chan: From<Ch64>,
+    {
+        let width = self.width().div_ceil(fx);
+        let height = self.height().div_ceil(fy);
+        if width == 0 || height == 0 {
+            return RasterBuilder::new().with_clear(width, height);
+        }
+        let n = P::CHANNELS;
+        let colors = P::default().components().len();
+        let premultiply = premultiply
+            && n > colors
+            && TypeId::of::<P::Alpha>() == TypeId::of::<Straight>();
+        let (fx, fy) = (fx as usize, fy as usize);
+        let src_width = self.width() as usize;
+        let src_height = self.height() as usize;
+        let chans = self.as_chan_slice();
+        let row_len = src_width * n;
+        let mut sums = Sums::new(width as usize * n, int_scale::<P::Chan>());
+        let len = width as usize * height as usize * n;
+        let mut buffer = Vec::with_capacity(len);
+        for by in 0..height as usize {
+            sums.clear();
+            let rows = by * fy..(by * fy + fy).min(src_height);
+            let bh = rows.len();
+            for row in chans[rows.start * row_len..rows.end * row_len]
+                .chunks_exact(row_len)
+            {
+                for (x, px) in row.chunks_exact(n).enumerate() {
+                    let base = (x / fx) * n;
+                    let alpha = f64::from(Ch64::from(px[n - 1]));
+                    for (c, v) in px.iter().enumerate() {
+                        let v = f64::from(Ch64::from(*v));
+                        if premultiply && c < colors {
+                            sums.add_weighted(base + c, v, alpha);
+                        } else {
+                            sums.add(base + c, v);
+                        }
+                    }
+                }
+            }
+            for bx in 0..width as usize {
+                let bw = (src_width - bx * fx).min(fx);
+                let count = (bw * bh) as u64;
+                let base = bx * n;
+                for c in 0..n {
+                    let v = if premultiply && c < colors {
+                        sums.unpremultiply(base + c, base + n - 1)
+                    } else {
+                        sums.mean(base + c, count)
+                    };
+                    buffer.push(P::Chan::from(Ch64::new(v)));
+                }
+            }
+        }
+        RasterBuilder::new()
+            .try_with_chan_buffer(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn rounding() {
+        let r = RasterBuilder::<Gray8>::new()
+            .with_u8_buffer(4, 1, vec![0, 1, 1, 2]);
+        let d = r.downscale_by(2, 1);
+        assert_eq!(d.as_u8_slice(), &[1, 2]);
+        let r = RasterBuilder::<Gray16>::new()
+            .with_u16_buffer(3, 1, vec![100, 101, 101]);
+        let d = r.downscale_by(3, 1);
+        assert_eq!(d.pixel(0, 0), Gray16::new(101));
+        let r = RasterBuilder::<Gray32>::new()
+            .with_fn(2, 1, |x, _y| Gray32::new(0.25 * (x + 1) as f32));
+        assert_eq!(r.downscale_by(2, 2).pixel(0, 0), Gray32::new(0.375));
+    }
+
+    #[test]
+    fn partial_blocks() {
+        let r = RasterBuilder::<Gray8>::new().with_u8_buffer(
+            3,
+            3,
+            vec![
+                10, 20, 90, //
+                30, 40, 70, //
+                50, 60, 200,
+            ],
+        );
+        let d = r.downscale_by(2, 2);
+        assert_eq!((d.width(), d.height()), (2, 2));
+        assert_eq!(d.as_u8_slice(), &[25, 80, 55, 200]);
+    }
+
+    #[test]
+    fn alpha() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(4, 2, |x, _y| {
+            SRgba8::with_alpha(0x10, 0x20, 0x30, (x * 0x40) as u8)
+        });
+        let d = r.downscale_by(4, 2);
+        assert_eq!(d.pixel(0, 0), SRgba8::with_alpha(0x10, 0x20, 0x30, 0x60));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_factor() {
+        RasterBuilder::<Gray8>::new()
+            .with_clear(4, 4)
+            .downscale_by(0, 2);
+    }
+
+    #[test]
+    fn mipmap_sizes() {
+        let r = RasterBuilder::<Gray8>::new().with_clear(8, 8);
+        let sizes: Vec<_> =
+            r.mipmaps().iter().map(|l| (l.width(), l.height())).collect();
+        assert_eq!(sizes, vec![(4, 4), (2, 2), (1, 1)]);
+        let r = RasterBuilder::<Gray8>::new().with_clear(5, 3);
+        let sizes: Vec<_> =
+            r.mipmaps().iter().map(|l| (l.width(), l.height())).collect();
+        assert_eq!(sizes, vec![(3, 2), (2, 1)]);
+        let r = RasterBuilder::<Gray8>::new().with_clear(1, 7);
+        let sizes: Vec<_> =
+            r.mipmaps().iter().map(|l| (l.width(), l.height())).collect();
+        assert_eq!(sizes, vec![(1, 4), (1, 2)]);
+        let r = RasterBuilder::<Gray8>::new().with_clear(1, 1);
+        assert!(r.mipmaps().is_empty());
+        let r = RasterBuilder::<Gray8>::new().with_clear(0, 2);
+        assert!(r.mipmaps().is_empty());
+        let r = RasterBuilder::<Gray8>::new().with_clear(4, 0);
+        assert!(r.mipmaps().is_empty());
+    }
+
+    #[test]
+    fn mipmap_values() {
+        let r = RasterBuilder::<Gray8>::new()
+            .with_u8_buffer(5, 1, vec![10, 30, 90, 50, 7]);
+        let levels = r.mipmaps();
+        assert_eq!(levels[0].as_u8_slice(), &[20, 70, 7]);
+        assert_eq!(levels[1].as_u8_slice(), &[45, 7]);
+    }
+
+    #[test]
+    fn mipmap_premultiplied() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(2, 1, |x, _y| {
+            if x == 0 {
+                SRgba8::with_alpha(0xFF, 0x00, 0x00, 0xFF)
+            } else {
+                SRgba8::with_alpha(0x00, 0x00, 0xFF, 0x00)
+            }
+        });
+        let levels = r.mipmaps();
+        assert_eq!(
+            levels[0].pixel(0, 0),
+            SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80)
+        );
+        let r = RasterBuilder::<SRgba32>::new().with_fn(2, 2, |x, _y| {
+            SRgba32::with_alpha(x as f32, 0.5, 0.0, 0.25 + x as f32 * 0.5)
+        });
+        let p = r.mipmaps()[0].pixel(0, 0);
+        assert_eq!(p, SRgba32::with_alpha(0.75, 0.5, 0.0, 0.5));
+    }
+}
@@ -0,0 +1,699 @@
+// draw.rs      Drawing primitives.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::diff::max_difference;
+use crate::{BitRaster, Channel, Pixel, Raster, Region};
+
+/// Neighboring pixels for [Raster](struct.Raster.html)`::`
+/// [flood_fill_with](struct.Raster.html#method.flood_fill_with).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// Pixels sharing an edge (left, right, above and below)
+    Four,
+    /// Pixels sharing an edge or a corner
+    Eight,
+}
+
+/// Get the range of steps which keep a line coordinate within bounds.
+///
+/// * `start` Starting coordinate.
+/// * `forward` Whether the coordinate increases with each step.
+/// * `steps` Number of steps after the start.
+/// * `len` Length of the bounds.
+fn clip_steps(start: i64, forward: bool, steps: i64, len: i64) -> (i64, i64) {
+    if forward {
+        ((-start).max(0), steps.min(len - 1 - start))
+    } else {
+        ((start - (len - 1)).max(0), steps.min(start))
+    }
+}
+
+/// Visit the spans of one quadrant of an ellipse, using the midpoint
+/// algorithm.
+///
+/// * `rx` Horizontal radius.
+/// * `ry` Vertical radius.
+/// * `span` Called with `(dy, lo, hi)` for each row, from `ry` down to 0.
+///
+/// The outline of each row covers horizontal offsets `lo..=hi`.
+fn quadrant_spans<F>(rx: i64, ry: i64, mut span: F)
+where
+    F: FnMut(i64, i64, i64),
+{
+    if rx == 0 || ry == 0 {
+        for dy in (0..=ry).rev() {
+            span(dy, 0, rx);
+        }
+        return;
+    }
+    // Decision variables are scaled by 4 to keep them integral
+    let (rx2, ry2) = (i128::from(rx).pow(2), i128::from(ry).pow(2));
+    let (mut x, mut y, mut lo) = (0, ry, 0);
+    let mut dx = 0;
+    let mut dy = 2 * rx2 * i128::from(ry);
+    let mut d = 4 * ry2 - 4 * rx2 * i128::from(ry) + rx2;
+    // Region 1: slope magnitude less than 1, step along x
+    while dx < dy {
+        dx += 2 * ry2;
+        if d < 0 {
+            d += 4 * (dx + ry2);
+        } else {
+            span(y, lo, x);
+            y -= 1;
+            lo = x + 1;
+            dy -= 2 * rx2;
+            d += 4 * (dx - dy + ry2);
+        }
+        x += 1;
+    }
+    // Region 2: slope magnitude at least 1, step along y
+    let x2 = i128::from(2 * x + 1);
+    let mut d = ry2 * x2 * x2 + 4 * rx2 * (i128::from(y) - 1).pow(2)
+        - 4 * rx2 * ry2;
+    while y > 0 {
+        span(y, lo, x);
+        dy -= 2 * rx2;
+        if d > 0 {
+            d += 4 * (rx2 - dy);
+        } else {
+            x += 1;
+            dx += 2 * ry2;
+            d += 4 * (dx - dy + rx2);
+        }
+        y -= 1;
+        lo = x;
+    }
+    span(0, lo, rx);
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Fill a horizontal span of one row, clipped to the raster.
+    fn fill_span(&mut self, y: i64, xa: i64, xb: i64, clr: P) {
+        let width = i64::from(self.width());
+        let (xa, xb) = (xa.max(0), xb.min(width - 1));
+        if (0..i64::from(self.height())).contains(&y) && xa <= xb {
+            let row = self.as_slice_row_mut(y as u32);
+            row[xa as usize..=xb as usize].fill(clr);
+        }
+    }
+    /// Draw a line, using Bresenham's algorithm.
+    ///
+    /// * `x0` X-position of start point.
+    /// * `y0` Y-position of start point.
+    /// * `x1` X-position of end point.
+    /// * `y1` Y-position of end point.
+    /// * `clr` Color of line.
+    ///
+    /// Both end points are drawn.  Points may be outside of the `Raster`;
+    /// only the visible part of the line is drawn.
+    ///
+    /// ### Draw a diagonal line
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+    /// r.draw_line(-2, -2, 10, 10, SGray8::new(0xFF));
+    /// assert_eq!(r.pixel(0, 0), SGray8::new(0xFF));
+    /// assert_eq!(r.pixel(3, 3), SGray8::new(0xFF));
+    /// assert_eq!(r.pixel(1, 0), SGray8::new(0x00));
+    /// ```
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, clr: P) {
+        let width = i64::from(self.width());
+        let height = i64::from(self.height());
+        let (x0, y0) = (i64::from(x0), i64::from(y0));
+        let (x1, y1) = (i64::from(x1), i64::from(y1));
+        if y0 == y1 {
+            self.fill_span(y0, x0.min(x1), x0.max(x1), clr);
+            return;
+        }
+        if x0 == x1 {
+            // Vertical line
+            let (ya, yb) = (y0.min(y1).max(0), y0.max(y1).min(height - 1));
+            if (0..width).contains(&x0) && ya <= yb {
+                let w = width as usize;
+                let pixels = self.as_slice_mut();
+                let start = ya as usize * w + x0 as usize;
+                let end = yb as usize * w + x0 as usize;
+                for p in pixels[start..=end].iter_mut().step_by(w) {
+                    *p = clr;
+                }
+            }
+            return;
+        }
+        let (dx, dy) = ((x1 - x0).abs(), (y1 - y0).abs());
+        // Major axis: one step per pixel; minor axis: rounded, which is the
+        // same as Bresenham's error term, but allows skipping clipped steps
+        let steep = dy > dx;
+        let (maj0, min0, dmaj, dmin, len) = if steep {
+            (y0, x0, dy, dx, height)
+        } else {
+            (x0, y0, dx, dy, width)
+        };
+        let (maj_fwd, min_fwd) = if steep {
+            (y1 > y0, x1 > x0)
+        } else {
+            (x1 > x0, y1 > y0)
+        };
+        let (first, last) = clip_steps(maj0, maj_fwd, dmaj, len);
+        for i in first..=last {
+            let maj = if maj_fwd { maj0 + i } else { maj0 - i };
+            let off = (2 * i128::from(i) * i128::from(dmin) + i128::from(dmaj))
+                / (2 * i128::from(dmaj));
+            let off = off as i64;
+            let min = if min_fwd { min0 + off } else { min0 - off };
+            let (x, y) = if steep { (min, maj) } else { (maj, min) };
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                *self.get_pixel_mut(x as u32, y as u32).unwrap() = clr;
+            }
+        }
+    }
+    /// Draw the outline of a rectangle.
+    ///
+    /// * `reg` Region of rectangle.
+    /// * `clr` Color of outline.
+    /// * `stroke` Width of outline, in pixels.
+    ///
+    /// The outline is drawn inside of the region; if `stroke` is more than
+    /// half of the width or height, the rectangle is filled.  Parts of the
+    /// region outside of the `Raster` are clipped.
+    ///
+    /// ### Draw a frame
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(8, 8);
+    /// r.draw_rect((1, 1, 6, 6), SGray8::new(0xFF), 2);
+    /// assert_eq!(r.pixel(2, 6), SGray8::new(0xFF));
+    /// assert_eq!(r.pixel(3, 3), SGray8::new(0x00));
+    /// ```
+    pub fn draw_rect<R>(&mut self, reg: R, clr: P, stroke: u32)
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into();
+        let (x, y) = (i64::from(reg.x()), i64::from(reg.y()));
+        let right = x + i64::from(reg.width()) - 1;
+        let bottom = y + i64::from(reg.height()) - 1;
+        let stroke = i64::from(stroke);
+        if stroke == 0 {
+            return;
+        }
+        let rows = y.max(0)..(bottom + 1).min(i64::from(self.height()));
+        for row in rows {
+            if row < y + stroke || row > bottom - stroke {
+                self.fill_span(row, x, right, clr);
+            } else {
+                self.fill_span(row, x, x + stroke - 1, clr);
+                self.fill_span(row, right + 1 - stroke, right, clr);
+            }
+        }
+    }
+    /// Draw an ellipse, using the midpoint algorithm.
+    ///
+    /// * `reg` Bounding region of ellipse.
+    /// * `clr` Color of ellipse.
+    /// * `filled` Whether to fill the ellipse, or only draw the outline.
+    ///
+    /// The ellipse touches all four sides of the region.  Parts of the region
+    /// outside of the `Raster` are clipped.
+    ///
+    /// ### Draw a filled circle
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(9, 9);
+    /// r.draw_ellipse((0, 0, 9, 9), SGray8::new(0xFF), true);
+    /// assert_eq!(r.pixel(4, 0), SGray8::new(0xFF));
+    /// assert_eq!(r.pixel(4, 4), SGray8::new(0xFF));
+    /// assert_eq!(r.pixel(0, 0), SGray8::new(0x00));
+    /// ```
+    pub fn draw_ellipse<R>(&mut self, reg: R, clr: P, filled: bool)
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into();
+        if reg.width() == 0 || reg.height() == 0 {
+            return;
+        }
+        let (w, h) = (i64::from(reg.width()), i64::from(reg.height()));
+        let (rx, ry) = ((w - 1) / 2, (h - 1) / 2);
+        // Even sizes have two center columns / rows
+        let (xl, yt) = (i64::from(reg.x()) + rx, i64::from(reg.y()) + ry);
+        let (xr, yb) = (xl + (w - 1) % 2, yt + (h - 1) % 2);
+        let height = i64::from(self.height());
+        quadrant_spans(rx, ry, |dy, lo, hi| {
+            for &y in [yt - dy, yb + dy].iter() {
+                if !(0..height).contains(&y) {
+                    continue;
+                }
+                if filled {
+                    self.fill_span(y, xl - hi, xr + hi, clr);
+                } else {
+                    self.fill_span(y, xl - hi, xl - lo, clr);
+                    self.fill_span(y, xr + lo, xr + hi, clr);
+                }
+            }
+        });
+    }
+    /// Fill the region of pixels which match a seed pixel.
+    ///
+    /// * `x` X-position of seed pixel.
+    /// * `y` Y-position of seed pixel.
+    /// * `clr` Color to fill.
+    ///
+    /// Pixels equal to the seed and connected to it by edges are replaced
+    /// with `clr`.  A seed outside of the `Raster` does nothing.  See
+    /// [flood_fill_with](struct.Raster.html#method.flood_fill_with) for more
+    /// options.
+    ///
+    /// ### Fill a background
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(8, 8);
+    /// r.draw_rect((2, 2, 4, 4), SGray8::new(0xFF), 1);
+    /// r.flood_fill(0, 0, SGray8::new(0x80));
+    /// assert_eq!(r.pixel(7, 7), SGray8::new(0x80));
+    /// assert_eq!(r.pixel(3, 3), SGray8::new(0x00));
+    /// ```
+    pub fn flood_fill(&mut self, x: u32, y: u32, clr: P) {
+        self.flood_fill_with(x, y, clr, P::Chan::MIN, Connectivity::Four);
+    }
+    /// Fill the region of pixels which are similar to a seed pixel.
+    ///
+    /// * `x` X-position of seed pixel.
+    /// * `y` Y-position of seed pixel.
+    /// * `clr` Color to fill.
+    /// * `tolerance` Largest channel difference from the seed to fill.
+    /// * `connectivity` Neighbors connected to each pixel.
+    ///
+    /// A pixel matches when no channel (including *alpha*) differs from the
+    /// seed by more than `tolerance`.  Each row span is filled at once, and the
+    /// spans still to be filled are kept on a heap-allocated stack, so large
+    /// regions cannot overflow the call stack.  A seed
+    /// outside of the `Raster` does nothing.
+    ///
+    /// ### Fill a noisy region
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new()
+    ///     .with_fn(8, 1, |x, _y| SGray8::new(0x40 + x as u8));
+    /// let clr = SGray8::new(0xFF);
+    /// r.flood_fill_with(0, 0, clr, Ch8::new(4), Connectivity::Four);
+    /// assert_eq!(r.pixel(4, 0), clr);
+    /// assert_eq!(r.pixel(5, 0), SGray8::new(0x45));
+    /// ```
+    pub fn flood_fill_with(
+        &mut self,
+        x: u32,
+        y: u32,
+        clr: P,
+        tolerance: P::Chan,
+        connectivity: Connectivity,
+    ) {
+        let seed = match self.get_pixel(x, y) {
+            Some(seed) => seed,
+            None => return,
+        };
+        if clr == seed && tolerance == P::Chan::MIN {
+            return;
+        }
+        let matches = |p: P| max_difference(p, seed) <= tolerance;
+        let (width, height) = (self.width(), self.height());
+        // Filled pixels may still match, so they must be tracked separately
+        let mut filled = BitRaster::new(width, height);
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if filled.pixel(x, y) {
+                continue;
+            }
+            let row = self.as_slice_row_mut(y);
+            let (mut x0, mut x1) = (x, x);
+            while x0 > 0
+                && !filled.pixel(x0 - 1, y)
+                && matches(row[x0 as usize - 1])
+            {
+                x0 -= 1;
+            }
+            while x1 + 1 < width
+                && !filled.pixel(x1 + 1, y)
+                && matches(row[x1 as usize + 1])
+            {
+                x1 += 1;
+            }
+            row[x0 as usize..=x1 as usize].fill(clr);
+            for xi in x0..=x1 {
+                filled.set_pixel(xi, y, true);
+            }
+            let (xa, xb) = match connectivity {
+                Connectivity::Four => (x0, x1),
+                Connectivity::Eight => {
+                    (x0.saturating_sub(1), (x1 + 1).min(width - 1))
+                }
+            };
+            let above = y.checked_sub(1);
+            let below = Some(y + 1).filter(|&y| y < height);
+            for yi in above.into_iter().chain(below) {
+                // Push the first pixel of each matching run
+                let row = self.as_slice_row(yi);
+                let mut run = false;
+                for xi in xa..=xb {
+                    let m = !filled.pixel(xi, yi) && matches(row[xi as usize]);
+                    if m && !run {
+                        stack.push((xi, yi));
+                    }
+                    run = m;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    /// Get the positions of all set pixels, in row-major order
+    fn points(r: &Raster<Mask8>) -> Vec<(u32, u32)> {
+        let mut pts = vec![];
+        for y in 0..r.height() {
+            for x in 0..r.width() {
+                if r.pixel(x, y) != Mask8::new(0) {
+                    pts.push((x, y));
+                }
+            }
+        }
+        pts
+    }
+
+    fn line(x0: i32, y0: i32, x1: i32, y1: i32) -> Vec<(u32, u32)> {
+        let mut r = RasterBuilder::<Mask8>::new().with_clear(7, 7);
+        r.draw_line(x0, y0, x1, y1, Mask8::new(0xFF));
+        points(&r)
+    }
+
+    #[test]
+    fn octants() {
+        // Lines from the center, 3 pixels along and 1 across
+        let cases = [
+            ((6, 4), vec![(3, 3), (4, 3), (5, 4), (6, 4)]),
+            ((4, 6), vec![(3, 3), (3, 4), (4, 5), (4, 6)]),
+            ((2, 6), vec![(3, 3), (3, 4), (2, 5), (2, 6)]),
+            ((0, 4), vec![(3, 3), (2, 3), (1, 4), (0, 4)]),
+            ((0, 2), vec![(3, 3), (2, 3), (1, 2), (0, 2)]),
+            ((2, 0), vec![(3, 3), (3, 2), (2, 1), (2, 0)]),
+            ((4, 0), vec![(3, 3), (3, 2), (4, 1), (4, 0)]),
+            ((6, 2), vec![(3, 3), (4, 3), (5, 2), (6, 2)]),
+        ];
+        for ((x, y), pts) in cases.iter() {
+            let mut pts = pts.clone();
+            pts.sort_by_key(|(x, y)| (*y, *x));
+            assert_eq!(line(3, 3, *x, *y), pts, "to {},{}", x, y);
+        }
+    }
+
+    #[test]
+    fn diagonals() {
+        let diag: Vec<_> = (0..7).map(|i| (i, i)).collect();
+        assert_eq!(line(0, 0, 6, 6), diag);
+        assert_eq!(line(6, 6, 0, 0), diag);
+        let anti: Vec<_> = (0..7).map(|i| (6 - i, i)).collect();
+        assert_eq!(line(6, 0, 0, 6), anti);
+        assert_eq!(line(0, 6, 6, 0), anti);
+    }
+
+    #[test]
+    fn straight() {
+        let row = vec![(1, 2), (2, 2), (3, 2), (4, 2), (5, 2)];
+        assert_eq!(line(5, 2, 1, 2), row);
+        assert_eq!(line(4, 1, 4, 3), vec![(4, 1), (4, 2), (4, 3)]);
+        assert_eq!(line(2, 2, 2, 2), vec![(2, 2)]);
+        assert_eq!(line(-9, 6, 99, 6).len(), 7);
+        assert_eq!(line(0, -9, 0, 99).len(), 7);
+        assert_eq!(line(9, 1, 99, 1), vec![]);
+        assert_eq!(line(-9, -1, -1, -1), vec![]);
+    }
+
+    #[test]
+    fn clipped() {
+        assert_eq!(line(-3, -3, 9, 9), line(0, 0, 6, 6));
+        let huge = line(i32::MIN, i32::MIN, i32::MAX, i32::MAX);
+        assert_eq!(huge.len(), 7);
+        assert_eq!(line(-10, 20, 20, -10), line(0, 10, 10, 0));
+        assert_eq!(line(-5, 0, -1, 9), vec![]);
+    }
+
+    /// Draw to a 9x9 raster, in rows of `#` and `.`
+    fn draw<F>(f: F) -> Vec<String>
+    where
+        F: Fn(&mut Raster<Mask8>),
+    {
+        let mut r = RasterBuilder::<Mask8>::new().with_clear(9, 9);
+        f(&mut r);
+        r.rows()
+            .map(|row| {
+                row.iter()
+                    .map(|p| if *p == Mask8::new(0) { '.' } else { '#' })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rect() {
+        let clr = Mask8::new(0xFF);
+        let r = draw(|r| r.draw_rect((1, 2, 6, 5), clr, 1));
+        assert_eq!(
+            r,
+            vec![
+                ".........",
+                ".........",
+                ".######..",
+                ".#....#..",
+                ".#....#..",
+                ".#....#..",
+                ".######..",
+                ".........",
+                ".........",
+            ]
+        );
+        let r = draw(|r| r.draw_rect((1, 1, 7, 7), clr, 2));
+        assert_eq!(
+            r,
+            vec![
+                ".........",
+                ".#######.",
+                ".#######.",
+                ".##...##.",
+                ".##...##.",
+                ".##...##.",
+                ".#######.",
+                ".#######.",
+                ".........",
+            ]
+        );
+        let filled = draw(|r| r.fill_region((2, 2, 4, 3), clr));
+        assert_eq!(draw(|r| r.draw_rect((2, 2, 4, 3), clr, 2)), filled);
+        assert_eq!(draw(|r| r.draw_rect((2, 2, 4, 3), clr, 9)), filled);
+        assert_eq!(draw(|r| r.draw_rect((2, 2, 4, 3), clr, 0)), draw(|_| ()));
+    }
+
+    #[test]
+    fn rect_clipped() {
+        let clr = Mask8::new(0xFF);
+        let r = draw(|r| r.draw_rect((-2, 6, 5, 9), clr, 1));
+        assert_eq!(
+            r,
+            vec![
+                ".........",
+                ".........",
+                ".........",
+                ".........",
+                ".........",
+                ".........",
+                "###......",
+                "..#......",
+                "..#......",
+            ]
+        );
+        let r = draw(|r| r.draw_rect((-1, -1, 11, 11), clr, 2));
+        assert_eq!(r[0], "#########");
+        assert_eq!(r[4], "#.......#");
+        let r = draw(|r| r.draw_rect((i32::MIN, 4, u32::MAX, 1), clr, 1));
+        assert_eq!(r[4], "#########");
+    }
+
+    #[test]
+    fn ellipse() {
+        let clr = Mask8::new(0xFF);
+        let r = draw(|r| r.draw_ellipse((1, 1, 7, 7), clr, false));
+        assert_eq!(
+            r,
+            vec![
+                ".........",
+                "...###...",
+                "..#...#..",
+                ".#.....#.",
+                ".#.....#.",
+                ".#.....#.",
+                "..#...#..",
+                "...###...",
+                ".........",
+            ]
+        );
+        let r = draw(|r| r.draw_ellipse((0, 2, 8, 5), clr, true));
+        assert_eq!(
+            r,
+            vec![
+                ".........",
+                ".........",
+                "..####...",
+                ".######..",
+                "########.",
+                ".######..",
+                "..####...",
+                ".........",
+                ".........",
+            ]
+        );
+        let r = draw(|r| r.draw_ellipse((4, 1, 1, 3), clr, false));
+        assert_eq!(r[0], ".........");
+        assert_eq!(r[1..4], ["....#....", "....#....", "....#...."]);
+        assert_eq!(r[4], ".........");
+        let empty = draw(|r| r.draw_ellipse((1, 1, 0, 5), clr, true));
+        assert_eq!(empty, draw(|_| ()));
+    }
+
+    #[test]
+    fn ellipse_symmetric() {
+        let clr = Mask8::new(0xFF);
+        for w in 1..=9 {
+            for h in 1..=9 {
+                for &filled in [false, true].iter() {
+                    let reg = (0, 0, w, h);
+                    let r = draw(|r| r.draw_ellipse(reg, clr, filled));
+                    let rows = &r[..h as usize];
+                    let w = w as usize;
+                    // Touches all four sides
+                    assert!(rows[0].contains('#'));
+                    assert!(rows[h as usize - 1].contains('#'));
+                    assert!(rows.iter().any(|row| row.starts_with('#')));
+                    assert!(rows.iter().any(|row| &row[w - 1..w] == "#"));
+                    for row in rows {
+                        let rev: String = row[..w].chars().rev().collect();
+                        assert_eq!(row[..w], rev);
+                    }
+                    for (a, b) in rows.iter().zip(rows.iter().rev()) {
+                        assert_eq!(a, b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ellipse_clipped() {
+        let clr = Mask8::new(0xFF);
+        for &filled in [false, true].iter() {
+            let mut big = RasterBuilder::<Mask8>::new().with_clear(21, 21);
+            big.draw_ellipse((0, 0, 21, 17), clr, filled);
+            let mut r = RasterBuilder::<Mask8>::new().with_clear(9, 9);
+            r.draw_ellipse((-6, -3, 21, 17), clr, filled);
+            let crop: Vec<_> = big.region_iter((6, 3, 9, 9)).collect();
+            assert_eq!(r.as_slice(), &crop[..]);
+        }
+    }
+
+    /// Ring of radius 3 centered in a 9x9 raster
+    fn ring() -> Raster<Mask8> {
+        let mut r = RasterBuilder::<Mask8>::new().with_clear(9, 9);
+        r.draw_ellipse((1, 1, 7, 7), Mask8::new(0xFF), false);
+        r
+    }
+
+    #[test]
+    fn flood_ring() {
+        let fill = Mask8::new(0x80);
+        let mut inner = ring();
+        inner.flood_fill(4, 4, fill);
+        let mut outer = ring();
+        outer.flood_fill(0, 0, fill);
+        for y in 0..9 {
+            for x in 0..9 {
+                let p = ring().pixel(x, y);
+                let (i, o) = (inner.pixel(x, y), outer.pixel(x, y));
+                if p == Mask8::new(0xFF) {
+                    assert_eq!((i, o), (p, p));
+                } else {
+                    // Every clear pixel is filled by exactly one of them
+                    assert!((i == fill) != (o == fill), "{} {}", x, y);
+                }
+            }
+        }
+        assert_eq!(inner.pixel(4, 4), fill);
+        assert_eq!(inner.pixel(0, 0), Mask8::new(0));
+        assert_eq!(outer.pixel(8, 8), fill);
+        assert_eq!(outer.pixel(4, 4), Mask8::new(0));
+    }
+
+    #[test]
+    fn flood_eight() {
+        // The ring is only connected diagonally at its corners
+        let fill = Mask8::new(0x80);
+        let mut r = ring();
+        r.flood_fill_with(4, 4, fill, Ch8::new(0), Connectivity::Eight);
+        assert_eq!(r.pixel(0, 0), fill);
+        let mut r = ring();
+        r.flood_fill_with(4, 1, fill, Ch8::new(0), Connectivity::Four);
+        assert_eq!(r.pixel(3, 1), fill);
+        assert_eq!(r.pixel(2, 2), Mask8::new(0xFF));
+        let mut r = ring();
+        r.flood_fill_with(4, 1, fill, Ch8::new(0), Connectivity::Eight);
+        assert_eq!(r.pixel(2, 2), fill);
+        assert_eq!(r.pixel(4, 7), fill);
+    }
+
+    #[test]
+    fn flood_tolerance() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_fn(8, 8, |x, y| {
+            SRgb8::new(0x80 + x as u8, 0x80 - y as u8, 0x80)
+        });
+        let clr = SRgb8::new(0x80, 0x80, 0x80);
+        r.flood_fill_with(0, 0, clr, Ch8::new(3), Connectivity::Four);
+        for y in 0..8 {
+            for x in 0..8 {
+                let filled = r.pixel(x, y) == clr;
+                assert_eq!(filled, x <= 3 && y <= 3, "{} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn flood_same_color() {
+        let mut r = ring();
+        r.flood_fill(4, 4, Mask8::new(0));
+        assert_eq!(r, ring());
+        r.flood_fill(9, 0, Mask8::new(0x80));
+        assert_eq!(r, ring());
+        // Filled pixels which still match are not filled again
+        let clr = Mask8::new(1);
+        r.flood_fill_with(0, 0, clr, Ch8::new(1), Connectivity::Four);
+        assert_eq!(r.pixel(8, 8), clr);
+    }
+
+    #[test]
+    fn flood_deep() {
+        // Serpentine path through walls with alternating gaps
+        let wall = Mask8::new(0xFF);
+        let mut r = RasterBuilder::<Mask8>::new().with_fn(64, 512, |x, y| {
+            match (y % 4, y / 4 % 2) {
+                (3, 0) if x < 63 => wall,
+                (3, 1) if x > 0 => wall,
+                _ => Mask8::new(0),
+            }
+        });
+        let fill = Mask8::new(0x80);
+        r.flood_fill(0, 0, fill);
+        assert_eq!(r.pixel(0, 511), fill);
+        r.flood_fill(0, 0, Mask8::new(0));
+        assert!(r.as_slice().iter().all(|p| *p != fill));
+    }
+}
+
@@ -0,0 +1,194 @@
+// dynraster.rs Rasters with runtime pixel format.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{
+    Ch16, Ch32, Ch8, DynPnm, Mask8, PixFmt, Pixel, Raster, SGray16, SGray8,
+    SGrayAlpha16, SGrayAlpha8, SRgb16, SRgb32, SRgb8, SRgba16, SRgba32,
+    SRgba8,
+};
+
+/// `Raster` with a pixel format chosen at runtime.
+///
+/// Image decoders often don't know the pixel format until a file header is
+/// parsed.  They can return a `DynRaster`, and let the caller
+/// [convert](enum.DynRaster.html#method.convert_to) it to a static format.
+///
+/// To access the `Raster` of any variant without matching each one, use
+/// the [map_dyn_raster](macro.map_dyn_raster.html) macro.
+///
+/// ### Convert to a known format
+/// ```
+/// # use pix::*;
+/// let r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+/// let dyn_r = DynRaster::from(r);
+/// assert_eq!(dyn_r.pix_fmt(), SGray8::PIX_FMT);
+/// let rgba: Raster<SRgba8> = dyn_r.convert_to();
+/// assert_eq!(rgba.pixel(0, 0), SRgba8::new(0, 0, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynRaster {
+    /// 8-bit alpha mask
+    Mask8(Raster<Mask8>),
+    /// 8-bit sRGB gray
+    SGray8(Raster<SGray8>),
+    /// 16-bit sRGB gray
+    SGray16(Raster<SGray16>),
+    /// 8-bit sRGB gray with alpha
+    SGrayAlpha8(Raster<SGrayAlpha8>),
+    /// 16-bit sRGB gray with alpha
+    SGrayAlpha16(Raster<SGrayAlpha16>),
+    /// 8-bit sRGB
+    SRgb8(Raster<SRgb8>),
+    /// 16-bit sRGB
+    SRgb16(Raster<SRgb16>),
+    /// 32-bit floating-point sRGB
+    SRgb32(Raster<SRgb32>),
+    /// 8-bit sRGB with alpha
+    SRgba8(Raster<SRgba8>),
+    /// 16-bit sRGB with alpha
+    SRgba16(Raster<SRgba16>),
+    /// 32-bit floating-point sRGB with alpha
+    SRgba32(Raster<SRgba32>),
+}
+
+/// Evaluate an expression with the `Raster` of any
+/// [DynRaster](enum.DynRaster.html) variant.
+///
+/// * `$dyn` `DynRaster` expression (or reference).
+/// * `$r` Identifier to bind to the `Raster` of each variant.
+/// * `$body` Expression to evaluate; it must have the same type for every
+///   variant.
+///
+/// ### Count opaque pixels
+/// ```
+/// # use pix::*;
+/// let dyn_r = DynRaster::from(
+///     RasterBuilder::<SRgba8>::new().with_color(3, 2, SRgba8::new(1, 2, 3)),
+/// );
+/// let opaque = map_dyn_raster!(&dyn_r, r => {
+///     r.pixels().filter(|p| p.alpha() == Channel::MAX).count()
+/// });
+/// assert_eq!(opaque, 6);
+/// ```
+#[macro_export]
+macro_rules! map_dyn_raster {
+    ($dyn:expr, $r:ident => $body:expr) => {
+        match $dyn {
+            $crate::DynRaster::Mask8($r) => $body,
+            $crate::DynRaster::SGray8($r) => $body,
+            $crate::DynRaster::SGray16($r) => $body,
+            $crate::DynRaster::SGrayAlpha8($r) => $body,
+            $crate::DynRaster::SGrayAlpha16($r) => $body,
+            $crate::DynRaster::SRgb8($r) => $body,
+            $crate::DynRaster::SRgb16($r) => $body,
+            $crate::DynRaster::SRgb32($r) => $body,
+            $crate::DynRaster::SRgba8($r) => $body,
+            $crate::DynRaster::SRgba16($r) => $body,
+            $crate::DynRaster::SRgba32($r) => $body,
+        }
+    };
+}
+
+/// Implement `From<Raster>` for a `DynRaster` variant
+macro_rules! impl_from_raster {
+    ($pix:ident) => {
+        impl From<Raster<$pix>> for DynRaster {
+            fn from(r: Raster<$pix>) -> Self {
+                DynRaster::$pix(r)
+            }
+        }
+    };
+}
+
+impl_from_raster!(Mask8);
+impl_from_raster!(SGray8);
+impl_from_raster!(SGray16);
+impl_from_raster!(SGrayAlpha8);
+impl_from_raster!(SGrayAlpha16);
+impl_from_raster!(SRgb8);
+impl_from_raster!(SRgb16);
+impl_from_raster!(SRgb32);
+impl_from_raster!(SRgba8);
+impl_from_raster!(SRgba16);
+impl_from_raster!(SRgba32);
+
+impl From<DynPnm> for DynRaster {
+    fn from(pnm: DynPnm) -> Self {
+        match pnm {
+            DynPnm::Gray8(r) => DynRaster::SGray8(r),
+            DynPnm::Gray16(r) => DynRaster::SGray16(r),
+            DynPnm::Rgb8(r) => DynRaster::SRgb8(r),
+            DynPnm::Rgb16(r) => DynRaster::SRgb16(r),
+        }
+    }
+}
+
+impl DynRaster {
+    /// Get width of `Raster`.
+    pub fn width(&self) -> u32 {
+        map_dyn_raster!(self, r => r.width())
+    }
+    /// Get height of `Raster`.
+    pub fn height(&self) -> u32 {
+        map_dyn_raster!(self, r => r.height())
+    }
+    /// Get the pixel format descriptor.
+    pub fn pix_fmt(&self) -> PixFmt {
+        fn fmt<P: Pixel>(_r: &Raster<P>) -> PixFmt {
+            P::PIX_FMT
+        }
+        map_dyn_raster!(self, r => fmt(r))
+    }
+    /// Get pixel data as a `u8` slice.
+    ///
+    /// Channels wider than 8 bits are in native byte order.
+    pub fn as_u8_slice(&self) -> &[u8] {
+        map_dyn_raster!(self, r => r.as_u8_slice())
+    }
+    /// Convert to a `Raster` with a static pixel format.
+    ///
+    /// * `P` `Pixel` format of new `Raster`.
+    ///
+    /// Converting to the same format as the variant copies the pixels
+    /// unchanged.
+    pub fn convert_to<P>(&self) -> Raster<P>
+    where
+        P: Pixel,
+        P::Chan: From<Ch8> + From<Ch16> + From<Ch32>,
+    {
+        map_dyn_raster!(self, r => r.convert())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn formats() {
+        let r = RasterBuilder::<SRgb16>::new().with_clear(5, 3);
+        let d = DynRaster::from(r);
+        assert_eq!((d.width(), d.height()), (5, 3));
+        assert_eq!(d.pix_fmt().depth(), PixDepth::U16);
+        assert_eq!(d.as_u8_slice().len(), 5 * 3 * 6);
+        let d = DynRaster::from(RasterBuilder::<Mask8>::new().with_clear(2, 2));
+        assert_eq!(d.pix_fmt(), Mask8::PIX_FMT);
+        assert_eq!(d.as_u8_slice(), &[0; 4]);
+    }
+
+    #[test]
+    fn convert() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(3, 2, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 0x80, 0xFF)
+        });
+        let d = DynRaster::from(r.clone());
+        assert_eq!(d.convert_to::<SRgba8>(), r);
+        let r16: Raster<SRgba16> = d.convert_to();
+        assert_eq!(r16.pixel(2, 1), SRgba16::new(0x0202, 0x0101, 0x8080));
+        let g = RasterBuilder::<SGray8>::new().with_color(1, 1, SGray8::new(9));
+        let d = DynRaster::from(DynPnm::Gray8(g));
+        assert!(matches!(d, DynRaster::SGray8(_)));
+        assert_eq!(d.convert_to::<SRgb8>().pixel(0, 0), SRgb8::new(9, 9, 9));
+    }
+}
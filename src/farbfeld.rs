@@ -0,0 +1,167 @@
+// farbfeld.rs  Farbfeld images.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::raster::pixel_count;
+use crate::{Pixel, Raster, RasterBuilder, SRgba16};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Magic number at start of farbfeld images
+const MAGIC: &[u8; 8] = b"farbfeld";
+
+/// Error reading a farbfeld image.
+///
+/// See `Raster`::[read_farbfeld](struct.Raster.html#method.read_farbfeld).
+#[derive(Debug)]
+pub enum FarbfeldError {
+    /// I/O error while reading
+    Io(io::Error),
+    /// Magic number is not `farbfeld`
+    InvalidMagic,
+    /// Size of `width` * `height` pixels does not fit in memory
+    Overflow,
+    /// Image data ended before all pixels were read
+    Truncated,
+}
+
+impl fmt::Display for FarbfeldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FarbfeldError::Io(e) => write!(f, "farbfeld I/O error: {}", e),
+            FarbfeldError::InvalidMagic => {
+                write!(f, "invalid farbfeld magic number")
+            }
+            FarbfeldError::Overflow => write!(f, "farbfeld size overflow"),
+            FarbfeldError::Truncated => write!(f, "truncated farbfeld data"),
+        }
+    }
+}
+
+impl Error for FarbfeldError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FarbfeldError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for FarbfeldError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => FarbfeldError::Truncated,
+            _ => FarbfeldError::Io(err),
+        }
+    }
+}
+
+impl Raster<SRgba16> {
+    /// Read a farbfeld image.
+    ///
+    /// * `r` Reader for image data.
+    ///
+    /// ### Read an image
+    /// ```
+    /// # use pix::*;
+    /// let mut ff = b"farbfeld\0\0\0\x01\0\0\0\x01".to_vec();
+    /// ff.extend_from_slice(&[0xFF, 0xFF, 0x80, 0x00, 0, 0, 0xFF, 0xFF]);
+    /// let r = Raster::read_farbfeld(&ff[..]).unwrap();
+    /// let clr = SRgba16::with_alpha(0xFFFF, 0x8000, 0, 0xFFFF);
+    /// assert_eq!(r.pixel(0, 0), clr);
+    /// ```
+    pub fn read_farbfeld(mut r: impl Read) -> Result<Self, FarbfeldError> {
+        let mut header = [0; 16];
+        r.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(FarbfeldError::InvalidMagic);
+        }
+        let width = u32::from_be_bytes([
+            header[8], header[9], header[10], header[11],
+        ]);
+        let height = u32::from_be_bytes([
+            header[12], header[13], header[14], header[15],
+        ]);
+        let len = pixel_count::<SRgba16>(width, height)
+            .map_err(|_| FarbfeldError::Overflow)?;
+        let len = len * std::mem::size_of::<SRgba16>();
+        // Read without allocating everything up front, in case the
+        // dimensions are bogus
+        let mut buf = vec![];
+        r.take(len as u64).read_to_end(&mut buf)?;
+        if buf.len() < len {
+            return Err(FarbfeldError::Truncated);
+        }
+        RasterBuilder::new()
+            .try_with_u16_buffer_be(width, height, &buf)
+            .map_err(|_| FarbfeldError::Overflow)
+    }
+    /// Write a farbfeld image.
+    ///
+    /// * `w` Writer for image data.
+    ///
+    /// ### Write an image
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgba16>::new().with_clear(3, 2);
+    /// let mut ff = vec![];
+    /// r.write_farbfeld(&mut ff).unwrap();
+    /// assert_eq!(ff.len(), 16 + 3 * 2 * 8);
+    /// ```
+    pub fn write_farbfeld(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&self.width().to_be_bytes())?;
+        w.write_all(&self.height().to_be_bytes())?;
+        let mut buf = Vec::with_capacity(self.width() as usize * 8);
+        for row in self.rows() {
+            buf.clear();
+            for chan in row.iter().flat_map(Pixel::channels) {
+                buf.extend_from_slice(&u16::from(*chan).to_be_bytes());
+            }
+            w.write_all(&buf)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn round_trip() {
+        let r = RasterBuilder::<SRgba16>::new().with_fn(7, 5, |x, y| {
+            let (x, y) = (x as u16, y as u16);
+            SRgba16::with_alpha(x * 0x1001, y * 0x2002, 0xABCD, 0xFFFF - x)
+        });
+        let mut ff = vec![];
+        r.write_farbfeld(&mut ff).unwrap();
+        assert_eq!(&ff[..16], b"farbfeld\0\0\0\x07\0\0\0\x05");
+        // Channels are big-endian
+        assert_eq!(&ff[24..32], &[0x10, 0x01, 0, 0, 0xAB, 0xCD, 0xFF, 0xFE]);
+        assert_eq!(Raster::read_farbfeld(&ff[..]).unwrap(), r);
+        let r = RasterBuilder::<SRgba16>::new().with_clear(0, 9);
+        let mut ff = vec![];
+        r.write_farbfeld(&mut ff).unwrap();
+        assert_eq!(Raster::read_farbfeld(&ff[..]).unwrap(), r);
+    }
+
+    #[test]
+    fn errors() {
+        let read = |ff: &[u8]| Raster::read_farbfeld(ff).unwrap_err();
+        assert!(matches!(read(b"farbfel"), FarbfeldError::Truncated));
+        assert!(matches!(
+            read(b"farbfelt\0\0\0\0\0\0\0\0"),
+            FarbfeldError::InvalidMagic
+        ));
+        assert!(matches!(
+            read(b"farbfeld\0\0\0\x01\0\0\0\x01\0\0\0\0\0\0\0"),
+            FarbfeldError::Truncated
+        ));
+        let huge = b"farbfeld\xFF\xFF\xFF\xFF\xFF\xFF\xFF\xFF";
+        assert!(matches!(read(huge), FarbfeldError::Overflow));
+        let big = b"farbfeld\0\x01\0\0\0\x01\0\0\0\0\0\0\0\0\0\0";
+        assert!(matches!(read(big), FarbfeldError::Truncated));
+    }
+}
@@ -5,7 +5,7 @@
 //
 //! Module for gamma encoding items
 use crate::private::Sealed;
-use crate::{Ch16, Ch32, Ch8, Channel};
+use crate::{Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, PixGamma};
 use std::any::Any;
 use std::fmt::Debug;
 
@@ -13,8 +13,10 @@ use std::fmt::Debug;
 ///
 /// This trait is *sealed*, and cannot be implemented outside of this crate.
 pub trait Mode:
-    Any + Copy + Clone + Debug + Default + PartialEq + Sealed
+    Any + Copy + Clone + Debug + Default + PartialEq + Send + Sync + Sealed
 {
+    /// Gamma mode, for [PixFmt](../struct.PixFmt.html)
+    const PIX_GAMMA: PixGamma;
     /// Convert a `Channel` value to linear.
     fn to_linear<C: Channel>(c: C) -> C;
     /// Convert a `Channel` value from linear.
@@ -22,11 +24,11 @@ pub trait Mode:
 }
 
 /// Linear gamma (no gamma correction)
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Linear;
 
 /// Gamma correction using the sRGB formula
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Srgb;
 
 // TODO: add PowerLawGamma when const generics feature is stable
@@ -122,16 +124,96 @@ impl SrgbValue for Ch8 {
 impl SrgbValue for u16 {
     /// Encode an sRGB gamma value from linear intensity
     fn encode_srgb(self) -> Self {
+        if self.is_multiple_of(257) {
+            // 8-bit value -- use look-up table
+            return ENCODE_SRGB_U16[usize::from(self / 257)];
+        }
         let s = f32::from(self) / 65535.0;
         (s.encode_srgb() * 65535.0).round() as u16
     }
     /// Decode an sRGB gamma value into linear intensity
     fn decode_srgb(self) -> Self {
+        if self.is_multiple_of(257) {
+            // 8-bit value -- use look-up table
+            return DECODE_SRGB_U16[usize::from(self / 257)];
+        }
         let s = f32::from(self) / 65535.0;
         (s.decode_srgb() * 65535.0).round() as u16
     }
 }
 
+/// Look-up table to encode 8-bit linear to 16-bit sRGB gamma
+const ENCODE_SRGB_U16: &[u16] = &[
+    0x0000, 0x0CC2, 0x15BF, 0x1C57, 0x21BE, 0x2667, 0x2A8B, 0x2E4D,
+    0x31C1, 0x34F7, 0x37F9, 0x3AD0, 0x3D81, 0x4011, 0x4284, 0x44DE,
+    0x4721, 0x494F, 0x4B6A, 0x4D74, 0x4F6E, 0x515A, 0x5338, 0x5509,
+    0x56CF, 0x588B, 0x5A3B, 0x5BE3, 0x5D81, 0x5F16, 0x60A4, 0x622A, // 32
+    0x63A9, 0x6520, 0x6691, 0x67FC, 0x6961, 0x6AC0, 0x6C1A, 0x6D6E,
+    0x6EBE, 0x7008, 0x714E, 0x728F, 0x73CC, 0x7505, 0x763A, 0x776B,
+    0x7898, 0x79C1, 0x7AE7, 0x7C0A, 0x7D29, 0x7E45, 0x7F5E, 0x8074,
+    0x8187, 0x8297, 0x83A4, 0x84AF, 0x85B7, 0x86BC, 0x87BF, 0x88C0, // 64
+    0x89BE, 0x8ABA, 0x8BB4, 0x8CAB, 0x8DA0, 0x8E94, 0x8F85, 0x9074,
+    0x9161, 0x924C, 0x9336, 0x941D, 0x9503, 0x95E7, 0x96CA, 0x97AA,
+    0x9889, 0x9966, 0x9A42, 0x9B1C, 0x9BF5, 0x9CCC, 0x9DA2, 0x9E76,
+    0x9F49, 0xA01B, 0xA0EB, 0xA1B9, 0xA287, 0xA353, 0xA41E, 0xA4E7, // 96
+    0xA5B0, 0xA677, 0xA73D, 0xA801, 0xA8C5, 0xA988, 0xAA49, 0xAB09,
+    0xABC8, 0xAC86, 0xAD43, 0xADFF, 0xAEBA, 0xAF74, 0xB02D, 0xB0E5,
+    0xB19C, 0xB253, 0xB308, 0xB3BC, 0xB46F, 0xB522, 0xB5D3, 0xB684,
+    0xB734, 0xB7E3, 0xB891, 0xB93E, 0xB9EA, 0xBA96, 0xBB41, 0xBBEB, // 128
+    0xBC94, 0xBD3D, 0xBDE4, 0xBE8B, 0xBF32, 0xBFD7, 0xC07C, 0xC120,
+    0xC1C3, 0xC266, 0xC308, 0xC3A9, 0xC44A, 0xC4EA, 0xC589, 0xC628,
+    0xC6C6, 0xC763, 0xC800, 0xC89C, 0xC937, 0xC9D2, 0xCA6D, 0xCB06,
+    0xCB9F, 0xCC38, 0xCCD0, 0xCD67, 0xCDFE, 0xCE94, 0xCF2A, 0xCFBF, // 160
+    0xD053, 0xD0E7, 0xD17B, 0xD20E, 0xD2A0, 0xD332, 0xD3C3, 0xD454,
+    0xD4E4, 0xD574, 0xD604, 0xD693, 0xD721, 0xD7AF, 0xD83C, 0xD8C9,
+    0xD956, 0xD9E2, 0xDA6D, 0xDAF8, 0xDB83, 0xDC0D, 0xDC97, 0xDD20,
+    0xDDA9, 0xDE32, 0xDEBA, 0xDF41, 0xDFC8, 0xE04F, 0xE0D6, 0xE15B, // 192
+    0xE1E1, 0xE266, 0xE2EB, 0xE36F, 0xE3F3, 0xE477, 0xE4FA, 0xE57C,
+    0xE5FF, 0xE681, 0xE702, 0xE784, 0xE804, 0xE885, 0xE905, 0xE985,
+    0xEA04, 0xEA83, 0xEB02, 0xEB80, 0xEBFE, 0xEC7C, 0xECF9, 0xED76,
+    0xEDF3, 0xEE6F, 0xEEEB, 0xEF67, 0xEFE2, 0xF05D, 0xF0D8, 0xF152, // 224
+    0xF1CC, 0xF246, 0xF2BF, 0xF338, 0xF3B1, 0xF429, 0xF4A1, 0xF519,
+    0xF591, 0xF608, 0xF67F, 0xF6F6, 0xF76C, 0xF7E2, 0xF858, 0xF8CD,
+    0xF942, 0xF9B7, 0xFA2C, 0xFAA0, 0xFB14, 0xFB88, 0xFBFC, 0xFC6F,
+    0xFCE2, 0xFD54, 0xFDC7, 0xFE39, 0xFEAB, 0xFF1D, 0xFF8E, 0xFFFF, // 256
+];
+
+/// Look-up table to decode 8-bit sRGB gamma to 16-bit linear
+const DECODE_SRGB_U16: &[u16] = &[
+    0x0000, 0x0014, 0x0028, 0x003C, 0x0050, 0x0063, 0x0077, 0x008B,
+    0x009F, 0x00B3, 0x00C7, 0x00DB, 0x00F1, 0x0108, 0x0120, 0x0139,
+    0x0154, 0x016F, 0x018C, 0x01AB, 0x01CA, 0x01EB, 0x020E, 0x0232,
+    0x0257, 0x027D, 0x02A5, 0x02CE, 0x02F9, 0x0325, 0x0353, 0x0382, // 32
+    0x03B3, 0x03E5, 0x0418, 0x044D, 0x0484, 0x04BC, 0x04F6, 0x0532,
+    0x056F, 0x05AD, 0x05ED, 0x062F, 0x0673, 0x06B8, 0x06FE, 0x0747,
+    0x0791, 0x07DD, 0x082A, 0x087A, 0x08CA, 0x091D, 0x0972, 0x09C8,
+    0x0A20, 0x0A79, 0x0AD5, 0x0B32, 0x0B91, 0x0BF2, 0x0C55, 0x0CBA, // 64
+    0x0D20, 0x0D88, 0x0DF2, 0x0E5E, 0x0ECC, 0x0F3C, 0x0FAE, 0x1021,
+    0x1097, 0x110E, 0x1188, 0x1203, 0x1280, 0x1300, 0x1381, 0x1404,
+    0x1489, 0x1510, 0x159A, 0x1625, 0x16B2, 0x1741, 0x17D3, 0x1866,
+    0x18FB, 0x1993, 0x1A2C, 0x1AC8, 0x1B66, 0x1C06, 0x1CA7, 0x1D4C, // 96
+    0x1DF2, 0x1E9A, 0x1F44, 0x1FF1, 0x20A0, 0x2150, 0x2204, 0x22B9,
+    0x2370, 0x242A, 0x24E5, 0x25A3, 0x2664, 0x2726, 0x27EB, 0x28B1,
+    0x297B, 0x2A46, 0x2B14, 0x2BE3, 0x2CB6, 0x2D8A, 0x2E61, 0x2F3A,
+    0x3015, 0x30F2, 0x31D2, 0x32B4, 0x3399, 0x3480, 0x3569, 0x3655, // 128
+    0x3742, 0x3833, 0x3925, 0x3A1A, 0x3B12, 0x3C0B, 0x3D07, 0x3E06,
+    0x3F07, 0x400A, 0x4110, 0x4218, 0x4323, 0x4430, 0x453F, 0x4651,
+    0x4765, 0x487C, 0x4995, 0x4AB1, 0x4BCF, 0x4CF0, 0x4E13, 0x4F39,
+    0x5061, 0x518C, 0x52B9, 0x53E9, 0x551B, 0x5650, 0x5787, 0x58C1, // 160
+    0x59FE, 0x5B3D, 0x5C7E, 0x5DC2, 0x5F09, 0x6052, 0x619E, 0x62ED,
+    0x643E, 0x6591, 0x66E8, 0x6840, 0x699C, 0x6AFA, 0x6C5B, 0x6DBE,
+    0x6F24, 0x708D, 0x71F8, 0x7366, 0x74D7, 0x764A, 0x77C0, 0x7939,
+    0x7AB4, 0x7C32, 0x7DB3, 0x7F37, 0x80BD, 0x8246, 0x83D1, 0x855F, // 192
+    0x86F0, 0x8884, 0x8A1B, 0x8BB4, 0x8D50, 0x8EEF, 0x9090, 0x9235,
+    0x93DC, 0x9586, 0x9732, 0x98E2, 0x9A94, 0x9C49, 0x9E01, 0x9FBB,
+    0xA179, 0xA339, 0xA4FC, 0xA6C2, 0xA88B, 0xAA56, 0xAC25, 0xADF6,
+    0xAFCA, 0xB1A1, 0xB37B, 0xB557, 0xB737, 0xB919, 0xBAFF, 0xBCE7, // 224
+    0xBED2, 0xC0C0, 0xC2B1, 0xC4A5, 0xC69C, 0xC895, 0xCA92, 0xCC91,
+    0xCE94, 0xD099, 0xD2A1, 0xD4AD, 0xD6BB, 0xD8CC, 0xDAE0, 0xDCF7,
+    0xDF11, 0xE12E, 0xE34E, 0xE571, 0xE797, 0xE9C0, 0xEBEC, 0xEE1B,
+    0xF04D, 0xF282, 0xF4BA, 0xF6F5, 0xF933, 0xFB74, 0xFDB8, 0xFFFF, // 256
+];
+
 impl SrgbValue for Ch16 {
     /// Encode an sRGB gamma value from linear intensity
     fn encode_srgb(self) -> Self {
@@ -170,6 +252,17 @@ impl SrgbValue for f32 {
     }
 }
 
+impl SrgbValue for Ch16f {
+    /// Encode an sRGB gamma value from linear intensity
+    fn encode_srgb(self) -> Self {
+        Self::new(f32::from(self).encode_srgb())
+    }
+    /// Decode an sRGB gamma value into linear intensity
+    fn decode_srgb(self) -> Self {
+        Self::new(f32::from(self).decode_srgb())
+    }
+}
+
 impl SrgbValue for Ch32 {
     /// Encode an sRGB gamma value from linear intensity
     fn encode_srgb(self) -> Self {
@@ -208,7 +301,20 @@ impl SrgbValue for f64 {
     }
 }
 
+impl SrgbValue for Ch64 {
+    /// Encode an sRGB gamma value from linear intensity
+    fn encode_srgb(self) -> Self {
+        Self::new(f64::from(self).encode_srgb())
+    }
+    /// Decode an sRGB gamma value into linear intensity
+    fn decode_srgb(self) -> Self {
+        Self::new(f64::from(self).decode_srgb())
+    }
+}
+
 impl Mode for Linear {
+    const PIX_GAMMA: PixGamma = PixGamma::Linear;
+
     /// Convert a `Channel` value to linear.
     fn to_linear<C: Channel>(c: C) -> C {
         c
@@ -220,6 +326,8 @@ impl Mode for Linear {
 }
 
 impl Mode for Srgb {
+    const PIX_GAMMA: PixGamma = PixGamma::Srgb;
+
     /// Convert a `Channel` value to linear.
     fn to_linear<C: Channel>(c: C) -> C {
         c.decode_srgb()
@@ -235,18 +343,40 @@ mod test {
     use super::*;
     #[test]
     fn lut_decode_u8() {
-        for i in 0..256 {
+        for (i, lut) in DECODE_SRGB_U8.iter().enumerate() {
             let s = i as f64 / 255.0;
             let v = (s.decode_srgb() * 255.0).round() as u8;
-            assert_eq!(v, DECODE_SRGB_U8[i]);
+            assert_eq!(v, *lut);
         }
     }
     #[test]
     fn lut_encode_u8() {
-        for i in 0..256 {
+        for (i, lut) in ENCODE_SRGB_U8.iter().enumerate() {
             let s = i as f64 / 255.0;
             let v = (s.encode_srgb() * 255.0).round() as u8;
-            assert_eq!(v, ENCODE_SRGB_U8[i]);
+            assert_eq!(v, *lut);
+        }
+    }
+    #[test]
+    fn lut_decode_u16() {
+        for (i, lut) in DECODE_SRGB_U16.iter().enumerate() {
+            let s = i as f64 / 255.0;
+            let v = (s.decode_srgb() * 65535.0).round() as u16;
+            assert_eq!(v, *lut);
+            let s = (i * 257) as f32 / 65535.0;
+            let v = (s.decode_srgb() * 65535.0).round() as u16;
+            assert!(v.max(*lut) - v.min(*lut) <= 1);
+        }
+    }
+    #[test]
+    fn lut_encode_u16() {
+        for (i, lut) in ENCODE_SRGB_U16.iter().enumerate() {
+            let s = i as f64 / 255.0;
+            let v = (s.encode_srgb() * 65535.0).round() as u16;
+            assert_eq!(v, *lut);
+            let s = (i * 257) as f32 / 65535.0;
+            let v = (s.encode_srgb() * 65535.0).round() as u16;
+            assert!(v.max(*lut) - v.min(*lut) <= 1);
         }
     }
 }
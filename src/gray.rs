@@ -7,7 +7,11 @@ use crate::alpha::{
     self, AChannel, Mode as _, Opaque, Premultiplied, Straight, Translucent,
 };
 use crate::gamma::{self, Linear, Srgb};
-use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, Pixel};
+use crate::pixel::fmt_pixel;
+use crate::{
+    Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel,
+};
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::Mul;
 
@@ -21,7 +25,7 @@ use std::ops::Mul;
 /// [color model]: trait.ColorModel.html
 /// [linear]: gamma/struct.Linear.html
 /// [sRGB]: gamma/struct.Srgb.html
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct Gray<C, A, M, G>
 where
@@ -78,9 +82,16 @@ where
         }
     }
     /// Get the *luma* / *relative luminance* component.
-    pub fn value(self) -> C {
+    pub const fn value(self) -> C {
         self.components[0]
     }
+    /// Set the *luma* / *relative luminance* component.
+    pub fn set_value<H>(&mut self, value: H)
+    where
+        C: From<H>,
+    {
+        self.components[0] = C::from(value);
+    }
     /// Get the *alpha* value.
     pub fn alpha(self) -> C {
         self.alpha.value()
@@ -106,6 +117,11 @@ where
         self.alpha.value()
     }
 
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
     /// Convert to *red*, *green*, *blue* and *alpha* components
     fn to_rgba(self) -> [Self::Chan; 4] {
         let value = self.value();
@@ -148,6 +164,19 @@ where
     }
 }
 
+impl<C, A, M, G> fmt::Debug for Gray<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
 impl<C, A, M, G> Pixel for Gray<C, A, M, G>
 where
     C: Channel,
@@ -155,6 +184,27 @@ where
     M: alpha::Mode,
     G: gamma::Mode,
 {
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Gray);
+
+    const WHITE: Self = Gray {
+        components: [C::MAX],
+        alpha: A::OPAQUE,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = Gray {
+        components: [C::MIN],
+        alpha: A::OPAQUE,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = Gray {
+        components: [C::MIN],
+        alpha: A::TRANSPARENT,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+
     type Alpha = M;
     type Gamma = G;
 }
@@ -219,6 +269,35 @@ where
     }
 }
 
+impl<C, A, G> Gray<C, A, Straight, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Premultiply the gray value with *alpha*.
+    ///
+    /// The value is multiplied in the gamma encoding of the pixel.
+    pub fn premultiply(self) -> Gray<C, A, Premultiplied, G> {
+        self.into()
+    }
+}
+
+impl<C, A, G> Gray<C, A, Premultiplied, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Divide the gray value by *alpha*.
+    ///
+    /// The value is divided in the gamma encoding of the pixel.  If *alpha*
+    /// is zero, the value is zero.
+    pub fn unpremultiply(self) -> Gray<C, A, Straight, G> {
+        self.into()
+    }
+}
+
 impl<C, A, M, G> From<u8> for Gray<C, A, M, G>
 where
     C: Channel + From<Ch8>,
@@ -255,10 +334,18 @@ pub type Gray8 = Gray<Ch8, Opaque<Ch8>, Straight, Linear>;
 /// alpha) [linear](gamma/struct.Linear.html) gamma
 /// [pixel](trait.Pixel.html) format.
 pub type Gray16 = Gray<Ch16, Opaque<Ch16>, Straight, Linear>;
+/// [Gray](struct.Gray.html) 16-bit floating-point
+/// [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Gray16f = Gray<Ch16f, Opaque<Ch16f>, Straight, Linear>;
 /// [Gray](struct.Gray.html) 32-bit [opaque](alpha/struct.Opaque.html) (no
 /// alpha) [linear](gamma/struct.Linear.html) gamma
 /// [pixel](trait.Pixel.html) format.
 pub type Gray32 = Gray<Ch32, Opaque<Ch32>, Straight, Linear>;
+/// [Gray](struct.Gray.html) 64-bit [opaque](alpha/struct.Opaque.html) (no
+/// alpha) [linear](gamma/struct.Linear.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type Gray64 = Gray<Ch64, Opaque<Ch64>, Straight, Linear>;
 
 type GrayAlpha<C, A> = Gray<C, A, Straight, Linear>;
 /// [Gray](struct.Gray.html) 8-bit [straight](alpha/struct.Straight.html) alpha
@@ -336,4 +423,24 @@ mod test {
         assert_eq!(std::mem::size_of::<SGrayAlpha16>(), 4);
         assert_eq!(std::mem::size_of::<SGrayAlpha32>(), 8);
     }
+
+    #[test]
+    fn premultiply() {
+        let a = SGrayAlpha16::with_alpha(0xFFFF, 0x4000);
+        assert_eq!(a.premultiply(), SGrayAlpha16p::with_alpha(0x4000, 0x4000));
+        let a = SGrayAlpha16p::with_alpha(0x2000, 0x4000);
+        assert_eq!(a.unpremultiply(), SGrayAlpha16::with_alpha(0x8000, 0x4000));
+        let a = GrayAlpha32p::with_alpha(0.5, 0.0);
+        assert_eq!(a.unpremultiply(), GrayAlpha32::with_alpha(0.0, 0.0));
+    }
+
+    #[test]
+    fn accessors() {
+        let mut clr = SGrayAlpha16::with_alpha(0x1234, 0x5678);
+        assert_eq!(clr.value(), Ch16::new(0x1234));
+        assert_eq!(clr.alpha(), Ch16::new(0x5678));
+        clr.set_value(0xFEDC);
+        assert_eq!(clr, SGrayAlpha16::with_alpha(0xFEDC, 0x5678));
+        assert_eq!(SGray8::new(0x40).alpha(), Ch8::MAX);
+    }
 }
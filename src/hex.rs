@@ -0,0 +1,361 @@
+// hex.rs       Hex color strings.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{
+    Bgr16, Bgr32, Bgr8, Bgra16, Bgra32, Bgra8, Ch16, Ch8, ColorModel, Rgb16,
+    Rgb32, Rgb64, Rgb8, Rgba16, Rgba16f, Rgba32, Rgba64, Rgba8, SBgr16, SBgr32,
+    SBgr8, SBgra16, SBgra32, SBgra8, SRgb16, SRgb32, SRgb8, SRgba16, SRgba32,
+    SRgba8,
+};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Error parsing a hex color string.
+///
+/// Colors can be parsed from strings like `#RRGGBB`, using
+/// [FromStr](https://doc.rust-lang.org/std/str/trait.FromStr.html).
+///
+/// ### Parse colors
+/// ```
+/// # use pix::*;
+/// let clr: SRgb8 = "#FF8000".parse().unwrap();
+/// assert_eq!(clr, SRgb8::new(0xFF, 0x80, 0x00));
+/// let clr: SRgba8 = "f808".parse().unwrap();
+/// assert_eq!(clr, SRgba8::with_alpha(0xFF, 0x88, 0x00, 0x88));
+/// assert_eq!(format!("{:#x}", clr), "#ff880088");
+/// assert_eq!(clr.to_string(), "#ff880088");
+/// let err = "#FF80".parse::<SRgb8>().unwrap_err();
+/// assert_eq!(err, HexError::InvalidLength(4));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    /// Number of hex digits is not valid for the pixel format
+    InvalidLength(usize),
+    /// Character is not a hex digit
+    InvalidDigit(char),
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HexError::InvalidLength(len) => {
+                write!(f, "invalid hex color length: {} digits", len)
+            }
+            HexError::InvalidDigit(c) => {
+                write!(f, "invalid hex color digit: {:?}", c)
+            }
+        }
+    }
+}
+
+impl Error for HexError {}
+
+/// Parse a hex color string into channel values.
+///
+/// * `wide` Use 4 digits per channel, instead of 1 or 2.
+/// * `alpha` Allow an *alpha* channel.
+///
+/// Missing *alpha* is fully opaque.
+fn parse_hex(s: &str, wide: bool, alpha: bool) -> Result<[u16; 4], HexError> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(HexError::InvalidDigit(c));
+    }
+    let (count, digits) = match (hex.len(), wide) {
+        (3, false) => (3, 1),
+        (6, false) => (3, 2),
+        (4, false) if alpha => (4, 1),
+        (8, false) if alpha => (4, 2),
+        (12, true) => (3, 4),
+        (16, true) if alpha => (4, 4),
+        (len, _) => return Err(HexError::InvalidLength(len)),
+    };
+    let mut chans = [if wide { 0xFFFF } else { 0xFF }; 4];
+    for (i, chan) in chans.iter_mut().take(count).enumerate() {
+        let d = &hex[i * digits..(i + 1) * digits];
+        // All digits were checked above
+        let v = u16::from_str_radix(d, 16).unwrap();
+        // Short form: 0xF => 0xFF
+        *chan = if digits == 1 { v * 0x11 } else { v };
+    }
+    Ok(chans)
+}
+
+/// Format channel values as a hex color string.
+///
+/// With the alternate flag (`{:#x}`), a leading `#` is added.
+fn fmt_hex(
+    f: &mut fmt::Formatter,
+    chans: &[u16],
+    digits: usize,
+    upper: bool,
+) -> fmt::Result {
+    if f.alternate() {
+        write!(f, "#")?;
+    }
+    for c in chans {
+        if upper {
+            write!(f, "{:01$X}", c, digits)?;
+        } else {
+            write!(f, "{:01$x}", c, digits)?;
+        }
+    }
+    Ok(())
+}
+
+/// Implement hex parsing and formatting for an 8-bit pixel format
+macro_rules! impl_hex_8 {
+    ($pix:ident, $alpha:expr) => {
+        impl FromStr for $pix {
+            type Err = HexError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let [r, g, b, a] = parse_hex(s, false, $alpha)?;
+                let c = |v: u16| Ch8::new(v as u8);
+                Ok($pix::with_alpha(c(r), c(g), c(b), c(a)))
+            }
+        }
+
+        impl fmt::Display for $pix {
+            /// Format as a hex color string, such as `#ff8000`
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:#x}", self)
+            }
+        }
+
+        impl fmt::LowerHex for $pix {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let chans = hex_chans_8(*self);
+                let n = if $alpha { 4 } else { 3 };
+                fmt_hex(f, &chans[..n], 2, false)
+            }
+        }
+
+        impl fmt::UpperHex for $pix {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let chans = hex_chans_8(*self);
+                let n = if $alpha { 4 } else { 3 };
+                fmt_hex(f, &chans[..n], 2, true)
+            }
+        }
+    };
+}
+
+/// Implement hex parsing and formatting for a 16-bit pixel format
+macro_rules! impl_hex_16 {
+    ($pix:ident, $alpha:expr) => {
+        impl FromStr for $pix {
+            type Err = HexError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let [r, g, b, a] = parse_hex(s, true, $alpha)?;
+                let c = Ch16::new;
+                Ok($pix::with_alpha(c(r), c(g), c(b), c(a)))
+            }
+        }
+
+        impl fmt::Display for $pix {
+            /// Format as a hex color string, such as `#ff8000`
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:#x}", self)
+            }
+        }
+
+        impl fmt::LowerHex for $pix {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let chans = hex_chans_16(*self);
+                let n = if $alpha { 4 } else { 3 };
+                fmt_hex(f, &chans[..n], 4, false)
+            }
+        }
+
+        impl fmt::UpperHex for $pix {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let chans = hex_chans_16(*self);
+                let n = if $alpha { 4 } else { 3 };
+                fmt_hex(f, &chans[..n], 4, true)
+            }
+        }
+    };
+}
+
+/// Implement hex formatting for a floating-point pixel format
+///
+/// Channels are converted to 8 bits, so `Rgb32::new(1.0, 0.5, 0.0)` is
+/// formatted as `#ff8000`.
+macro_rules! impl_hex_float {
+    ($pix:ident, $alpha:expr) => {
+        impl fmt::Display for $pix {
+            /// Format as a hex color string, such as `#ff8000`
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:#x}", self)
+            }
+        }
+
+        impl fmt::LowerHex for $pix {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let chans = hex_chans_8(*self);
+                let n = if $alpha { 4 } else { 3 };
+                fmt_hex(f, &chans[..n], 2, false)
+            }
+        }
+
+        impl fmt::UpperHex for $pix {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let chans = hex_chans_8(*self);
+                let n = if $alpha { 4 } else { 3 };
+                fmt_hex(f, &chans[..n], 2, true)
+            }
+        }
+    };
+}
+
+/// Get the channels of a pixel converted to 8 bits, for formatting
+fn hex_chans_8<P: ColorModel>(p: P) -> [u16; 4]
+where
+    Ch8: From<P::Chan>,
+{
+    let [r, g, b, a] = p.to_rgba();
+    [r, g, b, a].map(|c| u16::from(u8::from(Ch8::from(c))))
+}
+
+/// Get the channels of a 16-bit pixel, for formatting
+fn hex_chans_16<P: ColorModel<Chan = Ch16>>(p: P) -> [u16; 4] {
+    let [r, g, b, a] = p.to_rgba();
+    [r, g, b, a].map(u16::from)
+}
+
+impl_hex_8!(SRgb8, false);
+impl_hex_8!(SRgba8, true);
+impl_hex_8!(Rgb8, false);
+impl_hex_8!(Rgba8, true);
+impl_hex_8!(SBgr8, false);
+impl_hex_8!(SBgra8, true);
+impl_hex_8!(Bgr8, false);
+impl_hex_8!(Bgra8, true);
+impl_hex_16!(SRgb16, false);
+impl_hex_16!(SRgba16, true);
+impl_hex_16!(Rgb16, false);
+impl_hex_16!(Rgba16, true);
+impl_hex_16!(SBgr16, false);
+impl_hex_16!(SBgra16, true);
+impl_hex_16!(Bgr16, false);
+impl_hex_16!(Bgra16, true);
+impl_hex_float!(SRgb32, false);
+impl_hex_float!(SRgba32, true);
+impl_hex_float!(Rgb32, false);
+impl_hex_float!(Rgba32, true);
+impl_hex_float!(Rgb64, false);
+impl_hex_float!(Rgba64, true);
+impl_hex_float!(Rgba16f, true);
+impl_hex_float!(SBgr32, false);
+impl_hex_float!(SBgra32, true);
+impl_hex_float!(Bgr32, false);
+impl_hex_float!(Bgra32, true);
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn parse_8() {
+        let clr = SRgb8::new(0x11, 0xAA, 0xFF);
+        assert_eq!("#1af".parse(), Ok(clr));
+        assert_eq!("1AF".parse(), Ok(clr));
+        assert_eq!("#11aaff".parse(), Ok(clr));
+        assert_eq!("11AAfF".parse(), Ok(clr));
+        assert_eq!(format!("{:x}", clr), "11aaff");
+        assert_eq!(format!("{:#X}", clr), "#11AAFF");
+        let clr = SRgba8::with_alpha(0x12, 0x34, 0x56, 0x78);
+        assert_eq!("#12345678".parse(), Ok(clr));
+        assert_eq!(format!("{:#x}", clr), "#12345678");
+        let clr = SRgba8::with_alpha(0x00, 0x44, 0x88, 0xCC);
+        assert_eq!("#048c".parse(), Ok(clr));
+        assert_eq!("048C".parse(), Ok(clr));
+        assert_eq!(format!("{:X}", clr), "004488CC");
+        let clr = SRgba8::new(0x00, 0x44, 0x88);
+        assert_eq!("#048".parse(), Ok(clr));
+        assert_eq!("004488".parse(), Ok(clr));
+    }
+
+    #[test]
+    fn parse_16() {
+        let clr = SRgb16::new(0x0123, 0x4567, 0x89AB);
+        assert_eq!("#0123456789ab".parse(), Ok(clr));
+        assert_eq!(format!("{:#x}", clr), "#0123456789ab");
+        let clr = SRgba16::with_alpha(0xFFFF, 0, 0x8000, 0x1234);
+        assert_eq!("ffff000080001234".parse(), Ok(clr));
+        assert_eq!(format!("{:X}", clr), "FFFF000080001234");
+        let clr = SRgba16::new(1, 2, 3);
+        assert_eq!("#000100020003".parse(), Ok(clr));
+        assert_eq!(
+            "#fff".parse::<SRgb16>(),
+            Err(HexError::InvalidLength(3))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for v in (0..=255).step_by(15) {
+            let clr = SRgba8::with_alpha(v, 255 - v, v / 2, v);
+            let s = format!("{:#x}", clr);
+            assert_eq!(s.parse(), Ok(clr));
+            assert_eq!(format!("{:X}", clr).parse(), Ok(clr));
+            let clr = SRgb8::new(v, 255 - v, v / 2);
+            assert_eq!(format!("{:#X}", clr).parse(), Ok(clr));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let err = "#12345".parse::<SRgb8>().unwrap_err();
+        assert_eq!(err, HexError::InvalidLength(5));
+        assert_eq!(err.to_string(), "invalid hex color length: 5 digits");
+        assert_eq!(
+            "#1234".parse::<SRgb8>(),
+            Err(HexError::InvalidLength(4))
+        );
+        assert_eq!(
+            "#12345678".parse::<SRgb8>(),
+            Err(HexError::InvalidLength(8))
+        );
+        assert_eq!("".parse::<SRgba8>(), Err(HexError::InvalidLength(0)));
+        assert_eq!("#".parse::<SRgba8>(), Err(HexError::InvalidLength(0)));
+        let err = "#12g456".parse::<SRgb8>().unwrap_err();
+        assert_eq!(err, HexError::InvalidDigit('g'));
+        assert_eq!(err.to_string(), "invalid hex color digit: 'g'");
+        assert_eq!(
+            "##123".parse::<SRgb8>(),
+            Err(HexError::InvalidDigit('#'))
+        );
+        assert_eq!(
+            " 123".parse::<SRgb8>(),
+            Err(HexError::InvalidDigit(' '))
+        );
+        assert_eq!(
+            "#12é".parse::<SRgb8>(),
+            Err(HexError::InvalidDigit('é'))
+        );
+    }
+
+    #[test]
+    fn format_other() {
+        assert_eq!(Rgb8::new(0x11, 0xAA, 0xFF).to_string(), "#11aaff");
+        let clr = Bgra8::with_alpha(0x12, 0x34, 0x56, 0x78);
+        assert_eq!("#12345678".parse(), Ok(clr));
+        assert_eq!(format!("{:X}", clr), "12345678");
+        let clr = SBgr16::new(0x0123, 0x4567, 0x89AB);
+        assert_eq!(clr.to_string(), "#0123456789ab");
+        let clr = Rgba16::new(0, 0x4444, 0x8888);
+        assert_eq!("#000044448888".parse(), Ok(clr));
+        assert_eq!(Rgb32::new(1.0, 0.5, 0.0).to_string(), "#ff8000");
+        let clr = SRgba32::with_alpha(0.0, 0.25, 0.75, 1.0);
+        assert_eq!(format!("{:X}", clr), "0040BFFF");
+        let clr = Rgba16f::with_alpha(1.0, 0.0, 1.0, 0.5);
+        assert_eq!(clr.to_string(), "#ff00ff80");
+        assert_eq!(Bgr32::new(0.0, 0.0, 1.0).to_string(), "#0000ff");
+        assert_eq!(Rgb64::new(0.2, 0.4, 0.6).to_string(), "#336699");
+    }
+}
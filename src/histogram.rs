@@ -0,0 +1,298 @@
+// histogram.rs     Channel histograms and statistics.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Ch16, Ch64, Ch8, Channel, Pixel, Raster, Region};
+use std::any::TypeId;
+
+/// [Channel](trait.Channel.html) with a fixed number of discrete values,
+/// which can be counted in a [Histogram](struct.Histogram.html).
+///
+/// Implemented for [Ch8](struct.Ch8.html) and [Ch16](struct.Ch16.html).
+///
+/// This trait is *sealed*, and cannot be implemented outside of this crate.
+pub trait Discrete: Channel {
+    /// Number of distinct values
+    const VALUES: usize;
+
+    /// Get the value as an index, from 0 to `VALUES - 1`
+    fn index(self) -> usize;
+}
+
+impl Discrete for Ch8 {
+    const VALUES: usize = 256;
+
+    fn index(self) -> usize {
+        usize::from(u8::from(self))
+    }
+}
+
+impl Discrete for Ch16 {
+    const VALUES: usize = 65536;
+
+    fn index(self) -> usize {
+        usize::from(u16::from(self))
+    }
+}
+
+/// Counts of each value for all channels of a [Raster](struct.Raster.html).
+///
+/// Use `Raster`::[histogram](struct.Raster.html#method.histogram) to create.
+/// There is one set of bins for each color component, followed by one for
+/// alpha.  Pixels in formats without an alpha channel are counted as fully
+/// opaque.
+///
+/// ### Count red values
+/// ```
+/// # use pix::*;
+/// let mut r = RasterBuilder::<SRgb8>::new().with_clear(4, 4);
+/// r.set_region((0, 0, 4, 1), SRgb8::new(0xFF, 0x80, 0x00));
+/// let hist = r.histogram();
+/// assert_eq!(hist.channels(), 4);
+/// assert_eq!(hist.channel(0)[0xFF], 4);
+/// assert_eq!(hist.channel(0)[0x00], 12);
+/// assert_eq!(hist.alpha()[0xFF], 16);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    /// Number of bins in each channel
+    values: usize,
+    /// Counts for all channels
+    counts: Vec<u32>,
+}
+
+/// Summary statistics for one channel of a [Raster](struct.Raster.html).
+///
+/// Use `Raster`::[channel_stats](struct.Raster.html#method.channel_stats) to
+/// create.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChannelStats<C: Channel> {
+    /// Minimum value
+    pub min: C,
+    /// Maximum value
+    pub max: C,
+    /// Mean value, from 0 (`Channel::MIN`) to 1 (`Channel::MAX`)
+    pub mean: f64,
+}
+
+impl Histogram {
+    /// Create an empty histogram
+    fn new(channels: usize, values: usize) -> Self {
+        let counts = vec![0; channels * values];
+        Histogram { values, counts }
+    }
+    /// Get the number of channels, including alpha.
+    pub fn channels(&self) -> usize {
+        self.counts.len() / self.values
+    }
+    /// Get the counts for one channel.
+    ///
+    /// * `i` Channel index; color components come first, then alpha.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of
+    /// [channels](struct.Histogram.html#method.channels).
+    pub fn channel(&self, i: usize) -> &[u32] {
+        let channels = self.channels();
+        assert!(
+            i < channels,
+            "channel {} out of bounds for {} channels",
+            i,
+            channels
+        );
+        &self.counts[i * self.values..(i + 1) * self.values]
+    }
+    /// Get the counts for the alpha channel.
+    pub fn alpha(&self) -> &[u32] {
+        self.channel(self.channels() - 1)
+    }
+}
+
+impl<P> Raster<P>
+where
+    P: Pixel,
+    P::Chan: Discrete,
+{
+    /// Compute a [Histogram](struct.Histogram.html) of all pixels.
+    pub fn histogram(&self) -> Histogram {
+        self.histogram_region(self.region())
+    }
+    /// Compute a [Histogram](struct.Histogram.html) of pixels in a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds.
+    ///
+    /// ### Sample the center of a frame
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray16>::new().with_clear(64, 48);
+    /// let hist = r.histogram_region((16, 12, 32, 24));
+    /// assert_eq!(hist.channel(0)[0], 32 * 24);
+    /// ```
+    pub fn histogram_region<R>(&self, reg: R) -> Histogram
+    where
+        R: Into<Region>,
+    {
+        let values = P::Chan::VALUES;
+        let comps = P::default().components().len();
+        let mut hist = Histogram::new(comps + 1, values);
+        let (bins, alpha) = hist.counts.split_at_mut(comps * values);
+        for row in self.region_rows(reg) {
+            for p in row {
+                for (i, c) in p.components().iter().enumerate() {
+                    bins[i * values + c.index()] += 1;
+                }
+                alpha[p.alpha().index()] += 1;
+            }
+        }
+        hist
+    }
+}
+
+/// Get the scale of an integer channel, for summing values exactly
+pub(crate) fn int_scale<C: Channel>() -> Option<f64> {
+    if TypeId::of::<C>() == TypeId::of::<Ch8>() {
+        Some(255.0)
+    } else if TypeId::of::<C>() == TypeId::of::<Ch16>() {
+        Some(65535.0)
+    } else {
+        None
+    }
+}
+
+impl<P> Raster<P>
+where
+    P: Pixel,
+    Ch64: From<P::Chan>,
+{
+    /// Compute statistics for each channel of pixels in a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds.  There is one item for
+    /// each color component, followed by one for alpha.  If the clipped
+    /// region is empty, the result is empty.
+    ///
+    /// ### Find the range for auto-levels
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray16>::new()
+    ///     .with_fn(4, 4, |x, y| SGray16::new(0x1000 * (x + y) as u16));
+    /// let stats = r.channel_stats(r.region());
+    /// assert_eq!(stats.len(), 2);
+    /// assert_eq!(stats[0].min, Ch16::new(0));
+    /// assert_eq!(stats[0].max, Ch16::new(0x6000));
+    /// assert_eq!(stats[0].mean, f64::from(0x3000) / 65535.0);
+    /// assert!(r.channel_stats((4, 0, 1, 1)).is_empty());
+    /// ```
+    pub fn channel_stats<R>(&self, reg: R) -> Vec<ChannelStats<P::Chan>>
+    where
+        R: Into<Region>,
+    {
+        let scale = int_scale::<P::Chan>();
+        let comps = P::default().components().len();
+        let init = (P::Chan::MAX, P::Chan::MIN, 0u64, 0.0);
+        let mut stats = vec![init; comps + 1];
+        let mut count = 0u64;
+        for row in self.region_rows(reg) {
+            for p in row {
+                let chans = p.components().iter().copied();
+                let chans = chans.chain(Some(p.alpha()));
+                for ((min, max, isum, fsum), c) in stats.iter_mut().zip(chans) {
+                    *min = (*min).min(c);
+                    *max = (*max).max(c);
+                    let v = f64::from(Ch64::from(c));
+                    match scale {
+                        Some(scale) => *isum += (v * scale).round() as u64,
+                        None => *fsum += v,
+                    }
+                }
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return vec![];
+        }
+        let n = count as f64;
+        stats
+            .into_iter()
+            .map(|(min, max, isum, fsum)| {
+                let mean = match scale {
+                    Some(scale) => isum as f64 / scale / n,
+                    None => fsum / n,
+                };
+                ChannelStats { min, max, mean }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn histogram() {
+        let mut r = RasterBuilder::<SGrayAlpha16>::new().with_clear(10, 10);
+        r.set_region((0, 0, 5, 10), SGrayAlpha16::with_alpha(0x1234, 0x8000));
+        r.set_region((5, 0, 1, 1), SGrayAlpha16::new(0xFFFF));
+        let hist = r.histogram();
+        assert_eq!(hist.channels(), 2);
+        assert_eq!(hist.channel(0).len(), 65536);
+        assert_eq!(hist.channel(0)[0x1234], 50);
+        assert_eq!(hist.channel(0)[0xFFFF], 1);
+        assert_eq!(hist.channel(0)[0], 49);
+        assert_eq!(hist.alpha()[0x8000], 50);
+        assert_eq!(hist.alpha()[0xFFFF], 1);
+        assert_eq!(hist.alpha()[0], 49);
+        let hist = r.histogram_region((3, -2, 4, 4));
+        assert_eq!(hist.channel(0)[0x1234], 4);
+        assert_eq!(hist.channel(0)[0xFFFF], 1);
+        assert_eq!(hist.channel(0)[0], 3);
+        assert_eq!(hist.channel(0).iter().sum::<u32>(), 8);
+    }
+
+    #[test]
+    fn histogram_mask() {
+        let mut r = RasterBuilder::<Mask8>::new().with_clear(3, 3);
+        r.set_pixel(1, 1, Mask8::new(0x80));
+        let hist = r.histogram();
+        assert_eq!(hist.channels(), 1);
+        assert_eq!(hist.alpha()[0x80], 1);
+        assert_eq!(hist.alpha()[0], 8);
+    }
+
+    #[test]
+    fn channel_stats() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(16, 16, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 0x40, 0xFF)
+        });
+        let stats = r.channel_stats((8, 8, 100, 100));
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats[0].min, Ch8::new(8));
+        assert_eq!(stats[0].max, Ch8::new(15));
+        assert_eq!(stats[0].mean, 11.5 / 255.0);
+        assert_eq!(stats[1].mean, 11.5 / 255.0);
+        assert_eq!(stats[2].min, stats[2].max);
+        assert_eq!(stats[3].mean, 1.0);
+        assert!(r.channel_stats((0, 0, 0, 0)).is_empty());
+        let r = RasterBuilder::<Gray32>::new().with_fn(2, 1, |x, _| {
+            Gray32::new(x as f32 * 0.5)
+        });
+        let stats = r.channel_stats(r.region());
+        assert_eq!(stats[0].min, Ch32::new(0.0));
+        assert_eq!(stats[0].max, Ch32::new(0.5));
+        assert_eq!(stats[0].mean, 0.25);
+        assert_eq!(stats[1].mean, 1.0);
+    }
+
+    #[test]
+    fn channel_stats_exact() {
+        let r = RasterBuilder::<Gray16>::new()
+            .with_color(4096, 4096, Gray16::new(0xFFFE));
+        let stats = r.channel_stats(r.region());
+        assert_eq!(stats[0].mean, 65534.0 / 65535.0);
+    }
+}
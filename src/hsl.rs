@@ -0,0 +1,349 @@
+// hsl.rs       HSL color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight, Translucent};
+use crate::gamma::{self, Linear, Srgb};
+use crate::hsv::{channel_from_f32, hue_turns};
+use crate::pixel::fmt_pixel;
+use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// HSL bi-hexcone [color model], with optional [alpha channel].
+///
+/// The components are *hue*, *saturation* and *lightness*.  *Hue* is stored
+/// as a fraction of a full turn around the color wheel, starting at *red*,
+/// and is zero for achromatic colors.  It is derived from
+/// [Rgb](struct.Rgb.html) components with the same gamma encoding.
+///
+/// Like [Hsv](struct.Hsv.html), converting 8-bit colors to HSL and back can
+/// change them by a few steps.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Hsl<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    components: [C; 3],
+    alpha: A,
+    gamma: PhantomData<G>,
+}
+
+impl<C, A, G> Hsl<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying *hue*,
+    /// *saturation* and *lightness*.
+    pub fn new<H>(hue: H, saturation: H, lightness: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(hue, saturation, lightness, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying *hue*, *saturation*, *lightness* and *alpha*.
+    pub fn with_alpha<H, B>(
+        hue: H,
+        saturation: H,
+        lightness: H,
+        alpha: B,
+    ) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let hue = C::from(hue);
+        let saturation = C::from(saturation);
+        let lightness = C::from(lightness);
+        let components = [hue, saturation, lightness];
+        let alpha = A::from(alpha);
+        Hsl {
+            components,
+            alpha,
+            gamma: PhantomData,
+        }
+    }
+    /// Get the *hue* component.
+    pub fn hue(self) -> C {
+        self.components[0]
+    }
+    /// Get the *saturation* component.
+    pub fn saturation(self) -> C {
+        self.components[1]
+    }
+    /// Get the *lightness* component.
+    pub fn lightness(self) -> C {
+        self.components[2]
+    }
+}
+
+impl<C, A, G> ColorModel for Hsl<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let hue = f32::from(Ch32::from(self.hue()));
+        let saturation = f32::from(Ch32::from(self.saturation()));
+        let lightness = f32::from(Ch32::from(self.lightness()));
+        let rgb = hsl_to_rgb(hue, saturation, lightness);
+        [
+            channel_from_f32(rgb[0]),
+            channel_from_f32(rgb[1]),
+            channel_from_f32(rgb[2]),
+            self.alpha(),
+        ]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let red = f32::from(Ch32::from(rgba[0]));
+        let green = f32::from(Ch32::from(rgba[1]));
+        let blue = f32::from(Ch32::from(rgba[2]));
+        let hsl = rgb_to_hsl(red, green, blue);
+        let hue = channel_from_f32::<C>(hsl[0]);
+        let saturation = channel_from_f32::<C>(hsl[1]);
+        let lightness = channel_from_f32::<C>(hsl[2]);
+        Hsl::with_alpha(hue, saturation, lightness, rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let h = self.hue().max(rhs.hue()) - self.hue().min(rhs.hue());
+        let s = self.saturation().max(rhs.saturation())
+            - self.saturation().min(rhs.saturation());
+        let l = self.lightness().max(rhs.lightness())
+            - self.lightness().min(rhs.lightness());
+        let a = self.alpha().max(rhs.alpha()) - self.alpha().min(rhs.alpha());
+        Hsl::with_alpha(h, s, l, a)
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.hue() <= rhs.hue()
+            && self.saturation() <= rhs.saturation()
+            && self.lightness() <= rhs.lightness()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A, G> fmt::Debug for Hsl<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A, G> Pixel for Hsl<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Hsl);
+
+    const WHITE: Self = Hsl {
+        components: [C::MIN, C::MIN, C::MAX],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = Hsl {
+        components: [C::MIN; 3],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = Hsl {
+        components: [C::MIN; 3],
+        alpha: A::TRANSPARENT,
+        gamma: PhantomData,
+    };
+
+    type Alpha = Straight;
+    type Gamma = G;
+}
+
+impl<C, A, G> Iterator for Hsl<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// Convert *hue* (in turns), *saturation* and *lightness* to *red*, *green*
+/// and *blue*.
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let h = (hue * 6.0) % 6.0;
+    let x = chroma * (1.0 - ((h % 2.0) - 1.0).abs());
+    let m = lightness - chroma / 2.0;
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+/// Convert *red*, *green* and *blue* to *hue* (in turns), *saturation* and
+/// *lightness*.
+fn rgb_to_hsl(red: f32, green: f32, blue: f32) -> [f32; 3] {
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let chroma = max - min;
+    let lightness = (max + min) / 2.0;
+    let saturation = if chroma > 0.0 {
+        chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+    } else {
+        0.0
+    };
+    let (r, g, b) = (f64::from(red), f64::from(green), f64::from(blue));
+    let hue = hue_turns(r, g, b, f64::from(max), f64::from(chroma)) as f32;
+    [hue, saturation, lightness]
+}
+
+/// [Hsl](struct.Hsl.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsl8 = Hsl<Ch8, Opaque<Ch8>, Linear>;
+/// [Hsl](struct.Hsl.html) 16-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsl16 = Hsl<Ch16, Opaque<Ch16>, Linear>;
+/// [Hsl](struct.Hsl.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsl32 = Hsl<Ch32, Opaque<Ch32>, Linear>;
+
+/// [Hsl](struct.Hsl.html) 8-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsla8 = Hsl<Ch8, Translucent<Ch8>, Linear>;
+/// [Hsl](struct.Hsl.html) 16-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsla16 = Hsl<Ch16, Translucent<Ch16>, Linear>;
+/// [Hsl](struct.Hsl.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsla32 = Hsl<Ch32, Translucent<Ch32>, Linear>;
+
+/// [Hsl](struct.Hsl.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsl8 = Hsl<Ch8, Opaque<Ch8>, Srgb>;
+/// [Hsl](struct.Hsl.html) 16-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsl16 = Hsl<Ch16, Opaque<Ch16>, Srgb>;
+/// [Hsl](struct.Hsl.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsl32 = Hsl<Ch32, Opaque<Ch32>, Srgb>;
+
+/// [Hsl](struct.Hsl.html) 8-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsla8 = Hsl<Ch8, Translucent<Ch8>, Srgb>;
+/// [Hsl](struct.Hsl.html) 16-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsla16 = Hsl<Ch16, Translucent<Ch16>, Srgb>;
+/// [Hsl](struct.Hsl.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsla32 = Hsl<Ch32, Translucent<Ch32>, Srgb>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<SHsl8>(), 3);
+        assert_eq!(std::mem::size_of::<SHsl16>(), 6);
+        assert_eq!(std::mem::size_of::<SHsl32>(), 12);
+        assert_eq!(std::mem::size_of::<SHsla8>(), 4);
+        assert_eq!(std::mem::size_of::<SHsla16>(), 8);
+        assert_eq!(std::mem::size_of::<SHsla32>(), 16);
+    }
+
+    const COLORS: [((f32, f32, f32), f32); 6] = [
+        ((1.0, 0.0, 0.0), 0.0),
+        ((1.0, 1.0, 0.0), 1.0 / 6.0),
+        ((0.0, 1.0, 0.0), 2.0 / 6.0),
+        ((0.0, 1.0, 1.0), 3.0 / 6.0),
+        ((0.0, 0.0, 1.0), 4.0 / 6.0),
+        ((1.0, 0.0, 1.0), 5.0 / 6.0),
+    ];
+
+    #[test]
+    fn rgb_to_hsl() {
+        for ((r, g, b), h) in COLORS.iter() {
+            let hsl: SHsl32 = SRgb32::new(*r, *g, *b).convert();
+            let diff = hsl.difference(SHsl32::new(*h, 1.0, 0.5));
+            assert!(diff.components().iter().all(|c| *c <= Ch32::new(1e-6)));
+        }
+        let hsl: SHsl8 = SRgb8::new(0x00, 0xFF, 0xFF).convert();
+        assert_eq!(hsl, SHsl8::new(0x80, 0xFF, 0x80));
+        let hsl: SHsla16 = SRgba16::with_alpha(0, 0x8000, 0, 0x4000).convert();
+        assert_eq!(hsl, SHsla16::with_alpha(0x5555, 0xFFFF, 0x4000, 0x4000));
+    }
+
+    #[test]
+    fn hsl_to_rgb() {
+        for ((r, g, b), h) in COLORS.iter() {
+            let rgb: SRgb32 = SHsl32::new(*h, 1.0, 0.5).convert();
+            let diff = rgb.difference(SRgb32::new(*r, *g, *b));
+            assert!(diff.components().iter().all(|c| *c <= Ch32::new(1e-6)));
+        }
+        let rgb: SRgb8 = SHsl8::new(0xFF, 0xFF, 0x80).convert();
+        assert_eq!(rgb, SRgb8::new(0xFF, 0x01, 0x01));
+        let rgb: SRgb8 = SHsl8::new(0x00, 0x00, 0xFF).convert();
+        assert_eq!(rgb, SRgb8::new(0xFF, 0xFF, 0xFF));
+    }
+
+    #[test]
+    fn achromatic() {
+        for v in 0..=16 {
+            let v = v as f32 / 16.0;
+            let hsl: Hsl32 = Rgb32::new(v, v, v).convert();
+            assert_eq!(hsl, Hsl32::new(0.0, 0.0, v));
+            let rgb: Rgb32 = hsl.convert();
+            assert_eq!(rgb, Rgb32::new(v, v, v));
+        }
+    }
+}
@@ -0,0 +1,399 @@
+// hsv.rs       HSV color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight, Translucent};
+use crate::gamma::{self, Linear, Srgb};
+use crate::pixel::fmt_pixel;
+use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// HSV hexcone [color model], with optional [alpha channel].
+///
+/// The components are *hue*, *saturation* and *value*.  *Hue* is stored as a
+/// fraction of a full turn around the color wheel, starting at *red*.  It is
+/// derived from [Rgb](struct.Rgb.html) components with the same gamma
+/// encoding.
+///
+/// Converting from RGB to HSV and back is limited by *hue* precision.  With
+/// 8-bit channels, each sixth of the color wheel has only about 43 *hue*
+/// steps, so the middle component of a highly saturated color can change by
+/// up to 3 steps; the largest and smallest components are unchanged.  With
+/// 32-bit channels, components can differ by a few units in the last place.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Hsv<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    components: [C; 3],
+    alpha: A,
+    gamma: PhantomData<G>,
+}
+
+impl<C, A, G> Hsv<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying *hue*,
+    /// *saturation* and *value*.
+    pub fn new<H>(hue: H, saturation: H, value: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(hue, saturation, value, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying *hue*, *saturation*, *value* and *alpha*.
+    pub fn with_alpha<H, B>(hue: H, saturation: H, value: H, alpha: B) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let hue = C::from(hue);
+        let saturation = C::from(saturation);
+        let value = C::from(value);
+        let components = [hue, saturation, value];
+        let alpha = A::from(alpha);
+        Hsv {
+            components,
+            alpha,
+            gamma: PhantomData,
+        }
+    }
+    /// Get the *hue* component.
+    pub fn hue(self) -> C {
+        self.components[0]
+    }
+    /// Get the *saturation* component.
+    pub fn saturation(self) -> C {
+        self.components[1]
+    }
+    /// Get the *value* component.
+    pub fn value(self) -> C {
+        self.components[2]
+    }
+}
+
+impl<C, A, G> ColorModel for Hsv<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let hue = f64::from(f32::from(Ch32::from(self.hue())));
+        let saturation = f64::from(f32::from(Ch32::from(self.saturation())));
+        let value = f64::from(f32::from(Ch32::from(self.value())));
+        let rgb = hsv_to_rgb(hue, saturation, value);
+        [
+            channel_from_f32(rgb[0] as f32),
+            channel_from_f32(rgb[1] as f32),
+            channel_from_f32(rgb[2] as f32),
+            self.alpha(),
+        ]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let red = f64::from(f32::from(Ch32::from(rgba[0])));
+        let green = f64::from(f32::from(Ch32::from(rgba[1])));
+        let blue = f64::from(f32::from(Ch32::from(rgba[2])));
+        let hsv = rgb_to_hsv(red, green, blue);
+        let hue = channel_from_f32::<C>(hsv[0] as f32);
+        let saturation = channel_from_f32::<C>(hsv[1] as f32);
+        let value = channel_from_f32::<C>(hsv[2] as f32);
+        Hsv::with_alpha(hue, saturation, value, rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let h = self.hue().max(rhs.hue()) - self.hue().min(rhs.hue());
+        let s = self.saturation().max(rhs.saturation())
+            - self.saturation().min(rhs.saturation());
+        let v = self.value().max(rhs.value()) - self.value().min(rhs.value());
+        let a = self.alpha().max(rhs.alpha()) - self.alpha().min(rhs.alpha());
+        Hsv::with_alpha(h, s, v, a)
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.hue() <= rhs.hue()
+            && self.saturation() <= rhs.saturation()
+            && self.value() <= rhs.value()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A, G> fmt::Debug for Hsv<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A, G> Pixel for Hsv<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Hsv);
+
+    const WHITE: Self = Hsv {
+        components: [C::MIN, C::MIN, C::MAX],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = Hsv {
+        components: [C::MIN; 3],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = Hsv {
+        components: [C::MIN; 3],
+        alpha: A::TRANSPARENT,
+        gamma: PhantomData,
+    };
+
+    type Alpha = Straight;
+    type Gamma = G;
+}
+
+impl<C, A, G> Iterator for Hsv<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// Make a `Channel` from an `f32`, clamped to the valid range.
+pub(crate) fn channel_from_f32<C: Channel>(v: f32) -> C {
+    C::from(v.clamp(0.0, 1.0))
+}
+
+/// Convert *hue* (in turns), *saturation* and *value* to *red*, *green* and
+/// *blue*.
+///
+/// Intermediate values are `f64`, so that the only rounding error is from
+/// storing the components in channels.
+fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> [f64; 3] {
+    let chroma = value * saturation;
+    let h = (hue * 6.0) % 6.0;
+    let x = chroma * (1.0 - ((h % 2.0) - 1.0).abs());
+    let m = value - chroma;
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+/// Convert *red*, *green* and *blue* to *hue* (in turns), *saturation* and
+/// *value*.
+fn rgb_to_hsv(red: f64, green: f64, blue: f64) -> [f64; 3] {
+    let value = red.max(green).max(blue);
+    let chroma = value - red.min(green).min(blue);
+    let saturation = if value > 0.0 { chroma / value } else { 0.0 };
+    [hue_turns(red, green, blue, value, chroma), saturation, value]
+}
+
+/// Get *hue* (in turns) from *red*, *green*, *blue*, max component and
+/// *chroma*.
+pub(crate) fn hue_turns(
+    red: f64,
+    green: f64,
+    blue: f64,
+    max: f64,
+    chroma: f64,
+) -> f64 {
+    if chroma > 0.0 {
+        let h = if red >= max {
+            (green - blue) / chroma
+        } else if green >= max {
+            (blue - red) / chroma + 2.0
+        } else {
+            (red - green) / chroma + 4.0
+        };
+        let h = h / 6.0;
+        if h < 0.0 {
+            h + 1.0
+        } else {
+            h
+        }
+    } else {
+        0.0
+    }
+}
+
+/// [Hsv](struct.Hsv.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsv8 = Hsv<Ch8, Opaque<Ch8>, Linear>;
+/// [Hsv](struct.Hsv.html) 16-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsv16 = Hsv<Ch16, Opaque<Ch16>, Linear>;
+/// [Hsv](struct.Hsv.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsv32 = Hsv<Ch32, Opaque<Ch32>, Linear>;
+
+/// [Hsv](struct.Hsv.html) 8-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsva8 = Hsv<Ch8, Translucent<Ch8>, Linear>;
+/// [Hsv](struct.Hsv.html) 16-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsva16 = Hsv<Ch16, Translucent<Ch16>, Linear>;
+/// [Hsv](struct.Hsv.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Hsva32 = Hsv<Ch32, Translucent<Ch32>, Linear>;
+
+/// [Hsv](struct.Hsv.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsv8 = Hsv<Ch8, Opaque<Ch8>, Srgb>;
+/// [Hsv](struct.Hsv.html) 16-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsv16 = Hsv<Ch16, Opaque<Ch16>, Srgb>;
+/// [Hsv](struct.Hsv.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsv32 = Hsv<Ch32, Opaque<Ch32>, Srgb>;
+
+/// [Hsv](struct.Hsv.html) 8-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsva8 = Hsv<Ch8, Translucent<Ch8>, Srgb>;
+/// [Hsv](struct.Hsv.html) 16-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsva16 = Hsv<Ch16, Translucent<Ch16>, Srgb>;
+/// [Hsv](struct.Hsv.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [sRGB](gamma/struct.Srgb.html) gamma [pixel](trait.Pixel.html) format.
+pub type SHsva32 = Hsv<Ch32, Translucent<Ch32>, Srgb>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<SHsv8>(), 3);
+        assert_eq!(std::mem::size_of::<SHsv16>(), 6);
+        assert_eq!(std::mem::size_of::<SHsv32>(), 12);
+        assert_eq!(std::mem::size_of::<SHsva8>(), 4);
+        assert_eq!(std::mem::size_of::<SHsva16>(), 8);
+        assert_eq!(std::mem::size_of::<SHsva32>(), 16);
+    }
+
+    #[test]
+    fn rgb_to_hsv() {
+        let hsv: SHsv8 = SRgb8::new(0xFF, 0x00, 0x00).convert();
+        assert_eq!(hsv, SHsv8::new(0x00, 0xFF, 0xFF));
+        let hsv: SHsv8 = SRgb8::new(0x00, 0xFF, 0x00).convert();
+        assert_eq!(hsv, SHsv8::new(0x55, 0xFF, 0xFF));
+        let hsv: SHsv8 = SRgb8::new(0x00, 0x00, 0x80).convert();
+        assert_eq!(hsv, SHsv8::new(0xAA, 0xFF, 0x80));
+        assert_eq!(
+            SHsva8::with_alpha(0x00, 0x00, 0x40, 0x80),
+            SRgba8::with_alpha(0x40, 0x40, 0x40, 0x80).convert()
+        );
+        assert_eq!(
+            SHsv32::new(0.5, 0.5, 1.0),
+            SRgb32::new(0.5, 1.0, 1.0).convert()
+        );
+    }
+
+    #[test]
+    fn hsv_to_rgb() {
+        let rgb: SRgb8 = SHsv8::new(0xFF, 0xFF, 0xFF).convert();
+        assert_eq!(rgb, SRgb8::new(0xFF, 0x00, 0x00));
+        assert_eq!(
+            SRgb8::new(0x80, 0x40, 0x40),
+            SHsv8::new(0x00, 0x80, 0x80).convert()
+        );
+        assert_eq!(
+            SRgba16::with_alpha(0x0000, 0x0000, 0x0000, 0x1234),
+            SHsva16::with_alpha(0x4000, 0xFFFF, 0x0000, 0x1234).convert()
+        );
+        assert_eq!(
+            SRgb32::new(0.625, 0.25, 1.0),
+            SHsv32::new(0.75, 0.75, 1.0).convert()
+        );
+    }
+
+    #[test]
+    fn round_trip_8() {
+        for r in 0..=255 {
+            for g in (0..=255).step_by(5) {
+                for b in (0..=255).step_by(7) {
+                    let rgb = SRgb8::new(r, g, b);
+                    let hsv: SHsv8 = rgb.convert();
+                    let out: SRgb8 = hsv.convert();
+                    let max = |p: SRgb8| p.components().iter().copied().max();
+                    let min = |p: SRgb8| p.components().iter().copied().min();
+                    assert_eq!(max(rgb), max(out));
+                    assert_eq!(min(rgb), min(out));
+                    let diff = rgb.difference(out);
+                    let step = Ch8::new(3);
+                    assert!(diff.components().iter().all(|c| *c <= step));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_32() {
+        for r in 0..=16 {
+            for g in 0..=16 {
+                for b in 0..=16 {
+                    let (r, g, b) = (r as f32, g as f32, b as f32);
+                    let rgb = Rgb32::new(r / 16.0, g / 16.0, b / 16.0);
+                    let hsv: Hsv32 = rgb.convert();
+                    let diff = rgb.difference(hsv.convert());
+                    let max = Ch32::new(2.0 * f32::EPSILON);
+                    assert!(diff.components().iter().all(|c| *c <= max));
+                }
+            }
+        }
+    }
+}
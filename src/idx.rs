@@ -0,0 +1,141 @@
+// idx.rs       Indexed color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::Straight;
+use crate::gamma::Linear;
+use crate::pixel::fmt_pixel;
+use crate::{Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+
+/// 8-bit [Palette](struct.Palette.html) index [pixel](trait.Pixel.html)
+/// format.
+///
+/// An `Idx8` has no color on its own; a `Raster<Idx8>` is
+/// [expanded](struct.Raster.html#method.expand) using a `Palette`.  When
+/// converted directly to another format, the index is treated as an opaque
+/// gray value.
+///
+/// ### Expand an indexed `Raster`
+/// ```
+/// # use pix::*;
+/// let mut palette = Palette::new(2);
+/// palette.set_entry(SRgba8::with_alpha(0x00, 0x00, 0x00, 0x00));
+/// palette.set_entry(SRgba8::with_alpha(0xFF, 0x00, 0x00, 0xFF));
+/// let mut r = RasterBuilder::<Idx8>::new().with_clear(2, 1);
+/// r.set_pixel(1, 0, Idx8::new(1));
+/// let r = r.expand(&palette);
+/// assert_eq!(r.as_u8_slice(), &[0, 0, 0, 0, 0xFF, 0, 0, 0xFF]);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Idx8 {
+    index: Ch8,
+}
+
+impl Idx8 {
+    /// Create a new `Idx8` value.
+    pub fn new(index: u8) -> Self {
+        let index = Ch8::new(index);
+        Idx8 { index }
+    }
+    /// Get the palette index.
+    pub fn index(self) -> usize {
+        usize::from(u8::from(self.index))
+    }
+}
+
+impl ColorModel for Idx8 {
+    type Chan = Ch8;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        std::slice::from_ref(&self.index)
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        Ch8::MAX
+    }
+
+    /// Set the *alpha* component (ignored)
+    fn set_alpha(&mut self, _alpha: Self::Chan) {}
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        [self.index, self.index, self.index, Ch8::MAX]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        Idx8 { index: rgba[0] }
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let index = self.index.max(rhs.index) - self.index.min(rhs.index);
+        Idx8 { index }
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.index <= rhs.index
+    }
+}
+
+impl fmt::Debug for Idx8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl Pixel for Idx8 {
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Idx);
+
+    const WHITE: Self = Idx8 { index: Ch8::MAX };
+    const BLACK: Self = Idx8 { index: Ch8::MIN };
+    const TRANSPARENT: Self = Idx8 { index: Ch8::MIN };
+
+    type Alpha = Straight;
+    type Gamma = Linear;
+}
+
+impl Iterator for Idx8 {
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+impl From<u8> for Idx8 {
+    /// Get an `Idx8` from a `u8`
+    fn from(c: u8) -> Self {
+        Idx8::new(c)
+    }
+}
+
+impl From<Idx8> for u8 {
+    /// Get a `u8` from an `Idx8`
+    fn from(c: Idx8) -> Self {
+        c.index.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<Idx8>(), 1);
+    }
+
+    #[test]
+    fn with_u8_buffer() {
+        let r = RasterBuilder::<Idx8>::new()
+            .with_u8_buffer(3, 1, vec![0, 7, 255]);
+        assert_eq!(r.pixel(1, 0).index(), 7);
+        assert_eq!(u8::from(r.pixel(2, 0)), 255);
+    }
+}
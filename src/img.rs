@@ -0,0 +1,165 @@
+// img.rs       Conversions with the image crate.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::raster::pixels_buffer;
+use crate::{
+    Ch16, Ch8, Pixel, Raster, RasterBuilder, SBgr8, SBgra8, SGray16, SGray8,
+    SGrayAlpha16, SGrayAlpha8, SRgb16, SRgb8, SRgba16, SRgba8,
+};
+use image::{DynamicImage, ImageBuffer};
+use std::any::TypeId;
+
+/// Implement conversions between an `ImageBuffer` and a `Raster`.
+///
+/// Both store channels in the same order, so buffers are reused.
+macro_rules! impl_image_conv {
+    ($img:ty, $sub:ty, $pix:ty, $with_buffer:ident) => {
+        impl From<ImageBuffer<$img, Vec<$sub>>> for Raster<$pix> {
+            fn from(img: ImageBuffer<$img, Vec<$sub>>) -> Self {
+                let (width, height) = img.dimensions();
+                RasterBuilder::new().$with_buffer(width, height, img.into_raw())
+            }
+        }
+
+        impl From<Raster<$pix>> for ImageBuffer<$img, Vec<$sub>> {
+            fn from(raster: Raster<$pix>) -> Self {
+                let (width, height) = (raster.width(), raster.height());
+                let buffer: Box<[$sub]> = pixels_buffer(raster.into());
+                // Buffer length always matches dimensions
+                ImageBuffer::from_raw(width, height, buffer.into()).unwrap()
+            }
+        }
+    };
+}
+
+impl_image_conv!(image::Luma<u8>, u8, SGray8, with_u8_buffer);
+impl_image_conv!(image::LumaA<u8>, u8, SGrayAlpha8, with_u8_buffer);
+impl_image_conv!(image::Rgb<u8>, u8, SRgb8, with_u8_buffer);
+impl_image_conv!(image::Rgba<u8>, u8, SRgba8, with_u8_buffer);
+impl_image_conv!(image::Bgr<u8>, u8, SBgr8, with_u8_buffer);
+impl_image_conv!(image::Bgra<u8>, u8, SBgra8, with_u8_buffer);
+impl_image_conv!(image::Luma<u16>, u16, SGray16, with_u16_buffer);
+impl_image_conv!(image::LumaA<u16>, u16, SGrayAlpha16, with_u16_buffer);
+impl_image_conv!(image::Rgb<u16>, u16, SRgb16, with_u16_buffer);
+impl_image_conv!(image::Rgba<u16>, u16, SRgba16, with_u16_buffer);
+
+impl<P: Pixel> Raster<P> {
+    /// Create a `Raster` from an `image` crate `DynamicImage`.
+    ///
+    /// The image is first converted to the closest `pix` format, such as
+    /// [SRgba8](type.SRgba8.html) for `ImageRgba8`, and then to `P`.  When
+    /// these formats are the same, no conversion is needed.
+    ///
+    /// ### Load an image
+    /// ```
+    /// # use pix::*;
+    /// let img = image::DynamicImage::new_luma_a16(4, 3);
+    /// let r = Raster::<SRgba8>::from_dynamic_image(img);
+    /// assert_eq!((r.width(), r.height()), (4, 3));
+    /// ```
+    pub fn from_dynamic_image(img: DynamicImage) -> Self
+    where
+        P::Chan: From<Ch8> + From<Ch16>,
+    {
+        match img {
+            DynamicImage::ImageLuma8(img) => Self::from_image(img),
+            DynamicImage::ImageLumaA8(img) => Self::from_image(img),
+            DynamicImage::ImageRgb8(img) => Self::from_image(img),
+            DynamicImage::ImageRgba8(img) => Self::from_image(img),
+            DynamicImage::ImageBgr8(img) => Self::from_image(img),
+            DynamicImage::ImageBgra8(img) => Self::from_image(img),
+            DynamicImage::ImageLuma16(img) => Self::from_image(img),
+            DynamicImage::ImageLumaA16(img) => Self::from_image(img),
+            DynamicImage::ImageRgb16(img) => Self::from_image(img),
+            DynamicImage::ImageRgba16(img) => Self::from_image(img),
+        }
+    }
+
+    /// Create a `Raster` from an `ImageBuffer`, converting the format.
+    fn from_image<I, S>(img: I) -> Self
+    where
+        I: Into<Raster<S>>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+    {
+        let src = img.into();
+        if TypeId::of::<S>() == TypeId::of::<P>() {
+            // Same pixel format -- reuse the buffer
+            let (width, height) = (src.width(), src.height());
+            let pixels: Box<[P]> = pixels_buffer(src.into());
+            return RasterBuilder::new().with_pixels(width, height, pixels);
+        }
+        RasterBuilder::new().with_raster(&src)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+    use image::{DynamicImage, ImageOutputFormat};
+
+    /// Encode an image as PNG, then decode it
+    fn png_round_trip(img: DynamicImage) -> DynamicImage {
+        let mut png = vec![];
+        img.write_to(&mut png, ImageOutputFormat::Png).unwrap();
+        image::load_from_memory(&png).unwrap()
+    }
+
+    #[test]
+    fn rgba8() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(5, 4, |x, y| {
+            SRgba8::with_alpha(x as u8 * 50, y as u8 * 60, 0x80, 0xF0)
+        });
+        let img = DynamicImage::ImageRgba8(r.clone().into());
+        let img = png_round_trip(img);
+        assert_eq!(Raster::<SRgba8>::from_dynamic_image(img.clone()), r);
+        let img = img.into_rgba8();
+        assert_eq!(img.get_pixel(4, 3), &image::Rgba([200, 180, 0x80, 0xF0]));
+        assert_eq!(Raster::from(img), r);
+    }
+
+    #[test]
+    fn gray16() {
+        let r = RasterBuilder::<SGray16>::new().with_fn(3, 3, |x, y| {
+            SGray16::new((x * 0x1111 + y * 0x0101) as u16)
+        });
+        let img = DynamicImage::ImageLuma16(r.clone().into());
+        let img = png_round_trip(img);
+        assert_eq!(Raster::<SGray16>::from_dynamic_image(img.clone()), r);
+        let img = img.into_luma16();
+        assert_eq!(img.get_pixel(2, 1), &image::Luma([0x2323]));
+        assert_eq!(Raster::from(img), r);
+    }
+
+    #[test]
+    fn formats() {
+        let r = RasterBuilder::<SRgb16>::new()
+            .with_color(2, 2, SRgb16::new(0x1234, 0x5678, 0x9ABC));
+        let img: image::ImageBuffer<image::Rgb<u16>, Vec<u16>> =
+            r.clone().into();
+        assert_eq!(img.get_pixel(1, 1), &image::Rgb([0x1234, 0x5678, 0x9ABC]));
+        assert_eq!(Raster::from(img), r);
+        let r = RasterBuilder::<SBgra8>::new()
+            .with_color(2, 1, SBgra8::with_alpha(1, 2, 3, 4));
+        let img: image::ImageBuffer<image::Bgra<u8>, Vec<u8>> =
+            r.clone().into();
+        assert_eq!(img.as_raw(), &vec![3, 2, 1, 4, 3, 2, 1, 4]);
+        assert_eq!(Raster::from(img), r);
+        let img: image::GrayAlphaImage =
+            RasterBuilder::<SGrayAlpha8>::new().with_clear(0, 0).into();
+        assert_eq!(img.dimensions(), (0, 0));
+    }
+
+    #[test]
+    fn dynamic_convert() {
+        let img = image::RgbImage::from_pixel(2, 2, image::Rgb([255, 0, 0]));
+        let img = DynamicImage::ImageRgb8(img);
+        let r = Raster::<SRgba16>::from_dynamic_image(img);
+        let clr = SRgba16::with_alpha(0xFFFF, 0, 0, 0xFFFF);
+        assert!(r.as_slice().iter().all(|p| *p == clr));
+        let img = DynamicImage::new_luma_a8(3, 1);
+        let r = Raster::<SGray8>::from_dynamic_image(img);
+        assert_eq!(r.as_u8_slice(), &[0, 0, 0]);
+    }
+}
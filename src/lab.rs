@@ -0,0 +1,328 @@
+// lab.rs       CIE L*a*b* color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight, Translucent};
+use crate::gamma::Linear;
+use crate::hsv::channel_from_f32;
+use crate::xyz::{rgb_to_xyz, xyz_to_rgb, WHITE_X, WHITE_Z};
+use crate::pixel::fmt_pixel;
+use crate::{Ch32, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+
+/// CIE L\*a\*b\* [color model], with optional [alpha channel].
+///
+/// The components are *lightness* (L\*), *a\** (green to red) and *b\**
+/// (blue to yellow), converted from linear sRGB through
+/// [Xyz](struct.Xyz.html) with a D65 white point.
+///
+/// Components are scaled to fit the channel range:
+///
+/// * L\* from 0 to 100 is stored as 0.0 to 1.0.
+/// * a\* and b\* from -128 to 128 are stored as 0.0 to 1.0, so that zero is
+///   stored as 0.5.  Values outside that range are clamped.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Lab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    components: [C; 3],
+    alpha: A,
+}
+
+impl<C, A> Lab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying
+    /// scaled *L\**, *a\** and *b\** components.
+    pub fn new<H>(l: H, a: H, b: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(l, a, b, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying scaled *L\**, *a\**, *b\** and *alpha*.
+    pub fn with_alpha<H, B>(l: H, a: H, b: H, alpha: B) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let components = [C::from(l), C::from(a), C::from(b)];
+        let alpha = A::from(alpha);
+        Lab { components, alpha }
+    }
+    /// Get the scaled *lightness* (L\*) component.
+    pub fn l(self) -> C {
+        self.components[0]
+    }
+    /// Get the scaled *a\** component.
+    pub fn a(self) -> C {
+        self.components[1]
+    }
+    /// Get the scaled *b\** component.
+    pub fn b(self) -> C {
+        self.components[2]
+    }
+}
+
+impl<C, A> Lab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C>,
+{
+    /// Get unscaled *L\**, *a\** and *b\** values.
+    pub fn to_lab(self) -> [f32; 3] {
+        let l = f32::from(Ch32::from(self.l())) * 100.0;
+        let a = (f32::from(Ch32::from(self.a())) - 0.5) * 256.0;
+        let b = (f32::from(Ch32::from(self.b())) - 0.5) * 256.0;
+        [l, a, b]
+    }
+    /// Get the color difference (CIE76 ΔE\*) from another color.
+    ///
+    /// This is the Euclidean distance between unscaled L\*a\*b\* values.  A
+    /// difference around 2.3 is just noticeable.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let white: Lab32 = SRgb8::new(0xFF, 0xFF, 0xFF).convert();
+    /// let black: Lab32 = SRgb8::new(0x00, 0x00, 0x00).convert();
+    /// assert!((white.delta_e(black) - 100.0).abs() < 0.01);
+    /// ```
+    pub fn delta_e(self, rhs: Self) -> f32 {
+        let [l0, a0, b0] = self.to_lab();
+        let [l1, a1, b1] = rhs.to_lab();
+        let (dl, da, db) = (l0 - l1, a0 - a1, b0 - b1);
+        (dl * dl + da * da + db * db).sqrt()
+    }
+}
+
+impl<C, A> ColorModel for Lab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let [l, a, b] = self.to_lab();
+        let fy = (l + 16.0) / 116.0;
+        let fx = fy + a / 500.0;
+        let fz = fy - b / 200.0;
+        let xyz = [
+            lab_f_inv(fx) * WHITE_X,
+            lab_f_inv(fy),
+            lab_f_inv(fz) * WHITE_Z,
+        ];
+        let rgb = xyz_to_rgb(xyz);
+        [
+            channel_from_f32(rgb[0]),
+            channel_from_f32(rgb[1]),
+            channel_from_f32(rgb[2]),
+            self.alpha(),
+        ]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let red = f32::from(Ch32::from(rgba[0]));
+        let green = f32::from(Ch32::from(rgba[1]));
+        let blue = f32::from(Ch32::from(rgba[2]));
+        let [x, y, z] = rgb_to_xyz([red, green, blue]);
+        let fx = lab_f(x / WHITE_X);
+        let fy = lab_f(y);
+        let fz = lab_f(z / WHITE_Z);
+        let l = channel_from_f32::<C>((116.0 * fy - 16.0) / 100.0);
+        let a = channel_from_f32::<C>(500.0 * (fx - fy) / 256.0 + 0.5);
+        let b = channel_from_f32::<C>(200.0 * (fy - fz) / 256.0 + 0.5);
+        Lab::with_alpha(l, a, b, rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let diff = |a: C, b: C| a.max(b) - a.min(b);
+        Lab::with_alpha(
+            diff(self.l(), rhs.l()),
+            diff(self.a(), rhs.a()),
+            diff(self.b(), rhs.b()),
+            diff(self.alpha(), rhs.alpha()),
+        )
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.l() <= rhs.l()
+            && self.a() <= rhs.a()
+            && self.b() <= rhs.b()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A> fmt::Debug for Lab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A> Pixel for Lab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Lab);
+
+    const WHITE: Self = Lab {
+        components: [C::MAX, C::MID, C::MID],
+        alpha: A::OPAQUE,
+    };
+    const BLACK: Self = Lab {
+        components: [C::MIN, C::MID, C::MID],
+        alpha: A::OPAQUE,
+    };
+    const TRANSPARENT: Self = Lab {
+        components: [C::MIN, C::MID, C::MID],
+        alpha: A::TRANSPARENT,
+    };
+
+    type Alpha = Straight;
+    type Gamma = Linear;
+}
+
+impl<C, A> Iterator for Lab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// Threshold between linear and cube root segments of L\*a\*b\* function
+const DELTA: f32 = 6.0 / 29.0;
+
+/// L\*a\*b\* transfer function
+fn lab_f(t: f32) -> f32 {
+    if t > DELTA * DELTA * DELTA {
+        t.cbrt()
+    } else {
+        t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+    }
+}
+
+/// Inverse of L\*a\*b\* transfer function
+fn lab_f_inv(t: f32) -> f32 {
+    if t > DELTA {
+        t * t * t
+    } else {
+        3.0 * DELTA * DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+/// [Lab](struct.Lab.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [pixel](trait.Pixel.html) format.
+pub type Lab32 = Lab<Ch32, Opaque<Ch32>>;
+/// [Lab](struct.Lab.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [pixel](trait.Pixel.html) format.
+pub type Laba32 = Lab<Ch32, Translucent<Ch32>>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn assert_near(lab: Lab32, v: [f32; 3]) {
+        let [l, a, b] = lab.to_lab();
+        assert!((l - v[0]).abs() < 0.01, "{:?}", lab.to_lab());
+        assert!((a - v[1]).abs() < 0.01, "{:?}", lab.to_lab());
+        assert!((b - v[2]).abs() < 0.01, "{:?}", lab.to_lab());
+    }
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<Lab32>(), 12);
+        assert_eq!(std::mem::size_of::<Laba32>(), 16);
+    }
+
+    #[test]
+    fn rgb_to_lab() {
+        let colors = [
+            (SRgb8::new(0xFF, 0xFF, 0xFF), [100.0, 0.0, 0.0]),
+            (SRgb8::new(0x00, 0x00, 0x00), [0.0, 0.0, 0.0]),
+            (SRgb8::new(0xFF, 0x00, 0x00), [53.24, 80.09, 67.20]),
+            (SRgb8::new(0x00, 0xFF, 0x00), [87.73, -86.18, 83.18]),
+            (SRgb8::new(0x00, 0x00, 0xFF), [32.30, 79.19, -107.86]),
+            (SRgb8::new(0x80, 0x80, 0x80), [53.59, 0.0, 0.0]),
+        ];
+        for (rgb, lab) in colors.iter() {
+            assert_near(rgb.convert(), *lab);
+        }
+    }
+
+    #[test]
+    fn lab_to_rgb() {
+        let colors = [
+            SRgb8::new(0xFF, 0x00, 0x00),
+            SRgb8::new(0x00, 0xFF, 0x00),
+            SRgb8::new(0x00, 0x00, 0xFF),
+            SRgb8::new(0x80, 0x80, 0x80),
+            SRgb8::new(0x12, 0x34, 0x56),
+        ];
+        for rgb in colors.iter() {
+            let lab: Lab32 = rgb.convert();
+            let srgb: SRgb32 = lab.convert();
+            assert_eq!(*rgb, srgb.convert());
+        }
+    }
+
+    #[test]
+    fn with_raster() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(2, 1);
+        r.set_pixel(1, 0, SRgb8::new(0xFF, 0x00, 0x00));
+        let l = RasterBuilder::<Lab32>::new().with_raster(&r);
+        assert_near(l.pixel(0, 0), [0.0, 0.0, 0.0]);
+        assert_near(l.pixel(1, 0), [53.24, 80.09, 67.20]);
+    }
+
+    #[test]
+    fn delta_e() {
+        let a: Lab32 = SRgb8::new(0xFF, 0x00, 0x00).convert();
+        let b: Lab32 = SRgb8::new(0xFE, 0x00, 0x00).convert();
+        assert!(a.delta_e(b) < 1.0);
+        assert_eq!(a.delta_e(a), 0.0);
+    }
+}
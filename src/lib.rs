@@ -20,38 +20,138 @@
 //! # use pix::*;
 //! let mut src = RasterBuilder::<SRgb8>::new().with_clear(120, 120);
 //! // ... load pixels into raster
-//! let dst: Raster<Rgba8p> = RasterBuilder::new().with_raster(&src);
+//! let dst: Raster<Rgba8p> = src.convert();
 //! ```
 //!
+//! ### Features
+//!
+//! * `bytemuck`: Casting pixels and channels to and from bytes, using
+//!   [bytemuck].
+//! * `image`: Conversions with [image] crate buffers.
+//! * `rayon`: Parallel row iterators and conversions, using [rayon].
+//! * `serde`: Serialization of rasters, regions and pixels, using [serde].
+//! * `simd`: SIMD conversion of common 8-bit formats (x86_64 only).
+//! * `term`: Rendering rasters as text, for printing to a terminal.
+//!
+//! [bytemuck]: https://docs.rs/bytemuck
+//! [image]: https://docs.rs/image
+//! [rayon]: https://docs.rs/rayon
+//! [serde]: https://docs.rs/serde
 #![warn(missing_docs)]
 #![warn(missing_doc_code_examples)]
 
+mod adjust;
 pub mod alpha;
+mod bgr;
+mod bitraster;
+mod bmp;
 mod channel;
+mod chroma;
+mod cmyk;
+mod convolve;
+mod diff;
+mod dither;
+mod downscale;
+mod draw;
+mod dynraster;
+mod farbfeld;
 mod pixel;
 pub mod gamma;
 mod gray;
+mod hex;
+mod histogram;
+mod hsl;
+mod hsv;
+mod idx;
+#[cfg(feature = "image")]
+mod img;
+mod lab;
 mod mask;
 mod model;
+mod oklab;
+mod ops;
+mod pad;
 mod palette;
+mod pattern;
+mod pixfmt;
+#[cfg(feature = "rayon")]
+mod par;
+mod planar;
+mod pnm;
+#[cfg(feature = "bytemuck")]
+mod pod;
 mod private;
 mod raster;
 mod rgb;
+#[cfg(feature = "serde")]
+mod serialize;
+mod shared;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
+mod strided;
+#[cfg(feature = "term")]
+mod term;
+mod view;
+mod window;
+mod xyz;
+mod ycbcr;
+mod yuv;
 
-pub use crate::channel::{Ch16, Ch32, Ch8, Channel};
+pub use crate::bgr::{
+    Bgr, Bgr16, Bgr32, Bgr8, Bgra16, Bgra32, Bgra8, SBgr16, SBgr32, SBgr8,
+    SBgra16, SBgra16p, SBgra32, SBgra32p, SBgra8, SBgra8p,
+};
+pub use crate::bitraster::BitRaster;
+pub use crate::channel::{Ch16, Ch16f, Ch32, Ch64, Ch8, Channel};
 pub use crate::pixel::Pixel;
+pub use crate::diff::RasterDiff;
+pub use crate::cmyk::{
+    Cmyk, Cmyk16, Cmyk32, Cmyk8, Cmyka16, Cmyka32, Cmyka8,
+};
+pub use crate::convolve::{Edge, Kernel};
+pub use crate::draw::Connectivity;
+pub use crate::dynraster::DynRaster;
+pub use crate::farbfeld::FarbfeldError;
 pub use crate::gray::{
-    Gray, Gray16, Gray32, Gray8, GrayAlpha16, GrayAlpha16p, GrayAlpha32,
-    GrayAlpha32p, GrayAlpha8, GrayAlpha8p, SGray16, SGray32, SGray8,
-    SGrayAlpha16, SGrayAlpha16p, SGrayAlpha32, SGrayAlpha32p, SGrayAlpha8,
-    SGrayAlpha8p,
+    Gray, Gray16, Gray16f, Gray32, Gray64, Gray8, GrayAlpha16, GrayAlpha16p,
+    GrayAlpha32, GrayAlpha32p, GrayAlpha8, GrayAlpha8p, SGray16, SGray32,
+    SGray8, SGrayAlpha16, SGrayAlpha16p, SGrayAlpha32, SGrayAlpha32p,
+    SGrayAlpha8, SGrayAlpha8p,
 };
-pub use crate::mask::{Mask, Mask16, Mask32, Mask8};
+pub use crate::hex::HexError;
+pub use crate::histogram::{ChannelStats, Discrete, Histogram};
+pub use crate::hsl::{
+    Hsl, Hsl16, Hsl32, Hsl8, Hsla16, Hsla32, Hsla8, SHsl16, SHsl32, SHsl8,
+    SHsla16, SHsla32, SHsla8,
+};
+pub use crate::hsv::{
+    Hsv, Hsv16, Hsv32, Hsv8, Hsva16, Hsva32, Hsva8, SHsv16, SHsv32, SHsv8,
+    SHsva16, SHsva32, SHsva8,
+};
+pub use crate::idx::Idx8;
+pub use crate::lab::{Lab, Lab32, Laba32};
+pub use crate::mask::{Mask, Mask16, Mask32, Mask64, Mask8};
 pub use crate::model::ColorModel;
+pub use crate::oklab::{Oklab, Oklab32, OklabAlpha32};
+pub use crate::ops::{BlendMode, PorterDuff};
+pub use crate::pad::PadMode;
 pub use crate::palette::Palette;
-pub use crate::raster::{Raster, RasterBuilder, RasterIter, Region};
+pub use crate::pixfmt::{PixAlpha, PixDepth, PixFmt, PixGamma, PixModel};
+pub use crate::planar::PlanarRaster;
+pub use crate::pnm::{DynPnm, PnmError};
+pub use crate::raster::{
+    Raster, RasterBuilder, RasterError, RasterIter, RasterIterMut, Region,
+    RegionMismatch,
+};
 pub use crate::rgb::{
-    Rgb, Rgb16, Rgb32, Rgb8, Rgba16, Rgba16p, Rgba32, Rgba32p, Rgba8, Rgba8p,
-    SRgb16, SRgb32, SRgb8, SRgba16, SRgba16p, SRgba32, SRgba32p, SRgba8,
-    SRgba8p,
+    Rgb, Rgb16, Rgb32, Rgb64, Rgb8, Rgba16, Rgba16f, Rgba16p, Rgba32, Rgba32p,
+    Rgba64, Rgba8, Rgba8p, SRgb16, SRgb32, SRgb8, SRgba16, SRgba16p, SRgba32,
+    SRgba32p, SRgba8, SRgba8p,
 };
+pub use crate::shared::SharedRaster;
+pub use crate::strided::StridedRaster;
+pub use crate::view::{AsRasterView, RasterView, RasterViewMut};
+pub use crate::window::Window;
+pub use crate::xyz::{Xyz, Xyz32, Xyza32};
+pub use crate::ycbcr::{YCbCr, YCbCr16, YCbCr32, YCbCr8};
+pub use crate::yuv::{Yuv420Frame, YuvMatrix};
@@ -5,14 +5,19 @@
 //
 use crate::alpha::{AChannel, Premultiplied, Straight, Translucent};
 use crate::gamma::{self, Linear};
-use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, Gray, Pixel, Rgb};
+use crate::pixel::fmt_pixel;
+use crate::{
+    Ch16, Ch32, Ch64, Ch8, Channel, ColorModel, Gray, PixFmt, PixModel, Pixel,
+    Raster, Rgb,
+};
+use std::fmt;
 use std::ops::Mul;
 
 /// [Translucent] alpha mask [color model].
 ///
 /// [color model]: trait.ColorModel.html
 /// [translucent]: alpha/struct.Translucent.html
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct Mask<C: Channel> {
     alpha: Translucent<C>,
@@ -27,6 +32,17 @@ impl<C: Channel> Mask<C> {
         let alpha = C::from(alpha).into();
         Mask { alpha }
     }
+    /// Get the *alpha* value.
+    pub fn value(self) -> C {
+        self.alpha.value()
+    }
+    /// Set the *alpha* value.
+    pub fn set_value<A>(&mut self, alpha: A)
+    where
+        C: From<A>,
+    {
+        self.alpha = C::from(alpha).into();
+    }
 }
 
 impl<C: Channel> ColorModel for Mask<C> {
@@ -42,6 +58,11 @@ impl<C: Channel> ColorModel for Mask<C> {
         self.alpha.value()
     }
 
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = alpha.into();
+    }
+
     /// Convert to *red*, *green*, *blue* and *alpha* components
     fn to_rgba(self) -> [Self::Chan; 4] {
         [C::MAX, C::MAX, C::MAX, self.alpha()]
@@ -68,10 +89,32 @@ impl<C: Channel> ColorModel for Mask<C> {
     }
 }
 
+impl<C> fmt::Debug for Mask<C>
+where
+    C: Channel,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
 impl<C> Pixel for Mask<C>
 where
     C: Channel,
 {
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Mask);
+
+    const WHITE: Self = Mask {
+        alpha: Translucent::OPAQUE,
+    };
+    const BLACK: Self = Mask {
+        alpha: Translucent::OPAQUE,
+    };
+    const TRANSPARENT: Self = Mask {
+        alpha: Translucent::TRANSPARENT,
+    };
+
     type Alpha = Straight;
     type Gamma = Linear;
 }
@@ -105,6 +148,13 @@ impl From<f32> for Mask32 {
     }
 }
 
+impl From<f64> for Mask64 {
+    /// Get a `Mask` from an `f64`
+    fn from(c: f64) -> Self {
+        Mask::new(c)
+    }
+}
+
 impl<C, A, G> From<Mask<C>> for Rgb<C, A, Straight, G>
 where
     C: Channel,
@@ -173,6 +223,109 @@ impl<C: Channel> Mul<Self> for Mask<C> {
     }
 }
 
+/// Intersection of two mask values (multiply)
+fn and<C: Channel>(a: C, b: C) -> C {
+    a.scale(b)
+}
+
+/// Union of two mask values (maximum)
+fn or<C: Channel>(a: C, b: C) -> C {
+    a.max(b)
+}
+
+/// Exclusive or of two mask values
+fn xor<C: Channel>(a: C, b: C) -> C {
+    a.scale(C::MAX - b) + b.scale(C::MAX - a)
+}
+
+/// Boolean operations on mask rasters.
+///
+/// These work with anti-aliased masks, treating values as partial coverage:
+///
+/// * *and* multiplies coverage
+/// * *or* is the maximum coverage
+/// * *xor* is the coverage of exactly one mask: `a(1 - b) + b(1 - a)`
+/// * *not* is the complement
+///
+/// For binary masks, these match the usual boolean operations.
+///
+/// ### Intersect two clip paths
+/// ```
+/// # use pix::*;
+/// let mut a = RasterBuilder::<Mask8>::new().with_clear(4, 4);
+/// a.set_region((0, 0, 2, 4), Mask8::new(0xFF));
+/// let mut b = RasterBuilder::<Mask8>::new().with_clear(4, 4);
+/// b.set_region((1, 0, 3, 4), Mask8::new(0x80));
+/// let c = a.mask_and(&b);
+/// assert_eq!(c.pixel(0, 0), Mask8::new(0x00));
+/// assert_eq!(c.pixel(1, 0), Mask8::new(0x80));
+/// assert_eq!(c.pixel(2, 0), Mask8::new(0x00));
+/// ```
+impl<C: Channel> Raster<Mask<C>> {
+    /// Create a new mask by intersecting with another mask.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn mask_and(&self, other: &Self) -> Self {
+        self.zip_with(other, |p, q| Mask::new(and(p.alpha(), q.alpha())))
+    }
+    /// Intersect with another mask in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn mask_and_in_place(&mut self, other: &Self) {
+        self.zip_with_in_place(other, |p, q| {
+            *p = Mask::new(and(p.alpha(), q.alpha()))
+        })
+    }
+    /// Create a new mask from the union with another mask.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn mask_or(&self, other: &Self) -> Self {
+        self.zip_with(other, |p, q| Mask::new(or(p.alpha(), q.alpha())))
+    }
+    /// Combine with another mask in place, using union.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn mask_or_in_place(&mut self, other: &Self) {
+        self.zip_with_in_place(other, |p, q| {
+            *p = Mask::new(or(p.alpha(), q.alpha()))
+        })
+    }
+    /// Create a new mask from the exclusive or with another mask.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn mask_xor(&self, other: &Self) -> Self {
+        self.zip_with(other, |p, q| Mask::new(xor(p.alpha(), q.alpha())))
+    }
+    /// Combine with another mask in place, using exclusive or.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn mask_xor_in_place(&mut self, other: &Self) {
+        self.zip_with_in_place(other, |p, q| {
+            *p = Mask::new(xor(p.alpha(), q.alpha()))
+        })
+    }
+    /// Create a new mask with the complement of each value.
+    pub fn mask_not(&self) -> Self {
+        self.map(|p| Mask::new(C::MAX - p.alpha()))
+    }
+    /// Complement each value in place.
+    pub fn mask_not_in_place(&mut self) {
+        self.map_in_place(|p| *p = Mask::new(C::MAX - p.alpha()))
+    }
+}
+
 /// [Mask](struct.Mask.html) 8-bit [straight](alpha/struct.Straight.html) alpha
 /// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
 pub type Mask8 = Mask<Ch8>;
@@ -185,9 +338,14 @@ pub type Mask16 = Mask<Ch16>;
 /// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
 pub type Mask32 = Mask<Ch32>;
 
+/// [Mask](struct.Mask.html) 64-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Mask64 = Mask<Ch64>;
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::RasterBuilder;
 
     #[test]
     fn check_sizes() {
@@ -195,4 +353,91 @@ mod test {
         assert_eq!(std::mem::size_of::<Mask16>(), 2);
         assert_eq!(std::mem::size_of::<Mask32>(), 4);
     }
+
+    fn mask(v: [u8; 16]) -> Raster<Mask8> {
+        RasterBuilder::new().with_u8_buffer(4, 4, v.to_vec())
+    }
+
+    #[test]
+    fn mask_ops() {
+        let a = mask([
+            0xFF, 0xFF, 0x00, 0x00, //
+            0xFF, 0xFF, 0x00, 0x00, //
+            0x80, 0x80, 0x80, 0x80, //
+            0x00, 0xFF, 0x40, 0xC0, //
+        ]);
+        let b = mask([
+            0xFF, 0x00, 0xFF, 0x00, //
+            0x80, 0x40, 0x80, 0x40, //
+            0xFF, 0x00, 0x80, 0x40, //
+            0xFF, 0xFF, 0x80, 0x80, //
+        ]);
+        let and = a.mask_and(&b);
+        assert_eq!(and.as_u8_slice(), &[
+            0xFF, 0x00, 0x00, 0x00, //
+            0x80, 0x40, 0x00, 0x00, //
+            0x80, 0x00, 0x40, 0x20, //
+            0x00, 0xFF, 0x20, 0x60, //
+        ]);
+        let or = a.mask_or(&b);
+        assert_eq!(or.as_u8_slice(), &[
+            0xFF, 0xFF, 0xFF, 0x00, //
+            0xFF, 0xFF, 0x80, 0x40, //
+            0xFF, 0x80, 0x80, 0x80, //
+            0xFF, 0xFF, 0x80, 0xC0, //
+        ]);
+        let xor = a.mask_xor(&b);
+        assert_eq!(xor.as_u8_slice(), &[
+            0x00, 0xFF, 0xFF, 0x00, //
+            0x7F, 0xBF, 0x80, 0x40, //
+            0x7F, 0x80, 0x80, 0x80, //
+            0xFF, 0x00, 0x80, 0x80, //
+        ]);
+        let not = a.mask_not();
+        assert_eq!(not.as_u8_slice(), &[
+            0x00, 0x00, 0xFF, 0xFF, //
+            0x00, 0x00, 0xFF, 0xFF, //
+            0x7F, 0x7F, 0x7F, 0x7F, //
+            0xFF, 0x00, 0xBF, 0x3F, //
+        ]);
+        let mut c = a.clone();
+        c.mask_and_in_place(&b);
+        assert_eq!(c, and);
+        let mut c = a.clone();
+        c.mask_or_in_place(&b);
+        assert_eq!(c, or);
+        let mut c = a.clone();
+        c.mask_xor_in_place(&b);
+        assert_eq!(c, xor);
+        let mut c = a.clone();
+        c.mask_not_in_place();
+        assert_eq!(c, not);
+    }
+
+    #[test]
+    fn mask_ops_32() {
+        let a = RasterBuilder::new().with_color(4, 4, Mask32::new(0.5));
+        let b = RasterBuilder::new().with_color(4, 4, Mask32::new(0.25));
+        assert_eq!(a.mask_and(&b).pixel(3, 3), Mask32::new(0.125));
+        assert_eq!(a.mask_or(&b).pixel(3, 3), Mask32::new(0.5));
+        assert_eq!(a.mask_xor(&b).pixel(3, 3), Mask32::new(0.5));
+        assert_eq!(b.mask_not().pixel(0, 0), Mask32::new(0.75));
+    }
+
+    #[test]
+    #[should_panic]
+    fn mask_size() {
+        let a = RasterBuilder::<Mask16>::new().with_clear(4, 4);
+        let b = RasterBuilder::<Mask16>::new().with_clear(3, 4);
+        a.mask_or(&b);
+    }
+
+    #[test]
+    fn accessors() {
+        let mut m = Mask16::new(0x8000);
+        assert_eq!(m.value(), Ch16::new(0x8000));
+        m.set_value(0x1234);
+        assert_eq!(m, Mask16::new(0x1234));
+        assert_eq!(m.alpha(), m.value());
+    }
 }
@@ -17,9 +17,19 @@ pub trait ColorModel: Sealed {
     /// Get all non-alpha components
     fn components(&self) -> &[Self::Chan];
 
-    /// Get the *alpha* component
+    /// Get the *alpha* component.
+    ///
+    /// For models with an [opaque](alpha/struct.Opaque.html) alpha channel,
+    /// this is [Channel::MAX](trait.Channel.html#associatedconstant.MAX).
     fn alpha(self) -> Self::Chan;
 
+    /// Set the *alpha* component.
+    ///
+    /// For models with an [opaque](alpha/struct.Opaque.html) alpha channel,
+    /// this has no effect.  Other components are not changed, even with
+    /// [premultiplied](alpha/struct.Premultiplied.html) alpha.
+    fn set_alpha(&mut self, alpha: Self::Chan);
+
     /// Convert to *red*, *green*, *blue* and *alpha* components
     fn to_rgba(self) -> [Self::Chan; 4];
 
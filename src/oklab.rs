@@ -0,0 +1,296 @@
+// oklab.rs     Oklab color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight, Translucent};
+use crate::gamma::Linear;
+use crate::hsv::channel_from_f32;
+use crate::pixel::fmt_pixel;
+use crate::{Ch32, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+
+/// Oklab perceptual [color model], with optional [alpha channel].
+///
+/// The components are *lightness* (L), *a* (green to red) and *b* (blue to
+/// yellow), converted from linear sRGB.  Unlike [Lab](struct.Lab.html), hue
+/// stays constant when lightness or chroma change, which makes it a good fit
+/// for gradients.
+///
+/// Components are scaled to fit the channel range:
+///
+/// * L from 0 to 1 is stored unchanged.
+/// * a and b from -0.5 to 0.5 are offset by 0.5, so that zero is stored as
+///   0.5.  All sRGB colors fit within that range.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Oklab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    components: [C; 3],
+    alpha: A,
+}
+
+impl<C, A> Oklab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying
+    /// scaled *L*, *a* and *b* components.
+    pub fn new<H>(l: H, a: H, b: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(l, a, b, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying scaled *L*, *a*, *b* and *alpha*.
+    pub fn with_alpha<H, B>(l: H, a: H, b: H, alpha: B) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let components = [C::from(l), C::from(a), C::from(b)];
+        let alpha = A::from(alpha);
+        Oklab { components, alpha }
+    }
+    /// Get the *lightness* (L) component.
+    pub fn l(self) -> C {
+        self.components[0]
+    }
+    /// Get the scaled *a* component.
+    pub fn a(self) -> C {
+        self.components[1]
+    }
+    /// Get the scaled *b* component.
+    pub fn b(self) -> C {
+        self.components[2]
+    }
+}
+
+impl<C, A> Oklab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C>,
+{
+    /// Get unscaled *L*, *a* and *b* values.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let clr: Oklab32 = SRgb8::new(0xFF, 0xFF, 0xFF).convert();
+    /// let [l, a, b] = clr.to_oklab();
+    /// assert!((l - 1.0).abs() < 0.001);
+    /// assert!(a.abs() < 0.001 && b.abs() < 0.001);
+    /// ```
+    pub fn to_oklab(self) -> [f32; 3] {
+        let l = f32::from(Ch32::from(self.l()));
+        let a = f32::from(Ch32::from(self.a())) - 0.5;
+        let b = f32::from(Ch32::from(self.b())) - 0.5;
+        [l, a, b]
+    }
+}
+
+impl<C, A> ColorModel for Oklab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let [l, a, b] = self.to_oklab();
+        let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+        let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+        let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+        let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+        let red = 4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s;
+        let green = -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s;
+        let blue = -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s;
+        [
+            channel_from_f32(red),
+            channel_from_f32(green),
+            channel_from_f32(blue),
+            self.alpha(),
+        ]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let red = f32::from(Ch32::from(rgba[0]));
+        let green = f32::from(Ch32::from(rgba[1]));
+        let blue = f32::from(Ch32::from(rgba[2]));
+        let (r, g, b) = (red, green, blue);
+        let l = 0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+        let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+        let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+        let ll = 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s;
+        let a = 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s;
+        let b = 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s;
+        Oklab::with_alpha(
+            channel_from_f32::<C>(ll),
+            channel_from_f32::<C>(a + 0.5),
+            channel_from_f32::<C>(b + 0.5),
+            rgba[3],
+        )
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let diff = |a: C, b: C| a.max(b) - a.min(b);
+        Oklab::with_alpha(
+            diff(self.l(), rhs.l()),
+            diff(self.a(), rhs.a()),
+            diff(self.b(), rhs.b()),
+            diff(self.alpha(), rhs.alpha()),
+        )
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.l() <= rhs.l()
+            && self.a() <= rhs.a()
+            && self.b() <= rhs.b()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A> fmt::Debug for Oklab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A> Pixel for Oklab<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Oklab);
+
+    const WHITE: Self = Oklab {
+        components: [C::MAX, C::MID, C::MID],
+        alpha: A::OPAQUE,
+    };
+    const BLACK: Self = Oklab {
+        components: [C::MIN, C::MID, C::MID],
+        alpha: A::OPAQUE,
+    };
+    const TRANSPARENT: Self = Oklab {
+        components: [C::MIN, C::MID, C::MID],
+        alpha: A::TRANSPARENT,
+    };
+
+    type Alpha = Straight;
+    type Gamma = Linear;
+}
+
+impl<C, A> Iterator for Oklab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// [Oklab](struct.Oklab.html) 32-bit [opaque](alpha/struct.Opaque.html) (no
+/// alpha) [pixel](trait.Pixel.html) format.
+pub type Oklab32 = Oklab<Ch32, Opaque<Ch32>>;
+/// [Oklab](struct.Oklab.html) 32-bit [straight](alpha/struct.Straight.html)
+/// alpha [pixel](trait.Pixel.html) format.
+pub type OklabAlpha32 = Oklab<Ch32, Translucent<Ch32>>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn assert_near(clr: Oklab32, v: [f32; 3]) {
+        let [l, a, b] = clr.to_oklab();
+        assert!((l - v[0]).abs() < 0.001, "{:?}", clr.to_oklab());
+        assert!((a - v[1]).abs() < 0.001, "{:?}", clr.to_oklab());
+        assert!((b - v[2]).abs() < 0.001, "{:?}", clr.to_oklab());
+    }
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<Oklab32>(), 12);
+        assert_eq!(std::mem::size_of::<OklabAlpha32>(), 16);
+    }
+
+    #[test]
+    fn rgb_to_oklab() {
+        let colors = [
+            (Rgb32::new(1.0, 1.0, 1.0), [1.0, 0.0, 0.0]),
+            (Rgb32::new(0.0, 0.0, 0.0), [0.0, 0.0, 0.0]),
+            (Rgb32::new(1.0, 0.0, 0.0), [0.627_955, 0.224_863, 0.125_846]),
+            (Rgb32::new(0.0, 0.0, 1.0), [0.452_014, -0.032_457, -0.311_528]),
+        ];
+        for (rgb, lab) in colors.iter() {
+            assert_near(rgb.convert(), *lab);
+        }
+    }
+
+    #[test]
+    fn oklab_to_rgb() {
+        let colors = [
+            SRgb8::new(0xFF, 0xFF, 0xFF),
+            SRgb8::new(0xFF, 0x00, 0x00),
+            SRgb8::new(0x00, 0xFF, 0x00),
+            SRgb8::new(0x00, 0x00, 0xFF),
+            SRgb8::new(0x12, 0x34, 0x56),
+        ];
+        for rgb in colors.iter() {
+            let lab: Oklab32 = rgb.convert();
+            let srgb: SRgb32 = lab.convert();
+            assert_eq!(*rgb, srgb.convert());
+        }
+    }
+
+    #[test]
+    fn with_raster() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(2, 1);
+        r.set_pixel(1, 0, SRgb8::new(0x00, 0x00, 0xFF));
+        let l = RasterBuilder::<Oklab32>::new().with_raster(&r);
+        assert_near(l.pixel(0, 0), [0.0, 0.0, 0.0]);
+        assert_near(l.pixel(1, 0), [0.452_014, -0.032_457, -0.311_528]);
+        let s = RasterBuilder::<SRgb8>::new().with_raster(
+            &RasterBuilder::<SRgb32>::new().with_raster(&l),
+        );
+        assert_eq!(s.as_u8_slice(), r.as_u8_slice());
+    }
+}
@@ -0,0 +1,452 @@
+// ops.rs       Compositing operations.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{Mode as _, Premultiplied};
+use crate::gamma::Mode as _;
+use crate::{Ch16f, Ch32, Ch64, Channel, Pixel};
+use std::any::TypeId;
+
+/// Get *red*, *green*, *blue* and *alpha* components, premultiplied.
+fn to_premultiplied<P: Pixel>(p: P) -> [P::Chan; 4] {
+    let mut rgba = p.to_rgba();
+    if TypeId::of::<P::Alpha>() != TypeId::of::<Premultiplied>() {
+        let alpha = rgba[3];
+        for c in rgba[..3].iter_mut() {
+            *c = Premultiplied::encode(*c, alpha);
+        }
+    }
+    rgba
+}
+
+/// Make a pixel from premultiplied *red*, *green*, *blue* and *alpha*.
+fn from_premultiplied<P: Pixel>(mut rgba: [P::Chan; 4]) -> P {
+    if TypeId::of::<P::Alpha>() != TypeId::of::<Premultiplied>() {
+        let alpha = rgba[3];
+        for c in rgba[..3].iter_mut() {
+            *c = Premultiplied::decode(*c, alpha);
+        }
+    }
+    P::with_rgba(rgba)
+}
+
+/// Get *red*, *green*, *blue* and *alpha* components, with straight alpha.
+fn to_straight<P: Pixel>(p: P) -> [P::Chan; 4] {
+    let mut rgba = p.to_rgba();
+    if TypeId::of::<P::Alpha>() == TypeId::of::<Premultiplied>() {
+        let alpha = rgba[3];
+        for c in rgba[..3].iter_mut() {
+            *c = Premultiplied::decode(*c, alpha);
+        }
+    }
+    rgba
+}
+
+/// Make a pixel from straight *red*, *green*, *blue* and *alpha*.
+fn from_straight<P: Pixel>(mut rgba: [P::Chan; 4]) -> P {
+    if TypeId::of::<P::Alpha>() == TypeId::of::<Premultiplied>() {
+        let alpha = rgba[3];
+        for c in rgba[..3].iter_mut() {
+            *c = Premultiplied::encode(*c, alpha);
+        }
+    }
+    P::with_rgba(rgba)
+}
+
+/// Interpolate between four pixels, using premultiplied components.
+///
+/// * `p` Top-left, top-right, bottom-left and bottom-right pixels.
+/// * `fx` Horizontal position between left and right, from 0 to 1.
+/// * `fy` Vertical position between top and bottom, from 0 to 1.
+///
+/// Components are interpolated in the gamma encoding of the pixel format.
+/// Weights and intermediate values are kept as `f32`, and rounded to the
+/// precision of the pixel's `Channel` only once, at the end.
+pub(crate) fn bilinear<P: Pixel>(p: [P; 4], fx: f32, fy: f32) -> P
+where
+    Ch32: From<P::Chan>,
+{
+    let tl = to_premultiplied(p[0]);
+    let tr = to_premultiplied(p[1]);
+    let bl = to_premultiplied(p[2]);
+    let br = to_premultiplied(p[3]);
+    let v = |c: P::Chan| f32::from(Ch32::from(c));
+    let mut rgba = tl;
+    for (i, c) in rgba.iter_mut().enumerate() {
+        let top = v(tl[i]) + (v(tr[i]) - v(tl[i])) * fx;
+        let bottom = v(bl[i]) + (v(br[i]) - v(bl[i])) * fx;
+        *c = P::Chan::from(top + (bottom - top) * fy);
+    }
+    from_premultiplied(rgba)
+}
+
+/// Porter-Duff compositing operator.
+///
+/// Each operator combines a *source* and a *destination* pixel, using
+/// premultiplied components.  Results are weighted by the portion of each
+/// pixel covered by the other's alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PorterDuff {
+    /// Neither source nor destination (fully transparent)
+    Clear,
+    /// Source only
+    Copy,
+    /// Destination only
+    Dest,
+    /// Source over destination
+    SrcOver,
+    /// Destination over source
+    DestOver,
+    /// Source where destination is opaque
+    SrcIn,
+    /// Destination where source is opaque
+    DestIn,
+    /// Source where destination is transparent
+    SrcOut,
+    /// Destination where source is transparent
+    DestOut,
+    /// Source inside destination, over destination
+    SrcAtop,
+    /// Destination inside source, over source
+    DestAtop,
+    /// Source and destination where the other is transparent
+    Xor,
+}
+
+impl PorterDuff {
+    /// Get source and destination weights for the operator.
+    ///
+    /// * `sa` Source alpha.
+    /// * `da` Destination alpha.
+    fn weights<C: Channel>(self, sa: C, da: C) -> (C, C) {
+        use PorterDuff::*;
+        match self {
+            Clear => (C::MIN, C::MIN),
+            Copy => (C::MAX, C::MIN),
+            Dest => (C::MIN, C::MAX),
+            SrcOver => (C::MAX, C::MAX - sa),
+            DestOver => (C::MAX - da, C::MAX),
+            SrcIn => (da, C::MIN),
+            DestIn => (C::MIN, sa),
+            SrcOut => (C::MAX - da, C::MIN),
+            DestOut => (C::MIN, C::MAX - sa),
+            SrcAtop => (da, C::MAX - sa),
+            DestAtop => (C::MAX - da, sa),
+            Xor => (C::MAX - da, C::MAX - sa),
+        }
+    }
+}
+
+/// Composite a source pixel with a destination pixel.
+///
+/// * `src` Source pixel.
+/// * `dst` Destination pixel.
+/// * `op` Porter-Duff operator.
+///
+/// Components are premultiplied with alpha before compositing, using the
+/// gamma encoding of the pixel format.
+pub fn composite<P: Pixel>(src: P, dst: P, op: PorterDuff) -> P {
+    let (sa, da) = (src.alpha(), dst.alpha());
+    match op {
+        PorterDuff::Copy => return src,
+        PorterDuff::Dest => return dst,
+        PorterDuff::SrcOver => {
+            if sa == P::Chan::MIN {
+                return dst;
+            }
+            if sa == P::Chan::MAX || da == P::Chan::MIN {
+                return src;
+            }
+        }
+        _ => (),
+    }
+    let s = to_premultiplied(src);
+    let d = to_premultiplied(dst);
+    from_premultiplied(weigh(s, d, op.weights(sa, da)))
+}
+
+/// Composite a source pixel with partial coverage.
+///
+/// * `src` Source pixel.
+/// * `dst` Destination pixel.
+/// * `cov` Coverage of source, multiplied with its alpha.
+/// * `op` Porter-Duff operator.
+pub fn composite_coverage<P: Pixel>(
+    src: P,
+    dst: P,
+    cov: P::Chan,
+    op: PorterDuff,
+) -> P {
+    if cov == P::Chan::MAX {
+        return composite(src, dst, op);
+    }
+    if cov == P::Chan::MIN && op == PorterDuff::SrcOver {
+        return dst;
+    }
+    let mut s = to_premultiplied(src);
+    s.iter_mut().for_each(|c| *c = c.scale(cov));
+    let d = to_premultiplied(dst);
+    from_premultiplied(weigh(s, d, op.weights(s[3], dst.alpha())))
+}
+
+/// Add premultiplied source and destination components, with weights.
+fn weigh<C: Channel>(s: [C; 4], d: [C; 4], (fs, fd): (C, C)) -> [C; 4] {
+    [
+        s[0].scale(fs) + d[0].scale(fd),
+        s[1].scale(fs) + d[1].scale(fd),
+        s[2].scale(fs) + d[2].scale(fd),
+        s[3].scale(fs) + d[3].scale(fd),
+    ]
+}
+
+/// Separable blend mode.
+///
+/// Each mode combines the color components of a *source* and a
+/// *destination* pixel, independently for each channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Product of source and destination (darkens)
+    Multiply,
+    /// Complement of product of complements (lightens)
+    Screen,
+    /// Multiply or screen, depending on destination
+    Overlay,
+    /// Minimum of source and destination
+    Darken,
+    /// Maximum of source and destination
+    Lighten,
+    /// Absolute difference of source and destination
+    Difference,
+    /// Like `Difference`, but with lower contrast
+    Exclusion,
+}
+
+impl BlendMode {
+    /// Blend one source channel with a destination channel.
+    ///
+    /// * `s` Source channel.
+    /// * `d` Destination channel.
+    fn blend<C: Channel>(self, s: C, d: C) -> C {
+        use BlendMode::*;
+        match self {
+            Multiply => s.scale(d),
+            Screen => d + s.scale(C::MAX - d),
+            Overlay => {
+                if d <= C::MAX - d {
+                    s.scale(d + d)
+                } else {
+                    let d = d - (C::MAX - d);
+                    d + s.scale(C::MAX - d)
+                }
+            }
+            Darken => s.min(d),
+            Lighten => s.max(d),
+            Difference => s.max(d) - s.min(d),
+            Exclusion => s.scale(C::MAX - d) + d.scale(C::MAX - s),
+        }
+    }
+}
+
+/// Blend a source pixel with a destination pixel.
+///
+/// * `src` Source pixel.
+/// * `dst` Destination pixel.
+/// * `mode` Blend mode.
+///
+/// Where the destination is opaque, the blended color replaces the source
+/// color; it is then composited using *source-over*.  Floating-point
+/// components are blended in linear light, while 8- and 16-bit components
+/// are blended in the gamma encoding of the pixel format, to avoid losing
+/// precision.
+pub fn blend<P: Pixel>(src: P, dst: P, mode: BlendMode) -> P {
+    let (sa, da) = (src.alpha(), dst.alpha());
+    if sa == P::Chan::MIN {
+        return dst;
+    }
+    let chan = TypeId::of::<P::Chan>();
+    let linear = chan == TypeId::of::<Ch16f>()
+        || chan == TypeId::of::<Ch32>()
+        || chan == TypeId::of::<Ch64>();
+    let mut s = to_straight(src);
+    let d = to_straight(dst);
+    for (sc, dc) in s[..3].iter_mut().zip(&d[..3]) {
+        let (mut c, mut b) = (*sc, *dc);
+        if linear {
+            c = P::Gamma::to_linear(c);
+            b = P::Gamma::to_linear(b);
+        }
+        let mut v = mode.blend(c, b);
+        if linear {
+            v = P::Gamma::from_linear(v);
+        }
+        *sc = sc.scale(P::Chan::MAX - da) + v.scale(da);
+    }
+    composite(from_straight(s), dst, PorterDuff::SrcOver)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    #[test]
+    fn src_over_8() {
+        let d = SRgba8::with_alpha(0x00, 0x00, 0xFF, 0xFF);
+        let s = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80);
+        assert_eq!(
+            composite(s, d, PorterDuff::SrcOver),
+            SRgba8::with_alpha(0x80, 0x00, 0x7F, 0xFF)
+        );
+        let s = SRgba8::with_alpha(0x12, 0x34, 0x56, 0x00);
+        assert_eq!(composite(s, d, PorterDuff::SrcOver), d);
+        let s = SRgba8::with_alpha(0x12, 0x34, 0x56, 0xFF);
+        assert_eq!(composite(s, d, PorterDuff::SrcOver), s);
+        let d = SRgba8::with_alpha(0x00, 0x00, 0x00, 0x00);
+        let s = SRgba8::with_alpha(0x40, 0x80, 0xC0, 0x80);
+        assert_eq!(composite(s, d, PorterDuff::SrcOver), s);
+        let d = Rgba8p::with_alpha(0x00, 0x40, 0x80, 0x80);
+        let s = Rgba8p::with_alpha(0x40, 0x00, 0x00, 0x40);
+        assert_eq!(
+            composite(s, d, PorterDuff::SrcOver),
+            Rgba8p::with_alpha(0x40, 0x30, 0x60, 0xA0)
+        );
+    }
+    #[test]
+    fn porter_duff_8() {
+        use PorterDuff::*;
+        let s = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80);
+        let d = SRgba8::with_alpha(0x00, 0x00, 0xFF, 0xC0);
+        let ops = [
+            (Clear, SRgba8::with_alpha(0x00, 0x00, 0x00, 0x00)),
+            (Copy, s),
+            (Dest, d),
+            (SrcOver, SRgba8::with_alpha(0x92, 0x00, 0x6D, 0xE0)),
+            (DestOver, SRgba8::with_alpha(0x24, 0x00, 0xDB, 0xE0)),
+            (SrcIn, SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x60)),
+            (DestIn, SRgba8::with_alpha(0x00, 0x00, 0xFF, 0x60)),
+            (SrcOut, SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x20)),
+            (DestOut, SRgba8::with_alpha(0x00, 0x00, 0xFF, 0x60)),
+            (SrcAtop, SRgba8::with_alpha(0x80, 0x00, 0x80, 0xC0)),
+            (DestAtop, SRgba8::with_alpha(0x40, 0x00, 0xBF, 0x80)),
+            (Xor, SRgba8::with_alpha(0x40, 0x00, 0xBF, 0x80)),
+        ];
+        for (op, clr) in ops.iter() {
+            assert_eq!(composite(s, d, *op), *clr, "{:?}", op);
+            assert_eq!(s.composite(d, *op), *clr, "{:?}", op);
+        }
+    }
+    #[test]
+    fn porter_duff_8p() {
+        use PorterDuff::*;
+        let s = Rgba8p::with_alpha(0x20, 0x40, 0x00, 0x40);
+        let d = Rgba8p::with_alpha(0x00, 0x00, 0xFF, 0xFF);
+        assert_eq!(composite(s, d, SrcIn), s);
+        assert_eq!(composite(s, d, SrcOut), Rgba8p::default());
+        assert_eq!(
+            composite(s, d, DestOut),
+            Rgba8p::with_alpha(0x00, 0x00, 0xBF, 0xBF)
+        );
+        assert_eq!(
+            composite(s, d, Xor),
+            Rgba8p::with_alpha(0x00, 0x00, 0xBF, 0xBF)
+        );
+    }
+    #[test]
+    fn coverage_8() {
+        let d = SRgb8::new(0x00, 0x00, 0xFF);
+        let s = SRgb8::new(0xFF, 0x00, 0x00);
+        let op = PorterDuff::SrcOver;
+        assert_eq!(composite_coverage(s, d, Ch8::new(0x00), op), d);
+        assert_eq!(composite_coverage(s, d, Ch8::new(0xFF), op), s);
+        assert_eq!(
+            composite_coverage(s, d, Ch8::new(0x80), op),
+            SRgb8::new(0x80, 0x00, 0x7F)
+        );
+        let s = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80);
+        let d = SRgba8::with_alpha(0x00, 0x00, 0xFF, 0xFF);
+        assert_eq!(
+            composite_coverage(s, d, Ch8::new(0x80), op),
+            SRgba8::with_alpha(0x40, 0x00, 0xBF, 0xFF)
+        );
+        let s = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0xFF);
+        assert_eq!(
+            composite_coverage(s, d, Ch8::new(0x80), PorterDuff::DestOut),
+            SRgba8::with_alpha(0x00, 0x00, 0xFF, 0x7F)
+        );
+    }
+    #[test]
+    fn blend_8() {
+        use BlendMode::*;
+        let s = SRgb8::new(0x40, 0x80, 0xC0);
+        let d = SRgb8::new(0x80, 0x80, 0x20);
+        let modes = [
+            (Multiply, SRgb8::new(0x20, 0x40, 0x18)),
+            (Screen, SRgb8::new(0xA0, 0xC0, 0xC8)),
+            (Overlay, SRgb8::new(0x41, 0x80, 0x30)),
+            (Darken, SRgb8::new(0x40, 0x80, 0x20)),
+            (Lighten, SRgb8::new(0x80, 0x80, 0xC0)),
+            (Difference, SRgb8::new(0x40, 0x00, 0xA0)),
+            (Exclusion, SRgb8::new(0x80, 0x80, 0xB0)),
+        ];
+        for (mode, clr) in modes.iter() {
+            assert_eq!(blend(s, d, *mode), *clr, "{:?}", mode);
+        }
+    }
+    #[test]
+    fn blend_alpha() {
+        let d = SRgba8::with_alpha(0x80, 0x80, 0x80, 0x00);
+        let s = SRgba8::with_alpha(0x40, 0x80, 0xC0, 0xFF);
+        assert_eq!(blend(s, d, BlendMode::Multiply), s);
+        let d = SRgba8::with_alpha(0x80, 0x80, 0x80, 0xFF);
+        let s = SRgba8::with_alpha(0x40, 0x80, 0xC0, 0x00);
+        assert_eq!(blend(s, d, BlendMode::Multiply), d);
+        let s = SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0x80);
+        assert_eq!(blend(s, d, BlendMode::Multiply), d);
+    }
+    #[test]
+    fn blend_nan() {
+        let nan = Ch32::MID * f32::NAN;
+        let s = Rgb32::new(nan, Ch32::MID, Ch32::new(0.25));
+        let d = Rgb32::new(Ch32::MID, nan, Ch32::new(0.75));
+        assert_eq!(blend(s, d, BlendMode::Darken).blue(), Ch32::new(0.25));
+        assert_eq!(blend(s, d, BlendMode::Lighten).blue(), Ch32::new(0.75));
+    }
+    #[test]
+    fn blend_32() {
+        let s = Rgb32::new(0.5, 0.25, 1.0);
+        let d = Rgb32::new(0.5, 1.0, 0.0);
+        assert_eq!(
+            blend(s, d, BlendMode::Screen),
+            Rgb32::new(0.75, 1.0, 1.0)
+        );
+        let s = SRgb32::new(1.0, 0.0, 1.0);
+        let d = SRgb32::new(0.5, 0.5, 0.0);
+        assert_eq!(
+            blend(s, d, BlendMode::Multiply),
+            SRgb32::new(0.5, 0.0, 0.0)
+        );
+    }
+    #[test]
+    fn src_over_16() {
+        let d = SRgba16::with_alpha(0x0000, 0x0000, 0xFFFF, 0xFFFF);
+        let s = SRgba16::with_alpha(0xFFFF, 0x0000, 0x0000, 0x8000);
+        assert_eq!(
+            composite(s, d, PorterDuff::SrcOver),
+            SRgba16::with_alpha(0x8000, 0x0000, 0x7FFF, 0xFFFF)
+        );
+        let d = SGrayAlpha16::with_alpha(0x1234, 0x0001);
+        let s = SGrayAlpha16::with_alpha(0xFFFF, 0x0000);
+        assert_eq!(composite(s, d, PorterDuff::SrcOver), d);
+    }
+    #[test]
+    fn src_over_32() {
+        let d = SRgba32::with_alpha(0.0, 0.0, 1.0, 1.0);
+        let s = SRgba32::with_alpha(1.0, 0.0, 0.0, 0.25);
+        assert_eq!(
+            composite(s, d, PorterDuff::SrcOver),
+            SRgba32::with_alpha(0.25, 0.0, 0.75, 1.0)
+        );
+        let d = Mask32::new(0.5);
+        let s = Mask32::new(0.5);
+        assert_eq!(composite(s, d, PorterDuff::SrcOver), Mask32::new(0.75));
+    }
+}
@@ -0,0 +1,336 @@
+// pad.rs       Padding and trimming borders.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::raster::pixel_count;
+use crate::{Pixel, Raster, RasterBuilder, RasterError, Region};
+
+/// Border pixels for [Raster](struct.Raster.html)`::`
+/// [pad_with](struct.Raster.html#method.pad_with).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PadMode {
+    /// Repeat the nearest edge pixel
+    Replicate,
+    /// Reflect about the edge, including the edge pixel
+    ///
+    /// Unlike [Edge::Mirror](enum.Edge.html#variant.Mirror), the edge pixel
+    /// is repeated: `[a, b, c]` is padded as `b a | a b c | c b`.
+    Symmetric,
+}
+
+impl PadMode {
+    /// Get the source index for an index which may be beyond the edges.
+    ///
+    /// * `i` Index, relative to the start of the source.
+    /// * `len` Length of the source (non-zero).
+    fn source(self, i: i64, len: i64) -> usize {
+        let i = match self {
+            PadMode::Replicate => i.clamp(0, len - 1),
+            PadMode::Symmetric => {
+                // Reflections repeat with a period of twice the length
+                let i = i.rem_euclid(2 * len);
+                if i < len {
+                    i
+                } else {
+                    2 * len - 1 - i
+                }
+            }
+        };
+        i as usize
+    }
+}
+
+/// Get a padded length, panicking on overflow.
+fn padded(len: u32, before: u32, after: u32) -> u32 {
+    len.checked_add(before)
+        .and_then(|len| len.checked_add(after))
+        .unwrap_or_else(|| panic!("{}", RasterError::Overflow))
+}
+
+/// Get source indices for each padded index along one axis.
+///
+/// * `len` Source length (non-zero).
+/// * `before` Padding before the source.
+/// * `padded` Padded length.
+fn source_indices(
+    len: u32,
+    before: u32,
+    padded: u32,
+    mode: PadMode,
+) -> Vec<usize> {
+    let (len, before) = (i64::from(len), i64::from(before));
+    (0..i64::from(padded))
+        .map(|i| mode.source(i - before, len))
+        .collect()
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Create a larger `Raster` with a border of one color.
+    ///
+    /// * `left` Width of left border.
+    /// * `top` Height of top border.
+    /// * `right` Width of right border.
+    /// * `bottom` Height of bottom border.
+    /// * `fill` Color of border.
+    ///
+    /// This `Raster` is copied to (`left`, `top`) of the new `Raster`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the padded `Raster` would be too large.
+    ///
+    /// ### Add a margin
+    /// ```
+    /// # use pix::*;
+    /// let clr = SRgb8::new(0xFF, 0xFF, 0xFF);
+    /// let r = RasterBuilder::<SRgb8>::new().with_clear(10, 10);
+    /// let p = r.pad(4, 2, 4, 2, clr);
+    /// assert_eq!((p.width(), p.height()), (18, 14));
+    /// assert_eq!(p.pixel(3, 2), clr);
+    /// assert_eq!(p.pixel(4, 2), SRgb8::default());
+    /// ```
+    pub fn pad(
+        &self,
+        left: u32,
+        top: u32,
+        right: u32,
+        bottom: u32,
+        fill: P,
+    ) -> Raster<P> {
+        let width = padded(self.width(), left, right);
+        let height = padded(self.height(), top, bottom);
+        let mut r = RasterBuilder::new().with_color(width, height, fill);
+        let cols = left as usize..left as usize + self.width() as usize;
+        for (y, row) in self.rows().enumerate() {
+            let y = top + y as u32;
+            r.as_slice_row_mut(y)[cols.clone()].copy_from_slice(row);
+        }
+        r
+    }
+    /// Create a larger `Raster` with a border copied from the edges.
+    ///
+    /// * `left` Width of left border.
+    /// * `top` Height of top border.
+    /// * `right` Width of right border.
+    /// * `bottom` Height of bottom border.
+    /// * `mode` How border pixels are copied from this `Raster`.
+    ///
+    /// This `Raster` is copied to (`left`, `top`) of the new `Raster`.
+    /// Corners are copied from the nearest corner, so they match both of the
+    /// adjacent borders.  Borders wider than this `Raster` repeat the pattern
+    /// of the mode.  If this `Raster` is empty, the border is filled with the
+    /// default pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the padded `Raster` would be too large.
+    ///
+    /// ### Prepare for convolution
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new()
+    ///     .with_fn(4, 1, |x, _y| SGray8::new(x as u8));
+    /// let p = r.pad_with(2, 0, 2, 0, PadMode::Symmetric);
+    /// assert_eq!(p.as_u8_slice(), &[1, 0, 0, 1, 2, 3, 3, 2]);
+    /// let p = r.pad_with(2, 0, 2, 0, PadMode::Replicate);
+    /// assert_eq!(p.as_u8_slice(), &[0, 0, 0, 1, 2, 3, 3, 3]);
+    /// ```
+    pub fn pad_with(
+        &self,
+        left: u32,
+        top: u32,
+        right: u32,
+        bottom: u32,
+        mode: PadMode,
+    ) -> Raster<P> {
+        let width = padded(self.width(), left, right);
+        let height = padded(self.height(), top, bottom);
+        if let Err(e) = pixel_count::<P>(width, height) {
+            panic!("{}", e);
+        }
+        if self.width() == 0 || self.height() == 0 {
+            return RasterBuilder::new().with_clear(width, height);
+        }
+        let cols = source_indices(self.width(), left, width, mode);
+        let rows = source_indices(self.height(), top, height, mode);
+        let src = self.as_slice();
+        let stride = self.width() as usize;
+        RasterBuilder::new().with_fn(width, height, |x, y| {
+            src[rows[y as usize] * stride + cols[x as usize]]
+        })
+    }
+    /// Trim a uniform border.
+    ///
+    /// For formats with *alpha*, the border is made of fully transparent
+    /// pixels.  Otherwise, it is made of pixels equal to the top-left corner.
+    ///
+    /// Returns the smallest `Region` containing all pixels which are not part
+    /// of the border, along with a cropped copy of that region.  If every
+    /// pixel is part of the border, the region and `Raster` are empty (0x0).
+    ///
+    /// ### Trim a sprite
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgba8>::new().with_clear(32, 32);
+    /// r.fill_region((4, 8, 10, 6), SRgba8::new(0xFF, 0x80, 0x00));
+    /// let (reg, sprite) = r.trim();
+    /// assert_eq!(reg, Region::new(4, 8, 10, 6));
+    /// assert_eq!((sprite.width(), sprite.height()), (10, 6));
+    /// ```
+    pub fn trim(&self) -> (Region, Raster<P>) {
+        let alpha = P::CHANNELS > P::default().components().len();
+        let reg = if alpha {
+            self.nonzero_region()
+        } else {
+            match self.as_slice().first() {
+                Some(&corner) => self.bounding_region(|p| *p != corner),
+                None => None,
+            }
+        };
+        match reg {
+            Some(reg) => (reg, self.crop(reg)),
+            None => {
+                let empty = RasterBuilder::new().with_clear(0, 0);
+                (Region::new(0, 0, 0, 0), empty)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn square() -> Raster<Gray8> {
+        RasterBuilder::new().with_u8_buffer(2, 2, vec![1, 2, 3, 4])
+    }
+
+    #[test]
+    fn fill() {
+        let p = square().pad(2, 2, 2, 2, Gray8::new(9));
+        assert_eq!(
+            p.as_u8_slice(),
+            &[
+                9, 9, 9, 9, 9, 9, //
+                9, 9, 9, 9, 9, 9, //
+                9, 9, 1, 2, 9, 9, //
+                9, 9, 3, 4, 9, 9, //
+                9, 9, 9, 9, 9, 9, //
+                9, 9, 9, 9, 9, 9,
+            ][..]
+        );
+        let p = square().pad(0, 1, 3, 0, Gray8::new(9));
+        assert_eq!(
+            p.as_u8_slice(),
+            &[9, 9, 9, 9, 9, 1, 2, 9, 9, 9, 3, 4, 9, 9, 9][..]
+        );
+    }
+
+    #[test]
+    fn replicate() {
+        let p = square().pad_with(2, 2, 2, 2, PadMode::Replicate);
+        assert_eq!(
+            p.as_u8_slice(),
+            &[
+                1, 1, 1, 2, 2, 2, //
+                1, 1, 1, 2, 2, 2, //
+                1, 1, 1, 2, 2, 2, //
+                3, 3, 3, 4, 4, 4, //
+                3, 3, 3, 4, 4, 4, //
+                3, 3, 3, 4, 4, 4,
+            ][..]
+        );
+    }
+
+    #[test]
+    fn mirror() {
+        let p = square().pad_with(2, 2, 2, 2, PadMode::Symmetric);
+        assert_eq!(
+            p.as_u8_slice(),
+            &[
+                4, 3, 3, 4, 4, 3, //
+                2, 1, 1, 2, 2, 1, //
+                2, 1, 1, 2, 2, 1, //
+                4, 3, 3, 4, 4, 3, //
+                4, 3, 3, 4, 4, 3, //
+                2, 1, 1, 2, 2, 1,
+            ][..]
+        );
+        // Wider than the source
+        let r = RasterBuilder::<Gray8>::new().with_u8_buffer(2, 1, vec![1, 2]);
+        let p = r.pad_with(5, 0, 0, 0, PadMode::Symmetric);
+        assert_eq!(p.as_u8_slice(), &[1, 1, 2, 2, 1, 1, 2]);
+    }
+
+    #[test]
+    fn zero_padding() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(3, 5, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 7, 0x80)
+        });
+        assert_eq!(r.pad(0, 0, 0, 0, SRgba8::default()), r);
+        assert_eq!(r.pad_with(0, 0, 0, 0, PadMode::Replicate), r);
+        assert_eq!(r.pad_with(0, 0, 0, 0, PadMode::Symmetric), r);
+    }
+
+    #[test]
+    fn empty() {
+        let r = RasterBuilder::<Gray8>::new().with_clear(0, 3);
+        let p = r.pad_with(1, 0, 1, 0, PadMode::Replicate);
+        assert_eq!(p, RasterBuilder::new().with_clear(2, 3));
+        let p = r.pad(1, 1, 0, 0, Gray8::new(5));
+        assert_eq!(p, RasterBuilder::new().with_color(1, 4, Gray8::new(5)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn overflow() {
+        square().pad(u32::MAX, 0, 0, 0, Gray8::new(0));
+    }
+
+    #[test]
+    fn trim_alpha() {
+        let mut r = RasterBuilder::<Mask8>::new().with_clear(8, 6);
+        r.set_pixel(2, 1, Mask8::new(0x01));
+        r.set_pixel(5, 4, Mask8::new(0xFF));
+        let (reg, t) = r.trim();
+        assert_eq!(reg, Region::new(2, 1, 4, 4));
+        assert_eq!(t, r.crop(reg));
+        // Opaque corners are kept
+        let mut r = RasterBuilder::<SRgba8>::new().with_clear(5, 5);
+        r.set_pixel(0, 0, SRgba8::new(1, 2, 3));
+        assert_eq!(r.trim().0, Region::new(0, 0, 1, 1));
+    }
+
+    #[test]
+    fn trim_corner() {
+        let bg = SRgb8::new(0xFF, 0xFF, 0xFF);
+        let mut r = RasterBuilder::new().with_color(9, 7, bg);
+        r.set_pixel(3, 2, SRgb8::new(0, 0, 0));
+        r.set_pixel(6, 2, SRgb8::new(0xFF, 0xFF, 0xFE));
+        let (reg, t) = r.trim();
+        assert_eq!(reg, Region::new(3, 2, 4, 1));
+        assert_eq!(t.pixel(0, 0), SRgb8::new(0, 0, 0));
+        assert_eq!(t.pixel(1, 0), bg);
+    }
+
+    #[test]
+    fn trim_padded() {
+        let r = square().pad(3, 1, 0, 2, Gray8::new(0));
+        let (reg, t) = r.trim();
+        assert_eq!(reg, Region::new(3, 1, 2, 2));
+        assert_eq!(t, square());
+    }
+
+    #[test]
+    fn trim_uniform() {
+        let r = RasterBuilder::new().with_color(4, 4, SGray8::new(0x80));
+        let (reg, t) = r.trim();
+        assert_eq!(reg, Region::new(0, 0, 0, 0));
+        assert_eq!((t.width(), t.height()), (0, 0));
+        let r = RasterBuilder::<SRgba16>::new().with_clear(4, 4);
+        assert_eq!(r.trim().0, Region::new(0, 0, 0, 0));
+        let r = RasterBuilder::<SGray8>::new().with_clear(0, 4);
+        assert_eq!(r.trim().0, Region::new(0, 0, 0, 0));
+    }
+}
+
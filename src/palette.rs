@@ -2,7 +2,10 @@
 //
 // Copyright (c) 2019-2020  Douglas P Lau
 //
-use crate::{Ch8, Pixel};
+use crate::{
+    Ch8, ColorModel, Idx8, Pixel, Raster, RasterBuilder, SRgb8, SRgba8,
+};
+use std::collections::HashMap;
 
 /// Color table for use with indexed `Raster`s.
 #[derive(Clone)]
@@ -40,9 +43,8 @@ where
     /// Set the threshold function for matching entries.
     ///
     /// * `threshold_fn` Called when checking whether a color matches an
-    ///                  existing entry.  The parameter is the palette table
-    ///                  size.  Returns the maximum `Channel`-wise difference
-    ///                  to match.
+    ///   existing entry.  The parameter is the palette table size.  Returns
+    ///   the maximum `Channel`-wise difference to match.
     pub fn set_threshold_fn(&mut self, threshold_fn: fn(usize) -> P) {
         self.threshold_fn = threshold_fn;
     }
@@ -120,6 +122,54 @@ where
             None
         }
     }
+    /// Find the nearest entry to a color.
+    ///
+    /// Distance is the sum of squared differences of *red*, *green*, *blue*
+    /// and *alpha* components.  Only the first 256 entries are searched.
+    ///
+    /// # Returns
+    /// Index of the first nearest entry, or `None` if the palette is empty.
+    pub fn nearest(&self, clr: P) -> Option<usize> {
+        let rgba = clr.to_rgba();
+        self.table
+            .iter()
+            .take(256)
+            .map(|c| {
+                c.to_rgba()
+                    .iter()
+                    .zip(rgba.iter())
+                    .map(|(a, b)| {
+                        let d = i32::from(u8::from(*a))
+                            - i32::from(u8::from(*b));
+                        d * d
+                    })
+                    .sum::<i32>()
+            })
+            .enumerate()
+            .min_by_key(|(_, d)| *d)
+            .map(|(i, _)| i)
+    }
+    /// Quantize a `Raster` to indices of nearest `Palette` entries.
+    ///
+    /// If the palette is empty, all indices are 0.
+    ///
+    /// ### Quantize to a two-color palette
+    /// ```
+    /// # use pix::*;
+    /// let mut palette = Palette::new(2);
+    /// palette.set_entry(SRgb8::new(0x00, 0x00, 0x00));
+    /// palette.set_entry(SRgb8::new(0xFF, 0xFF, 0xFF));
+    /// let mut r = RasterBuilder::<SRgb8>::new().with_clear(2, 1);
+    /// r.set_pixel(1, 0, SRgb8::new(0xC0, 0xD0, 0xE0));
+    /// let r = palette.quantize(&r);
+    /// assert_eq!(r.as_u8_slice(), &[0, 1]);
+    /// ```
+    pub fn quantize(&self, src: &Raster<P>) -> Raster<Idx8> {
+        RasterBuilder::new().with_fn(src.width(), src.height(), |x, y| {
+            let i = self.nearest(src.pixel(x, y)).unwrap_or(0);
+            Idx8::new(i as u8)
+        })
+    }
     /// Create a histogram of `Palette` entries.
     ///
     /// * `ent` Slice of entry indices (pixel values).
@@ -141,6 +191,234 @@ where
     }
 }
 
+impl Raster<Idx8> {
+    /// Expand indices to colors from a [Palette](struct.Palette.html).
+    ///
+    /// Indices outside of the palette use entry 0 as a fallback, or the
+    /// default color if the palette is empty.
+    pub fn expand<P>(&self, palette: &Palette<P>) -> Raster<P>
+    where
+        P: Pixel<Chan = Ch8>,
+    {
+        let fallback = palette.entry(0).unwrap_or_default();
+        RasterBuilder::new().with_fn(self.width(), self.height(), |x, y| {
+            palette.entry(self.pixel(x, y).index()).unwrap_or(fallback)
+        })
+    }
+}
+
+/// Box of colors for median-cut quantization, with pixel counts
+struct ColorBox {
+    colors: Vec<([u8; 3], u32)>,
+}
+
+impl ColorBox {
+    /// Get the channel with the widest range, and the range
+    fn widest(&self) -> (usize, u8) {
+        (0..3)
+            .map(|c| {
+                let it = self.colors.iter().map(|(clr, _)| clr[c]);
+                let min = it.clone().min().unwrap_or(0);
+                let max = it.max().unwrap_or(0);
+                (c, max - min)
+            })
+            .max_by_key(|(c, range)| (*range, 3 - c))
+            .unwrap_or((0, 0))
+    }
+    /// Split the box at the weighted median of its widest channel
+    fn split(mut self) -> (ColorBox, ColorBox) {
+        let (c, _) = self.widest();
+        self.colors.sort_by_key(|(clr, _)| clr[c]);
+        let total: u64 = self.colors.iter().map(|(_, n)| u64::from(*n)).sum();
+        let mut sum = 0;
+        let mut i = 0;
+        while i + 1 < self.colors.len() && sum * 2 < total {
+            sum += u64::from(self.colors[i].1);
+            i += 1;
+        }
+        let i = i.clamp(1, self.colors.len() - 1);
+        let colors = self.colors.split_off(i);
+        (self, ColorBox { colors })
+    }
+    /// Get the mean color, weighted by pixel count
+    fn mean(&self) -> [u8; 3] {
+        let total: u64 = self.colors.iter().map(|(_, n)| u64::from(*n)).sum();
+        let mut clr = [0; 3];
+        for (c, v) in clr.iter_mut().enumerate() {
+            let sum: u64 = self
+                .colors
+                .iter()
+                .map(|(clr, n)| u64::from(clr[c]) * u64::from(*n))
+                .sum();
+            *v = ((sum + total / 2) / total.max(1)) as u8;
+        }
+        clr
+    }
+}
+
+/// Choose up to `capacity` colors by median cut
+fn median_cut(counts: HashMap<[u8; 3], u32>, capacity: usize) -> Vec<[u8; 3]> {
+    let mut colors: Vec<_> = counts.into_iter().collect();
+    colors.sort_unstable();
+    if colors.len() <= capacity {
+        return colors.into_iter().map(|(clr, _)| clr).collect();
+    }
+    let mut boxes = vec![ColorBox { colors }];
+    while boxes.len() < capacity {
+        let (i, range) = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (i, b.widest().1))
+            .max_by_key(|(i, range)| (*range, boxes.len() - i))
+            .unwrap_or((0, 0));
+        if range == 0 {
+            break;
+        }
+        let (a, b) = boxes.swap_remove(i).split();
+        boxes.push(a);
+        boxes.push(b);
+    }
+    let mut table: Vec<_> = boxes.iter().map(ColorBox::mean).collect();
+    table.sort_unstable();
+    table.dedup();
+    table
+}
+
+/// Find the index of the nearest color in a table
+fn nearest_color(table: &[[u8; 3]], clr: [u8; 3]) -> usize {
+    table
+        .iter()
+        .map(|c| {
+            c.iter()
+                .zip(clr.iter())
+                .map(|(a, b)| {
+                    let d = i32::from(*a) - i32::from(*b);
+                    d * d
+                })
+                .sum::<i32>()
+        })
+        .enumerate()
+        .min_by_key(|(_, d)| *d)
+        .map_or(0, |(i, _)| i)
+}
+
+/// Create a palette from a color table
+///
+/// * `table` Color table.
+/// * `transparent` Reserve entry 0 for transparent pixels.
+fn table_palette(table: &[[u8; 3]], transparent: bool) -> Palette<SRgb8> {
+    let mut palette = Palette::new(table.len() + 1);
+    if transparent {
+        palette.table.push(SRgb8::default());
+    }
+    palette
+        .table
+        .extend(table.iter().map(|c| SRgb8::new(c[0], c[1], c[2])));
+    palette
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Quantize to an indexed `Raster` with a generated
+    /// [Palette](struct.Palette.html).
+    ///
+    /// * `max_colors` Maximum number of palette entries, up to 256.
+    ///
+    /// When the raster has no more than `max_colors` distinct colors, every
+    /// color gets its own entry, and quantization is lossless.  Otherwise,
+    /// colors are chosen by median cut, and each pixel is mapped to the
+    /// nearest entry.  Alpha is ignored.
+    ///
+    /// ### Quantize to 4 colors
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_fn(16, 16, |x, y| SRgb8::new(x as u8 * 16, y as u8 * 16, 0));
+    /// let (palette, indexed) = r.quantize(4);
+    /// assert_eq!(palette.len(), 4);
+    /// assert_eq!(indexed.width(), 16);
+    /// ```
+    pub fn quantize(&self, max_colors: usize) -> (Palette<SRgb8>, Raster<Idx8>)
+    where
+        Ch8: From<P::Chan>,
+    {
+        self.quantize_colors(max_colors.clamp(1, 256), None)
+    }
+    /// Quantize to an indexed `Raster`, with a reserved transparent index.
+    ///
+    /// * `max_colors` Maximum number of palette entries, from 2 to 256.
+    /// * `cutoff` Pixels with *alpha* below this value are transparent.
+    ///
+    /// Index 0 is reserved for transparent pixels; its palette entry is
+    /// black.  Other pixels are quantized as with
+    /// [quantize](struct.Raster.html#method.quantize), using the remaining
+    /// entries.
+    ///
+    /// ### Quantize with transparency
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgba8>::new().with_clear(2, 1);
+    /// r.set_pixel(1, 0, SRgba8::with_alpha(0xFF, 0x80, 0x00, 0xFF));
+    /// let (palette, indexed) = r.quantize_transparent(16, Ch8::new(0x80));
+    /// assert_eq!(indexed.as_u8_slice(), &[0, 1]);
+    /// assert_eq!(palette.entry(1), Some(SRgb8::new(0xFF, 0x80, 0x00)));
+    /// ```
+    pub fn quantize_transparent(
+        &self,
+        max_colors: usize,
+        cutoff: Ch8,
+    ) -> (Palette<SRgb8>, Raster<Idx8>)
+    where
+        Ch8: From<P::Chan>,
+    {
+        self.quantize_colors(max_colors.clamp(2, 256) - 1, Some(cutoff))
+    }
+    /// Quantize colors, with an optional transparent cutoff
+    fn quantize_colors(
+        &self,
+        capacity: usize,
+        cutoff: Option<Ch8>,
+    ) -> (Palette<SRgb8>, Raster<Idx8>)
+    where
+        Ch8: From<P::Chan>,
+    {
+        let transparent =
+            |p: SRgba8| cutoff.is_some_and(|c| ColorModel::alpha(p) < c);
+        let rgb = |p: SRgba8| {
+            let rgba = p.to_rgba();
+            [u8::from(rgba[0]), u8::from(rgba[1]), u8::from(rgba[2])]
+        };
+        let mut counts = HashMap::new();
+        for p in self.as_slice() {
+            let p = p.convert::<SRgba8>();
+            if !transparent(p) {
+                *counts.entry(rgb(p)).or_insert(0) += 1;
+            }
+        }
+        let table = median_cut(counts, capacity);
+        let base = if cutoff.is_some() { 1 } else { 0 };
+        let mut lookup = HashMap::new();
+        let indices = self.as_slice().iter().map(|p| {
+            let p = p.convert::<SRgba8>();
+            if transparent(p) {
+                return Idx8::new(0);
+            }
+            let clr = rgb(p);
+            let i = *lookup
+                .entry(clr)
+                .or_insert_with(|| base + nearest_color(&table, clr));
+            Idx8::new(i as u8)
+        });
+        let indices: Vec<Idx8> = indices.collect();
+        let palette = table_palette(&table, cutoff.is_some());
+        let raster = RasterBuilder::new().with_pixels(
+            self.width(),
+            self.height(),
+            indices,
+        );
+        (palette, raster)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::super::*;
@@ -197,4 +475,71 @@ mod test {
         p.set_threshold_fn(|_| SRgb8::new(5, 5, 5));
         assert_eq!(p.set_entry(SRgb8::new(35, 35, 35)), Some(2));
     }
+    #[test]
+    fn expand() {
+        let mut p = Palette::new(4);
+        p.set_entry(SRgba8::with_alpha(0x10, 0x20, 0x30, 0x40));
+        p.set_entry(SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0xFF));
+        let r = RasterBuilder::<Idx8>::new()
+            .with_u8_buffer(3, 1, vec![1, 0, 9]);
+        let r = r.expand(&p);
+        let v = [
+            0xFF,0xFF,0xFF,0xFF, 0x10,0x20,0x30,0x40, 0x10,0x20,0x30,0x40,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        let r = RasterBuilder::<Idx8>::new().with_clear(1, 1);
+        let r = r.expand(&Palette::<SRgb8>::new(0));
+        assert_eq!(r.pixel(0, 0), SRgb8::default());
+    }
+    #[test]
+    fn quantize() {
+        let mut p = Palette::new(4);
+        p.set_entry(SRgb8::new(0x00, 0x00, 0x00));
+        p.set_entry(SRgb8::new(0xFF, 0x00, 0x00));
+        p.set_entry(SRgb8::new(0x00, 0x00, 0xFF));
+        assert_eq!(p.nearest(SRgb8::new(0x80, 0x10, 0x90)), Some(2));
+        assert_eq!(p.nearest(SRgb8::new(0xF0, 0x00, 0x00)), Some(1));
+        let r = RasterBuilder::<SRgb8>::new().with_fn(3, 1, |x, _| {
+            p.entry(x as usize).unwrap()
+        });
+        let q = p.quantize(&r);
+        assert_eq!(q.as_u8_slice(), &[0, 1, 2]);
+        assert_eq!(q.expand(&p).as_u8_slice(), r.as_u8_slice());
+        assert_eq!(Palette::<SRgb8>::new(4).nearest(SRgb8::default()), None);
+    }
+    #[test]
+    fn quantize_lossless() {
+        let r = RasterBuilder::<SRgb8>::new().with_fn(16, 16, |x, y| {
+            SRgb8::new((x % 4) as u8 * 60, (y % 4) as u8 * 60, 0x80)
+        });
+        let (p, q) = r.quantize(16);
+        assert_eq!(p.len(), 16);
+        assert_eq!(q.expand(&p), r);
+        let (p, q) = r.quantize(256);
+        assert_eq!(p.len(), 16);
+        assert_eq!(q.expand(&p), r);
+    }
+    #[test]
+    fn quantize_median_cut() {
+        let r = RasterBuilder::<SRgb8>::new().with_fn(64, 64, |x, y| {
+            SRgb8::new(x as u8 * 4, y as u8 * 4, 0x40)
+        });
+        let (p, q) = r.quantize(16);
+        assert_eq!(p.len(), 16);
+        let d = r.diff(&q.expand(&p), Ch8::new(40));
+        assert_eq!(d.count, 0);
+    }
+    #[test]
+    fn quantize_transparent() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(4, 4, |x, y| {
+            let v = (x * 64) as u8;
+            SRgba8::with_alpha(v, v, v, (y * 64) as u8)
+        });
+        let (p, q) = r.quantize_transparent(8, Ch8::new(0x40));
+        assert_eq!(p.len(), 5);
+        assert_eq!(p.entry(0), Some(SRgb8::default()));
+        assert_eq!(p.entry(1), Some(SRgb8::new(0, 0, 0)));
+        assert_eq!(&q.as_u8_slice()[..4], &[0, 0, 0, 0]);
+        assert_eq!(&q.as_u8_slice()[4..8], &[1, 2, 3, 4]);
+    }
 }
@@ -0,0 +1,111 @@
+// par.rs       Parallel raster operations (rayon feature).
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::raster::convert_row;
+use crate::{Pixel, Raster};
+use rayon::prelude::*;
+
+/// Minimum number of pixels for parallel conversion in `with_raster`.
+const PAR_MIN_PIXELS: usize = 1 << 16;
+
+impl<P: Pixel> Raster<P> {
+    /// Get a parallel iterator of rows, from top to bottom.
+    ///
+    /// Requires the `rayon` feature.
+    pub fn par_rows(&self) -> impl IndexedParallelIterator<Item = &[P]> {
+        let width = (self.width() as usize).max(1);
+        self.as_slice().par_chunks(width)
+    }
+    /// Get a parallel iterator of mutable rows, from top to bottom.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// ### Fill each row with its index
+    /// ```
+    /// # use pix::*;
+    /// use rayon::prelude::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(16, 16);
+    /// r.par_rows_mut().enumerate().for_each(|(y, row)| {
+    ///     row.iter_mut().for_each(|p| *p = SGray8::new(y as u8));
+    /// });
+    /// assert_eq!(r.pixel(3, 12), SGray8::new(12));
+    /// ```
+    pub fn par_rows_mut(
+        &mut self,
+    ) -> impl IndexedParallelIterator<Item = &mut [P]> {
+        let width = (self.width() as usize).max(1);
+        self.as_slice_mut().par_chunks_mut(width)
+    }
+    /// Modify each pixel with a function, processing rows in parallel.
+    ///
+    /// * `f` Function to modify each pixel.
+    ///
+    /// Requires the `rayon` feature.
+    ///
+    /// ### Invert a gray raster
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(64, 64);
+    /// r.par_map_in_place(|p| *p = SGray8::new(0xFF - u8::from(p.value())));
+    /// assert_eq!(r.pixel(0, 0), SGray8::new(0xFF));
+    /// ```
+    pub fn par_map_in_place<F>(&mut self, f: F)
+    where
+        F: Fn(&mut P) + Send + Sync,
+    {
+        self.par_rows_mut()
+            .for_each(|row| row.iter_mut().for_each(&f));
+    }
+}
+
+/// Convert pixels of a `Raster` in parallel, if it is large enough.
+///
+/// Returns `false` if the `Raster` is too small for parallel conversion.
+pub(crate) fn convert_raster<P, S>(dst: &mut Raster<P>, src: &Raster<S>) -> bool
+where
+    P: Pixel,
+    S: Pixel,
+    P::Chan: From<S::Chan>,
+{
+    if src.as_slice().len() < PAR_MIN_PIXELS {
+        return false;
+    }
+    dst.par_rows_mut()
+        .zip(src.par_rows())
+        .for_each(|(drow, srow)| convert_row(drow, srow));
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn gray_to_rgb_4k() {
+        let g = RasterBuilder::<SGray8>::new()
+            .with_fn(4096, 4096, |x, y| SGray8::new((x ^ y) as u8));
+        let r = RasterBuilder::<SRgb8>::new().with_raster(&g);
+        let mut s = RasterBuilder::<SRgb8>::new().with_clear(4096, 4096);
+        s.set_region(s.region(), g.region_iter(g.region()));
+        assert_eq!(r, s);
+        assert_eq!(r.pixel(4095, 1), SRgb8::new(0xFE, 0xFE, 0xFE));
+    }
+
+    #[test]
+    fn par_map_in_place() {
+        let mut r = RasterBuilder::<SRgb16>::new().with_fn(300, 500, |x, y| {
+            SRgb16::new(x as u16, y as u16, 0)
+        });
+        let mut s = r.clone();
+        let f = |p: &mut SRgb16| {
+            *p = SRgb16::new(p.green(), p.red(), Ch16::new(1))
+        };
+        r.par_map_in_place(f);
+        s.map_in_place(f);
+        assert_eq!(r, s);
+        assert_eq!(r.pixel(299, 499), SRgb16::new(499, 299, 1));
+        assert_eq!(r.par_rows().count(), 500);
+    }
+}
@@ -0,0 +1,152 @@
+// pattern.rs   Test pattern generators.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Ch8, Pixel, Raster, RasterBuilder, SRgb8};
+
+/// Get the colors of 75% color bars, from left to right
+fn bar_colors() -> [SRgb8; 7] {
+    [
+        SRgb8::new(0xBF, 0xBF, 0xBF),
+        SRgb8::new(0xBF, 0xBF, 0x00),
+        SRgb8::new(0x00, 0xBF, 0xBF),
+        SRgb8::new(0x00, 0xBF, 0x00),
+        SRgb8::new(0xBF, 0x00, 0xBF),
+        SRgb8::new(0xBF, 0x00, 0x00),
+        SRgb8::new(0x00, 0x00, 0xBF),
+    ]
+}
+
+impl<P: Pixel> RasterBuilder<P> {
+    /// Build a `Raster` with a checkerboard pattern.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `cell` Width and height of each square cell.
+    /// * `a` Color of top-left cell.
+    /// * `b` Color of alternate cells.
+    ///
+    /// When the width or height is not a multiple of `cell`, cells on the
+    /// right or bottom edge are cut off.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell` is zero.
+    ///
+    /// ### Transparency background
+    /// ```
+    /// # use pix::*;
+    /// let a = SGray8::new(0x66);
+    /// let b = SGray8::new(0x99);
+    /// let r = RasterBuilder::new().with_checkerboard(20, 12, 8, a, b);
+    /// assert_eq!(r.pixel(7, 7), a);
+    /// assert_eq!(r.pixel(8, 7), b);
+    /// assert_eq!(r.pixel(19, 11), b);
+    /// ```
+    pub fn with_checkerboard(
+        self,
+        width: u32,
+        height: u32,
+        cell: u32,
+        a: P,
+        b: P,
+    ) -> Raster<P> {
+        assert!(cell > 0, "checkerboard cell size must be non-zero");
+        self.with_fn(width, height, |x, y| {
+            if (x / cell + y / cell).is_multiple_of(2) {
+                a
+            } else {
+                b
+            }
+        })
+    }
+    /// Build a `Raster` with vertical color bars.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    ///
+    /// There are seven bars at 75% intensity: white, yellow, cyan, green,
+    /// magenta, red and blue, like the top of an SMPTE test pattern.  Bar
+    /// widths differ by at most one pixel.
+    ///
+    /// ### Color bars
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_color_bars(70, 10);
+    /// assert_eq!(r.pixel(0, 0), SRgb8::new(0xBF, 0xBF, 0xBF));
+    /// assert_eq!(r.pixel(69, 9), SRgb8::new(0x00, 0x00, 0xBF));
+    /// ```
+    pub fn with_color_bars(self, width: u32, height: u32) -> Raster<P>
+    where
+        P::Chan: From<Ch8>,
+    {
+        let colors = bar_colors();
+        let colors: Vec<P> = colors.iter().map(|c| c.convert()).collect();
+        let bars = colors.len() as u64;
+        self.with_fn(width, height, |x, _y| {
+            let bar = u64::from(x) * bars / u64::from(width);
+            colors[bar as usize]
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn checkerboard() {
+        let (a, b) = (Mask8::new(0xFF), Mask8::new(0));
+        let r = RasterBuilder::new().with_checkerboard(3, 2, 1, a, b);
+        assert_eq!(r.as_u8_slice(), &[0xFF, 0, 0xFF, 0, 0xFF, 0]);
+        let r = RasterBuilder::new().with_checkerboard(5, 5, 2, a, b);
+        assert_eq!(
+            r.as_u8_slice(),
+            &[
+                0xFF, 0xFF, 0, 0, 0xFF, //
+                0xFF, 0xFF, 0, 0, 0xFF, //
+                0, 0, 0xFF, 0xFF, 0, //
+                0, 0, 0xFF, 0xFF, 0, //
+                0xFF, 0xFF, 0, 0, 0xFF,
+            ][..]
+        );
+        let r = RasterBuilder::new().with_checkerboard(3, 3, 9, a, b);
+        assert_eq!(r, RasterBuilder::new().with_color(3, 3, a));
+    }
+
+    #[test]
+    #[should_panic]
+    fn checkerboard_zero_cell() {
+        let clr = Mask8::new(0);
+        RasterBuilder::new().with_checkerboard(4, 4, 0, clr, clr);
+    }
+
+    #[test]
+    fn color_bars() {
+        let colors = super::bar_colors();
+        for width in [1, 6, 7, 10, 100, 1921].iter() {
+            let r = RasterBuilder::<SRgb8>::new().with_color_bars(*width, 2);
+            assert_eq!(r.as_slice_row(0), r.as_slice_row(1));
+            let row = r.as_slice_row(0);
+            assert_eq!(row[0], colors[0]);
+            let mut counts = vec![];
+            for clr in colors.iter() {
+                let n = row.iter().filter(|p| *p == clr).count() as u32;
+                counts.push(n);
+                // Each bar is one contiguous run
+                if let Some(i) = row.iter().position(|p| p == clr) {
+                    assert!(row[i..i + n as usize].iter().all(|p| p == clr));
+                }
+            }
+            assert_eq!(counts.iter().sum::<u32>(), *width);
+            if *width >= 7 {
+                assert_eq!(row[*width as usize - 1], colors[6]);
+                let min = counts.iter().min().unwrap();
+                let max = counts.iter().max().unwrap();
+                assert!(max - min <= 1, "{:?}", counts);
+            }
+        }
+        let r = RasterBuilder::<SGray16>::new().with_color_bars(7, 1);
+        assert_eq!(r.pixel(0, 0), SGray16::new(0xBFBF));
+    }
+}
@@ -5,8 +5,12 @@
 //
 use crate::alpha::{self, Mode as _};
 use crate::gamma::{self, Mode as _};
-use crate::ColorModel;
-use std::any::{Any, TypeId};
+use crate::ops::{self, PorterDuff};
+use crate::private::PlainData;
+use crate::{Ch32, Channel, ColorModel, PixDepth, PixFmt, PixModel};
+use std::any::{type_name, Any, TypeId};
+use std::fmt;
+use std::mem::size_of;
 
 /// Pixel format determines [color model], bit depth, [alpha mode] and
 /// [gamma mode].
@@ -21,29 +25,43 @@ use std::any::{Any, TypeId};
 /// ### Type Alias Naming Scheme
 ///
 /// * _Gamma_: `S` for [sRGB] gamma encoding; [linear] if omitted.
-/// * _Color model_: [Gray] / `GrayAlpha` / [Rgb] / `Rgba` / [Mask].
-/// * _Bit depth_: `8` / `16` / `32` for 8-bit integer, 16-bit integer and
-///   32-bit floating-point [channels].
+/// * _Color model_: [Gray] / `GrayAlpha` / [Rgb] / `Rgba` / [Bgr] / `Bgra` /
+///   [Hsv] / `Hsva` / [Hsl] / `Hsla` / [YCbCr] / [Cmyk] / `Cmyka` / [Xyz] /
+///   `Xyza` / [Lab] / `Laba` / [Oklab] / `OklabAlpha` / [Mask].
+/// * _Bit depth_: `8` / `16` / `16f` / `32` / `64` for 8-bit integer, 16-bit
+///   integer, 16-bit floating-point, 32-bit floating-point and 64-bit
+///   floating-point [channels].
 /// * _Alpha mode_: `p` for [premultiplied]; [straight] if omitted.
 ///
+/// [Bgr]: struct.Bgr.html
 /// [channels]: trait.Channel.html
+/// [Cmyk]: struct.Cmyk.html
 /// [gray]: struct.Gray.html
+/// [Hsl]: struct.Hsl.html
+/// [Hsv]: struct.Hsv.html
+/// [Lab]: struct.Lab.html
 /// [linear]: gamma/struct.Linear.html
 /// [Mask]: struct.Mask.html
+/// [Oklab]: struct.Oklab.html
 /// [premultiplied]: alpha/struct.Premultiplied.html
 /// [Rgb]: struct.Rgb.html
 /// [sRGB]: gamma/struct.Srgb.html
 /// [straight]: alpha/struct.Straight.html
+/// [Xyz]: struct.Xyz.html
+/// [YCbCr]: struct.YCbCr.html
 ///
 /// ### Type Aliases
 ///
 /// * Opaque, linear gamma:
 ///   [Gray8](type.Gray8.html),
 ///   [Gray16](type.Gray16.html),
+///   [Gray16f](type.Gray16f.html),
 ///   [Gray32](type.Gray32.html),
+///   [Gray64](type.Gray64.html),
 ///   [Rgb8](type.Rgb8.html),
 ///   [Rgb16](type.Rgb16.html),
-///   [Rgb32](type.Rgb32.html)
+///   [Rgb32](type.Rgb32.html),
+///   [Rgb64](type.Rgb64.html)
 /// * Opaque, sRGB gamma:
 ///   [SGray8](type.SGray8.html),
 ///   [SGray16](type.SGray16.html),
@@ -57,7 +75,9 @@ use std::any::{Any, TypeId};
 ///   [GrayAlpha32](type.GrayAlpha32.html)
 ///   [Rgba8](type.Rgba8.html),
 ///   [Rgba16](type.Rgba16.html),
-///   [Rgba32](type.Rgba32.html)
+///   [Rgba16f](type.Rgba16f.html),
+///   [Rgba32](type.Rgba32.html),
+///   [Rgba64](type.Rgba64.html)
 /// * Translucent (premultiplied alpha), linear gamma:
 ///   [GrayAlpha8p](type.GrayAlpha8p.html),
 ///   [GrayAlpha16p](type.GrayAlpha16p.html),
@@ -79,13 +99,92 @@ use std::any::{Any, TypeId};
 ///   [SRgba8p](type.SRgba8p.html),
 ///   [SRgba16p](type.SRgba16p.html),
 ///   [SRgba32p](type.SRgba32p.html)
+/// * BGR channel order, linear gamma:
+///   [Bgr8](type.Bgr8.html),
+///   [Bgr16](type.Bgr16.html),
+///   [Bgr32](type.Bgr32.html),
+///   [Bgra8](type.Bgra8.html),
+///   [Bgra16](type.Bgra16.html),
+///   [Bgra32](type.Bgra32.html)
+/// * BGR channel order, sRGB gamma:
+///   [SBgr8](type.SBgr8.html),
+///   [SBgr16](type.SBgr16.html),
+///   [SBgr32](type.SBgr32.html),
+///   [SBgra8](type.SBgra8.html),
+///   [SBgra16](type.SBgra16.html),
+///   [SBgra32](type.SBgra32.html),
+///   [SBgra8p](type.SBgra8p.html),
+///   [SBgra16p](type.SBgra16p.html),
+///   [SBgra32p](type.SBgra32p.html)
+/// * HSV, linear gamma:
+///   [Hsv8](type.Hsv8.html),
+///   [Hsv16](type.Hsv16.html),
+///   [Hsv32](type.Hsv32.html),
+///   [Hsva8](type.Hsva8.html),
+///   [Hsva16](type.Hsva16.html),
+///   [Hsva32](type.Hsva32.html)
+/// * HSV, sRGB gamma:
+///   [SHsv8](type.SHsv8.html),
+///   [SHsv16](type.SHsv16.html),
+///   [SHsv32](type.SHsv32.html),
+///   [SHsva8](type.SHsva8.html),
+///   [SHsva16](type.SHsva16.html),
+///   [SHsva32](type.SHsva32.html)
+/// * HSL, linear gamma:
+///   [Hsl8](type.Hsl8.html),
+///   [Hsl16](type.Hsl16.html),
+///   [Hsl32](type.Hsl32.html),
+///   [Hsla8](type.Hsla8.html),
+///   [Hsla16](type.Hsla16.html),
+///   [Hsla32](type.Hsla32.html)
+/// * HSL, sRGB gamma:
+///   [SHsl8](type.SHsl8.html),
+///   [SHsl16](type.SHsl16.html),
+///   [SHsl32](type.SHsl32.html),
+///   [SHsla8](type.SHsla8.html),
+///   [SHsla16](type.SHsla16.html),
+///   [SHsla32](type.SHsla32.html)
+/// * Y′CbCr, sRGB gamma:
+///   [YCbCr8](type.YCbCr8.html),
+///   [YCbCr16](type.YCbCr16.html),
+///   [YCbCr32](type.YCbCr32.html)
+/// * CMYK, sRGB gamma:
+///   [Cmyk8](type.Cmyk8.html),
+///   [Cmyk16](type.Cmyk16.html),
+///   [Cmyk32](type.Cmyk32.html),
+///   [Cmyka8](type.Cmyka8.html),
+///   [Cmyka16](type.Cmyka16.html),
+///   [Cmyka32](type.Cmyka32.html)
+/// * CIE XYZ and L\*a\*b\*:
+///   [Xyz32](type.Xyz32.html),
+///   [Xyza32](type.Xyza32.html),
+///   [Lab32](type.Lab32.html),
+///   [Laba32](type.Laba32.html)
+/// * Oklab:
+///   [Oklab32](type.Oklab32.html),
+///   [OklabAlpha32](type.OklabAlpha32.html)
 /// * Alpha mask:
 ///   [Mask8](type.Mask8.html),
 ///   [Mask16](type.Mask16.html),
-///   [Mask32](type.Mask32.html)
+///   [Mask32](type.Mask32.html),
+///   [Mask64](type.Mask64.html)
+/// * Palette index:
+///   [Idx8](struct.Idx8.html)
 ///
 /// This trait is *sealed*, and cannot be implemented outside of this crate.
-pub trait Pixel: Any + Clone + Copy + Default + PartialEq + ColorModel {
+pub trait Pixel:
+    Any
+    + Clone
+    + Copy
+    + Default
+    + PartialEq
+    + Send
+    + Sync
+    + PlainData
+    + ColorModel
+{
+    /// Runtime pixel format descriptor
+    const PIX_FMT: PixFmt;
 
     /// Alpha mode
     type Alpha: alpha::Mode;
@@ -93,14 +192,128 @@ pub trait Pixel: Any + Clone + Copy + Default + PartialEq + ColorModel {
     /// Gamma mode
     type Gamma: gamma::Mode;
 
+    /// Number of channels, including *alpha*
+    const CHANNELS: usize = size_of::<Self>() / size_of::<Self::Chan>();
+
+    /// Opaque white.
+    ///
+    /// For [Mask](struct.Mask.html), this is fully opaque; for
+    /// [Idx8](struct.Idx8.html), it is the last palette index.
+    ///
+    /// ### Pad with white
+    /// ```
+    /// # use pix::*;
+    /// const PAD: SRgb8 = SRgb8::WHITE;
+    /// assert_eq!(PAD, SRgb8::new(0xFF, 0xFF, 0xFF));
+    /// assert_eq!(Cmyk8::WHITE.convert::<SRgb8>(), PAD);
+    /// ```
+    const WHITE: Self;
+
+    /// Opaque black.
+    ///
+    /// For [Mask](struct.Mask.html), this is fully opaque; for
+    /// [Idx8](struct.Idx8.html), it is the first palette index.
+    const BLACK: Self;
+
+    /// Fully transparent black.
+    ///
+    /// For formats with [opaque](alpha/struct.Opaque.html) alpha, this is
+    /// the same as [BLACK](trait.Pixel.html#associatedconstant.BLACK).
+    const TRANSPARENT: Self;
+
+    /// Get all channels, including *alpha*
+    ///
+    /// Channels are in memory order, with *alpha* last.  Formats with
+    /// [opaque](alpha/struct.Opaque.html) alpha have no *alpha* channel; use
+    /// [alpha](trait.ColorModel.html#tymethod.alpha) to get `MAX` instead.
+    fn channels(&self) -> &[Self::Chan] {
+        unsafe { std::slice::from_ref(self).align_to::<Self::Chan>().1 }
+    }
+
+    /// Get all channels mutably, including *alpha*
+    fn channels_mut(&mut self) -> &mut [Self::Chan] {
+        unsafe { std::slice::from_mut(self).align_to_mut::<Self::Chan>().1 }
+    }
+
+    /// Get one channel by index
+    ///
+    /// * `i` Channel index, less than
+    ///   [CHANNELS](trait.Pixel.html#associatedconstant.CHANNELS).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    ///
+    /// ### Swap red and blue channels
+    /// ```
+    /// # use pix::*;
+    /// let mut clr = SRgba8::with_alpha(0x10, 0x20, 0x30, 0x40);
+    /// let red = clr.channel(0);
+    /// clr.set_channel(0, clr.channel(2));
+    /// clr.set_channel(2, red);
+    /// assert_eq!(clr, SRgba8::with_alpha(0x30, 0x20, 0x10, 0x40));
+    /// assert_eq!(SRgba8::CHANNELS, 4);
+    /// ```
+    fn channel(&self, i: usize) -> Self::Chan {
+        check_channel::<Self>(i);
+        self.channels()[i]
+    }
+
+    /// Set one channel by index
+    ///
+    /// * `i` Channel index, less than
+    ///   [CHANNELS](trait.Pixel.html#associatedconstant.CHANNELS).
+    /// * `c` New channel value.
+    ///
+    /// Other channels are not changed, even with
+    /// [premultiplied](alpha/struct.Premultiplied.html) alpha.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of range.
+    fn set_channel(&mut self, i: usize, c: Self::Chan) {
+        check_channel::<Self>(i);
+        self.channels_mut()[i] = c;
+    }
+
+    /// Check if two pixels are approximately equal
+    ///
+    /// * `other` Pixel to compare.
+    /// * `epsilon` Maximum difference of each channel, treating values as
+    ///   though they range between 0 and 1.
+    ///
+    /// See [Channel::approx_eq](trait.Channel.html#tymethod.approx_eq).
+    ///
+    /// ### Compare after float math
+    /// ```
+    /// # use pix::*;
+    /// let a = SRgba32::with_alpha(0.1 + 0.2, 0.5, 0.7, 1.0);
+    /// let b = SRgba32::with_alpha(0.3, 0.5, 0.7, 1.0);
+    /// assert!(a.approx_eq(&b, 1e-6));
+    /// let c = SRgba32::with_alpha(0.3, 0.5, 0.7, 0.99);
+    /// assert!(!a.approx_eq(&c, 1e-6));
+    /// ```
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.channels()
+            .iter()
+            .zip(other.channels())
+            .all(|(a, b)| a.approx_eq(*b, epsilon))
+    }
+
     /// Convert a pixel to another format
     ///
     /// * `D` Destination format.
+    ///
+    /// Converting to the same format returns the pixel unchanged.
     fn convert<D>(self) -> D
     where
         D: Pixel,
         D::Chan: From<Self::Chan>,
     {
+        if TypeId::of::<Self>() == TypeId::of::<D>() {
+            // Same pixel format -- avoid a lossy round trip through RGBA
+            return unsafe { std::mem::transmute_copy(&self) };
+        }
         let rgba = self.to_rgba();
         // Convert to destination bit depth
         let mut rgba = [
@@ -112,11 +325,145 @@ pub trait Pixel: Any + Clone + Copy + Default + PartialEq + ColorModel {
         if TypeId::of::<Self::Alpha>() != TypeId::of::<D::Alpha>() ||
            TypeId::of::<Self::Gamma>() != TypeId::of::<D::Gamma>()
         {
-            let (mut components, alpha) = rgba.split_at_mut(3);
-            convert_alpha_gamma::<Self, D>(&mut components, alpha[0]);
+            let (components, alpha) = rgba.split_at_mut(3);
+            convert_alpha_gamma::<Self, D>(components, alpha[0]);
         }
         D::with_rgba(rgba)
     }
+
+    /// Invert the color channels
+    ///
+    /// For [Rgb](struct.Rgb.html), [Bgr](struct.Bgr.html) and
+    /// [Gray](struct.Gray.html), each color channel is complemented (`MAX` -
+    /// value), and *alpha* is unchanged.  With premultiplied alpha, channels
+    /// are complemented relative to *alpha* instead, so the result stays
+    /// premultiplied.  A [Mask](struct.Mask.html) has no color channels, so
+    /// its *alpha* is complemented.
+    ///
+    /// Other color models are inverted in RGB, so that red becomes cyan, as
+    /// with an RGB format.
+    ///
+    /// Inverting twice restores integer channels exactly.  Floating-point
+    /// channels are restored exactly for values of one half or more, but
+    /// smaller values can differ in the last place: there are more
+    /// floating-point values below one half than above it, so no exact
+    /// inverse exists for all of them.
+    ///
+    /// ### Invert a color
+    /// ```
+    /// # use pix::*;
+    /// let clr = SRgba8::with_alpha(0x00, 0x40, 0xFF, 0x80);
+    /// assert_eq!(clr.invert(), SRgba8::with_alpha(0xFF, 0xBF, 0x00, 0x80));
+    /// assert_eq!(Mask8::new(0x40).invert(), Mask8::new(0xBF));
+    /// ```
+    fn invert(self) -> Self {
+        let mut p = self;
+        match Self::PIX_FMT.model() {
+            PixModel::Rgb | PixModel::Bgr | PixModel::Gray => {
+                let colors = self.components().len();
+                let max = if TypeId::of::<Self::Alpha>()
+                    == TypeId::of::<alpha::Premultiplied>()
+                {
+                    self.alpha()
+                } else {
+                    Self::Chan::MAX
+                };
+                let chans = &mut p.channels_mut()[..colors];
+                chans.iter_mut().for_each(|c| *c = max - *c);
+            }
+            PixModel::Mask => {
+                let chans = p.channels_mut();
+                chans.iter_mut().for_each(|c| *c = Self::Chan::MAX - *c);
+            }
+            _ => {
+                let mut rgba = self.to_rgba();
+                let max = Self::Chan::MAX;
+                rgba[..3].iter_mut().for_each(|c| *c = max - *c);
+                p = Self::with_rgba(rgba);
+            }
+        }
+        p
+    }
+
+    /// Composite the pixel with a destination pixel
+    ///
+    /// * `dst` Destination pixel.
+    /// * `op` Porter-Duff compositing operator.
+    ///
+    /// Components are premultiplied with alpha while compositing, so colors
+    /// of transparent pixels do not bleed into the result.
+    ///
+    /// ### Composite one pixel over another
+    /// ```
+    /// # use pix::*;
+    /// let src = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0xFF);
+    /// let dst = SRgba8::with_alpha(0x00, 0x00, 0xFF, 0x80);
+    /// assert_eq!(src.composite(dst, PorterDuff::SrcOver), src);
+    /// assert_eq!(src.composite(dst, PorterDuff::DestOut), SRgba8::default());
+    /// ```
+    fn composite(self, dst: Self, op: PorterDuff) -> Self {
+        ops::composite(self, dst, op)
+    }
+}
+
+/// Format a pixel for `Debug`, such as `SRgba8(255, 128, 0, a=200)`
+pub(crate) fn fmt_pixel<P: Pixel>(p: &P, f: &mut fmt::Formatter) -> fmt::Result
+where
+    Ch32: From<P::Chan>,
+{
+    let fmt = P::PIX_FMT;
+    // The only channel of a mask is alpha, but it is not labeled
+    let colors = match fmt.model() {
+        PixModel::Mask => P::CHANNELS,
+        model => usize::from(model.colors()),
+    };
+    write!(f, "{}(", fmt)?;
+    for (i, c) in p.channels().iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        if i >= colors {
+            write!(f, "a=")?;
+        }
+        fmt_channel(*c, f)?;
+    }
+    write!(f, ")")
+}
+
+/// Format a channel value in natural units.
+///
+/// Integer channels are formatted as integers, and floating-point channels
+/// with up to 4 decimal places.
+fn fmt_channel<C: Channel>(c: C, f: &mut fmt::Formatter) -> fmt::Result
+where
+    Ch32: From<C>,
+{
+    let v = f32::from(Ch32::from(c));
+    match C::DEPTH {
+        PixDepth::U8 => write!(f, "{}", (v * 255.0).round() as u8),
+        PixDepth::U16 => write!(f, "{}", (v * 65535.0).round() as u16),
+        _ => {
+            let s = format!("{:.4}", v);
+            let s = s.trim_end_matches('0');
+            if s.ends_with('.') {
+                write!(f, "{}0", s)
+            } else {
+                write!(f, "{}", s)
+            }
+        }
+    }
+}
+
+/// Check that a channel index is in range for a pixel format
+fn check_channel<P: Pixel>(i: usize) {
+    if i >= P::CHANNELS {
+        panic!(
+            "channel index {} out of range for {} ({} channels)",
+            i,
+            type_name::<P>(),
+            P::CHANNELS
+        );
+    }
 }
 
 /// Convert alpha/gamma between two pixel formats
@@ -234,18 +581,137 @@ mod test {
         );
     }
     #[test]
+    fn premultiplied_zero_alpha() {
+        assert_eq!(
+            Rgba8p::with_alpha(0x00, 0x00, 0x00, 0x00),
+            Rgba8::with_alpha(0xFF, 0x80, 0x40, 0x00).convert(),
+        );
+        assert_eq!(
+            Rgba8::with_alpha(0x00, 0x00, 0x00, 0x00),
+            Rgba8p::with_alpha(0x20, 0x40, 0x80, 0x00).convert(),
+        );
+        assert_eq!(
+            Rgba16::with_alpha(0x0000, 0x0000, 0x0000, 0x0000),
+            Rgba16p::with_alpha(0x2000, 0x4000, 0x8000, 0x0000).convert(),
+        );
+        assert_eq!(
+            Rgba32::with_alpha(0.0, 0.0, 0.0, 0.0),
+            Rgba32p::with_alpha(0.25, 0.5, 0.75, 0.0).convert(),
+        );
+    }
+    #[test]
+    fn premultiplied_round_trip() {
+        for a in 0..=255 {
+            for c in 0..=255 {
+                let p: Rgba8p = Rgba8::with_alpha(c, c, c, a).convert();
+                let s: Rgba8 = p.convert();
+                let v = s.components()[0];
+                let v = v.max(Ch8::new(c)) - v.min(Ch8::new(c));
+                // Error is bounded by precision of premultiplied value
+                assert!(a == 0 || u32::from(u8::from(v)) * u32::from(a) <= 255);
+            }
+        }
+    }
+    #[test]
     fn straight_to_premultiplied_srgb() {
         assert_eq!(
-            SRgba8p::with_alpha(0x16, 0x2A, 0x5C, 0x80),
+            SRgba8p::with_alpha(0x16, 0x2E, 0x5D, 0x80),
             SRgba8::with_alpha(0x20, 0x40, 0x80, 0x80).convert(),
         );
         assert_eq!(
-            SRgba8p::with_alpha(0x0D, 0x1C, 0x40, 0x40),
+            SRgba8p::with_alpha(0x0D, 0x1C, 0x42, 0x40),
             SRgba16::with_alpha(0x2000, 0x4000, 0x8000, 0x4000).convert(),
         );
         assert_eq!(
-            SRgba8p::with_alpha(0x70, 0xE0, 0xA7, 0xBF),
+            SRgba8p::with_alpha(0x70, 0xE0, 0xA8, 0xBF),
             SRgba32::with_alpha(0.5, 1.0, 0.75, 0.75).convert(),
         );
     }
+    #[test]
+    fn channels() {
+        assert_eq!(SGray8::CHANNELS, 1);
+        assert_eq!(SGrayAlpha16::CHANNELS, 2);
+        assert_eq!(Cmyka8::CHANNELS, 5);
+        assert_eq!(Mask32::CHANNELS, 1);
+        let mut clr = Bgra8::with_alpha(1, 2, 3, 4);
+        let chans: Vec<u8> =
+            clr.channels().iter().map(|c| u8::from(*c)).collect();
+        assert_eq!(chans, [3, 2, 1, 4]);
+        clr.set_channel(3, Ch8::new(0x80));
+        assert_eq!(clr.channel(3), Ch8::new(0x80));
+        assert_eq!(clr.alpha(), Ch8::new(0x80));
+        let clr = SRgb16::new(1, 2, 3);
+        assert_eq!(clr.channels().len(), 3);
+        assert_eq!(clr.alpha(), Ch16::MAX);
+    }
+    #[test]
+    #[should_panic(expected = "channel index 3 out of range for")]
+    fn channel_out_of_range() {
+        SRgb8::new(1, 2, 3).channel(3);
+    }
+    fn check_consts<P: Pixel>()
+    where
+        Ch8: From<P::Chan>,
+    {
+        let white = SRgba8::new::<u8>(0xFF, 0xFF, 0xFF);
+        let black = SRgba8::new::<u8>(0, 0, 0);
+        let name = std::any::type_name::<P>();
+        assert_eq!(P::WHITE.convert::<SRgba8>(), white, "{}", name);
+        assert_eq!(P::BLACK.convert::<SRgba8>(), black, "{}", name);
+        let clear = P::TRANSPARENT.convert::<SRgba8>();
+        if P::PIX_FMT.alpha() == PixAlpha::Opaque {
+            assert_eq!(clear, black, "{}", name);
+        } else {
+            assert_eq!(clear.alpha(), Ch8::MIN, "{}", name);
+        }
+    }
+    #[test]
+    fn white_black() {
+        check_consts::<SRgb8>();
+        check_consts::<SRgba16>();
+        check_consts::<Rgba32p>();
+        check_consts::<Bgra8>();
+        check_consts::<SGrayAlpha8>();
+        check_consts::<Cmyka16>();
+        check_consts::<Hsv8>();
+        check_consts::<Hsla32>();
+        check_consts::<YCbCr8>();
+        check_consts::<Xyza32>();
+        check_consts::<Lab32>();
+        check_consts::<OklabAlpha32>();
+        assert_eq!(Mask8::TRANSPARENT, Mask8::default());
+        assert_eq!(SRgba8::TRANSPARENT, SRgba8::default());
+        assert_eq!(Idx8::WHITE, Idx8::new(0xFF));
+    }
+    #[test]
+    fn debug() {
+        let clr = SRgba8::with_alpha(255, 128, 0, 200);
+        assert_eq!(format!("{:?}", clr), "SRgba8(255, 128, 0, a=200)");
+        assert_eq!(format!("{:?}", Mask32::new(0.25)), "Mask32(0.25)");
+        let clr = Rgba32p::with_alpha(1.0, 0.0, 1.0 / 3.0, 0.5);
+        assert_eq!(format!("{:?}", clr), "Rgba32p(1.0, 0.0, 0.3333, a=0.5)");
+        let clr = SGrayAlpha16::with_alpha(0x1234, 0xFFFF);
+        assert_eq!(format!("{:?}", clr), "SGrayAlpha16(4660, a=65535)");
+        assert_eq!(format!("{:?}", Gray64::new(0.125)), "Gray64(0.125)");
+        let clr = Rgba16f::with_alpha(0.5, 0.25, 1.0, 1.0);
+        assert_eq!(format!("{:?}", clr), "Rgba16f(0.5, 0.25, 1.0, a=1.0)");
+        assert_eq!(format!("{:?}", Cmyk8::BLACK), "Cmyk8(0, 0, 0, 255)");
+        assert_eq!(format!("{:?}", Idx8::new(7)), "Idx8(7)");
+        assert_eq!(SRgb8::new(0xFF, 0x80, 0).to_string(), "#ff8000");
+    }
+    #[test]
+    fn approx_eq() {
+        let a = SRgba8::with_alpha(10, 20, 30, 40);
+        assert!(a.approx_eq(&a, 0.0));
+        let b = SRgba8::with_alpha(11, 19, 30, 40);
+        assert!(!a.approx_eq(&b, 0.0));
+        assert!(a.approx_eq(&b, 1.0 / 255.0));
+        let c = SRgb32::new(0.2, 0.4, 0.6);
+        assert!(c.approx_eq(&SRgb32::new(0.2001, 0.3999, 0.6), 0.001));
+        assert!(!c.approx_eq(&SRgb32::new(0.2, 0.4, 0.61), 0.001));
+        let mut nan = c;
+        nan.set_channel(1, Ch32::MID * f32::NAN);
+        assert!(!nan.approx_eq(&nan, 1.0));
+        assert!(Mask64::new(0.5).approx_eq(&Mask64::new(0.5), 0.0));
+    }
 }
@@ -0,0 +1,256 @@
+// pixfmt.rs    Runtime pixel format descriptor.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::Mode as _;
+use crate::gamma::Mode as _;
+use crate::{Channel, ColorModel, Pixel};
+use std::fmt;
+
+/// [Color model](trait.ColorModel.html) of a [PixFmt](struct.PixFmt.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixModel {
+    /// [Gray](struct.Gray.html)
+    Gray,
+    /// [Rgb](struct.Rgb.html)
+    Rgb,
+    /// [Bgr](struct.Bgr.html)
+    Bgr,
+    /// [Hsv](struct.Hsv.html)
+    Hsv,
+    /// [Hsl](struct.Hsl.html)
+    Hsl,
+    /// [YCbCr](struct.YCbCr.html)
+    YCbCr,
+    /// [Cmyk](struct.Cmyk.html)
+    Cmyk,
+    /// [Xyz](struct.Xyz.html)
+    Xyz,
+    /// [Lab](struct.Lab.html)
+    Lab,
+    /// [Oklab](struct.Oklab.html)
+    Oklab,
+    /// [Mask](struct.Mask.html)
+    Mask,
+    /// [Idx8](struct.Idx8.html)
+    Idx,
+}
+
+/// Channel bit depth of a [PixFmt](struct.PixFmt.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixDepth {
+    /// 8-bit integer ([Ch8](struct.Ch8.html))
+    U8,
+    /// 16-bit integer ([Ch16](struct.Ch16.html))
+    U16,
+    /// 16-bit float ([Ch16f](struct.Ch16f.html))
+    F16,
+    /// 32-bit float ([Ch32](struct.Ch32.html))
+    F32,
+    /// 64-bit float ([Ch64](struct.Ch64.html))
+    F64,
+}
+
+/// Alpha mode of a [PixFmt](struct.PixFmt.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixAlpha {
+    /// No *alpha* channel
+    Opaque,
+    /// [Straight](alpha/struct.Straight.html) *alpha*
+    Straight,
+    /// [Premultiplied](alpha/struct.Premultiplied.html) *alpha*
+    Premultiplied,
+}
+
+/// Gamma mode of a [PixFmt](struct.PixFmt.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixGamma {
+    /// [Linear](gamma/struct.Linear.html) gamma
+    Linear,
+    /// [Srgb](gamma/struct.Srgb.html) gamma
+    Srgb,
+}
+
+/// Runtime descriptor of a [Pixel](trait.Pixel.html) format.
+///
+/// Every `Pixel` type has one, as the
+/// [PIX_FMT](trait.Pixel.html#associatedconstant.PIX_FMT) constant.  It is
+/// useful for choosing formats at runtime, such as with a
+/// [DynRaster](enum.DynRaster.html).
+///
+/// ### Describe a pixel format
+/// ```
+/// # use pix::*;
+/// let fmt = SRgba8::PIX_FMT;
+/// assert_eq!(fmt.model(), PixModel::Rgb);
+/// assert_eq!(fmt.depth(), PixDepth::U8);
+/// assert_eq!(fmt.alpha(), PixAlpha::Straight);
+/// assert_eq!(fmt.gamma(), PixGamma::Srgb);
+/// assert_eq!(fmt.channels(), 4);
+/// assert_eq!(fmt.bits_per_pixel(), 32);
+/// assert_eq!(fmt.to_string(), "SRgba8");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PixFmt {
+    model: PixModel,
+    depth: PixDepth,
+    alpha: PixAlpha,
+    gamma: PixGamma,
+    channels: u8,
+}
+
+impl PixModel {
+    /// Get the number of color (non-alpha) channels
+    pub const fn colors(self) -> u8 {
+        match self {
+            PixModel::Gray | PixModel::Idx => 1,
+            PixModel::Cmyk => 4,
+            PixModel::Mask => 0,
+            _ => 3,
+        }
+    }
+}
+
+impl PixDepth {
+    /// Get the number of bits per channel
+    pub const fn bits(self) -> u8 {
+        match self {
+            PixDepth::U8 => 8,
+            PixDepth::U16 | PixDepth::F16 => 16,
+            PixDepth::F32 => 32,
+            PixDepth::F64 => 64,
+        }
+    }
+}
+
+impl PixFmt {
+    /// Create a pixel format descriptor for a `Pixel` type
+    pub(crate) const fn new<P: Pixel>(model: PixModel) -> Self {
+        let channels = P::CHANNELS as u8;
+        let alpha = if channels > model.colors() {
+            P::Alpha::PIX_ALPHA
+        } else {
+            PixAlpha::Opaque
+        };
+        PixFmt {
+            model,
+            depth: <<P as ColorModel>::Chan as Channel>::DEPTH,
+            alpha,
+            gamma: P::Gamma::PIX_GAMMA,
+            channels,
+        }
+    }
+    /// Get the color model
+    pub fn model(self) -> PixModel {
+        self.model
+    }
+    /// Get the channel bit depth
+    pub fn depth(self) -> PixDepth {
+        self.depth
+    }
+    /// Get the alpha mode
+    pub fn alpha(self) -> PixAlpha {
+        self.alpha
+    }
+    /// Get the gamma mode
+    pub fn gamma(self) -> PixGamma {
+        self.gamma
+    }
+    /// Get the number of channels, including *alpha*
+    pub fn channels(self) -> u8 {
+        self.channels
+    }
+    /// Get the number of bits per pixel
+    pub fn bits_per_pixel(self) -> u32 {
+        u32::from(self.channels) * u32::from(self.depth.bits())
+    }
+}
+
+impl fmt::Display for PixFmt {
+    /// Format as the name of the `Pixel` type alias, such as `SRgba8`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let model = match self.model {
+            PixModel::Gray => "Gray",
+            PixModel::Rgb => "Rgb",
+            PixModel::Bgr => "Bgr",
+            PixModel::Hsv => "Hsv",
+            PixModel::Hsl => "Hsl",
+            PixModel::YCbCr => "YCbCr",
+            PixModel::Cmyk => "Cmyk",
+            PixModel::Xyz => "Xyz",
+            PixModel::Lab => "Lab",
+            PixModel::Oklab => "Oklab",
+            PixModel::Mask => "Mask",
+            PixModel::Idx => "Idx",
+        };
+        // CMYK and Y'CbCr are always sRGB, without an `S` prefix
+        let srgb = match self.model {
+            PixModel::Cmyk | PixModel::YCbCr => false,
+            _ => self.gamma == PixGamma::Srgb,
+        };
+        let alpha = match (self.model, self.alpha) {
+            (_, PixAlpha::Opaque) | (PixModel::Mask, _) => "",
+            (PixModel::Gray, _) | (PixModel::Oklab, _) => "Alpha",
+            _ => "a",
+        };
+        let half = if self.depth == PixDepth::F16 { "f" } else { "" };
+        let premul = match self.alpha {
+            PixAlpha::Premultiplied => "p",
+            _ => "",
+        };
+        write!(
+            f,
+            "{}{}{}{}{}{}",
+            if srgb { "S" } else { "" },
+            model,
+            alpha,
+            self.depth.bits(),
+            half,
+            premul
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn formats() {
+        let fmt = SGray16::PIX_FMT;
+        assert_eq!(fmt.model(), PixModel::Gray);
+        assert_eq!(fmt.alpha(), PixAlpha::Opaque);
+        assert_eq!(fmt.channels(), 1);
+        assert_eq!(fmt.bits_per_pixel(), 16);
+        let fmt = Rgba32p::PIX_FMT;
+        assert_eq!(fmt.depth(), PixDepth::F32);
+        assert_eq!(fmt.alpha(), PixAlpha::Premultiplied);
+        assert_eq!(fmt.gamma(), PixGamma::Linear);
+        assert_eq!(fmt.bits_per_pixel(), 128);
+        let fmt = Mask8::PIX_FMT;
+        assert_eq!(fmt.model(), PixModel::Mask);
+        assert_eq!(fmt.alpha(), PixAlpha::Straight);
+        assert_eq!(fmt.channels(), 1);
+        assert_eq!(Cmyka16::PIX_FMT.channels(), 5);
+        assert_eq!(Idx8::PIX_FMT.alpha(), PixAlpha::Opaque);
+        assert_ne!(SRgb8::PIX_FMT, Bgr8::PIX_FMT);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(SRgb8::PIX_FMT.to_string(), "SRgb8");
+        assert_eq!(Rgba16f::PIX_FMT.to_string(), "Rgba16f");
+        assert_eq!(SBgra16p::PIX_FMT.to_string(), "SBgra16p");
+        assert_eq!(SGrayAlpha32p::PIX_FMT.to_string(), "SGrayAlpha32p");
+        assert_eq!(Gray16f::PIX_FMT.to_string(), "Gray16f");
+        assert_eq!(Cmyka8::PIX_FMT.to_string(), "Cmyka8");
+        assert_eq!(YCbCr16::PIX_FMT.to_string(), "YCbCr16");
+        assert_eq!(SHsva8::PIX_FMT.to_string(), "SHsva8");
+        assert_eq!(Hsl32::PIX_FMT.to_string(), "Hsl32");
+        assert_eq!(Laba32::PIX_FMT.to_string(), "Laba32");
+        assert_eq!(OklabAlpha32::PIX_FMT.to_string(), "OklabAlpha32");
+        assert_eq!(Xyz32::PIX_FMT.to_string(), "Xyz32");
+        assert_eq!(Mask64::PIX_FMT.to_string(), "Mask64");
+        assert_eq!(Idx8::PIX_FMT.to_string(), "Idx8");
+    }
+}
@@ -0,0 +1,254 @@
+// planar.rs    Planar raster layout.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::raster::pixel_count;
+use crate::{Ch8, Pixel, Raster, RasterBuilder, RasterError};
+use std::marker::PhantomData;
+
+/// Image with one contiguous plane for each channel.
+///
+/// A [Raster](struct.Raster.html) stores channels interleaved within each
+/// pixel.  A `PlanarRaster` stores all values of the first channel, followed
+/// by a separate plane for each other channel.  Planes are in the same order
+/// as channels are stored in the pixel format, with alpha last.
+///
+/// ### Convert planar video data
+/// ```
+/// # use pix::*;
+/// let y = vec![0x80; 4 * 2];
+/// let cb = vec![0x40; 4 * 2];
+/// let cr = vec![0xC0; 4 * 2];
+/// let planar = PlanarRaster::<YCbCr8>::with_u8_planes(4, 2, vec![y, cb, cr]);
+/// let r = planar.to_interleaved();
+/// assert_eq!(r.pixel(3, 1), YCbCr8::new(0x80, 0x40, 0xC0));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanarRaster<P: Pixel> {
+    width: u32,
+    height: u32,
+    planes: Vec<Box<[P::Chan]>>,
+    pixel: PhantomData<P>,
+}
+
+impl<P: Pixel> PlanarRaster<P> {
+    /// Create a `PlanarRaster` from owned planes.
+    ///
+    /// * `width` Width of raster.
+    /// * `height` Height of raster.
+    /// * `planes` One plane of `width` * `height` values for each channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of planes does not match the pixel format, or if
+    /// the length of any plane is wrong.
+    pub fn with_planes<B>(width: u32, height: u32, planes: Vec<B>) -> Self
+    where
+        B: Into<Box<[P::Chan]>>,
+    {
+        Self::try_with_planes(width, height, planes)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to create a `PlanarRaster` from owned planes.
+    ///
+    /// * `width` Width of raster.
+    /// * `height` Height of raster.
+    /// * `planes` One plane of `width` * `height` values for each channel.
+    ///
+    /// Each plane is checked separately; the planes are not copied.
+    pub fn try_with_planes<B>(
+        width: u32,
+        height: u32,
+        planes: Vec<B>,
+    ) -> Result<Self, RasterError>
+    where
+        B: Into<Box<[P::Chan]>>,
+    {
+        let expected = P::CHANNELS;
+        if planes.len() != expected {
+            let found = planes.len();
+            return Err(RasterError::ChannelMismatch { expected, found });
+        }
+        let expected = pixel_count::<P>(width, height)?;
+        let planes: Vec<Box<[P::Chan]>> =
+            planes.into_iter().map(|p| p.into()).collect();
+        for plane in planes.iter() {
+            if plane.len() != expected {
+                let found = plane.len();
+                return Err(RasterError::LengthMismatch { expected, found });
+            }
+        }
+        Ok(PlanarRaster {
+            width,
+            height,
+            planes,
+            pixel: PhantomData,
+        })
+    }
+    /// Create a `PlanarRaster` by copying channels from a `Raster`.
+    ///
+    /// ### Split an RGB raster into planes
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(2, 2, SRgb8::new(1, 2, 3));
+    /// let planar = PlanarRaster::from_interleaved(&r);
+    /// assert_eq!(planar.plane(2), &[Ch8::new(3); 4]);
+    /// ```
+    pub fn from_interleaved(raster: &Raster<P>) -> Self {
+        let n = P::CHANNELS;
+        let chans = raster.as_chan_slice();
+        let planes = (0..n)
+            .map(|i| chans.iter().skip(i).step_by(n).copied().collect())
+            .collect();
+        PlanarRaster {
+            width: raster.width(),
+            height: raster.height(),
+            planes,
+            pixel: PhantomData,
+        }
+    }
+    /// Create a `Raster` by interleaving the planes.
+    pub fn to_interleaved(&self) -> Raster<P> {
+        let len = self.planes.first().map_or(0, |p| p.len());
+        let mut buffer = Vec::with_capacity(len * self.planes.len());
+        for i in 0..len {
+            buffer.extend(self.planes.iter().map(|p| p[i]));
+        }
+        RasterBuilder::new()
+            .try_with_chan_buffer(self.width, self.height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Get width of raster.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of raster.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get the number of planes.
+    pub fn planes(&self) -> usize {
+        self.planes.len()
+    }
+    /// Get one plane of channel values.
+    ///
+    /// * `i` Plane index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of planes.
+    pub fn plane(&self, i: usize) -> &[P::Chan] {
+        &self.planes[i]
+    }
+    /// Get one mutable plane of channel values.
+    ///
+    /// * `i` Plane index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is not less than the number of planes.
+    pub fn plane_mut(&mut self, i: usize) -> &mut [P::Chan] {
+        &mut self.planes[i]
+    }
+}
+
+impl<P> PlanarRaster<P>
+where
+    P: Pixel<Chan = Ch8>,
+{
+    /// Create a `PlanarRaster` from 8-bit planes.
+    ///
+    /// * `width` Width of raster.
+    /// * `height` Height of raster.
+    /// * `planes` One plane of `width` * `height` bytes for each channel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of planes does not match the pixel format, or if
+    /// the length of any plane is wrong.
+    pub fn with_u8_planes<B>(width: u32, height: u32, planes: Vec<B>) -> Self
+    where
+        B: Into<Box<[u8]>>,
+    {
+        Self::try_with_u8_planes(width, height, planes)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to create a `PlanarRaster` from 8-bit planes.
+    ///
+    /// * `width` Width of raster.
+    /// * `height` Height of raster.
+    /// * `planes` One plane of `width` * `height` bytes for each channel.
+    ///
+    /// Each plane is checked separately; the planes are not copied.
+    ///
+    /// ### Wrong plane length
+    /// ```
+    /// # use pix::*;
+    /// let planes = vec![vec![0; 16], vec![0; 16], vec![0; 15]];
+    /// let r = PlanarRaster::<SRgb8>::try_with_u8_planes(4, 4, planes);
+    /// assert_eq!(
+    ///     r,
+    ///     Err(RasterError::LengthMismatch { expected: 16, found: 15 })
+    /// );
+    /// ```
+    pub fn try_with_u8_planes<B>(
+        width: u32,
+        height: u32,
+        planes: Vec<B>,
+    ) -> Result<Self, RasterError>
+    where
+        B: Into<Box<[u8]>>,
+    {
+        let planes: Vec<Box<[Ch8]>> = planes
+            .into_iter()
+            .map(|p| {
+                let p = Box::into_raw(p.into());
+                // Safety: Ch8 is a transparent wrapper of u8
+                unsafe { Box::from_raw(p as *mut [Ch8]) }
+            })
+            .collect();
+        Self::try_with_planes(width, height, planes)
+    }
+    /// Get one plane as a `u8` slice.
+    ///
+    /// * `i` Plane index.
+    pub fn as_u8_plane(&self, i: usize) -> &[u8] {
+        let plane = self.plane(i);
+        // Safety: Ch8 is a transparent wrapper of u8
+        unsafe { &*(plane as *const [Ch8] as *const [u8]) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn round_trip() {
+        let r = RasterBuilder::<SRgba16>::new().with_fn(5, 3, |x, y| {
+            SRgba16::with_alpha(x as u16, y as u16, 7, 0xFFFF)
+        });
+        let planar = PlanarRaster::from_interleaved(&r);
+        assert_eq!(planar.planes(), 4);
+        assert_eq!(planar.plane(0)[4], Ch16::new(4));
+        assert_eq!(planar.plane(1)[14], Ch16::new(2));
+        assert_eq!(planar.to_interleaved(), r);
+    }
+
+    #[test]
+    fn u8_planes() {
+        let planes = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9; 4]];
+        let mut planar = PlanarRaster::<SBgr8>::with_u8_planes(2, 2, planes);
+        assert_eq!(planar.as_u8_plane(1), &[5, 6, 7, 8]);
+        planar.plane_mut(2)[3] = Ch8::new(0);
+        let r = planar.to_interleaved();
+        assert_eq!(r.as_u8_slice(), &[1, 5, 9, 2, 6, 9, 3, 7, 9, 4, 8, 0]);
+        let r = PlanarRaster::<SBgr8>::try_with_u8_planes(2, 2, vec![[0; 4]]);
+        let err = RasterError::ChannelMismatch {
+            expected: 3,
+            found: 1,
+        };
+        assert_eq!(r, Err(err));
+    }
+}
@@ -0,0 +1,596 @@
+// pnm.rs       Netpbm (PBM, PGM and PPM) images.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{
+    Pixel, Raster, RasterBuilder, RasterError, SGray16, SGray8, SRgb16, SRgb8,
+};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Maximum length of a line in plain (ASCII) images
+const PLAIN_LINE_LEN: usize = 70;
+
+/// Error reading a PNM image.
+///
+/// See `Raster`::[read_pnm](struct.Raster.html#method.read_pnm).
+#[derive(Debug)]
+pub enum PnmError {
+    /// I/O error while reading
+    Io(io::Error),
+    /// Header is malformed, or the magic number is unknown
+    InvalidHeader,
+    /// Maximum sample value is not between 1 and 65535
+    InvalidMaxval(u32),
+    /// Sample is not a number, or is greater than the maximum value
+    InvalidSample,
+    /// Image data ended before all samples were read
+    Truncated,
+    /// Raster could not be built
+    Raster(RasterError),
+}
+
+impl fmt::Display for PnmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PnmError::Io(e) => write!(f, "PNM I/O error: {}", e),
+            PnmError::InvalidHeader => write!(f, "invalid PNM header"),
+            PnmError::InvalidMaxval(maxval) => {
+                write!(f, "invalid PNM maxval {}", maxval)
+            }
+            PnmError::InvalidSample => write!(f, "invalid PNM sample"),
+            PnmError::Truncated => write!(f, "truncated PNM data"),
+            PnmError::Raster(e) => write!(f, "PNM raster error: {}", e),
+        }
+    }
+}
+
+impl Error for PnmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PnmError::Io(e) => Some(e),
+            PnmError::Raster(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PnmError {
+    fn from(err: io::Error) -> Self {
+        PnmError::Io(err)
+    }
+}
+
+impl From<RasterError> for PnmError {
+    fn from(err: RasterError) -> Self {
+        PnmError::Raster(err)
+    }
+}
+
+/// `Raster` read from a PNM image.
+///
+/// The format is the closest match to the image; samples with a maximum
+/// value other than 255 or 65535 are scaled to the full range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DynPnm {
+    /// 8-bit gray, from a PGM or PBM (black and white) image
+    Gray8(Raster<SGray8>),
+    /// 16-bit gray, from a PGM image
+    Gray16(Raster<SGray16>),
+    /// 8-bit RGB, from a PPM image
+    Rgb8(Raster<SRgb8>),
+    /// 16-bit RGB, from a PPM image
+    Rgb16(Raster<SRgb16>),
+}
+
+/// PNM image header
+struct Header {
+    /// Magic number, from 1 to 6
+    magic: u8,
+    /// Width in pixels
+    width: u32,
+    /// Height in pixels
+    height: u32,
+    /// Maximum sample value
+    maxval: u32,
+}
+
+impl Header {
+    /// Check if the image is plain (ASCII)
+    fn is_plain(&self) -> bool {
+        self.magic <= 3
+    }
+    /// Check if the image is a bitmap (PBM)
+    fn is_bitmap(&self) -> bool {
+        self.magic == 1 || self.magic == 4
+    }
+    /// Get the number of samples per pixel
+    fn channels(&self) -> u64 {
+        if self.magic == 3 || self.magic == 6 {
+            3
+        } else {
+            1
+        }
+    }
+}
+
+/// Parser for PNM data
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Get the number of bytes remaining
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+    /// Skip whitespace and comments
+    fn skip_space(&mut self) {
+        while let Some(b) = self.data.get(self.pos) {
+            if *b == b'#' {
+                while let Some(b) = self.data.get(self.pos) {
+                    if *b == b'\n' || *b == b'\r' {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            } else if b.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+    /// Parse a decimal number, after whitespace and comments
+    fn number(&mut self) -> Option<u32> {
+        self.skip_space();
+        let start = self.pos;
+        let mut value: u32 = 0;
+        while let Some(b) = self.data.get(self.pos) {
+            if !b.is_ascii_digit() {
+                break;
+            }
+            value = value.checked_mul(10)?.checked_add(u32::from(b - b'0'))?;
+            self.pos += 1;
+        }
+        if self.pos > start {
+            Some(value)
+        } else {
+            None
+        }
+    }
+    /// Parse a plain sample
+    fn sample(&mut self) -> Result<u32, PnmError> {
+        match self.number() {
+            Some(v) => Ok(v),
+            None if self.remaining() == 0 => Err(PnmError::Truncated),
+            None => Err(PnmError::InvalidSample),
+        }
+    }
+    /// Parse a plain bitmap sample (one digit)
+    fn bit(&mut self) -> Result<u32, PnmError> {
+        self.skip_space();
+        let b = self.data.get(self.pos).ok_or(PnmError::Truncated)?;
+        self.pos += 1;
+        match b {
+            b'0' => Ok(0),
+            b'1' => Ok(1),
+            _ => Err(PnmError::InvalidSample),
+        }
+    }
+    /// Parse the header
+    fn header(&mut self) -> Result<Header, PnmError> {
+        let magic = match self.data.get(..2) {
+            Some([b'P', m @ b'1'..=b'6']) => m - b'0',
+            _ => return Err(PnmError::InvalidHeader),
+        };
+        self.pos = 2;
+        let width = self.number().ok_or(PnmError::InvalidHeader)?;
+        let height = self.number().ok_or(PnmError::InvalidHeader)?;
+        let mut header = Header {
+            magic,
+            width,
+            height,
+            maxval: 1,
+        };
+        if !header.is_bitmap() {
+            let maxval = self.number().ok_or(PnmError::InvalidHeader)?;
+            if maxval == 0 || maxval > 65535 {
+                return Err(PnmError::InvalidMaxval(maxval));
+            }
+            header.maxval = maxval;
+        }
+        if !header.is_plain() {
+            // Exactly one whitespace character precedes binary data
+            match self.data.get(self.pos) {
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                _ => return Err(PnmError::InvalidHeader),
+            }
+        }
+        Ok(header)
+    }
+    /// Parse all samples
+    fn samples(&mut self, header: &Header) -> Result<Vec<u32>, PnmError> {
+        let width = u64::from(header.width);
+        let count = width
+            .checked_mul(u64::from(header.height))
+            .and_then(|n| n.checked_mul(header.channels()))
+            .ok_or(PnmError::InvalidHeader)?;
+        // Every sample needs at least one byte (or bit, for bitmaps)
+        let bytes = if header.magic == 4 {
+            width.div_ceil(8) * u64::from(header.height)
+        } else {
+            count
+        };
+        if bytes > self.remaining() as u64 {
+            return Err(PnmError::Truncated);
+        }
+        let count = count as usize;
+        let mut samples = Vec::with_capacity(count);
+        match header.magic {
+            1 => {
+                for _ in 0..count {
+                    // 1 is black
+                    samples.push(1 - self.bit()?);
+                }
+            }
+            2 | 3 => {
+                for _ in 0..count {
+                    samples.push(self.sample()?);
+                }
+            }
+            4 => {
+                let row_len = (header.width as usize).div_ceil(8);
+                let rows = self.data[self.pos..].chunks_exact(row_len.max(1));
+                for row in rows.take(header.height as usize) {
+                    for x in 0..header.width as usize {
+                        let bit = (row[x / 8] >> (7 - x % 8)) & 1;
+                        samples.push(1 - u32::from(bit));
+                    }
+                }
+            }
+            _ => {
+                let data = &self.data[self.pos..];
+                if header.maxval < 256 {
+                    samples.extend(data[..count].iter().map(|b| u32::from(*b)));
+                } else if count * 2 > data.len() {
+                    return Err(PnmError::Truncated);
+                } else {
+                    samples.extend(
+                        data[..count * 2].chunks_exact(2).map(|b| {
+                            u32::from(u16::from_be_bytes([b[0], b[1]]))
+                        }),
+                    );
+                }
+            }
+        }
+        if samples.iter().any(|s| *s > header.maxval) {
+            return Err(PnmError::InvalidSample);
+        }
+        Ok(samples)
+    }
+}
+
+/// Scale samples to 8 bits
+fn scale_u8(samples: &[u32], maxval: u32) -> Vec<u8> {
+    samples
+        .iter()
+        .map(|s| ((s * 255 + maxval / 2) / maxval) as u8)
+        .collect()
+}
+
+/// Scale samples to 16 bits
+fn scale_u16(samples: &[u32], maxval: u32) -> Vec<u16> {
+    samples
+        .iter()
+        .map(|s| ((s * 65535 + maxval / 2) / maxval) as u16)
+        .collect()
+}
+
+/// Write a PNM image.
+///
+/// * `magic` Magic number of plain format.
+fn write_pnm<P: Pixel>(
+    raster: &Raster<P>,
+    mut w: impl Write,
+    magic: u8,
+    plain: bool,
+) -> io::Result<()> {
+    let bytes = raster.to_be_bytes();
+    let wide = std::mem::size_of::<P::Chan>() > 1;
+    let maxval = if wide { 65535 } else { 255 };
+    let magic = if plain { magic } else { magic + 3 };
+    writeln!(w, "P{}", magic)?;
+    writeln!(w, "{} {}", raster.width(), raster.height())?;
+    writeln!(w, "{}", maxval)?;
+    if !plain {
+        return w.write_all(&bytes);
+    }
+    let samples: Vec<u16> = if wide {
+        bytes
+            .chunks_exact(2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .collect()
+    } else {
+        bytes.iter().map(|b| u16::from(*b)).collect()
+    };
+    let row_len = raster.width() as usize * P::CHANNELS;
+    let mut line = String::new();
+    for row in samples.chunks(row_len.max(1)) {
+        for sample in row {
+            let s = sample.to_string();
+            if !line.is_empty() && line.len() + 1 + s.len() > PLAIN_LINE_LEN {
+                writeln!(w, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&s);
+        }
+        writeln!(w, "{}", line)?;
+        line.clear();
+    }
+    Ok(())
+}
+
+impl Raster<SRgb8> {
+    /// Read a PNM image.
+    ///
+    /// * `r` Reader for image data.
+    ///
+    /// All six formats are supported: plain (ASCII) PBM, PGM and PPM (`P1`
+    /// to `P3`) and their binary equivalents (`P4` to `P6`).  Only the first
+    /// image is read.
+    ///
+    /// ### Read a PGM image
+    /// ```
+    /// # use pix::*;
+    /// let pgm = b"P2\n# gray\n2 1\n15\n0 15\n";
+    /// match Raster::read_pnm(&pgm[..]).unwrap() {
+    ///     DynPnm::Gray8(r) => assert_eq!(r.pixel(1, 0), SGray8::new(0xFF)),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn read_pnm(mut r: impl Read) -> Result<DynPnm, PnmError> {
+        let mut data = vec![];
+        r.read_to_end(&mut data)?;
+        let mut parser = Parser { data: &data, pos: 0 };
+        let header = parser.header()?;
+        let samples = parser.samples(&header)?;
+        let (w, h, maxval) = (header.width, header.height, header.maxval);
+        Ok(match (header.channels(), maxval < 256) {
+            (3, true) => {
+                let buf = scale_u8(&samples, maxval);
+                let r = RasterBuilder::new().try_with_u8_buffer(w, h, buf)?;
+                DynPnm::Rgb8(r)
+            }
+            (3, false) => {
+                let buf = scale_u16(&samples, maxval);
+                let r = RasterBuilder::new().try_with_u16_buffer(w, h, buf)?;
+                DynPnm::Rgb16(r)
+            }
+            (_, true) => {
+                let buf = scale_u8(&samples, maxval);
+                let r = RasterBuilder::new().try_with_u8_buffer(w, h, buf)?;
+                DynPnm::Gray8(r)
+            }
+            (_, false) => {
+                let buf = scale_u16(&samples, maxval);
+                let r = RasterBuilder::new().try_with_u16_buffer(w, h, buf)?;
+                DynPnm::Gray16(r)
+            }
+        })
+    }
+    /// Write a binary PPM image.
+    ///
+    /// ### Write a PPM file
+    /// ```no_run
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_color_bars(64, 48);
+    /// let file = std::fs::File::create("bars.ppm").unwrap();
+    /// r.write_ppm(std::io::BufWriter::new(file)).unwrap();
+    /// ```
+    pub fn write_ppm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 3, false)
+    }
+    /// Write a plain (ASCII) PPM image.
+    pub fn write_plain_ppm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 3, true)
+    }
+}
+
+impl Raster<SRgb16> {
+    /// Write a binary PPM image, with 16-bit big-endian samples.
+    pub fn write_ppm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 3, false)
+    }
+    /// Write a plain (ASCII) PPM image, with 16-bit samples.
+    pub fn write_plain_ppm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 3, true)
+    }
+}
+
+impl Raster<SGray8> {
+    /// Write a binary PGM image.
+    pub fn write_pgm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 2, false)
+    }
+    /// Write a plain (ASCII) PGM image.
+    pub fn write_plain_pgm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 2, true)
+    }
+}
+
+impl Raster<SGray16> {
+    /// Write a binary PGM image, with 16-bit big-endian samples.
+    pub fn write_pgm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 2, false)
+    }
+    /// Write a plain (ASCII) PGM image, with 16-bit samples.
+    pub fn write_plain_pgm(&self, w: impl Write) -> io::Result<()> {
+        write_pnm(self, w, 2, true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn rgb8() -> Raster<SRgb8> {
+        RasterBuilder::new().with_fn(13, 5, |x, y| {
+            SRgb8::new((x * 19) as u8, (y * 50) as u8, (x * y) as u8)
+        })
+    }
+
+    fn gray16() -> Raster<SGray16> {
+        RasterBuilder::new()
+            .with_fn(30, 3, |x, y| SGray16::new((x * 2000 + y * 7) as u16))
+    }
+
+    fn read(data: &[u8]) -> Result<DynPnm, PnmError> {
+        Raster::read_pnm(data)
+    }
+
+    #[test]
+    fn round_trip_8() {
+        let r = rgb8();
+        let mut buf = vec![];
+        r.write_ppm(&mut buf).unwrap();
+        assert!(buf.starts_with(b"P6\n13 5\n255\n"));
+        assert_eq!(buf.len(), 12 + 13 * 5 * 3);
+        assert_eq!(read(&buf).unwrap(), DynPnm::Rgb8(r.clone()));
+        let mut buf = vec![];
+        r.write_plain_ppm(&mut buf).unwrap();
+        assert!(buf.starts_with(b"P3\n13 5\n255\n"));
+        assert!(buf.split(|b| *b == b'\n').all(|l| l.len() <= 70));
+        assert_eq!(read(&buf).unwrap(), DynPnm::Rgb8(r));
+        let g = RasterBuilder::<SGray8>::new()
+            .with_fn(7, 7, |x, y| SGray8::new((x * 30 + y) as u8));
+        let mut buf = vec![];
+        g.write_pgm(&mut buf).unwrap();
+        assert_eq!(read(&buf).unwrap(), DynPnm::Gray8(g.clone()));
+        let mut buf = vec![];
+        g.write_plain_pgm(&mut buf).unwrap();
+        assert_eq!(read(&buf).unwrap(), DynPnm::Gray8(g));
+    }
+
+    #[test]
+    fn round_trip_16() {
+        let g = gray16();
+        let mut buf = vec![];
+        g.write_pgm(&mut buf).unwrap();
+        assert!(buf.starts_with(b"P5\n30 3\n65535\n"));
+        // Samples are big-endian
+        assert_eq!(&buf[14..18], &[0, 0, 0x07, 0xD0]);
+        assert_eq!(read(&buf).unwrap(), DynPnm::Gray16(g.clone()));
+        let mut buf = vec![];
+        g.write_plain_pgm(&mut buf).unwrap();
+        assert_eq!(read(&buf).unwrap(), DynPnm::Gray16(g));
+        let r = RasterBuilder::<SRgb16>::new()
+            .with_fn(4, 4, |x, y| SRgb16::new(x as u16, y as u16, 0xFFFF));
+        let mut buf = vec![];
+        r.write_ppm(&mut buf).unwrap();
+        assert_eq!(read(&buf).unwrap(), DynPnm::Rgb16(r.clone()));
+        let mut buf = vec![];
+        r.write_plain_ppm(&mut buf).unwrap();
+        assert_eq!(read(&buf).unwrap(), DynPnm::Rgb16(r));
+    }
+
+    #[test]
+    fn bitmaps() {
+        let black = SGray8::new(0);
+        let white = SGray8::new(0xFF);
+        let expected = RasterBuilder::new().with_fn(10, 2, |x, y| {
+            if (x + y) % 3 == 0 {
+                black
+            } else {
+                white
+            }
+        });
+        let plain = b"P1\n# bits\n10 2\n1001001001\n0 0 1 0 0 1 0 0 1 0\n";
+        assert_eq!(read(plain).unwrap(), DynPnm::Gray8(expected.clone()));
+        let binary = b"P4 10 2\n\x92\x40\x24\x80";
+        assert_eq!(read(binary).unwrap(), DynPnm::Gray8(expected));
+        let empty = RasterBuilder::new().with_clear(0, 5);
+        assert_eq!(read(b"P4 0 5\n").unwrap(), DynPnm::Gray8(empty));
+    }
+
+    #[test]
+    fn scaled() {
+        let pgm = b"P5 3 1 3\n\x00\x01\x03";
+        let r = RasterBuilder::<SGray8>::new()
+            .with_u8_buffer(3, 1, vec![0x00, 0x55, 0xFF]);
+        assert_eq!(read(pgm).unwrap(), DynPnm::Gray8(r));
+        let ppm = b"P3 1 1 1000 0 500 1000";
+        let r = RasterBuilder::<SRgb16>::new()
+            .with_color(1, 1, SRgb16::new(0, 0x8000, 0xFFFF));
+        assert_eq!(read(ppm).unwrap(), DynPnm::Rgb16(r));
+    }
+
+    #[test]
+    fn errors() {
+        let invalid = |data: &[u8]| match read(data) {
+            Err(PnmError::InvalidHeader) => (),
+            r => panic!("{:?}", r),
+        };
+        invalid(b"");
+        invalid(b"P7 1 1 255\n\x00");
+        invalid(b"P5 1");
+        invalid(b"P5 1 1 x");
+        invalid(b"P5 99999999999 1 255\n");
+        invalid(b"P5 1 1 255");
+        assert!(matches!(
+            read(b"P5 1 1 65536\n\x00\x00"),
+            Err(PnmError::InvalidMaxval(65536))
+        ));
+        assert!(matches!(
+            read(b"P2 1 1 0\n0"),
+            Err(PnmError::InvalidMaxval(0))
+        ));
+        assert!(matches!(
+            read(b"P2 2 1 10\n3 11"),
+            Err(PnmError::InvalidSample)
+        ));
+        assert!(matches!(
+            read(b"P3 1 1 255\n1 x 3"),
+            Err(PnmError::InvalidSample)
+        ));
+        assert!(matches!(read(b"P1 2 1\n1 2"), Err(PnmError::InvalidSample)));
+    }
+
+    #[test]
+    fn truncated() {
+        let truncated = |data: &[u8]| match read(data) {
+            Err(PnmError::Truncated) => (),
+            r => panic!("{:?}", r),
+        };
+        truncated(b"P6 2 1 255\n\x00\x00\x00\x00\x00");
+        truncated(b"P5 2 1 65535\n\x00\x00\x00");
+        truncated(b"P4 9 2\n\x00\x00\x00");
+        truncated(b"P2 3 1 255\n1 2  ");
+        truncated(b"P1 3 1\n1 0");
+        truncated(b"P6 65535 65535 255\n");
+        let mut buf = vec![];
+        gray16().write_plain_pgm(&mut buf).unwrap();
+        truncated(&buf[..buf.len() - 20]);
+    }
+
+    #[test]
+    fn huge() {
+        assert!(matches!(
+            read(b"P3 4294967295 4294967295 255\n0 0 0"),
+            Err(PnmError::InvalidHeader)
+        ));
+        assert!(matches!(
+            read(b"P6 4294967295 4294967295 255\n\x00\x00\x00"),
+            Err(PnmError::InvalidHeader)
+        ));
+        assert!(matches!(
+            read(b"P5 4294967295 4294967295 65535\n\x00\x00"),
+            Err(PnmError::Truncated)
+        ));
+    }
+}
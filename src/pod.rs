@@ -0,0 +1,290 @@
+// pod.rs       Plain old data casting.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{self, AChannel, Opaque, Translucent};
+use crate::gamma;
+use crate::raster::pixel_count;
+use crate::{
+    Bgr, Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, Cmyk, Gray, Hsl, Hsv, Idx8,
+    Lab, Mask, Oklab, Pixel, Raster, RasterBuilder, RasterError, Rgb, Xyz,
+    YCbCr,
+};
+use bytemuck::checked::{self, CheckedCastError};
+use bytemuck::{AnyBitPattern, CheckedBitPattern, NoUninit, Pod, Zeroable};
+
+// Channels are `repr(transparent)` wrappers of primitive numbers.
+unsafe impl Zeroable for Ch8 {}
+unsafe impl Pod for Ch8 {}
+unsafe impl Zeroable for Ch16 {}
+unsafe impl Pod for Ch16 {}
+
+// Floating-point channels must be between 0 and 1, so they can be cast to
+// bytes, but only cast from bytes with a range check.
+unsafe impl Zeroable for Ch16f {}
+unsafe impl NoUninit for Ch16f {}
+unsafe impl Zeroable for Ch32 {}
+unsafe impl NoUninit for Ch32 {}
+unsafe impl Zeroable for Ch64 {}
+unsafe impl NoUninit for Ch64 {}
+
+unsafe impl CheckedBitPattern for Ch16f {
+    type Bits = u16;
+
+    fn is_valid_bit_pattern(bits: &u16) -> bool {
+        Ch16f::from_bits(*bits).to_bits() == *bits
+    }
+}
+
+unsafe impl CheckedBitPattern for Ch32 {
+    type Bits = f32;
+
+    fn is_valid_bit_pattern(bits: &f32) -> bool {
+        (0.0..=1.0).contains(bits)
+    }
+}
+
+unsafe impl CheckedBitPattern for Ch64 {
+    type Bits = f64;
+
+    fn is_valid_bit_pattern(bits: &f64) -> bool {
+        (0.0..=1.0).contains(bits)
+    }
+}
+
+// Alpha channels are `repr(transparent)` wrappers of a channel, or nothing.
+unsafe impl<C: Channel> Zeroable for Opaque<C> {}
+unsafe impl<C: Channel> Pod for Opaque<C> {}
+unsafe impl<C: Channel> Zeroable for Translucent<C> {}
+unsafe impl<C: Channel> NoUninit for Translucent<C> {}
+unsafe impl<C> AnyBitPattern for Translucent<C> where C: Channel + AnyBitPattern
+{}
+
+/// Implement `Zeroable`, `NoUninit` and `AnyBitPattern` for a pixel type.
+///
+/// Pixels are `repr(C)`, containing only channels of one type (and
+/// zero-sized markers), so they have no padding.  Only pixels with integer
+/// channels are `AnyBitPattern`.
+macro_rules! impl_pod_pixel {
+    ($pix:ident, [$($gen:ident),*], [$($bound:tt)*]) => {
+        unsafe impl<$($gen),*> Zeroable for $pix<$($gen),*>
+        where
+            $($bound)*
+        {}
+
+        unsafe impl<$($gen),*> NoUninit for $pix<$($gen),*>
+        where
+            $($bound)*
+        {}
+
+        unsafe impl<$($gen),*> AnyBitPattern for $pix<$($gen),*>
+        where
+            C: AnyBitPattern,
+            $($bound)*
+        {}
+    };
+}
+
+unsafe impl Zeroable for Idx8 {}
+unsafe impl Pod for Idx8 {}
+
+impl_pod_pixel!(Mask, [C], [C: Channel]);
+impl_pod_pixel!(Lab, [C, A], [C: Channel, A: AChannel<Chan = C>]);
+impl_pod_pixel!(Oklab, [C, A], [C: Channel, A: AChannel<Chan = C>]);
+impl_pod_pixel!(Xyz, [C, A], [C: Channel, A: AChannel<Chan = C>]);
+impl_pod_pixel!(
+    Cmyk,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_pod_pixel!(
+    Hsl,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_pod_pixel!(
+    Hsv,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_pod_pixel!(
+    YCbCr,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_pod_pixel!(
+    Bgr,
+    [C, A, M, G],
+    [C: Channel, A: AChannel<Chan = C>, M: alpha::Mode, G: gamma::Mode]
+);
+impl_pod_pixel!(
+    Gray,
+    [C, A, M, G],
+    [C: Channel, A: AChannel<Chan = C>, M: alpha::Mode, G: gamma::Mode]
+);
+impl_pod_pixel!(
+    Rgb,
+    [C, A, M, G],
+    [C: Channel, A: AChannel<Chan = C>, M: alpha::Mode, G: gamma::Mode]
+);
+
+impl<P: Pixel> Raster<P> {
+    /// Try to create a `Raster` by copying pixel data from bytes.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `bytes` Pixel data, in native byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LengthMismatch` if the length of `bytes` is not equal to
+    /// `width` * `height` * `std::mem::size_of::<P>()`, `Misaligned` if
+    /// `bytes` is not aligned for `P`, or `OutOfRange` if a floating-point
+    /// channel is not between 0.0 and 1.0, inclusive (including `NaN`).
+    ///
+    /// ### Copy from a mapped buffer
+    /// ```
+    /// # use pix::*;
+    /// let buf = [0x12u8, 0x34, 0x56, 0xFF, 0x78, 0x9A, 0xBC, 0x80];
+    /// let r = Raster::<SRgba8>::try_from_bytes(2, 1, &buf).unwrap();
+    /// assert_eq!(r.pixel(1, 0), SRgba8::with_alpha(0x78, 0x9A, 0xBC, 0x80));
+    /// ```
+    pub fn try_from_bytes(
+        width: u32,
+        height: u32,
+        bytes: &[u8],
+    ) -> Result<Self, RasterError>
+    where
+        P::Chan: CheckedBitPattern,
+    {
+        let len = pixel_count::<P>(width, height)?;
+        let expected = len * std::mem::size_of::<P>();
+        if bytes.len() != expected {
+            let found = bytes.len();
+            return Err(RasterError::LengthMismatch { expected, found });
+        }
+        let chans: &[P::Chan] = match checked::try_cast_slice(bytes) {
+            Ok(chans) => chans,
+            Err(CheckedCastError::InvalidBitPattern) => {
+                let size = std::mem::size_of::<P::Chan>();
+                let index = bytes
+                    .chunks_exact(size)
+                    .position(|b| checked::try_from_bytes::<P::Chan>(b).is_err())
+                    .unwrap_or_default();
+                return Err(RasterError::OutOfRange { index });
+            }
+            Err(CheckedCastError::PodCastError(_)) => {
+                return Err(RasterError::Misaligned);
+            }
+        };
+        let mut raster = RasterBuilder::<P>::new().with_clear(width, height);
+        for (p, c) in raster
+            .as_slice_mut()
+            .iter_mut()
+            .zip(chans.chunks_exact(P::CHANNELS))
+        {
+            p.channels_mut().copy_from_slice(c);
+        }
+        Ok(raster)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn cast_pixels() {
+        let pixels = [
+            SRgba8::with_alpha(1, 2, 3, 4),
+            SRgba8::with_alpha(5, 6, 7, 8),
+        ];
+        let quads: &[[u8; 4]] = bytemuck::cast_slice(&pixels[..]);
+        assert_eq!(quads, &[[1, 2, 3, 4], [5, 6, 7, 8]]);
+        let p: SRgb16 = bytemuck::cast([0x1234u16, 0x5678, 0x9ABC]);
+        assert_eq!(p, SRgb16::new(0x1234, 0x5678, 0x9ABC));
+        let p: Gray32 = bytemuck::Zeroable::zeroed();
+        assert_eq!(p, Gray32::new(0.0));
+        assert_eq!(std::mem::size_of::<Rgb8>(), 3);
+        assert_eq!(bytemuck::bytes_of(&Mask8::new(7)), &[7]);
+    }
+
+    #[test]
+    fn cast_float() {
+        use bytemuck::checked;
+
+        let pixels = [Rgb32::new(0.25, 0.5, 1.0)];
+        let floats: &[f32] = bytemuck::cast_slice(&pixels[..]);
+        assert_eq!(floats, &[0.25, 0.5, 1.0]);
+        let buf = [0.5f32, 1.5, -0.5, f32::NAN];
+        let bytes: &[u8] = bytemuck::cast_slice(&buf[..]);
+        let c: Result<&Ch32, _> = checked::try_from_bytes(&bytes[..4]);
+        assert_eq!(c, Ok(&Ch32::new(0.5)));
+        for i in 1..4 {
+            let b = &bytes[i * 4..i * 4 + 4];
+            assert!(checked::try_from_bytes::<Ch32>(b).is_err());
+        }
+        assert!(checked::try_cast::<u16, Ch16f>(0x3C00).is_ok());
+        assert!(checked::try_cast::<u16, Ch16f>(0x3C01).is_err());
+        assert!(checked::try_cast::<u16, Ch16f>(0x8000).is_err());
+    }
+
+    #[test]
+    fn as_u8_slice() {
+        let r = RasterBuilder::<Bgra8>::new()
+            .with_color(3, 2, Bgra8::with_alpha(1, 2, 3, 4));
+        assert_eq!(r.as_u8_slice().len(), 24);
+        assert_eq!(&r.as_u8_slice()[..4], &[3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn from_bytes() {
+        let r = RasterBuilder::<SRgb16>::new().with_fn(4, 3, |x, y| {
+            SRgb16::new(x as u16 * 0x1000, y as u16 * 0x0100, 0x5555)
+        });
+        let r2 = Raster::<SRgb16>::try_from_bytes(4, 3, r.as_u8_slice());
+        assert_eq!(r2, Ok(r));
+        let r = Raster::<Mask8>::try_from_bytes(0, 0, &[]).unwrap();
+        assert_eq!(r.width(), 0);
+    }
+
+    #[test]
+    fn from_bytes_invalid() {
+        let buf = [0u16; 7];
+        let bytes: &[u8] = bytemuck::cast_slice(&buf[..]);
+        assert_eq!(
+            Raster::<SRgb16>::try_from_bytes(2, 1, &bytes[..11]),
+            Err(RasterError::LengthMismatch {
+                expected: 12,
+                found: 11
+            })
+        );
+        assert_eq!(
+            Raster::<SRgb16>::try_from_bytes(2, 1, &bytes[1..13]),
+            Err(RasterError::Misaligned)
+        );
+        assert_eq!(
+            Raster::<SRgb8>::try_from_bytes(u32::MAX, u32::MAX, &[]),
+            Err(RasterError::Overflow)
+        );
+    }
+
+    #[test]
+    fn from_bytes_out_of_range() {
+        let buf = [0.5f32, 0.25, 1.0, 0.0];
+        let bytes: &[u8] = bytemuck::cast_slice(&buf[..]);
+        let r = Raster::<Mask32>::try_from_bytes(4, 1, bytes).unwrap();
+        let v: Vec<f32> =
+            r.as_slice().iter().map(|p| f32::from(p.alpha())).collect();
+        assert_eq!(v, vec![0.5, 0.25, 1.0, 0.0]);
+        for (i, bad) in [-0.5f32, 3.0, f32::NAN].iter().enumerate() {
+            let mut buf = buf;
+            buf[i + 1] = *bad;
+            let bytes: &[u8] = bytemuck::cast_slice(&buf[..]);
+            assert_eq!(
+                Raster::<Mask32>::try_from_bytes(4, 1, bytes),
+                Err(RasterError::OutOfRange { index: i + 1 })
+            );
+        }
+    }
+}
@@ -4,11 +4,28 @@
 //
 use crate::alpha::{self, AChannel, Opaque, Translucent};
 use crate::gamma;
-use crate::{Channel, Ch16, Ch32, Ch8, Gray, Mask, Rgb};
+use crate::{
+    Bgr, Channel, Ch16, Ch16f, Ch32, Ch64, Ch8, Cmyk, Gray, Hsl, Hsv, Idx8, Lab,
+    Mask, Oklab, Rgb, Xyz, YCbCr,
+};
 
 /// Sealed trait to prevent outside crates from implementing traits
 pub trait Sealed {}
 
+/// Plain old data, which can be cast to and from bytes
+#[cfg(feature = "bytemuck")]
+pub trait PlainData: bytemuck::NoUninit + bytemuck::Zeroable {}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::NoUninit + bytemuck::Zeroable> PlainData for T {}
+
+/// Plain old data, which can be cast to and from bytes
+#[cfg(not(feature = "bytemuck"))]
+pub trait PlainData {}
+
+#[cfg(not(feature = "bytemuck"))]
+impl<T> PlainData for T {}
+
 impl<C> Sealed for Opaque<C> {}
 
 impl<C: Channel> Sealed for Translucent<C> {}
@@ -25,8 +42,12 @@ impl Sealed for Ch8 {}
 
 impl Sealed for Ch16 {}
 
+impl Sealed for Ch16f {}
+
 impl Sealed for Ch32 {}
 
+impl Sealed for Ch64 {}
+
 impl Sealed for u8 {}
 
 impl Sealed for u16 {}
@@ -35,8 +56,49 @@ impl Sealed for f32 {}
 
 impl Sealed for f64 {}
 
+impl Sealed for Idx8 {}
+
 impl<C: Channel> Sealed for Mask<C> {}
 
+impl<C, A> Sealed for Lab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{}
+
+impl<C, A> Sealed for Oklab<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{}
+
+impl<C, A> Sealed for Xyz<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{}
+
+impl<C, A, G> Sealed for Cmyk<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{}
+
+impl<C, A, G> Sealed for Hsl<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{}
+
+impl<C, A, G> Sealed for Hsv<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{}
+
 impl<C, A, M, G> Sealed for Gray<C, A, M, G>
 where
     C: Channel,
@@ -45,6 +107,14 @@ where
     G: gamma::Mode,
 {}
 
+impl<C, A, M, G> Sealed for Bgr<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{}
+
 impl<C, A, M, G> Sealed for Rgb<C, A, M, G>
 where
     C: Channel,
@@ -52,3 +122,10 @@ where
     M: alpha::Mode,
     G: gamma::Mode,
 {}
+
+impl<C, A, G> Sealed for YCbCr<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{}
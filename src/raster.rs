@@ -3,9 +3,23 @@
 // Copyright (c) 2017-2020  Douglas P Lau
 // Copyright (c) 2019-2020  Jeron Aldaron Lau
 //
-use crate::{Ch16, Ch8, Pixel};
+use crate::alpha::{
+    self, AChannel, Mode as _, Opaque, Premultiplied, Straight, Translucent,
+};
+use crate::gamma;
+use crate::ops;
+use crate::{
+    BlendMode, Bgr, Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, ColorModel, Gray, Mask,
+    Mask8, Pixel, PorterDuff, Rgb,
+};
+use std::any::TypeId;
 use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::iter::FusedIterator;
 use std::marker::PhantomData;
+use std::ops::{Index, IndexMut, Range};
+use std::slice::{ChunksExactMut, IterMut};
 
 /// Builder for [Raster](struct.Raster.html) images.
 ///
@@ -14,9 +28,19 @@ use std::marker::PhantomData;
 /// * [with_clear](struct.RasterBuilder.html#method.with_clear)
 /// * [with_color](struct.RasterBuilder.html#method.with_color)
 /// * [with_raster](struct.RasterBuilder.html#method.with_raster)
+/// * [with_fn](struct.RasterBuilder.html#method.with_fn)
+/// * [with_iter](struct.RasterBuilder.html#method.with_iter)
 /// * [with_pixels](struct.RasterBuilder.html#method.with_pixels)
 /// * [with_u8_buffer](struct.RasterBuilder.html#method.with_u8_buffer)
 /// * [with_u16_buffer](struct.RasterBuilder.html#method.with_u16_buffer)
+/// * [with_u16_buffer_le](struct.RasterBuilder.html#method.with_u16_buffer_le)
+/// * [with_u16_buffer_be](struct.RasterBuilder.html#method.with_u16_buffer_be)
+/// * [with_f16_buffer](struct.RasterBuilder.html#method.with_f16_buffer)
+/// * [with_f64_buffer](struct.RasterBuilder.html#method.with_f64_buffer)
+///
+/// These methods panic if the pixel data is invalid.  Each one also has a
+/// *try_with_* variant, which returns a [RasterError](enum.RasterError.html)
+/// instead.
 ///
 /// ### Create a `Raster`
 /// ```
@@ -35,6 +59,9 @@ pub struct RasterBuilder<P: Pixel> {
 /// let mut raster = RasterBuilder::<SRgb8>::new().with_clear(10, 10);
 /// raster.set_region((2, 4, 3, 3), SRgb8::new(0xFF, 0xFF, 0x00));
 /// ```
+///
+/// Two rasters are equal if they have the same dimensions and pixel data.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Raster<P: Pixel> {
     width: u32,
     height: u32,
@@ -66,9 +93,88 @@ pub struct RasterIter<'a, P: Pixel> {
     raster: &'a Raster<P>,
     left: u32,
     right: u32,
-    bottom: u32,
+    /// Position of next pixel from the front
     x: u32,
     y: u32,
+    /// Position just past next pixel from the back
+    end_x: u32,
+    end_y: u32,
+    /// Number of pixels remaining
+    len: usize,
+}
+
+/// Mutable `Iterator` for pixels within a [Raster](struct.Raster.html).
+///
+/// Use `Raster`::[region_iter_mut](struct.Raster.html#method.region_iter_mut)
+/// to create.
+///
+/// ### Adjust pixels within a `Region`
+/// ```
+/// # use pix::*;
+/// let mut gray = RasterBuilder::<SGray8>::new().with_clear(40, 40);
+/// // ... load raster data
+/// for p in gray.region_iter_mut((10, 10, 20, 20)) {
+///     *p = SGray8::new(0xFF);
+/// }
+/// ```
+pub struct RasterIterMut<'a, P: Pixel> {
+    rows: ChunksExactMut<'a, P>,
+    row: IterMut<'a, P>,
+    left: usize,
+    right: usize,
+}
+
+/// Error returned when the number of pixels yielded by an `Iterator` does
+/// not match a [Region](struct.Region.html).
+///
+/// See `Raster`::[try_set_region](struct.Raster.html#method.try_set_region).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegionMismatch {
+    /// Number of pixels in the clipped region
+    pub expected: usize,
+    /// Number of pixels yielded by the iterator.  When too many were yielded,
+    /// this is a lower bound.
+    pub found: usize,
+}
+
+/// Error returned when building a [Raster](struct.Raster.html) fails.
+///
+/// See the *try_with_* methods of
+/// [RasterBuilder](struct.RasterBuilder.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RasterError {
+    /// Size of `width` * `height` pixels does not fit in memory
+    Overflow,
+    /// Length of pixel data does not match `width` * `height`
+    LengthMismatch {
+        /// Required length, in elements of the pixel data
+        expected: usize,
+        /// Actual length of the pixel data
+        found: usize,
+    },
+    /// Number of channel rasters does not match the pixel format
+    ChannelMismatch {
+        /// Number of channels in the pixel format
+        expected: usize,
+        /// Number of channel rasters
+        found: usize,
+    },
+    /// Dimensions of channel rasters are not all equal
+    SizeMismatch,
+    /// Row stride of plane data is smaller than the row length
+    StrideTooSmall {
+        /// Row length, in elements
+        minimum: usize,
+        /// Actual row stride
+        found: usize,
+    },
+    /// Pixel data is not aligned for the pixel format
+    Misaligned,
+    /// Channel value is not between 0 and 1, inclusive
+    OutOfRange {
+        /// Index of the first invalid value in the pixel data
+        index: usize,
+    },
 }
 
 /// Location / dimensions of pixels relative to a [Raster](struct.Raster.html).
@@ -86,6 +192,7 @@ pub struct RasterIter<'a, P: Pixel> {
 /// let reg = r.region(); // (0, 0, 100, 100)
 /// ```
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Region {
     x: i32,
     y: i32,
@@ -93,20 +200,172 @@ pub struct Region {
     height: u32,
 }
 
-impl<P: Pixel> Into<Box<[P]>> for Raster<P> {
+impl<P: Pixel> From<Raster<P>> for Box<[P]> {
     /// Get internal pixel data as boxed slice.
-    fn into(self) -> Box<[P]> {
-        self.pixels
+    fn from(raster: Raster<P>) -> Self {
+        raster.pixels
     }
 }
 
-impl<P: Pixel> Into<Vec<P>> for Raster<P> {
+impl<P: Pixel> From<Raster<P>> for Vec<P> {
     /// Get internal pixel data as `Vec` of pixels.
-    fn into(self) -> Vec<P> {
-        self.pixels.into()
+    fn from(raster: Raster<P>) -> Self {
+        raster.pixels.into()
+    }
+}
+
+impl<P: Pixel> IntoIterator for Raster<P> {
+    type Item = P;
+    type IntoIter = std::vec::IntoIter<P>;
+
+    /// Get an `Iterator` of owned pixels, in row-major order.
+    fn into_iter(self) -> Self::IntoIter {
+        Vec::from(self).into_iter()
+    }
+}
+
+impl<'a, P: Pixel> IntoIterator for &'a Raster<P> {
+    type Item = &'a P;
+    type IntoIter = std::slice::Iter<'a, P>;
+
+    /// Get an `Iterator` of pixels, in row-major order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.pixels()
+    }
+}
+
+impl<'a, P: Pixel> IntoIterator for &'a mut Raster<P> {
+    type Item = &'a mut P;
+    type IntoIter = IterMut<'a, P>;
+
+    /// Get an `Iterator` of mutable pixels, in row-major order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.pixels_mut()
+    }
+}
+
+/// Side of square blocks, in pixels, for cache-friendly rotation.
+const BLOCK: u32 = 32;
+
+/// Number of pixels converted at a time by `Raster::into_converted`.
+const CONVERT_CHUNK: usize = 1024;
+
+/// Maximum number of pixels printed by `Debug` for a `Raster`.
+const DEBUG_PIXELS: usize = 4;
+
+/// Pixels printed by `Debug`, shortened to `DEBUG_PIXELS`.
+struct PixelPreview<'a, P>(&'a [P]);
+
+impl<P: fmt::Debug> fmt::Debug for PixelPreview<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut list = f.debug_list();
+        list.entries(self.0.iter().take(DEBUG_PIXELS));
+        if self.0.len() > DEBUG_PIXELS {
+            let more = self.0.len() - DEBUG_PIXELS;
+            list.entry(&format_args!("... {} more", more));
+        }
+        list.finish()
+    }
+}
+
+impl<P: Pixel + fmt::Debug> fmt::Debug for Raster<P> {
+    /// Format dimensions, pixel type and the first few pixels.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Raster")
+            .field("pixel", &format_args!("{}", P::PIX_FMT))
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("pixels", &PixelPreview(&self.pixels))
+            .finish()
+    }
+}
+
+impl<P: Pixel> Index<(u32, u32)> for Raster<P> {
+    type Output = P;
+
+    /// Get a pixel by `(x, y)` coordinates.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new().with_clear(4, 4);
+    /// r[(1, 2)] = SRgb8::new(0xFF, 0x80, 0x00);
+    /// assert_eq!(r[(1, 2)], SRgb8::new(0xFF, 0x80, 0x00));
+    /// ```
+    fn index(&self, pos: (u32, u32)) -> &P {
+        &self.pixels[self.pixel_index(pos.0, pos.1)]
+    }
+}
+
+impl<P: Pixel> IndexMut<(u32, u32)> for Raster<P> {
+    /// Get a mutable pixel by `(x, y)` coordinates.
+    fn index_mut(&mut self, pos: (u32, u32)) -> &mut P {
+        let i = self.pixel_index(pos.0, pos.1);
+        &mut self.pixels[i]
+    }
+}
+
+impl<P: Pixel> Index<usize> for Raster<P> {
+    type Output = P;
+
+    /// Get a pixel by linear index, in row-major order.
+    fn index(&self, i: usize) -> &P {
+        &self.pixels[i]
+    }
+}
+
+impl<P: Pixel> IndexMut<usize> for Raster<P> {
+    /// Get a mutable pixel by linear index, in row-major order.
+    fn index_mut(&mut self, i: usize) -> &mut P {
+        &mut self.pixels[i]
+    }
+}
+
+impl fmt::Display for RegionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "region has {} pixels, but iterator yielded {}",
+            self.expected, self.found
+        )
+    }
+}
+
+impl Error for RegionMismatch {}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RasterError::Overflow => write!(f, "raster size overflow"),
+            RasterError::LengthMismatch { expected, found } => write!(
+                f,
+                "pixel data length {} does not match expected {}",
+                found, expected
+            ),
+            RasterError::ChannelMismatch { expected, found } => write!(
+                f,
+                "channel count {} does not match expected {}",
+                found, expected
+            ),
+            RasterError::SizeMismatch => {
+                write!(f, "raster dimensions do not match")
+            }
+            RasterError::StrideTooSmall { minimum, found } => write!(
+                f,
+                "row stride {} is less than row length {}",
+                found, minimum
+            ),
+            RasterError::Misaligned => {
+                write!(f, "pixel data is not aligned")
+            }
+            RasterError::OutOfRange { index } => {
+                write!(f, "pixel data value at {} is out of range", index)
+            }
+        }
     }
 }
 
+impl Error for RasterError {}
+
 impl<P: Pixel> Default for RasterBuilder<P> {
     fn default() -> Self {
         Self::new()
@@ -132,7 +391,23 @@ impl<P: Pixel> RasterBuilder<P> {
     /// let r4 = RasterBuilder::<SGrayAlpha32>::new().with_clear(100, 250);
     /// ```
     pub fn with_clear(self, width: u32, height: u32) -> Raster<P> {
-        self.with_color(width, height, P::default())
+        self.try_with_clear(width, height).unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` with all pixels set to the default value.
+    ///
+    /// ### Too large
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .try_with_clear(u32::MAX, u32::MAX);
+    /// assert_eq!(r.err(), Some(RasterError::Overflow));
+    /// ```
+    pub fn try_with_clear(
+        self,
+        width: u32,
+        height: u32,
+    ) -> Result<Raster<P>, RasterError> {
+        self.try_with_color(width, height, P::default())
     }
     /// Build a `Raster` with all pixels set to one color.
     ///
@@ -143,18 +418,34 @@ impl<P: Pixel> RasterBuilder<P> {
     /// let r = RasterBuilder::<SRgb8>::new().with_color(15, 15, clr);
     /// ```
     pub fn with_color(self, width: u32, height: u32, clr: P) -> Raster<P> {
-        let len = (width * height) as usize;
+        self.try_with_color(width, height, clr)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` with all pixels set to one color.
+    pub fn try_with_color(
+        self,
+        width: u32,
+        height: u32,
+        clr: P,
+    ) -> Result<Raster<P>, RasterError> {
+        let len = pixel_count::<P>(width, height)?;
         let pixels = vec![clr; len].into_boxed_slice();
-        Raster {
+        Ok(Raster {
             width,
             height,
             pixels,
-        }
+        })
     }
     /// Build a `Raster` by copying another `Raster`.
     ///
     /// * `S` `Pixel` format of source `Raster`.
     ///
+    /// If `S` is the same format as the new `Raster`, the pixels are copied
+    /// without conversion.
+    ///
+    /// `Raster`::[convert](struct.Raster.html#method.convert) does the same
+    /// thing, and is usually more convenient.
+    ///
     /// ### Convert from Rgb8 to Rgba16
     /// ```
     /// # use pix::*;
@@ -162,16 +453,162 @@ impl<P: Pixel> RasterBuilder<P> {
     /// // load pixels into raster
     /// let r1 = RasterBuilder::<SRgba16>::new().with_raster(&r0);
     /// ```
+    ///
+    /// ### Convert to premultiplied alpha for upload to a GPU
+    /// ```
+    /// # use pix::*;
+    /// let mut r0 = RasterBuilder::<Rgba8>::new().with_clear(2, 1);
+    /// r0.set_pixel(0, 0, Rgba8::with_alpha(0xFF, 0x80, 0x00, 0x80));
+    /// let r1 = RasterBuilder::<Rgba8p>::new().with_raster(&r0);
+    /// assert_eq!(r1.as_u8_slice(), &[0x80,0x40,0x00,0x80, 0,0,0,0]);
+    /// ```
     pub fn with_raster<S>(self, src: &Raster<S>) -> Raster<P>
     where
         S: Pixel,
         P::Chan: From<S::Chan>,
     {
+        if TypeId::of::<S>() == TypeId::of::<P>() {
+            // Same pixel format -- no conversion needed
+            let pixels = src.as_slice();
+            let pixels = unsafe { &*(pixels as *const [S] as *const [P]) };
+            return RasterBuilder::new().with_pixels(
+                src.width(),
+                src.height(),
+                pixels.to_vec(),
+            );
+        }
         let mut r = RasterBuilder::new().with_clear(src.width(), src.height());
-        let reg = src.region();
-        r.set_region(reg, src.region_iter(reg));
+        #[cfg(feature = "rayon")]
+        {
+            if crate::par::convert_raster(&mut r, src) {
+                return r;
+            }
+        }
+        convert_row(r.as_slice_mut(), src.as_slice());
         r
     }
+    /// Build a `Raster` by calling a function for each pixel.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `f` Function called with `(x, y)` coordinates, returning a pixel.
+    ///
+    /// The function is called exactly once per pixel, in row-major order.
+    ///
+    /// ### Horizontal gradient
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new()
+    ///     .with_fn(256, 16, |x, _y| SGray8::new(x as u8));
+    /// ```
+    pub fn with_fn<F>(self, width: u32, height: u32, f: F) -> Raster<P>
+    where
+        F: FnMut(u32, u32) -> P,
+    {
+        self.try_with_fn(width, height, f)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` by calling a function for each pixel.
+    ///
+    /// The function is not called if the `Raster` is too large.
+    pub fn try_with_fn<F>(
+        self,
+        width: u32,
+        height: u32,
+        mut f: F,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        F: FnMut(u32, u32) -> P,
+    {
+        let len = pixel_count::<P>(width, height)?;
+        let mut pixels = Vec::with_capacity(len);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(f(x, y));
+            }
+        }
+        self.try_with_pixels(width, height, pixels)
+    }
+    /// Build a `Raster` by collecting an `Iterator` of pixels.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `it` Pixels, in row-major order.
+    ///
+    /// Pixels are collected directly into the `Raster`, without an
+    /// intermediate `Vec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `it` does not yield exactly `width` * `height` pixels.
+    ///
+    /// ### Collect a computation
+    /// ```
+    /// # use pix::*;
+    /// let it = (0..64).map(|v| SGray8::new(v * 4));
+    /// let r = RasterBuilder::new().with_iter(8, 8, it);
+    /// assert_eq!(r.pixel(7, 7), SGray8::new(252));
+    /// ```
+    pub fn with_iter<I>(self, width: u32, height: u32, it: I) -> Raster<P>
+    where
+        I: IntoIterator<Item = P>,
+    {
+        self.try_with_iter(width, height, it)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` by collecting an `Iterator` of pixels.
+    ///
+    /// If the iterator yields too few or too many pixels,
+    /// `RasterError::LengthMismatch` is returned.  When too many were
+    /// yielded, `found` is a lower bound.
+    ///
+    /// ### Too short
+    /// ```
+    /// # use pix::*;
+    /// let it = std::iter::repeat(SGray8::new(0)).take(15);
+    /// let r = RasterBuilder::new().try_with_iter(4, 4, it);
+    /// let e = RasterError::LengthMismatch { expected: 16, found: 15 };
+    /// assert_eq!(r.err(), Some(e));
+    /// ```
+    pub fn try_with_iter<I>(
+        self,
+        width: u32,
+        height: u32,
+        it: I,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        I: IntoIterator<Item = P>,
+    {
+        let expected = pixel_count::<P>(width, height)?;
+        let mut it = it.into_iter();
+        let (lower, upper) = it.size_hint();
+        if lower > expected {
+            return Err(RasterError::LengthMismatch {
+                expected,
+                found: lower,
+            });
+        }
+        if let Some(upper) = upper {
+            if upper < expected {
+                return Err(RasterError::LengthMismatch {
+                    expected,
+                    found: upper,
+                });
+            }
+        }
+        // Reserve only what the iterator promises, since it may be short
+        let mut pixels = Vec::with_capacity(lower);
+        pixels.extend(it.by_ref().take(expected));
+        let found = pixels.len();
+        if found < expected {
+            return Err(RasterError::LengthMismatch { expected, found });
+        }
+        if it.next().is_some() {
+            let found = found + 1 + it.size_hint().0;
+            return Err(RasterError::LengthMismatch { expected, found });
+        }
+        self.try_with_pixels(width, height, pixels)
+    }
     /// Build a `Raster` with owned pixel data.  You can get ownership of the
     /// pixel data back from the `Raster` as either a `Vec<P>` or a `Box<[P]>`
     /// by calling `into()`.
@@ -199,17 +636,40 @@ impl<P: Pixel> RasterBuilder<P> {
     where
         B: Into<Box<[P]>>,
     {
-        let len = (width * height) as usize;
+        self.try_with_pixels(width, height, pixels)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` with owned pixel data.
+    ///
+    /// ### Wrong length
+    /// ```
+    /// # use pix::*;
+    /// let p = vec![SRgb8::new(255, 0, 255); 15];
+    /// let r = RasterBuilder::new().try_with_pixels(4, 4, p);
+    /// let e = RasterError::LengthMismatch { expected: 16, found: 15 };
+    /// assert_eq!(r.err(), Some(e));
+    /// ```
+    pub fn try_with_pixels<B>(
+        self,
+        width: u32,
+        height: u32,
+        pixels: B,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        B: Into<Box<[P]>>,
+    {
         let pixels = pixels.into();
-        assert_eq!(len, pixels.len());
-        Raster {
+        check_buffer::<P, P>(width, height, &pixels)?;
+        Ok(Raster {
             width,
             height,
             pixels,
-        }
+        })
     }
     /// Build a `Raster` from a `u8` buffer.
     ///
+    /// The buffer is reused for the `Raster` without copying.
+    ///
     /// * `B` Owned pixed type (`Vec` or boxed slice).
     /// * `width` Width of `Raster`.
     /// * `height` Height of `Raster`.
@@ -229,24 +689,33 @@ impl<P: Pixel> RasterBuilder<P> {
         B: Into<Box<[u8]>>,
         P: Pixel<Chan = Ch8>,
     {
-        let len = (width * height) as usize;
+        self.try_with_u8_buffer(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` from a `u8` buffer.
+    pub fn try_with_u8_buffer<B>(
+        self,
+        width: u32,
+        height: u32,
+        buffer: B,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        B: Into<Box<[u8]>>,
+        P: Pixel<Chan = Ch8>,
+    {
         let buffer: Box<[u8]> = buffer.into();
-        let capacity = buffer.len();
-        assert_eq!(len * std::mem::size_of::<P>(), capacity);
-        let slice = std::boxed::Box::<[u8]>::into_raw(buffer);
-        let pixels: Box<[P]> = unsafe {
-            let ptr = (*slice).as_mut_ptr() as *mut P;
-            let slice = std::slice::from_raw_parts_mut(ptr, len);
-            Box::from_raw(slice)
-        };
-        Raster {
+        let len = check_buffer::<P, u8>(width, height, &buffer)?;
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
             width,
             height,
             pixels,
-        }
+        })
     }
     /// Build a `Raster` from a `u16` buffer.
     ///
+    /// The buffer is reused for the `Raster` without copying.
+    ///
     /// * `B` Owned pixed type (`Vec` or boxed slice).
     /// * `width` Width of `Raster`.
     /// * `height` Height of `Raster`.
@@ -266,83 +735,534 @@ impl<P: Pixel> RasterBuilder<P> {
         B: Into<Box<[u16]>>,
         P: Pixel<Chan = Ch16>,
     {
-        let len = (width * height) as usize;
+        self.try_with_u16_buffer(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` from a `u16` buffer.
+    pub fn try_with_u16_buffer<B>(
+        self,
+        width: u32,
+        height: u32,
+        buffer: B,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        B: Into<Box<[u16]>>,
+        P: Pixel<Chan = Ch16>,
+    {
         let buffer: Box<[u16]> = buffer.into();
-        let capacity = buffer.len();
-        assert_eq!(
-            len * std::mem::size_of::<P>(),
-            capacity * std::mem::size_of::<u16>()
-        );
-        let slice = std::boxed::Box::<[u16]>::into_raw(buffer);
-        let pixels: Box<[P]> = unsafe {
-            let ptr = (*slice).as_mut_ptr() as *mut P;
-            let slice = std::slice::from_raw_parts_mut(ptr, len);
-            Box::from_raw(slice)
-        };
-        Raster {
+        let len = check_buffer::<P, u16>(width, height, &buffer)?;
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
             width,
             height,
             pixels,
-        }
-    }
-}
-
-impl<P: Pixel> Raster<P> {
-    /// Get width of `Raster`.
-    pub fn width(&self) -> u32 {
-        self.width
-    }
-    /// Get height of `Raster`.
-    pub fn height(&self) -> u32 {
-        self.height
-    }
-    /// Get one pixel.
-    pub fn pixel(&self, x: u32, y: u32) -> P {
-        let row = &self.as_slice_row(y);
-        row[x as usize]
+        })
     }
-    /// Set one pixel value.
-    pub fn set_pixel<S>(&mut self, x: u32, y: u32, p: S)
+    /// Build a `Raster` from a buffer of little-endian `u16` values.
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `buffer` Buffer of pixel data, 2 bytes per channel.
+    ///
+    /// The result is the same on any host, unlike
+    /// [with_u16_buffer](struct.RasterBuilder.html#method.with_u16_buffer).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` length is not equal to `width` * `height` *
+    /// `std::mem::size_of::<P>()`.
+    pub fn with_u16_buffer_le(
+        self,
+        width: u32,
+        height: u32,
+        buffer: &[u8],
+    ) -> Raster<P>
     where
-        P: From<S>,
+        P: Pixel<Chan = Ch16>,
     {
-        let row = &mut self.as_slice_row_mut(y);
-        row[x as usize] = p.into();
-    }
-    /// Clear all pixels to format default.
-    pub fn clear(&mut self) {
-        for p in self.pixels.iter_mut() {
-            *p = P::default();
-        }
-    }
-    /// Get `Region` of entire `Raster`.
-    pub fn region(&self) -> Region {
-        Region::new(0, 0, self.width(), self.height())
+        self.try_with_u16_buffer_le(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
     }
-    /// Get an `Iterator` of pixels within a `Region`.
-    ///
-    /// * `reg` Region within `Raster`.
-    pub fn region_iter<R>(&self, reg: R) -> RasterIter<P>
+    /// Try to build a `Raster` from a buffer of little-endian `u16` values.
+    pub fn try_with_u16_buffer_le(
+        self,
+        width: u32,
+        height: u32,
+        buffer: &[u8],
+    ) -> Result<Raster<P>, RasterError>
     where
-        R: Into<Region>,
+        P: Pixel<Chan = Ch16>,
     {
-        RasterIter::new(self, reg.into())
+        let len = check_buffer::<P, u8>(width, height, buffer)?;
+        let buffer: Box<[u16]> = buffer
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
+            width,
+            height,
+            pixels,
+        })
     }
-    /// Set a `Region` using a pixel `Iterator`.
+    /// Build a `Raster` from a buffer of big-endian `u16` values.
     ///
-    /// * `reg` Region within `Raster`.
-    /// * `it` `Iterator` of pixels in `Region`.
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `buffer` Buffer of pixel data, 2 bytes per channel.
     ///
-    /// ### Set entire raster to one color
+    /// # Panics
+    ///
+    /// Panics if `buffer` length is not equal to `width` * `height` *
+    /// `std::mem::size_of::<P>()`.
+    ///
+    /// ### Load 16-bit PNG data (big-endian)
     /// ```
     /// # use pix::*;
-    /// let mut r = RasterBuilder::<SRgb32>::new().with_clear(360, 240);
-    /// r.set_region(r.region(), SRgb32::new(0.5, 0.2, 0.8));
+    /// let buf = [0x12, 0x34, 0xFF, 0xFF];
+    /// let r = RasterBuilder::<SGrayAlpha16>::new()
+    ///     .with_u16_buffer_be(1, 1, &buf);
+    /// assert_eq!(r.pixel(0, 0), SGrayAlpha16::new(0x1234));
     /// ```
-    /// ### Set rectangle to solid color
-    /// ```
-    /// # use pix::*;
-    /// let mut raster = RasterBuilder::<SRgb8>::new().with_clear(100, 100);
+    pub fn with_u16_buffer_be(
+        self,
+        width: u32,
+        height: u32,
+        buffer: &[u8],
+    ) -> Raster<P>
+    where
+        P: Pixel<Chan = Ch16>,
+    {
+        self.try_with_u16_buffer_be(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` from a buffer of big-endian `u16` values.
+    pub fn try_with_u16_buffer_be(
+        self,
+        width: u32,
+        height: u32,
+        buffer: &[u8],
+    ) -> Result<Raster<P>, RasterError>
+    where
+        P: Pixel<Chan = Ch16>,
+    {
+        let len = check_buffer::<P, u8>(width, height, buffer)?;
+        let buffer: Box<[u16]> = buffer
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
+            width,
+            height,
+            pixels,
+        })
+    }
+    /// Build a `Raster` from a buffer of raw half-float bits.
+    ///
+    /// * `B` Owned pixed type (`Vec` or boxed slice).
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `buffer` Buffer of IEEE 754 `binary16` bits (native-endian).
+    ///
+    /// Values outside of 0.0 to 1.0 are clamped, as with
+    /// [Ch16f::from_bits](struct.Ch16f.html#method.from_bits).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` length is not equal to `width` * `height` *
+    /// `P::CHANNELS`.
+    ///
+    /// ### Load an RGBA16F texture
+    /// ```
+    /// # use pix::*;
+    /// let buf = vec![0x3C00, 0x3800, 0x0000, 0x3C00];
+    /// let r = RasterBuilder::<Rgba16f>::new().with_f16_buffer(1, 1, buf);
+    /// assert_eq!(r.pixel(0, 0), Rgba16f::with_alpha(1.0, 0.5, 0.0, 1.0));
+    /// ```
+    pub fn with_f16_buffer<B>(
+        self,
+        width: u32,
+        height: u32,
+        buffer: B,
+    ) -> Raster<P>
+    where
+        B: Into<Box<[u16]>>,
+        P: Pixel<Chan = Ch16f>,
+    {
+        self.try_with_f16_buffer(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` from a buffer of raw half-float bits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LengthMismatch` if `buffer` length is not equal to `width` *
+    /// `height` * `P::CHANNELS`.
+    pub fn try_with_f16_buffer<B>(
+        self,
+        width: u32,
+        height: u32,
+        buffer: B,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        B: Into<Box<[u16]>>,
+        P: Pixel<Chan = Ch16f>,
+    {
+        let mut buffer: Box<[u16]> = buffer.into();
+        let len = check_buffer::<P, u16>(width, height, &buffer)?;
+        for v in buffer.iter_mut() {
+            *v = Ch16f::from_bits(*v).to_bits();
+        }
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
+            width,
+            height,
+            pixels,
+        })
+    }
+    /// Build a `Raster` from an `f64` buffer.
+    ///
+    /// * `B` Owned pixed type (`Vec` or boxed slice).
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `buffer` Buffer of pixel data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` length is not equal to `width` * `height` *
+    /// `P::CHANNELS`, or if any value is not between 0.0 and 1.0, inclusive
+    /// (including `NaN`).
+    ///
+    /// ### Load scientific data
+    /// ```
+    /// # use pix::*;
+    /// let buf = vec![0.1, 0.2, 0.3, 1.0 - 1e-12];
+    /// let r = RasterBuilder::<Gray64>::new().with_f64_buffer(2, 2, buf);
+    /// assert_eq!(r.pixel(1, 1), Gray64::new(1.0 - 1e-12));
+    /// ```
+    pub fn with_f64_buffer<B>(
+        self,
+        width: u32,
+        height: u32,
+        buffer: B,
+    ) -> Raster<P>
+    where
+        B: Into<Box<[f64]>>,
+        P: Pixel<Chan = Ch64>,
+    {
+        self.try_with_f64_buffer(width, height, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` from an `f64` buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LengthMismatch` if `buffer` length is not equal to `width` *
+    /// `height` * `P::CHANNELS`, or `OutOfRange` if any value is not between
+    /// 0.0 and 1.0, inclusive (including `NaN`).
+    pub fn try_with_f64_buffer<B>(
+        self,
+        width: u32,
+        height: u32,
+        buffer: B,
+    ) -> Result<Raster<P>, RasterError>
+    where
+        B: Into<Box<[f64]>>,
+        P: Pixel<Chan = Ch64>,
+    {
+        let buffer: Box<[f64]> = buffer.into();
+        let len = check_buffer::<P, f64>(width, height, &buffer)?;
+        if let Some(index) =
+            buffer.iter().position(|v| !(0.0..=1.0).contains(v))
+        {
+            return Err(RasterError::OutOfRange { index });
+        }
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
+            width,
+            height,
+            pixels,
+        })
+    }
+    /// Build a `Raster` by merging one `Gray` raster for each channel.
+    ///
+    /// * `channels` Rasters with values for each channel, in the order
+    ///   produced by
+    ///   [split_channels](struct.Raster.html#method.split_channels).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of rasters does not match the pixel format, or if
+    /// their dimensions are not all equal.
+    ///
+    /// ### Merge gray channels
+    /// ```
+    /// # use pix::*;
+    /// let red = RasterBuilder::new().with_color(4, 4, SGray8::new(1));
+    /// let green = RasterBuilder::new().with_clear(4, 4);
+    /// let blue = RasterBuilder::new().with_clear(4, 4);
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_channels(&[red, green, blue]);
+    /// assert_eq!(r.pixel(2, 2), SRgb8::new(1, 0, 0));
+    /// ```
+    pub fn with_channels(self, channels: &[Raster<ChanGray<P>>]) -> Raster<P> {
+        self.try_with_channels(channels)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a `Raster` by merging one `Gray` raster for each channel.
+    ///
+    /// ### Wrong number of channels
+    /// ```
+    /// # use pix::*;
+    /// let gray = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+    /// let r = RasterBuilder::<SRgba8>::new().try_with_channels(&[gray]);
+    /// assert_eq!(
+    ///     r,
+    ///     Err(RasterError::ChannelMismatch { expected: 4, found: 1 })
+    /// );
+    /// ```
+    pub fn try_with_channels(
+        self,
+        channels: &[Raster<ChanGray<P>>],
+    ) -> Result<Raster<P>, RasterError> {
+        let expected = P::CHANNELS;
+        if channels.len() != expected {
+            let found = channels.len();
+            return Err(RasterError::ChannelMismatch { expected, found });
+        }
+        let (width, height) = match channels.first() {
+            Some(r) => (r.width(), r.height()),
+            None => (0, 0),
+        };
+        if channels
+            .iter()
+            .any(|r| r.width() != width || r.height() != height)
+        {
+            return Err(RasterError::SizeMismatch);
+        }
+        let len = pixel_count::<P>(width, height)?;
+        let mut buffer = Vec::with_capacity(len * expected);
+        for i in 0..len {
+            buffer.extend(channels.iter().map(|r| r.pixels[i].value()));
+        }
+        self.try_with_chan_buffer(width, height, buffer)
+    }
+    /// Try to build a `Raster` from interleaved channel values.
+    pub(crate) fn try_with_chan_buffer(
+        self,
+        width: u32,
+        height: u32,
+        buffer: Vec<P::Chan>,
+    ) -> Result<Raster<P>, RasterError> {
+        let buffer = buffer.into_boxed_slice();
+        let len = check_buffer::<P, P::Chan>(width, height, &buffer)?;
+        let pixels = buffer_pixels(buffer, len);
+        Ok(Raster {
+            width,
+            height,
+            pixels,
+        })
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Get width of `Raster`.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of `Raster`.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get one pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is outside of the `Raster`.
+    pub fn pixel(&self, x: u32, y: u32) -> P {
+        self.pixels[self.pixel_index(x, y)]
+    }
+    /// Get one pixel, or `None` if `x` or `y` is outside of the `Raster`.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+    /// assert_eq!(r.get_pixel(3, 3), Some(SGray8::new(0)));
+    /// assert_eq!(r.get_pixel(4, 0), None);
+    /// ```
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<P> {
+        self.checked_index(x, y).map(|i| self.pixels[i])
+    }
+    /// Get a mutable reference to one pixel, or `None` if `x` or `y` is
+    /// outside of the `Raster`.
+    pub fn get_pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut P> {
+        self.checked_index(x, y).map(move |i| &mut self.pixels[i])
+    }
+    /// Set one pixel value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is outside of the `Raster`.
+    pub fn set_pixel<S>(&mut self, x: u32, y: u32, p: S)
+    where
+        P: From<S>,
+    {
+        let i = self.pixel_index(x, y);
+        self.pixels[i] = p.into();
+    }
+    /// Set one pixel value, if `x` and `y` are within the `Raster`.
+    ///
+    /// Returns `true` if the pixel was set.
+    pub fn try_set_pixel<S>(&mut self, x: u32, y: u32, p: S) -> bool
+    where
+        P: From<S>,
+    {
+        match self.get_pixel_mut(x, y) {
+            Some(px) => {
+                *px = p.into();
+                true
+            }
+            None => false,
+        }
+    }
+    /// Get index of one pixel, or `None` if out of bounds.
+    fn checked_index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+    /// Get index of one pixel, panicking if out of bounds.
+    fn pixel_index(&self, x: u32, y: u32) -> usize {
+        match self.checked_index(x, y) {
+            Some(i) => i,
+            None => panic!(
+                "pixel ({}, {}) out of bounds for {}x{} raster",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+    /// Clear all pixels to format default.
+    pub fn clear(&mut self) {
+        for p in self.pixels.iter_mut() {
+            *p = P::default();
+        }
+    }
+    /// Get `Region` of entire `Raster`.
+    pub fn region(&self) -> Region {
+        Region::new(0, 0, self.width(), self.height())
+    }
+    /// Get an `Iterator` of tile `Region`s covering the `Raster`.
+    ///
+    /// * `tw` Tile width.
+    /// * `th` Tile height.
+    ///
+    /// See [Region::tiles](struct.Region.html#method.tiles).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tw` or `th` is zero.
+    ///
+    /// ### Process 8x8 blocks
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new().with_clear(20, 16);
+    /// for reg in r.tiles(8, 8) {
+    ///     let block = r.view(reg);
+    ///     assert!(block.width() <= 8 && block.height() == 8);
+    /// }
+    /// assert_eq!(r.tiles(8, 8).count(), 6);
+    /// ```
+    pub fn tiles(&self, tw: u32, th: u32) -> impl Iterator<Item = Region> {
+        self.region().tiles(tw, th)
+    }
+    /// Get an `Iterator` of pixels within a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    pub fn region_iter<R>(&self, reg: R) -> RasterIter<'_, P>
+    where
+        R: Into<Region>,
+    {
+        RasterIter::new(self, reg.into())
+    }
+    /// Get an `Iterator` of pixels with their coordinates within a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// Each item is a tuple of `(x, y, pixel)`, with coordinates relative to
+    /// the `Raster`.  Pixels are visited in row-major order (left to right,
+    /// then top to bottom), exactly matching
+    /// [region_iter](struct.Raster.html#method.region_iter).  The region is
+    /// clipped to the `Raster` bounds.
+    ///
+    /// ### Darken pixels far from the center
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(16, 16);
+    /// let reg = r.region();
+    /// let vignette: Vec<_> = r
+    ///     .region_iter_enumerated(reg)
+    ///     .map(|(x, y, p)| {
+    ///         let dx = x as i32 - 8;
+    ///         let dy = y as i32 - 8;
+    ///         if dx * dx + dy * dy > 49 { SGray8::new(0) } else { p }
+    ///     })
+    ///     .collect();
+    /// r.set_region(reg, vignette.into_iter());
+    /// ```
+    pub fn region_iter_enumerated<R>(
+        &self,
+        reg: R,
+    ) -> impl Iterator<Item = (u32, u32, P)> + '_
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into().intersection(self.region());
+        let (x0, y0) = (reg.x as u32, reg.y as u32);
+        let (x1, y1) = if reg.is_empty() {
+            (x0, y0)
+        } else {
+            (x0 + reg.width, y0 + reg.height)
+        };
+        (y0..y1).flat_map(move |y| {
+            let row = self.as_slice_row(y);
+            (x0..x1).map(move |x| (x, y, row[x as usize]))
+        })
+    }
+    /// Get a mutable `Iterator` of pixels within a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// Pixels are visited in the same order as
+    /// [region_iter](struct.Raster.html#method.region_iter).  The region is
+    /// clipped to the `Raster` bounds.
+    pub fn region_iter_mut<R>(&mut self, reg: R) -> RasterIterMut<'_, P>
+    where
+        R: Into<Region>,
+    {
+        RasterIterMut::new(self, reg.into())
+    }
+    /// Set a `Region` using a pixel `Iterator`.
+    ///
+    /// * `reg` Region within `Raster`.
+    /// * `it` `Iterator` of pixels in `Region`.
+    ///
+    /// For copying between rasters,
+    /// [copy_raster](struct.Raster.html#method.copy_raster) is faster,
+    /// especially when the pixel formats are the same.
+    ///
+    /// ### Set entire raster to one color
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb32>::new().with_clear(360, 240);
+    /// r.set_region(r.region(), SRgb32::new(0.5, 0.2, 0.8));
+    /// ```
+    /// ### Set rectangle to solid color
+    /// ```
+    /// # use pix::*;
+    /// let mut raster = RasterBuilder::<SRgb8>::new().with_clear(100, 100);
     /// raster.set_region((20, 40, 25, 50), SRgb8::new(0xDD, 0x96, 0x70));
     /// ```
     /// ### Copy part of one `Raster` to another, converting pixel format
@@ -363,31 +1283,1284 @@ impl<P: Pixel> Raster<P> {
         P::Chan: From<S::Chan>,
         I: Iterator<Item = S>,
     {
-        let reg = reg.into();
-        let x0 = if reg.x >= 0 {
-            reg.x as u32
-        } else {
-            self.width()
-        };
-        let x1 = self.width().min(x0 + reg.width);
-        let (x0, x1) = (x0 as usize, x1 as usize);
-        let y0 = if reg.y >= 0 {
-            reg.y as u32
-        } else {
-            self.height()
-        };
-        let y1 = self.height().min(y0 + reg.height);
-        if y0 < y1 && x0 < x1 {
-            for yi in y0..y1 {
-                let row = self.as_slice_row_mut(yi);
-                for x in x0..x1 {
-                    if let Some(p) = it.next() {
-                        row[x] = p.convert();
-                    }
+        let (cols, rows) = self.set_region_bounds(reg.into());
+        for yi in rows {
+            let row = self.as_slice_row_mut(yi);
+            for p in &mut row[cols.clone()] {
+                if let Some(s) = it.next() {
+                    *p = s.convert();
                 }
             }
         }
     }
+    /// Set a `Region` using a pixel `Iterator`, checking that the number of
+    /// pixels matches.
+    ///
+    /// * `reg` Region within `Raster`.
+    /// * `it` `Iterator` of pixels in `Region`.
+    ///
+    /// The region is clipped exactly as with
+    /// [set_region](struct.Raster.html#method.set_region), but the iterator
+    /// must yield exactly one pixel for each pixel of the clipped region.
+    /// Since pixels can also be used as (endless) iterators of a single color,
+    /// use `set_region` for filling with a solid color.
+    ///
+    /// # Errors
+    ///
+    /// If the iterator's `size_hint` rules out a match, the `Raster` is not
+    /// modified.  Otherwise, pixels are written until the iterator runs out;
+    /// if it yields too few or too many, `RegionMismatch` is returned.
+    ///
+    /// ### Catch a shape mismatch
+    /// ```
+    /// # use pix::*;
+    /// let mut rgb = RasterBuilder::<SRgb8>::new().with_clear(100, 100);
+    /// let gray = RasterBuilder::<SGray8>::new().with_clear(50, 50);
+    /// let it = gray.region_iter((0, 0, 10, 10));
+    /// assert!(rgb.try_set_region((0, 0, 20, 20), it).is_err());
+    /// ```
+    pub fn try_set_region<R, S, I>(
+        &mut self,
+        reg: R,
+        mut it: I,
+    ) -> Result<(), RegionMismatch>
+    where
+        R: Into<Region>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+        I: Iterator<Item = S>,
+    {
+        let (cols, rows) = self.set_region_bounds(reg.into());
+        let expected = cols.len() * rows.len();
+        let (lower, upper) = it.size_hint();
+        if lower > expected {
+            return Err(RegionMismatch { expected, found: lower });
+        }
+        if let Some(upper) = upper {
+            if upper < expected {
+                return Err(RegionMismatch { expected, found: upper });
+            }
+        }
+        let mut found = 0;
+        for yi in rows {
+            let row = self.as_slice_row_mut(yi);
+            for p in &mut row[cols.clone()] {
+                match it.next() {
+                    Some(s) => *p = s.convert(),
+                    None => return Err(RegionMismatch { expected, found }),
+                }
+                found += 1;
+            }
+        }
+        if it.next().is_some() {
+            let found = found + 1 + it.size_hint().0;
+            return Err(RegionMismatch { expected, found });
+        }
+        Ok(())
+    }
+    /// Fill a `Region` with a single color.
+    ///
+    /// * `reg` Region within `Raster`.
+    /// * `clr` Color to fill.
+    ///
+    /// This has the same result as passing the color to
+    /// [set_region](struct.Raster.html#method.set_region), including how the
+    /// region is clipped, but the color is only converted once.
+    ///
+    /// ### Fill a rectangle
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new().with_clear(100, 100);
+    /// r.fill_region((20, 40, 25, 50), SRgb8::new(0xDD, 0x96, 0x70));
+    /// assert_eq!(r.pixel(44, 89), SRgb8::new(0xDD, 0x96, 0x70));
+    /// assert_eq!(r.pixel(45, 89), SRgb8::default());
+    /// ```
+    pub fn fill_region<R, S>(&mut self, reg: R, clr: S)
+    where
+        R: Into<Region>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+    {
+        let clr: P = clr.convert();
+        let (cols, rows) = self.set_region_bounds(reg.into());
+        for yi in rows {
+            self.as_slice_row_mut(yi)[cols.clone()].fill(clr);
+        }
+    }
+    /// Get the columns and rows of a `Region` used by `set_region`.
+    ///
+    /// Regions with a negative `x` or `y` are not clipped; they are treated
+    /// as empty, since the source pixels could not be lined up.
+    fn set_region_bounds(&self, reg: Region) -> (Range<usize>, Range<u32>) {
+        let x0 = if reg.x >= 0 {
+            reg.x as u32
+        } else {
+            self.width()
+        };
+        let x1 = self.width().min(x0.saturating_add(reg.width));
+        let y0 = if reg.y >= 0 {
+            reg.y as u32
+        } else {
+            self.height()
+        };
+        let y1 = self.height().min(y0.saturating_add(reg.height));
+        if y0 < y1 && x0 < x1 {
+            (x0 as usize..x1 as usize, y0..y1)
+        } else {
+            (0..0, 0..0)
+        }
+    }
+    /// Create a new `Raster` by copying the pixels within a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds, so the new `Raster` may
+    /// be smaller than the region.  If they do not intersect, the result is
+    /// an empty 0x0 `Raster`.
+    ///
+    /// ### Crop a face out of a photo
+    /// ```
+    /// # use pix::*;
+    /// let photo = RasterBuilder::<SRgb8>::new().with_clear(640, 480);
+    /// // ... load photo, then detect a face
+    /// let face = Region::new(300, 120, 96, 128);
+    /// let r = photo.crop(face);
+    /// assert_eq!((r.width(), r.height()), (96, 128));
+    /// ```
+    pub fn crop<R>(&self, reg: R) -> Raster<P>
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into().intersection(self.region());
+        if reg.is_empty() {
+            return RasterBuilder::new().with_clear(0, 0);
+        }
+        let mut r = RasterBuilder::new().with_clear(reg.width, reg.height);
+        for (drow, srow) in r.rows_mut().zip(self.region_rows(reg)) {
+            drow.copy_from_slice(srow);
+        }
+        r
+    }
+    /// Get the smallest `Region` containing all pixels with non-zero alpha.
+    ///
+    /// For `Mask` rasters, this is the bounding box of any coverage.  If all
+    /// pixels are fully transparent, `None` is returned.
+    ///
+    /// ### Trim a rasterized glyph
+    /// ```
+    /// # use pix::*;
+    /// let mut m = RasterBuilder::<Mask8>::new().with_clear(16, 16);
+    /// m.set_region((3, 5, 4, 2), Mask8::new(0x40));
+    /// m.set_pixel(9, 6, Mask8::new(0x01));
+    /// assert_eq!(m.nonzero_region(), Some(Region::new(3, 5, 7, 2)));
+    /// m.clear();
+    /// assert_eq!(m.nonzero_region(), None);
+    /// ```
+    pub fn nonzero_region(&self) -> Option<Region> {
+        self.nonzero_region_above(P::Chan::MIN)
+    }
+    /// Get the smallest `Region` containing all pixels with alpha above a
+    /// threshold.
+    ///
+    /// * `t` Threshold alpha value.
+    ///
+    /// If no pixels have alpha above `t`, `None` is returned.
+    pub fn nonzero_region_above(&self, t: P::Chan) -> Option<Region> {
+        self.bounding_region(|p| p.alpha() > t)
+    }
+    /// Get the smallest `Region` containing all pixels matching a predicate.
+    pub(crate) fn bounding_region<F>(&self, covered: F) -> Option<Region>
+    where
+        F: Fn(&P) -> bool,
+    {
+        let covered = &covered;
+        let mut rows = self.rows().enumerate();
+        let top = rows.find(|(_, row)| row.iter().any(covered))?.0;
+        let bottom = rows
+            .rev()
+            .find(|(_, row)| row.iter().any(covered))
+            .map_or(top, |(y, _)| y);
+        let (mut left, mut right) = (self.width as usize, 0);
+        for row in self.rows().skip(top).take(bottom - top + 1) {
+            if let Some(x) = row[..left].iter().position(covered) {
+                left = x;
+            }
+            if let Some(x) = row[right..].iter().rposition(covered) {
+                right += x;
+            }
+        }
+        let width = (right + 1 - left) as u32;
+        let height = (bottom + 1 - top) as u32;
+        Some(Region::new(left as i32, top as i32, width, height))
+    }
+    /// Flip the `Raster` upside down, in place.
+    ///
+    /// ### Flip a GL framebuffer
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new()
+    ///     .with_u8_buffer(2, 3, vec![1, 2, 3, 4, 5, 6]);
+    /// r.flip_vertical_in_place();
+    /// assert_eq!(r.as_u8_slice(), &[5, 6, 3, 4, 1, 2]);
+    /// ```
+    pub fn flip_vertical_in_place(&mut self) {
+        let width = self.width as usize;
+        if width == 0 {
+            return;
+        }
+        let half = self.height as usize / 2;
+        let (top, rest) = self.pixels.split_at_mut(half * width);
+        let bottom = &mut rest[(self.height as usize % 2) * width..];
+        let rows = top
+            .chunks_exact_mut(width)
+            .zip(bottom.chunks_exact_mut(width).rev());
+        for (t, b) in rows {
+            t.swap_with_slice(b);
+        }
+    }
+    /// Flip the `Raster` left to right, in place.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new()
+    ///     .with_u8_buffer(3, 2, vec![1, 2, 3, 4, 5, 6]);
+    /// r.flip_horizontal_in_place();
+    /// assert_eq!(r.as_u8_slice(), &[3, 2, 1, 6, 5, 4]);
+    /// ```
+    pub fn flip_horizontal_in_place(&mut self) {
+        for row in self.rows_mut() {
+            row.reverse();
+        }
+    }
+    /// Create a new `Raster` flipped upside down.
+    pub fn flip_vertical(&self) -> Raster<P> {
+        let mut r = self.clone();
+        r.flip_vertical_in_place();
+        r
+    }
+    /// Create a new `Raster` flipped left to right.
+    pub fn flip_horizontal(&self) -> Raster<P> {
+        let mut r = self.clone();
+        r.flip_horizontal_in_place();
+        r
+    }
+    /// Create a new `Raster` rotated a quarter turn clockwise.
+    ///
+    /// The width and height of the new `Raster` are swapped.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new()
+    ///     .with_u8_buffer(3, 2, vec![1, 2, 3, 4, 5, 6]);
+    /// let r = r.rotate90();
+    /// assert_eq!((r.width(), r.height()), (2, 3));
+    /// assert_eq!(r.as_u8_slice(), &[4, 1, 5, 2, 6, 3]);
+    /// ```
+    pub fn rotate90(&self) -> Raster<P> {
+        let h = self.height;
+        self.remap_blocked(h, self.width, |x, y| (h - 1 - y, x))
+    }
+    /// Create a new `Raster` rotated a half turn.
+    pub fn rotate180(&self) -> Raster<P> {
+        let mut r = self.clone();
+        r.rotate180_in_place();
+        r
+    }
+    /// Rotate the `Raster` a half turn, in place.
+    pub fn rotate180_in_place(&mut self) {
+        self.pixels.reverse();
+    }
+    /// Create a new `Raster` rotated a quarter turn counter-clockwise.
+    ///
+    /// The width and height of the new `Raster` are swapped.
+    pub fn rotate270(&self) -> Raster<P> {
+        let w = self.width;
+        self.remap_blocked(self.height, w, |x, y| (y, w - 1 - x))
+    }
+    /// Create a new `Raster` with rows and columns swapped.
+    ///
+    /// ### Transpose for a separable filter
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new()
+    ///     .with_u8_buffer(3, 2, vec![1, 2, 3, 4, 5, 6]);
+    /// // ... filter rows
+    /// let r = r.transpose();
+    /// assert_eq!((r.width(), r.height()), (2, 3));
+    /// assert_eq!(r.as_u8_slice(), &[1, 4, 2, 5, 3, 6]);
+    /// // ... filter rows again (columns of the original)
+    /// let r = r.transpose();
+    /// ```
+    pub fn transpose(&self) -> Raster<P> {
+        self.remap_blocked(self.height, self.width, |x, y| (y, x))
+    }
+    /// Swap rows and columns of a square `Raster`, in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Raster` is not square.
+    pub fn transpose_in_place(&mut self) {
+        assert_eq!(self.width, self.height, "raster is not square");
+        let n = self.width as usize;
+        let block = BLOCK as usize;
+        for by in (0..n).step_by(block) {
+            for bx in (by..n).step_by(block) {
+                for y in by..n.min(by + block) {
+                    let x0 = if bx == by { y + 1 } else { bx };
+                    for x in x0..n.min(bx + block) {
+                        self.pixels.swap(y * n + x, x * n + y);
+                    }
+                }
+            }
+        }
+    }
+    /// Create a new `Raster` by moving each pixel to a new position.
+    ///
+    /// * `width` Width of new `Raster`.
+    /// * `height` Height of new `Raster`.
+    /// * `f` Function to map source `(x, y)` to destination coordinates.
+    ///
+    /// Pixels are visited in square blocks, so that both the source and
+    /// destination are accessed in a cache-friendly order.
+    fn remap_blocked<F>(&self, width: u32, height: u32, f: F) -> Raster<P>
+    where
+        F: Fn(u32, u32) -> (u32, u32),
+    {
+        let mut r = RasterBuilder::new().with_clear(width, height);
+        let stride = width as usize;
+        for by in (0..self.height).step_by(BLOCK as usize) {
+            let y1 = self.height.min(by + BLOCK);
+            for bx in (0..self.width).step_by(BLOCK as usize) {
+                let x1 = self.width.min(bx + BLOCK);
+                for y in by..y1 {
+                    let row = self.as_slice_row(y);
+                    for x in bx..x1 {
+                        let (dx, dy) = f(x, y);
+                        let i = dy as usize * stride + dx as usize;
+                        r.pixels[i] = row[x as usize];
+                    }
+                }
+            }
+        }
+        r
+    }
+    /// Copy pixels from a region of another `Raster`, converting pixel format.
+    ///
+    /// * `to` Region within destination `Raster`.
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    ///
+    /// The copied area is the smaller of the two region sizes.  Both regions
+    /// are clipped consistently, so any pixels outside of either `Raster` are
+    /// dropped on both sides.
+    ///
+    /// ### Copy part of one `Raster` to another
+    /// ```
+    /// # use pix::*;
+    /// let mut dst = RasterBuilder::<SRgb8>::new().with_clear(100, 100);
+    /// let src = RasterBuilder::<SGray8>::new().with_clear(50, 50);
+    /// // ... load image data
+    /// dst.copy_raster((40, 40, 25, 25), &src, (20, 10, 25, 25));
+    /// ```
+    pub fn copy_raster<R0, S, R1>(&mut self, to: R0, src: &Raster<S>, from: R1)
+    where
+        R0: Into<Region>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+        R1: Into<Region>,
+    {
+        let (to, from) = match clip_pair(
+            to.into(),
+            self.region(),
+            from.into(),
+            src.region(),
+        ) {
+            Some(regs) => regs,
+            None => return,
+        };
+        let (dx, sx) = (to.x as usize, from.x as usize);
+        let width = to.width as usize;
+        for row in 0..to.height {
+            let drow = self.as_slice_row_mut(to.y as u32 + row);
+            let drow = &mut drow[dx..dx + width];
+            let srow = src.as_slice_row(from.y as u32 + row);
+            let srow = &srow[sx..sx + width];
+            convert_row(drow, srow);
+        }
+    }
+    /// Get an `Iterator` of all pixels, in row-major order.
+    ///
+    /// This is the same as iterating over `&raster`.
+    ///
+    /// ### Count white pixels
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(8, 8);
+    /// r.set_region((2, 2, 3, 3), SGray8::new(0xFF));
+    /// let white = r.pixels().filter(|p| **p == SGray8::new(0xFF)).count();
+    /// assert_eq!(white, 9);
+    /// for p in &r {
+    ///     assert!(u8::from(p.value()) % 0xFF == 0);
+    /// }
+    /// ```
+    pub fn pixels(&self) -> std::slice::Iter<'_, P> {
+        self.pixels.iter()
+    }
+    /// Get an `Iterator` of all mutable pixels, in row-major order.
+    ///
+    /// This is the same as iterating over `&mut raster`.
+    pub fn pixels_mut(&mut self) -> IterMut<'_, P> {
+        self.pixels.iter_mut()
+    }
+    /// Get an `Iterator` of rows, from top to bottom.
+    ///
+    /// Each row is a slice of pixels.  There is one row for each unit of
+    /// height, even if the width is zero.
+    ///
+    /// ### Walk rows from bottom to top
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_clear(20, 10);
+    /// for row in r.rows().rev() {
+    ///     assert_eq!(row.len(), 20);
+    /// }
+    /// ```
+    pub fn rows(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &[P]> + ExactSizeIterator {
+        let width = self.width() as usize;
+        (0..self.height() as usize)
+            .map(move |y| &self.pixels[y * width..(y + 1) * width])
+    }
+    /// Get an `Iterator` of mutable rows, from top to bottom.
+    ///
+    /// Each row is a mutable slice of pixels.  There is one row for each
+    /// unit of height, even if the width is zero.
+    pub fn rows_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut [P]> + ExactSizeIterator {
+        let width = self.width() as usize;
+        if width > 0 {
+            RowsMut::Rows(self.pixels.chunks_exact_mut(width))
+        } else {
+            RowsMut::Empty(0..self.height() as usize)
+        }
+    }
+    /// Get an `Iterator` of rows within a `Region`, from top to bottom.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds, and each item is the
+    /// slice of pixels within one row of that region.
+    pub fn region_rows<R>(
+        &self,
+        reg: R,
+    ) -> impl DoubleEndedIterator<Item = &[P]> + ExactSizeIterator
+    where
+        R: Into<Region>,
+    {
+        let (pixels, left, right) = self.region_pixels(reg.into());
+        let width = (self.width() as usize).max(1);
+        self.pixels[pixels]
+            .chunks_exact(width)
+            .map(move |row| &row[left..right])
+    }
+    /// Get an `Iterator` of mutable rows within a `Region`, from top to
+    /// bottom.
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds, and each item is the
+    /// mutable slice of pixels within one row of that region.
+    pub fn region_rows_mut<R>(
+        &mut self,
+        reg: R,
+    ) -> impl DoubleEndedIterator<Item = &mut [P]> + ExactSizeIterator
+    where
+        R: Into<Region>,
+    {
+        let (pixels, left, right) = self.region_pixels(reg.into());
+        let width = (self.width() as usize).max(1);
+        self.pixels[pixels]
+            .chunks_exact_mut(width)
+            .map(move |row| &mut row[left..right])
+    }
+    /// Get range of pixels for full rows within a `Region`.
+    ///
+    /// Returns range of pixels, plus left and right column.
+    fn region_pixels(&self, reg: Region) -> (Range<usize>, usize, usize) {
+        let reg = reg.intersection(self.region());
+        if reg.is_empty() {
+            return (0..0, 0, 0);
+        }
+        let width = self.width() as usize;
+        let left = reg.x as usize;
+        let right = left + reg.width as usize;
+        let top = reg.y as usize;
+        let bottom = top + reg.height as usize;
+        (top * width..bottom * width, left, right)
+    }
+    /// Set pixels in a `Region` by calling a function for each pixel.
+    ///
+    /// * `reg` Region within `Raster`.
+    /// * `f` Function called with `(x, y)` coordinates, returning a pixel.
+    ///
+    /// The region is clipped to the `Raster` bounds.  Coordinates passed to
+    /// the function are relative to the `Raster`, and pixels are visited in
+    /// row-major order.
+    ///
+    /// ### Draw a diagonal gradient
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(64, 64);
+    /// r.set_region_fn((16, 16, 32, 32), |x, y| SGray8::new((x + y) as u8));
+    /// ```
+    pub fn set_region_fn<R, F>(&mut self, reg: R, mut f: F)
+    where
+        R: Into<Region>,
+        F: FnMut(u32, u32) -> P,
+    {
+        let reg = reg.into().intersection(self.region());
+        let (x0, y0) = (reg.x as u32, reg.y as u32);
+        for (y, row) in (y0..).zip(self.region_rows_mut(reg)) {
+            for (x, p) in (x0..).zip(row.iter_mut()) {
+                *p = f(x, y);
+            }
+        }
+    }
+    /// Composite pixels from a region of another `Raster`.
+    ///
+    /// * `to` Region within destination `Raster`.
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    ///
+    /// Each source pixel is composited over the destination using Porter-Duff
+    /// *source-over*.  Fully transparent source pixels leave the destination
+    /// unchanged, and fully opaque ones replace it.
+    ///
+    /// See [composite_raster_op](struct.Raster.html#method.composite_raster_op)
+    /// for details.
+    ///
+    /// ### Composite a sprite onto a background
+    /// ```
+    /// # use pix::*;
+    /// let mut bg = RasterBuilder::<SRgba8>::new().with_clear(100, 100);
+    /// let sprite = RasterBuilder::<SRgba8>::new().with_clear(16, 16);
+    /// // ... load image data
+    /// bg.composite_raster((20, 30, 16, 16), &sprite, sprite.region());
+    /// ```
+    pub fn composite_raster<R0, R1>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        self.composite_raster_op(to, src, from, PorterDuff::SrcOver);
+    }
+
+    /// Composite pixels from a region of another `Raster` with an operator.
+    ///
+    /// * `to` Region within destination `Raster`.
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `op` Porter-Duff compositing operator.
+    ///
+    /// Components are premultiplied with alpha while compositing, in the
+    /// gamma encoding of the pixel format.
+    ///
+    /// The regions are clipped in the same way as
+    /// [copy_raster](struct.Raster.html#method.copy_raster).
+    ///
+    /// ### Cut a hole using a mask
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<Mask8>::new()
+    ///     .with_color(4, 4, Mask8::new(0xFF));
+    /// let hole = RasterBuilder::<Mask8>::new()
+    ///     .with_color(2, 2, Mask8::new(0xFF));
+    /// r.composite_raster_op((1, 1, 2, 2), &hole, hole.region(),
+    ///     PorterDuff::DestOut);
+    /// assert_eq!(r.pixel(1, 1), Mask8::new(0x00));
+    /// assert_eq!(r.pixel(0, 0), Mask8::new(0xFF));
+    /// ```
+    pub fn composite_raster_op<R0, R1>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+        op: PorterDuff,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        let (to, from) = match clip_pair(
+            to.into(),
+            self.region(),
+            from.into(),
+            src.region(),
+        ) {
+            Some(regs) => regs,
+            None => return,
+        };
+        let rows = self.region_rows_mut(to).zip(src.region_rows(from));
+        for (drow, srow) in rows {
+            for (d, s) in drow.iter_mut().zip(srow) {
+                *d = ops::composite(*s, *d, op);
+            }
+        }
+    }
+
+    /// Composite a color over all pixels in a `Region`.
+    ///
+    /// * `reg` Region within `Raster`.
+    /// * `clr` Color to composite, using *source-over*.
+    ///
+    /// The color is converted to the `Raster` format once, keeping its
+    /// *alpha*, so a translucent color can be composited onto an opaque
+    /// `Raster`.  The region is clipped to the `Raster` bounds.  Rounding is
+    /// the same as
+    /// [composite_raster](struct.Raster.html#method.composite_raster), and an
+    /// opaque color produces the same result as
+    /// [set_region](struct.Raster.html#method.set_region).
+    ///
+    /// ### Highlight a rectangle
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(64, 64, SRgb8::new(0x00, 0x00, 0x00));
+    /// let clr = SRgba8::with_alpha(0xFF, 0xFF, 0x00, 0x80);
+    /// r.composite_color((16, 16, 32, 32), clr);
+    /// assert_eq!(r.pixel(16, 16), SRgb8::new(0x80, 0x80, 0x00));
+    /// ```
+    pub fn composite_color<R, S>(&mut self, reg: R, clr: S)
+    where
+        R: Into<Region>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+    {
+        let alpha = P::Chan::from(clr.alpha());
+        let clr: P = clr.convert();
+        // An opaque format drops alpha, so use it as coverage instead
+        let opaque = clr.alpha() != alpha;
+        for row in self.region_rows_mut(reg) {
+            for d in row.iter_mut() {
+                *d = if opaque {
+                    ops::composite_coverage(clr, *d, alpha, PorterDuff::SrcOver)
+                } else {
+                    ops::composite(clr, *d, PorterDuff::SrcOver)
+                };
+            }
+        }
+    }
+
+    /// Composite a color through a `Mask` raster.
+    ///
+    /// * `reg` Region within `Raster`.
+    /// * `mask` Coverage `Mask` raster.
+    /// * `mask_reg` Region within `mask`.
+    /// * `clr` Color to composite.
+    /// * `op` Porter-Duff compositing operator.
+    ///
+    /// The color's alpha is multiplied by the mask value of each pixel before
+    /// compositing.  The regions are clipped in the same way as
+    /// [copy_raster](struct.Raster.html#method.copy_raster).
+    ///
+    /// ### Paint a color through a mask
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgba8>::new()
+    ///     .with_color(4, 4, SRgba8::new(0x00, 0x00, 0xFF));
+    /// let mut m = RasterBuilder::<Mask8>::new().with_clear(4, 4);
+    /// m.set_pixel(1, 1, Mask8::new(0x80));
+    /// let clr = SRgba8::new(0xFF, 0x00, 0x00);
+    /// r.composite_matte(r.region(), &m, m.region(), clr, PorterDuff::SrcOver);
+    /// assert_eq!(r.pixel(0, 0), SRgba8::new(0x00, 0x00, 0xFF));
+    /// assert_eq!(r.pixel(1, 1), SRgba8::new(0x80, 0x00, 0x7F));
+    /// ```
+    pub fn composite_matte<R0, C, R1>(
+        &mut self,
+        reg: R0,
+        mask: &Raster<Mask<C>>,
+        mask_reg: R1,
+        clr: P,
+        op: PorterDuff,
+    ) where
+        R0: Into<Region>,
+        C: Channel,
+        P::Chan: From<C>,
+        R1: Into<Region>,
+    {
+        let (reg, mask_reg) = match clip_pair(
+            reg.into(),
+            self.region(),
+            mask_reg.into(),
+            mask.region(),
+        ) {
+            Some(regs) => regs,
+            None => return,
+        };
+        let rows = self.region_rows_mut(reg).zip(mask.region_rows(mask_reg));
+        for (drow, mrow) in rows {
+            for (d, m) in drow.iter_mut().zip(mrow) {
+                let cov = P::Chan::from(m.alpha());
+                *d = ops::composite_coverage(clr, *d, cov, op);
+            }
+        }
+    }
+
+    /// Blend pixels from a region of another `Raster`.
+    ///
+    /// * `to` Region within destination `Raster`.
+    /// * `src` Source `Raster`.
+    /// * `from` Region within source `Raster`.
+    /// * `mode` Blend mode.
+    ///
+    /// Each channel is blended separately, and the result is composited
+    /// using *source-over*.  Floating-point channels are blended in linear
+    /// light; 8- and 16-bit channels are blended in the gamma encoding of the
+    /// pixel format.
+    ///
+    /// The regions are clipped in the same way as
+    /// [copy_raster](struct.Raster.html#method.copy_raster).
+    ///
+    /// ### Multiply two rasters
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(4, 4, SRgb8::new(0x80, 0x80, 0x80));
+    /// let s = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(4, 4, SRgb8::new(0x80, 0x40, 0xFF));
+    /// r.blend_raster(r.region(), &s, s.region(), BlendMode::Multiply);
+    /// assert_eq!(r.pixel(0, 0), SRgb8::new(0x40, 0x20, 0x80));
+    /// ```
+    pub fn blend_raster<R0, R1>(
+        &mut self,
+        to: R0,
+        src: &Raster<P>,
+        from: R1,
+        mode: BlendMode,
+    ) where
+        R0: Into<Region>,
+        R1: Into<Region>,
+    {
+        let (to, from) = match clip_pair(
+            to.into(),
+            self.region(),
+            from.into(),
+            src.region(),
+        ) {
+            Some(regs) => regs,
+            None => return,
+        };
+        let rows = self.region_rows_mut(to).zip(src.region_rows(from));
+        for (drow, srow) in rows {
+            for (d, s) in drow.iter_mut().zip(srow) {
+                *d = ops::blend(*s, *d, mode);
+            }
+        }
+    }
+
+    /// Sample the pixel at a position.
+    ///
+    /// * `x` Horizontal position.
+    /// * `y` Vertical position.
+    ///
+    /// Pixel `(0, 0)` covers positions from 0.0 up to 1.0 on both axes.
+    /// Positions beyond the edges are clamped, and an empty `Raster` yields
+    /// the default value.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+    /// r.set_pixel(1, 0, SGray8::new(0xFF));
+    /// assert_eq!(r.sample_nearest(1.9, 0.2), SGray8::new(0xFF));
+    /// assert_eq!(r.sample_nearest(5.0, -3.0), SGray8::new(0xFF));
+    /// ```
+    pub fn sample_nearest(&self, x: f32, y: f32) -> P {
+        if self.width == 0 || self.height == 0 {
+            return P::default();
+        }
+        let x = nearest_pos(x, self.width);
+        let y = nearest_pos(y, self.height);
+        self.pixel(x, y)
+    }
+    /// Sample a position with bilinear interpolation.
+    ///
+    /// * `x` Horizontal position.
+    /// * `y` Vertical position.
+    ///
+    /// Pixel centers are at positions halfway between integers, so
+    /// `(0.5, 0.5)` is the center of pixel `(0, 0)`.  The four nearest pixels
+    /// are interpolated, with components premultiplied by alpha.  Positions
+    /// beyond the edges are clamped, and an empty `Raster` yields the default
+    /// value.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+    /// r.set_pixel(1, 0, SGray8::new(0xFF));
+    /// assert_eq!(r.sample_linear(1.5, 0.5), SGray8::new(0xFF));
+    /// assert_eq!(r.sample_linear(1.0, 0.5), SGray8::new(0x80));
+    /// assert_eq!(r.sample_linear(1.0, 1.0), SGray8::new(0x40));
+    /// ```
+    pub fn sample_linear(&self, x: f32, y: f32) -> P
+    where
+        Ch32: From<P::Chan>,
+    {
+        if self.width == 0 || self.height == 0 {
+            return P::default();
+        }
+        let (x0, x1, fx) = linear_pair(f64::from(x) - 0.5, self.width);
+        let (y0, y1, fy) = linear_pair(f64::from(y) - 0.5, self.height);
+        let p = [
+            self.pixel(x0, y0),
+            self.pixel(x1, y0),
+            self.pixel(x0, y1),
+            self.pixel(x1, y1),
+        ];
+        ops::bilinear(p, fx, fy)
+    }
+    /// Create a new `Raster` scaled to a size with nearest-neighbor sampling.
+    ///
+    /// * `width` Width of new `Raster`.
+    /// * `height` Height of new `Raster`.
+    ///
+    /// Each destination pixel center is mapped to the source pixel which
+    /// contains it.  If the source `Raster` is empty, all pixels are set to
+    /// the default value.
+    ///
+    /// ### Double the size of a checkerboard
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+    /// r.set_pixel(0, 0, SGray8::new(0xFF));
+    /// r.set_pixel(1, 1, SGray8::new(0xFF));
+    /// let r = r.scale_nearest(4, 4);
+    /// assert_eq!(r.as_u8_slice(), &[
+    ///     0xFF, 0xFF, 0x00, 0x00,
+    ///     0xFF, 0xFF, 0x00, 0x00,
+    ///     0x00, 0x00, 0xFF, 0xFF,
+    ///     0x00, 0x00, 0xFF, 0xFF,
+    /// ]);
+    /// ```
+    pub fn scale_nearest(&self, width: u32, height: u32) -> Raster<P> {
+        if self.width == 0 || self.height == 0 {
+            return RasterBuilder::new().with_clear(width, height);
+        }
+        let xs: Vec<u32> = (0..width)
+            .map(|x| nearest_index(x, self.width, width))
+            .collect();
+        let mut r = RasterBuilder::new().with_clear(width, height);
+        for (y, row) in r.rows_mut().enumerate() {
+            let sy = nearest_index(y as u32, self.height, height);
+            let srow = self.as_slice_row(sy);
+            for (d, sx) in row.iter_mut().zip(&xs) {
+                *d = srow[*sx as usize];
+            }
+        }
+        r
+    }
+
+    /// Create a new `Raster` scaled to a size with bilinear filtering.
+    ///
+    /// * `width` Width of new `Raster`.
+    /// * `height` Height of new `Raster`.
+    ///
+    /// Each destination pixel center is mapped into the source, and the four
+    /// nearest source pixels are interpolated.  Pixels beyond the edges are
+    /// clamped.  Components are premultiplied with alpha while interpolating,
+    /// so transparent pixels do not darken edges.
+    ///
+    /// Only four source pixels contribute to each destination pixel, so
+    /// reducing by more than half will skip some source pixels.  If the
+    /// source `Raster` is empty, all pixels are set to the default value.
+    ///
+    /// ### Stretch a gradient
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 1);
+    /// r.set_pixel(1, 0, SGray8::new(0x80));
+    /// let r = r.scale_linear(4, 1);
+    /// assert_eq!(r.as_u8_slice(), &[0x00, 0x20, 0x60, 0x80]);
+    /// ```
+    pub fn scale_linear(&self, width: u32, height: u32) -> Raster<P>
+    where
+        Ch32: From<P::Chan>,
+    {
+        if width == self.width && height == self.height {
+            return self.clone();
+        }
+        if self.width == 0 || self.height == 0 {
+            return RasterBuilder::new().with_clear(width, height);
+        }
+        let xs: Vec<(u32, u32, f32)> = (0..width)
+            .map(|x| linear_index(x, self.width, width))
+            .collect();
+        let mut r = RasterBuilder::new().with_clear(width, height);
+        for (y, row) in r.rows_mut().enumerate() {
+            let (y0, y1, fy) = linear_index(y as u32, self.height, height);
+            let (top, bottom) = (self.as_slice_row(y0), self.as_slice_row(y1));
+            for (d, (x0, x1, fx)) in row.iter_mut().zip(&xs) {
+                let (x0, x1) = (*x0 as usize, *x1 as usize);
+                let p = [top[x0], top[x1], bottom[x0], bottom[x1]];
+                *d = ops::bilinear(p, *fx, fy);
+            }
+        }
+        r
+    }
+
+    /// Convert to another pixel format.
+    ///
+    /// * `Q` `Pixel` format of new `Raster`.
+    ///
+    /// This is the same as
+    /// [with_raster](struct.RasterBuilder.html#method.with_raster).
+    ///
+    /// ### Convert from SRgb8 to SRgba16
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_clear(50, 50);
+    /// // ... load pixels into raster
+    /// let rgba: Raster<SRgba16> = r.convert();
+    /// ```
+    pub fn convert<Q>(&self) -> Raster<Q>
+    where
+        Q: Pixel,
+        Q::Chan: From<P::Chan>,
+    {
+        RasterBuilder::new().with_raster(self)
+    }
+    /// Convert into another pixel format, consuming the `Raster`.
+    ///
+    /// * `Q` `Pixel` format of new `Raster`.
+    ///
+    /// When `Q` has the same size and alignment as `P`, such as
+    /// [SRgb8](type.SRgb8.html) and [SBgr8](type.SBgr8.html), pixels are
+    /// converted in place, without a new allocation.
+    ///
+    /// ### Swap red and blue
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(2, 2, SRgb8::new(1, 2, 3));
+    /// let bgr: Raster<SBgr8> = r.into_converted();
+    /// assert_eq!(bgr.as_u8_slice()[..3], [3, 2, 1]);
+    /// ```
+    pub fn into_converted<Q>(self) -> Raster<Q>
+    where
+        Q: Pixel,
+        Q::Chan: From<P::Chan>,
+    {
+        if std::mem::size_of::<Q>() != std::mem::size_of::<P>()
+            || std::mem::align_of::<Q>() != std::mem::align_of::<P>()
+        {
+            return self.convert();
+        }
+        let (width, height) = (self.width, self.height);
+        let len = self.pixels.len();
+        // Same layout, so the allocation can be reused
+        let ptr = Box::into_raw(self.pixels) as *mut Q;
+        let ptr = std::ptr::slice_from_raw_parts_mut(ptr, len);
+        let mut pixels = unsafe { Box::from_raw(ptr) };
+        if TypeId::of::<Q>() != TypeId::of::<P>() {
+            // Copy chunks of source pixels before overwriting them
+            let mut src: Vec<P> = Vec::with_capacity(len.min(CONVERT_CHUNK));
+            for chunk in pixels.chunks_mut(CONVERT_CHUNK) {
+                let s = unsafe {
+                    std::slice::from_raw_parts(
+                        chunk.as_ptr() as *const P,
+                        chunk.len(),
+                    )
+                };
+                src.clear();
+                src.extend_from_slice(s);
+                convert_row(chunk, &src);
+            }
+        }
+        Raster {
+            width,
+            height,
+            pixels,
+        }
+    }
+    /// Check if two `Raster`s are approximately equal.
+    ///
+    /// * `other` `Raster` to compare.
+    /// * `epsilon` Maximum difference of each channel, treating values as
+    ///   though they range between 0 and 1.
+    ///
+    /// Both `Raster`s must have the same dimensions, and each pair of pixels
+    /// must be [approximately equal](trait.Pixel.html#method.approx_eq).
+    ///
+    /// ### Compare after float math
+    /// ```
+    /// # use pix::*;
+    /// let a = RasterBuilder::<Rgba32>::new()
+    ///     .with_color(4, 4, Rgba32::new(0.1, 0.2, 0.3));
+    /// let b = a.map(|_| Rgba32::new(0.3 - 0.2, 0.2, 0.6 / 2.0));
+    /// assert!(a.approx_eq(&b, 1e-6));
+    /// ```
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self
+                .pixels
+                .iter()
+                .zip(other.pixels.iter())
+                .all(|(a, b)| a.approx_eq(b, epsilon))
+    }
+    /// Create a new `Raster` by applying a function to each pixel.
+    ///
+    /// * `Q` `Pixel` format of new `Raster`.
+    /// * `f` Function to map each pixel.
+    ///
+    /// ### Threshold a gray raster into a mask
+    /// ```
+    /// # use pix::*;
+    /// let gray = RasterBuilder::<SGray8>::new().with_clear(32, 32);
+    /// // ... load raster data
+    /// let mask = gray.map(|p| {
+    ///     let v = if u8::from(p.value()) < 0x80 { 0x00 } else { 0xFF };
+    ///     Mask8::new(v)
+    /// });
+    /// ```
+    pub fn map<Q, F>(&self, f: F) -> Raster<Q>
+    where
+        Q: Pixel,
+        F: FnMut(P) -> Q,
+    {
+        let pixels: Vec<Q> = self.pixels.iter().copied().map(f).collect();
+        RasterBuilder::new().with_pixels(self.width(), self.height(), pixels)
+    }
+    /// Apply a function to each pixel in place.
+    ///
+    /// * `f` Function to modify each pixel.
+    ///
+    /// Pixels are visited row by row, in row-major order.
+    ///
+    /// ### Swap red and blue channels
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new().with_clear(32, 32);
+    /// // ... load raster data
+    /// r.map_in_place(|p| *p = SRgb8::new(p.blue(), p.green(), p.red()));
+    /// ```
+    pub fn map_in_place<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut P),
+    {
+        for row in self.rows_mut() {
+            row.iter_mut().for_each(&mut f);
+        }
+    }
+    /// Create a `Mask` raster by thresholding the value of each pixel.
+    ///
+    /// * `t` Threshold value.
+    ///
+    /// A pixel is fully covered in the mask if its value is greater than or
+    /// equal to `t`, and uncovered otherwise.  The value is found the same way
+    /// as when converting to [Gray](struct.Gray.html), after undoing any
+    /// premultiplied alpha.  Alpha does not otherwise affect coverage.
+    ///
+    /// A threshold of `Channel::MIN` covers every pixel.  As a special case,
+    /// a threshold of `Channel::MAX` covers no pixels, even those at full
+    /// intensity.
+    ///
+    /// ### Threshold a gray raster
+    /// ```
+    /// # use pix::*;
+    /// let gray = RasterBuilder::<SGray8>::new()
+    ///     .with_u8_buffer(4, 1, vec![0x00, 0x7F, 0x80, 0xFF]);
+    /// let mask = gray.threshold(Ch8::new(0x80));
+    /// assert_eq!(mask.as_u8_slice(), &[0x00, 0x00, 0xFF, 0xFF]);
+    /// ```
+    pub fn threshold(&self, t: P::Chan) -> Raster<Mask8> {
+        if t == P::Chan::MAX {
+            return self.threshold_map(|_| false);
+        }
+        self.threshold_map(|p| {
+            let [r, g, b, a] = p.to_rgba();
+            let rgba = [
+                P::Alpha::decode(r, a),
+                P::Alpha::decode(g, a),
+                P::Alpha::decode(b, a),
+                a,
+            ];
+            let gray: Gray<_, Opaque<_>, Straight, P::Gamma> =
+                Gray::with_rgba(rgba);
+            gray.value() >= t
+        })
+    }
+    /// Create a `Mask` raster using a predicate for each pixel.
+    ///
+    /// * `f` Function returning `true` for covered pixels.
+    ///
+    /// ### Mask pixels with a red tint
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgb8>::new().with_clear(4, 4);
+    /// r.set_pixel(2, 1, SRgb8::new(0xC0, 0x40, 0x40));
+    /// let mask = r.threshold_map(|p| p.red() > p.green().max(p.blue()));
+    /// assert_eq!(mask.pixel(2, 1), Mask8::new(0xFF));
+    /// assert_eq!(mask.pixel(0, 0), Mask8::new(0x00));
+    /// ```
+    pub fn threshold_map<F>(&self, mut f: F) -> Raster<Mask8>
+    where
+        F: FnMut(P) -> bool,
+    {
+        self.map(|p| {
+            let v = if f(p) { Ch8::MAX } else { Ch8::MIN };
+            Mask8::new(v)
+        })
+    }
+    /// Create a `Mask` raster from the alpha channel.
+    ///
+    /// * `C` `Channel` of the mask.
+    ///
+    /// Pixel formats with opaque alpha produce a fully covered mask.
+    ///
+    /// ### Get coverage of an RGBA raster
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgba8>::new().with_clear(2, 1);
+    /// r.set_pixel(1, 0, SRgba8::with_alpha(0x10, 0x20, 0x30, 0x80));
+    /// let m: Raster<Mask16> = r.extract_alpha();
+    /// assert_eq!(m.pixel(1, 0), Mask16::new(0x8080));
+    /// ```
+    pub fn extract_alpha<C>(&self) -> Raster<Mask<C>>
+    where
+        C: Channel + From<P::Chan>,
+    {
+        self.map(|p| Mask::new(p.alpha()))
+    }
+    /// Set the alpha channel from a `Mask` raster.
+    ///
+    /// * `mask` `Mask` raster with new alpha values.
+    ///
+    /// Other components are not changed, even for pixel formats with
+    /// [premultiplied](alpha/struct.Premultiplied.html) alpha.  Pixel formats
+    /// with opaque alpha are not changed at all.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    ///
+    /// ### Attach coverage to an RGBA raster
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SRgba8>::new()
+    ///     .with_color(2, 1, SRgba8::new(0x10, 0x20, 0x30));
+    /// let mut m = RasterBuilder::<Mask8>::new().with_clear(2, 1);
+    /// m.set_pixel(0, 0, Mask8::new(0x40));
+    /// r.set_alpha(&m);
+    /// assert_eq!(r.pixel(0, 0), SRgba8::with_alpha(0x10, 0x20, 0x30, 0x40));
+    /// assert_eq!(r.pixel(1, 0), SRgba8::with_alpha(0x10, 0x20, 0x30, 0x00));
+    /// ```
+    pub fn set_alpha<C>(&mut self, mask: &Raster<Mask<C>>)
+    where
+        C: Channel,
+        P::Chan: From<C>,
+    {
+        self.zip_with_in_place(mask, |p, m| p.set_alpha(m.alpha().into()))
+    }
+    /// Split into one `Gray` raster for each channel.
+    ///
+    /// Channels are in the same order as stored in the pixel format,
+    /// followed by alpha if the format has an alpha channel.  For example,
+    /// `SRgba8` is split into red, green, blue and alpha; `SBgr8` into blue,
+    /// green and red; and `Mask8` into just alpha.  Values are copied
+    /// unchanged, without converting premultiplied alpha.  Each `Gray` raster
+    /// has the same gamma mode as the pixel format.
+    ///
+    /// Use `RasterBuilder`::
+    /// [with_channels](struct.RasterBuilder.html#method.with_channels) to
+    /// merge them back.
+    ///
+    /// ### Shift the red channel
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_clear(16, 16);
+    /// let mut channels = r.split_channels();
+    /// assert_eq!(channels.len(), 3);
+    /// channels[0] = channels[0].scale_linear(17, 17).crop((1, 1, 16, 16));
+    /// let r = RasterBuilder::<SRgb8>::new().with_channels(&channels);
+    /// ```
+    pub fn split_channels(&self) -> Vec<Raster<ChanGray<P>>> {
+        let n = P::CHANNELS;
+        let chans = self.as_chan_slice();
+        (0..n)
+            .map(|i| {
+                let chans = chans.iter().skip(i).step_by(n);
+                let pixels: Vec<_> = chans.map(|c| Gray::new(*c)).collect();
+                let (w, h) = (self.width, self.height);
+                RasterBuilder::new().with_pixels(w, h, pixels)
+            })
+            .collect()
+    }
+    /// Create a new `Raster` by combining pixels with another `Raster`.
+    ///
+    /// * `other` `Raster` to combine with.
+    /// * `f` Function to combine each pair of pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    ///
+    /// ### Absolute difference of two rasters
+    /// ```
+    /// # use pix::*;
+    /// let a = RasterBuilder::<SGray8>::new().with_color(8, 8, 0x40.into());
+    /// let b = RasterBuilder::<SGray8>::new().with_color(8, 8, 0x50.into());
+    /// let d = a.zip_with(&b, |p, q| p.difference(q));
+    /// assert_eq!(d.pixel(0, 0), SGray8::new(0x10));
+    /// ```
+    pub fn zip_with<S, Q, F>(&self, other: &Raster<S>, mut f: F) -> Raster<Q>
+    where
+        S: Pixel,
+        Q: Pixel,
+        F: FnMut(P, S) -> Q,
+    {
+        self.assert_same_size(other);
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for (row, orow) in self.rows().zip(other.rows()) {
+            pixels.extend(row.iter().zip(orow).map(|(p, s)| f(*p, *s)));
+        }
+        RasterBuilder::new().with_pixels(self.width(), self.height(), pixels)
+    }
+    /// Combine pixels with another `Raster` in place.
+    ///
+    /// * `other` `Raster` to combine with.
+    /// * `f` Function to modify each pixel using the matching `other` pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the dimensions of the two `Raster`s are not equal.
+    pub fn zip_with_in_place<S, F>(&mut self, other: &Raster<S>, mut f: F)
+    where
+        S: Pixel,
+        F: FnMut(&mut P, S),
+    {
+        self.assert_same_size(other);
+        for (row, orow) in self.rows_mut().zip(other.rows()) {
+            for (p, s) in row.iter_mut().zip(orow) {
+                f(p, *s);
+            }
+        }
+    }
+    /// Check that another `Raster` has the same dimensions.
+    pub(crate) fn assert_same_size<S: Pixel>(&self, other: &Raster<S>) {
+        assert!(
+            self.width() == other.width() && self.height() == other.height(),
+            "raster size mismatch: {}x{} vs {}x{}",
+            self.width(),
+            self.height(),
+            other.width(),
+            other.height(),
+        );
+    }
     /// Get view of pixels as a slice.
     pub fn as_slice(&self) -> &[P] {
         &self.pixels
@@ -418,14 +2591,31 @@ impl<P: Pixel> Raster<P> {
         Self::u8_slice(&self.pixels[s..t])
     }
     /// Get view of a pixel slice as a `u8` slice.
+    #[cfg(feature = "bytemuck")]
+    fn u8_slice(pix: &[P]) -> &[u8] {
+        bytemuck::cast_slice(pix)
+    }
+    /// Get view of a pixel slice as a `u8` slice.
+    #[cfg(not(feature = "bytemuck"))]
     fn u8_slice(pix: &[P]) -> &[u8] {
         unsafe { pix.align_to::<u8>().1 }
     }
+    /// Get view of pixels as a slice of interleaved channel values.
+    pub(crate) fn as_chan_slice(&self) -> &[P::Chan] {
+        unsafe { self.pixels.align_to::<P::Chan>().1 }
+    }
+    /// Get view of pixels as a mutable slice of interleaved channel values.
+    pub(crate) fn as_chan_slice_mut(&mut self) -> &mut [P::Chan] {
+        unsafe { self.pixels.align_to_mut::<P::Chan>().1 }
+    }
     /// Get view of pixels as a `u8` slice.
     pub fn as_u8_slice(&self) -> &[u8] {
         Self::u8_slice(&self.pixels)
     }
     /// Get view of a pixel slice as a mutable `u8` slice.
+    ///
+    /// This cannot use `bytemuck::cast_slice_mut`, which requires every bit
+    /// pattern to be a valid pixel; floating-point channels are not.
     fn u8_slice_mut(pix: &mut [P]) -> &mut [u8] {
         unsafe { pix.align_to_mut::<u8>().1 }
     }
@@ -433,6 +2623,501 @@ impl<P: Pixel> Raster<P> {
     pub fn as_u8_slice_mut(&mut self) -> &mut [u8] {
         Self::u8_slice_mut(&mut self.pixels)
     }
+    /// Convert into a `u8` `Vec`.
+    ///
+    /// The pixel data is reused without copying, with the same byte order as
+    /// [as_u8_slice](struct.Raster.html#method.as_u8_slice).  The length is
+    /// `width` * `height` * `std::mem::size_of::<P>()`.
+    ///
+    /// ### Hand off to an encoder
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new()
+    ///     .with_color(2, 2, SRgb8::new(1, 2, 3));
+    /// let buf = r.into_u8_vec();
+    /// assert_eq!(buf, vec![1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3]);
+    /// ```
+    pub fn into_u8_vec(self) -> Vec<u8>
+    where
+        P: Pixel<Chan = Ch8>,
+    {
+        pixels_buffer::<P, u8>(self.pixels).into()
+    }
+    /// Convert into a `u16` `Vec`.
+    ///
+    /// The pixel data is reused without copying, in native-endian byte order.
+    /// The length is `width` * `height` * the number of channels.
+    pub fn into_u16_vec(self) -> Vec<u16>
+    where
+        P: Pixel<Chan = Ch16>,
+    {
+        pixels_buffer::<P, u16>(self.pixels).into()
+    }
+    /// Get pixel data as bytes, with channels in little-endian order.
+    ///
+    /// Unlike [as_u8_slice](struct.Raster.html#method.as_u8_slice), the
+    /// result is the same on any host.
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.ordered_bytes(cfg!(target_endian = "big"))
+    }
+    /// Get pixel data as bytes, with channels in big-endian order.
+    ///
+    /// ### Save 16-bit PNG data
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb16>::new()
+    ///     .with_color(1, 1, SRgb16::new(0x0102, 0x0304, 0x0506));
+    /// assert_eq!(r.to_be_bytes(), vec![1, 2, 3, 4, 5, 6]);
+    /// ```
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.ordered_bytes(cfg!(target_endian = "little"))
+    }
+    /// Create a `Raster` from a raw pointer to pixel data.
+    ///
+    /// Ownership of the pixel data is transferred to the `Raster`, which
+    /// frees it when dropped.  This is the inverse of
+    /// [into_raw_parts](struct.Raster.html#method.into_raw_parts).
+    ///
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `ptr` Pointer to first pixel.
+    /// * `len` Number of pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is not equal to `width` * `height`.  The pixel data
+    /// is not freed in this case.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` and `len` must describe a boxed slice of pixels allocated by the
+    /// global allocator, such as those returned by `into_raw_parts`, or a
+    /// `Vec` whose capacity is equal to its length.  After calling this, the
+    /// pixel data must not be used or freed through `ptr`.
+    ///
+    /// Pixel data allocated by another library (with `malloc`, `mmap`, etc.)
+    /// must not be passed here; borrow it as a
+    /// [RasterView](struct.RasterView.html) instead.
+    ///
+    /// ### Hand off pixel data and take it back
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new().with_clear(4, 3);
+    /// let (width, height, ptr, len) = r.into_raw_parts();
+    /// // ... pass the pointer through FFI
+    /// let r: Raster<SGray8> =
+    ///     unsafe { Raster::from_raw_parts(width, height, ptr, len) };
+    /// assert_eq!((r.width(), r.height()), (4, 3));
+    /// ```
+    #[must_use]
+    pub unsafe fn from_raw_parts(
+        width: u32,
+        height: u32,
+        ptr: *mut P,
+        len: usize,
+    ) -> Self {
+        match pixel_count::<P>(width, height) {
+            Ok(n) if n == len => (),
+            _ => panic!(
+                "pixel length {} does not match {}x{} raster",
+                len, width, height
+            ),
+        }
+        let pixels = std::ptr::slice_from_raw_parts_mut(ptr, len);
+        Raster {
+            width,
+            height,
+            pixels: Box::from_raw(pixels),
+        }
+    }
+    /// Decompose a `Raster` into its raw parts.
+    ///
+    /// Returns `(width, height, ptr, len)`.  The pixel data is leaked, so
+    /// the caller is responsible for freeing it, by passing the parts to
+    /// [from_raw_parts](struct.Raster.html#method.from_raw_parts).
+    #[must_use = "dropping the pointer leaks the pixel data"]
+    pub fn into_raw_parts(self) -> (u32, u32, *mut P, usize) {
+        let len = self.pixels.len();
+        let ptr = Box::into_raw(self.pixels) as *mut P;
+        (self.width, self.height, ptr, len)
+    }
+    /// Clamp floating-point channels copied from raw bytes into range.
+    ///
+    /// `NaN` and negative values become 0; values greater than 1 become 1.
+    #[cfg(feature = "serde")]
+    pub(crate) fn clamp_float_channels(&mut self) {
+        use std::any::Any;
+
+        if TypeId::of::<P::Chan>() == TypeId::of::<Ch8>()
+            || TypeId::of::<P::Chan>() == TypeId::of::<Ch16>()
+        {
+            return;
+        }
+        for chan in self.as_chan_slice_mut() {
+            let chan: &mut dyn Any = chan;
+            if let Some(c) = chan.downcast_mut::<Ch32>() {
+                *c = Ch32::new(f32::from(*c));
+            } else if let Some(c) = chan.downcast_mut::<Ch64>() {
+                *c = Ch64::new(f64::from(*c));
+            } else if let Some(c) = chan.downcast_mut::<Ch16f>() {
+                *c = Ch16f::from_bits(c.to_bits());
+            }
+        }
+    }
+    /// Get pixel data as bytes, optionally swapping channel byte order.
+    fn ordered_bytes(&self, swap: bool) -> Vec<u8> {
+        let mut bytes = self.as_u8_slice().to_vec();
+        if swap {
+            let n = std::mem::size_of::<P::Chan>();
+            for chan in bytes.chunks_exact_mut(n) {
+                chan.reverse();
+            }
+        }
+        bytes
+    }
+    /// Convert all pixels to a format with the same layout, in place.
+    ///
+    /// * `lut` Operation on 8-bit translucent pixels, using lookup tables.
+    /// * `f` Function to convert each pixel.
+    fn convert_in_place<Q, F>(
+        mut self,
+        lut: Option<fn(Ch8, Ch8) -> Ch8>,
+        mut f: F,
+    ) -> Raster<Q>
+    where
+        Q: Pixel,
+        F: FnMut(P) -> Q,
+    {
+        assert_eq!(std::mem::size_of::<P>(), std::mem::size_of::<Q>());
+        assert_eq!(std::mem::align_of::<P>(), std::mem::align_of::<Q>());
+        if let Some(op) = lut {
+            alpha_lut_u8(self.as_u8_slice_mut(), std::mem::size_of::<P>(), op);
+        }
+        let Raster {
+            width,
+            height,
+            pixels,
+        } = self;
+        let len = pixels.len();
+        let pixels = Box::into_raw(pixels);
+        let src = pixels as *mut P;
+        let dst = pixels as *mut Q;
+        // Safety: P and Q have the same size and alignment, and are both
+        //         `Copy`, so each pixel can be replaced in turn.
+        let pixels = unsafe {
+            for i in 0..len {
+                let p = std::ptr::read(src.add(i));
+                let q = if lut.is_some() {
+                    std::mem::transmute_copy::<P, Q>(&p)
+                } else {
+                    f(p)
+                };
+                std::ptr::write(dst.add(i), q);
+            }
+            Box::from_raw(pixels as *mut [Q])
+        };
+        Raster {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+impl<C, A, G> Raster<Rgb<C, A, Straight, G>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Premultiply all pixels with alpha, in place.
+    ///
+    /// The pixel buffer is reused for the premultiplied `Raster`.  Components
+    /// are multiplied in the gamma encoding of the pixel format.
+    ///
+    /// ### Premultiply before upload to a GPU
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgba8>::new()
+    ///     .with_color(2, 2, SRgba8::with_alpha(0xFF, 0x80, 0x00, 0x80));
+    /// let r = r.premultiply_in_place();
+    /// assert_eq!(r.pixel(0, 0), SRgba8p::with_alpha(0x80, 0x40, 0x00, 0x80));
+    /// ```
+    pub fn premultiply_in_place(self) -> Raster<Rgb<C, A, Premultiplied, G>> {
+        let lut = alpha_lut::<C, A>(|c, a| c.scale(a));
+        self.convert_in_place(lut, Rgb::premultiply)
+    }
+}
+
+impl<C, A, G> Raster<Rgb<C, A, Premultiplied, G>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Divide all pixels by alpha, in place.
+    ///
+    /// The pixel buffer is reused for the straight alpha `Raster`.  Pixels
+    /// with zero alpha have all components zero.
+    pub fn unpremultiply_in_place(self) -> Raster<Rgb<C, A, Straight, G>> {
+        let lut = alpha_lut::<C, A>(|c, a| c.div_scale(a));
+        self.convert_in_place(lut, Rgb::unpremultiply)
+    }
+}
+
+impl<C, A, G> Raster<Gray<C, A, Straight, G>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Premultiply all pixels with alpha, in place.
+    ///
+    /// The pixel buffer is reused for the premultiplied `Raster`.  Values are
+    /// multiplied in the gamma encoding of the pixel format.
+    pub fn premultiply_in_place(self) -> Raster<Gray<C, A, Premultiplied, G>> {
+        let lut = alpha_lut::<C, A>(|c, a| c.scale(a));
+        self.convert_in_place(lut, Gray::premultiply)
+    }
+}
+
+impl<C, A, G> Raster<Gray<C, A, Premultiplied, G>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Divide all pixels by alpha, in place.
+    ///
+    /// The pixel buffer is reused for the straight alpha `Raster`.  Pixels
+    /// with zero alpha have a zero value.
+    pub fn unpremultiply_in_place(self) -> Raster<Gray<C, A, Straight, G>> {
+        let lut = alpha_lut::<C, A>(|c, a| c.div_scale(a));
+        self.convert_in_place(lut, Gray::unpremultiply)
+    }
+}
+
+impl<C, A, M, G> Raster<Rgb<C, A, M, G>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    /// Swizzle all pixels to [Bgr](struct.Bgr.html) order, in place.
+    ///
+    /// The pixel buffer is reused for the `Bgr` raster.
+    ///
+    /// ### Swizzle before handing to Cairo
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgba8p>::new()
+    ///     .with_color(1, 1, SRgba8p::with_alpha(0x10, 0x20, 0x30, 0x40));
+    /// let r = r.into_bgr();
+    /// assert_eq!(r.as_u8_slice(), &[0x30, 0x20, 0x10, 0x40]);
+    /// ```
+    pub fn into_bgr(self) -> Raster<Bgr<C, A, M, G>> {
+        self.convert_in_place(None, Bgr::from)
+    }
+}
+
+impl<C, A, M, G> Raster<Bgr<C, A, M, G>>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+{
+    /// Swizzle all pixels to [Rgb](struct.Rgb.html) order, in place.
+    ///
+    /// The pixel buffer is reused for the `Rgb` raster.
+    pub fn into_rgb(self) -> Raster<Rgb<C, A, M, G>> {
+        self.convert_in_place(None, Rgb::from)
+    }
+}
+
+/// Get source index of pixel center when scaling from `src` to `dst` pixels.
+fn nearest_index(i: u32, src: u32, dst: u32) -> u32 {
+    let i = (2 * u64::from(i) + 1) * u64::from(src) / (2 * u64::from(dst));
+    i as u32
+}
+
+/// Get source indices and weight of pixel center when scaling from `src` to
+/// `dst` pixels with bilinear filtering.
+fn linear_index(i: u32, src: u32, dst: u32) -> (u32, u32, f32) {
+    let v = (f64::from(i) + 0.5) * f64::from(src) / f64::from(dst) - 0.5;
+    linear_pair(v, src)
+}
+
+/// Get indices and weight of the two pixels nearest a position.
+///
+/// * `v` Position, where pixel centers are integers.
+/// * `len` Number of pixels (must not be zero).
+///
+/// Positions beyond the edges are clamped.
+fn linear_pair(v: f64, len: u32) -> (u32, u32, f32) {
+    let v = if v.is_nan() {
+        0.0
+    } else {
+        v.clamp(0.0, f64::from(len - 1))
+    };
+    let i0 = v.floor() as u32;
+    let i1 = (i0 + 1).min(len - 1);
+    (i0, i1, (v - f64::from(i0)) as f32)
+}
+
+/// Get index of the pixel containing a position, clamped to the edges.
+///
+/// * `v` Position, where pixel edges are integers.
+/// * `len` Number of pixels (must not be zero).
+fn nearest_pos(v: f32, len: u32) -> u32 {
+    // NaN and negative values convert to 0
+    (v.floor() as u32).min(len - 1)
+}
+
+/// `Gray` pixel format for one channel of another format
+type ChanGray<P> = Gray<
+    <P as ColorModel>::Chan,
+    Opaque<<P as ColorModel>::Chan>,
+    Straight,
+    <P as Pixel>::Gamma,
+>;
+
+/// Get the number of pixels in a `Raster`, checking for overflow.
+pub(crate) fn pixel_count<P: Pixel>(
+    width: u32,
+    height: u32,
+) -> Result<usize, RasterError> {
+    let len = u64::from(width) * u64::from(height);
+    let bytes = len.checked_mul(std::mem::size_of::<P>() as u64);
+    match (usize::try_from(len), bytes) {
+        (Ok(len), Some(bytes)) if bytes <= isize::MAX as u64 => Ok(len),
+        _ => Err(RasterError::Overflow),
+    }
+}
+
+/// Check that a buffer can hold the pixels of a `Raster`.
+///
+/// Returns the number of pixels.
+fn check_buffer<P: Pixel, T>(
+    width: u32,
+    height: u32,
+    buffer: &[T],
+) -> Result<usize, RasterError> {
+    let len = pixel_count::<P>(width, height)?;
+    let expected = len * std::mem::size_of::<P>() / std::mem::size_of::<T>();
+    if buffer.len() != expected {
+        let found = buffer.len();
+        return Err(RasterError::LengthMismatch { expected, found });
+    }
+    Ok(len)
+}
+
+/// Convert a checked buffer to pixels.
+///
+/// The buffer allocation is reused when `P` has the same alignment as `T`,
+/// since the allocation layout is then identical.  Otherwise, pixels are
+/// copied to a new allocation.
+fn buffer_pixels<P: Pixel, T: Copy>(buffer: Box<[T]>, len: usize) -> Box<[P]> {
+    debug_assert_eq!(
+        len * std::mem::size_of::<P>(),
+        buffer.len() * std::mem::size_of::<T>()
+    );
+    if std::mem::align_of::<P>() == std::mem::align_of::<T>() {
+        let ptr = Box::into_raw(buffer) as *mut P;
+        unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)) }
+    } else {
+        let ptr = buffer.as_ptr() as *const P;
+        (0..len).map(|i| unsafe { ptr.add(i).read_unaligned() }).collect()
+    }
+}
+
+/// Convert boxed pixels to a buffer of another type.
+///
+/// `T` must be `P` or the primitive type of its channels.  The allocation is
+/// reused if `T` has the same alignment as `P`, which is the case when each
+/// channel is a `T`.  Otherwise, values are copied to a new allocation.
+pub(crate) fn pixels_buffer<P: Pixel, T: Copy>(pixels: Box<[P]>) -> Box<[T]> {
+    debug_assert_eq!(std::mem::size_of::<P>() % std::mem::size_of::<T>(), 0);
+    let len =
+        pixels.len() * std::mem::size_of::<P>() / std::mem::size_of::<T>();
+    if std::mem::align_of::<P>() == std::mem::align_of::<T>() {
+        let ptr = Box::into_raw(pixels) as *mut T;
+        unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(ptr, len)) }
+    } else {
+        let ptr = pixels.as_ptr() as *const T;
+        (0..len).map(|i| unsafe { ptr.add(i).read_unaligned() }).collect()
+    }
+}
+
+/// Convert a row of pixels to another format.
+///
+/// * `dst` Destination pixels.
+/// * `src` Source pixels (same length as `dst`).
+pub(crate) fn convert_row<S, P>(dst: &mut [P], src: &[S])
+where
+    S: Pixel,
+    P: Pixel,
+    P::Chan: From<S::Chan>,
+{
+    if TypeId::of::<S>() == TypeId::of::<P>() {
+        // Same pixel format -- no conversion needed
+        let src = unsafe { &*(src as *const [S] as *const [P]) };
+        dst.copy_from_slice(src);
+        return;
+    }
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    {
+        if crate::simd::convert_row(dst, src) {
+            return;
+        }
+    }
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d = s.convert();
+    }
+}
+
+/// Get an 8-bit lookup table operation, if channels are 8-bit translucent.
+fn alpha_lut<C, A>(op: fn(Ch8, Ch8) -> Ch8) -> Option<fn(Ch8, Ch8) -> Ch8>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    if TypeId::of::<A>() == TypeId::of::<Translucent<Ch8>>() {
+        Some(op)
+    } else {
+        None
+    }
+}
+
+/// Apply an alpha operation to 8-bit pixels, using lookup tables.
+///
+/// * `buf` Pixel data, with alpha in the last byte of each pixel.
+/// * `stride` Number of bytes per pixel.
+/// * `op` Operation on each component with alpha.
+///
+/// A table is built for each distinct alpha value, when first needed.
+fn alpha_lut_u8(buf: &mut [u8], stride: usize, op: fn(Ch8, Ch8) -> Ch8) {
+    let mut luts = vec![[0; 256]; 256];
+    let mut built = [false; 256];
+    for pix in buf.chunks_exact_mut(stride) {
+        let (comp, alpha) = pix.split_at_mut(stride - 1);
+        let a = usize::from(alpha[0]);
+        // Opaque pixels are unchanged by both operations
+        if a == 255 {
+            continue;
+        }
+        let lut = &mut luts[a];
+        if !built[a] {
+            for (i, v) in lut.iter_mut().enumerate() {
+                *v = op(Ch8::new(i as u8), Ch8::new(a as u8)).into();
+            }
+            built[a] = true;
+        }
+        for c in comp.iter_mut() {
+            *c = lut[usize::from(*c)];
+        }
+    }
 }
 
 impl<'a, P: Pixel> RasterIter<'a, P> {
@@ -440,18 +3125,22 @@ impl<'a, P: Pixel> RasterIter<'a, P> {
     ///
     /// * `region` Region of pixels to iterate.
     fn new(raster: &'a Raster<P>, region: Region) -> Self {
+        let region = region.intersection(raster.region());
         let y = u32::try_from(region.y).unwrap_or(0);
-        let bottom = u32::try_from(region.bottom()).unwrap_or(0);
         let x = u32::try_from(region.x).unwrap_or(0);
-        let right = u32::try_from(region.right()).unwrap_or(0);
-        let left = x;
+        // Empty regions have zero width and height, so `len` is 0
+        let len = region.width as usize * region.height as usize;
+        let right = x + region.width;
+        let end_y = (y + region.height).saturating_sub(1);
         RasterIter {
             raster,
-            left,
+            left: x,
             right,
-            bottom,
             x,
             y,
+            end_x: right,
+            end_y,
+            len,
         }
     }
 }
@@ -460,17 +3149,166 @@ impl<'a, P: Pixel> Iterator for RasterIter<'a, P> {
     type Item = P;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let p = self.raster.pixel(self.x, self.y);
+        self.len -= 1;
+        self.x += 1;
         if self.x >= self.right {
             self.x = self.left;
             self.y += 1;
-            if self.y >= self.bottom {
-                return None;
-            }
         }
-        let p = self.raster.pixel(self.x, self.y);
-        self.x += 1;
         Some(p)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, P: Pixel> DoubleEndedIterator for RasterIter<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        if self.end_x <= self.left {
+            self.end_x = self.right;
+            self.end_y -= 1;
+        }
+        self.end_x -= 1;
+        self.len -= 1;
+        Some(self.raster.pixel(self.end_x, self.end_y))
+    }
+}
+
+impl<'a, P: Pixel> ExactSizeIterator for RasterIter<'a, P> {}
+
+impl<'a, P: Pixel> FusedIterator for RasterIter<'a, P> {}
+
+impl<'a, P: Pixel> RasterIterMut<'a, P> {
+    /// Create a new mutable `Raster` pixel `Iterator`.
+    ///
+    /// * `region` Region of pixels to iterate.
+    fn new(raster: &'a mut Raster<P>, region: Region) -> Self {
+        let (pixels, left, right) = raster.region_pixels(region);
+        let width = (raster.width() as usize).max(1);
+        let rows = raster.pixels[pixels].chunks_exact_mut(width);
+        let row = [].iter_mut();
+        RasterIterMut {
+            rows,
+            row,
+            left,
+            right,
+        }
+    }
+}
+
+impl<'a, P: Pixel> Iterator for RasterIterMut<'a, P> {
+    type Item = &'a mut P;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(p) = self.row.next() {
+                return Some(p);
+            }
+            let row = self.rows.next()?;
+            self.row = row[self.left..self.right].iter_mut();
+        }
+    }
+}
+
+/// Mutable `Iterator` of rows, for
+/// [rows_mut](struct.Raster.html#method.rows_mut).
+enum RowsMut<'a, P> {
+    /// Rows of a `Raster` with non-zero width
+    Rows(ChunksExactMut<'a, P>),
+    /// Remaining rows of a `Raster` with zero width
+    Empty(Range<usize>),
+}
+
+impl<'a, P> Iterator for RowsMut<'a, P> {
+    type Item = &'a mut [P];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            RowsMut::Rows(rows) => rows.next(),
+            RowsMut::Empty(rows) => rows.next().map(|_| &mut [][..]),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            RowsMut::Rows(rows) => rows.size_hint(),
+            RowsMut::Empty(rows) => rows.size_hint(),
+        }
+    }
+}
+
+impl<'a, P> DoubleEndedIterator for RowsMut<'a, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            RowsMut::Rows(rows) => rows.next_back(),
+            RowsMut::Empty(rows) => rows.next_back().map(|_| &mut [][..]),
+        }
+    }
+}
+
+impl<'a, P> ExactSizeIterator for RowsMut<'a, P> {}
+
+/// Clip a pair of regions to the bounds of their rasters.
+///
+/// * `to` Destination region.
+/// * `dst` Bounds of destination raster.
+/// * `from` Source region.
+/// * `src` Bounds of source raster.
+///
+/// Returns regions of equal size, which are empty if `None`.
+fn clip_pair(
+    to: Region,
+    dst: Region,
+    from: Region,
+    src: Region,
+) -> Option<(Region, Region)> {
+    let (dx, sx, width) = clip_span(
+        to.x,
+        dst.width,
+        from.x,
+        src.width,
+        to.width.min(from.width),
+    )?;
+    let (dy, sy, height) = clip_span(
+        to.y,
+        dst.height,
+        from.y,
+        src.height,
+        to.height.min(from.height),
+    )?;
+    Some((
+        Region::new(dx, dy, width, height),
+        Region::new(sx, sy, width, height),
+    ))
+}
+
+/// Clip a span along one axis of a pair of rasters.
+fn clip_span(
+    d: i32,
+    dlen: u32,
+    s: i32,
+    slen: u32,
+    len: u32,
+) -> Option<(i32, i32, u32)> {
+    let (d, s) = (i64::from(d), i64::from(s));
+    let shift = 0.max(-d).max(-s);
+    let (d, s) = (d + shift, s + shift);
+    let len = (i64::from(len) - shift)
+        .min(i64::from(dlen) - d)
+        .min(i64::from(slen) - s);
+    if len > 0 {
+        Some((d as i32, s as i32, len as u32))
+    } else {
+        None
+    }
 }
 
 impl From<(i32, i32, u32, u32)> for Region {
@@ -489,6 +3327,22 @@ impl Region {
             height,
         }
     }
+    /// Get left side
+    pub fn x(self) -> i32 {
+        self.x
+    }
+    /// Get top side
+    pub fn y(self) -> i32 {
+        self.y
+    }
+    /// Get width
+    pub fn width(self) -> u32 {
+        self.width
+    }
+    /// Get height
+    pub fn height(self) -> u32 {
+        self.height
+    }
     /// Get intersection with another `Region`
     pub fn intersection<R>(self, rhs: R) -> Self
     where
@@ -497,29 +3351,102 @@ impl Region {
         let rhs = rhs.into();
         let x0 = self.x.max(rhs.x);
         let x1 = self.right().min(rhs.right());
-        let w = (x1 - x0) as u32;
         let y0 = self.y.max(rhs.y);
         let y1 = self.bottom().min(rhs.bottom());
-        let h = (y1 - y0) as u32;
+        if x1 > i64::from(x0) && y1 > i64::from(y0) {
+            let w = (x1 - i64::from(x0)) as u32;
+            let h = (y1 - i64::from(y0)) as u32;
+            Region::new(x0, y0, w, h)
+        } else {
+            // Disjoint regions have an empty intersection
+            Region::new(x0, y0, 0, 0)
+        }
+    }
+    /// Get union with another `Region`
+    ///
+    /// The result is the smallest `Region` containing both.  An empty region
+    /// does not contribute to the union.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let r = Region::new(0, 0, 5, 5).union((10, 2, 5, 5));
+    /// assert_eq!(r, Region::new(0, 0, 15, 7));
+    /// ```
+    pub fn union<R>(self, rhs: R) -> Self
+    where
+        R: Into<Self>,
+    {
+        let rhs = rhs.into();
+        if rhs.is_empty() {
+            return self;
+        }
+        if self.is_empty() {
+            return rhs;
+        }
+        let x0 = self.x.min(rhs.x);
+        let x1 = self.right().max(rhs.right());
+        let w = (x1 - i64::from(x0)).min(u32::MAX.into()) as u32;
+        let y0 = self.y.min(rhs.y);
+        let y1 = self.bottom().max(rhs.bottom());
+        let h = (y1 - i64::from(y0)).min(u32::MAX.into()) as u32;
         Region::new(x0, y0, w, h)
     }
+    /// Check whether another `Region` overlaps this one
+    ///
+    /// Regions which only touch at an edge do not intersect.
+    pub fn intersects<R>(self, rhs: R) -> bool
+    where
+        R: Into<Self>,
+    {
+        !self.intersection(rhs).is_empty()
+    }
+    /// Get an `Iterator` of tiles covering the region.
+    ///
+    /// * `tw` Tile width.
+    /// * `th` Tile height.
+    ///
+    /// Tiles do not overlap, and are yielded from left to right, then top to
+    /// bottom, starting at the top-left corner of the region.  Tiles on the
+    /// right and bottom edges are clipped to the region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tw` or `th` is zero.
+    ///
+    /// ```
+    /// # use pix::*;
+    /// let mut tiles = Region::new(10, 20, 5, 3).tiles(4, 4);
+    /// assert_eq!(tiles.next(), Some(Region::new(10, 20, 4, 3)));
+    /// assert_eq!(tiles.next(), Some(Region::new(14, 20, 1, 3)));
+    /// assert_eq!(tiles.next(), None);
+    /// ```
+    pub fn tiles(self, tw: u32, th: u32) -> impl Iterator<Item = Region> {
+        assert!(tw > 0 && th > 0, "tile size must be non-zero");
+        let cols = self.width.div_ceil(tw);
+        let rows = self.height.div_ceil(th);
+        (0..rows).flat_map(move |row| {
+            (0..cols).filter_map(move |col| {
+                let (dx, dy) = (col * tw, row * th);
+                let w = (self.width - dx).min(tw);
+                let h = (self.height - dy).min(th);
+                // Skip tiles with positions beyond the range of i32
+                let x = i32::try_from(i64::from(self.x) + i64::from(dx));
+                let y = i32::try_from(i64::from(self.y) + i64::from(dy));
+                Some(Region::new(x.ok()?, y.ok()?, w, h))
+            })
+        })
+    }
+    /// Check if the region is empty
+    fn is_empty(self) -> bool {
+        self.width == 0 || self.height == 0
+    }
     /// Get right side
-    fn right(self) -> i32 {
-        let x = i64::from(self.x) + i64::from(self.width);
-        if x < std::i32::MAX.into() {
-            x as i32
-        } else {
-            self.x
-        }
+    fn right(self) -> i64 {
+        i64::from(self.x) + i64::from(self.width)
     }
     /// Get bottom side
-    fn bottom(self) -> i32 {
-        let y = i64::from(self.y) + i64::from(self.height);
-        if y < std::i32::MAX.into() {
-            y as i32
-        } else {
-            self.y
-        }
+    fn bottom(self) -> i64 {
+        i64::from(self.y) + i64::from(self.height)
     }
 }
 
@@ -638,8 +3565,7 @@ mod test {
             0x02,0x20,0x06,0x60, 0x44,0x44,0xFF,0xFF, 0x44,0x44,0xFF,0xFF,
             0x03,0x30,0x07,0x70, 0x0F,0xE0,0x0D,0xC0, 0x0B,0xA0,0x09,0x80,
         ];
-        // FIXME: this will fail on big-endian archs
-        assert_eq!(r.as_u8_slice(), &v[..]);
+        assert_eq!(r.to_le_bytes(), v);
     }
     #[test]
     fn gray_to_rgb() {
@@ -741,6 +3667,675 @@ mod test {
         let _ = RasterBuilder::<Mask32>::new().with_raster(&r);
     }
     #[test]
+    fn region_iter_mut() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+        for p in r.region_iter_mut((1, 1, 2, 2)) {
+            *p = SGray8::new(0x11);
+        }
+        for p in r.region_iter_mut((-2, 3, 3, 5)) {
+            *p = SGray8::new(0x22);
+        }
+        for p in r.region_iter_mut((3, -1, 10, 2)) {
+            *p = SGray8::new(0x33);
+        }
+        assert_eq!(r.region_iter_mut((5, 0, 2, 2)).count(), 0);
+        assert_eq!(r.region_iter_mut((0, -4, 2, 2)).count(), 0);
+        assert_eq!(r.region_iter_mut((1, 1, 0, 2)).count(), 0);
+        let v = vec![
+            0x00, 0x00, 0x00, 0x33,
+            0x00, 0x11, 0x11, 0x00,
+            0x00, 0x11, 0x11, 0x00,
+            0x22, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn convert() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(40, 30, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 0x80, 0xFF - x as u8)
+        });
+        let c: Raster<SRgba16> = r.convert();
+        assert_eq!(c, RasterBuilder::<SRgba16>::new().with_raster(&r));
+        assert_eq!(r.clone().into_converted::<SRgba16>(), c);
+        // Same layout is converted in place
+        let copy = r.clone();
+        let ptr = copy.as_slice().as_ptr() as *const u8;
+        let b: Raster<SBgra8> = copy.into_converted();
+        assert_eq!(b.as_u8_slice().as_ptr(), ptr);
+        assert_eq!(b, r.convert());
+        assert_eq!(b.pixel(39, 29), SBgra8::with_alpha(39, 29, 0x80, 0xD8));
+        let p: Raster<SRgba8p> = r.clone().into_converted();
+        assert_eq!(p, r.convert());
+        let s: Raster<SRgba8> = r.clone().into_converted();
+        assert_eq!(s, r);
+    }
+    #[test]
+    fn with_iter() {
+        let it = (0..6).map(|v| SGray8::new(v * 10));
+        let r = RasterBuilder::new().with_iter(3, 2, it);
+        assert_eq!(r.as_u8_slice(), &[0, 10, 20, 30, 40, 50]);
+        let r = RasterBuilder::<SGray8>::new().with_iter(0, 5, vec![]);
+        assert_eq!((r.width(), r.height()), (0, 5));
+        let mismatch = |expected, found| {
+            Err(RasterError::LengthMismatch { expected, found })
+        };
+        // Too short, with and without an exact size hint
+        let r = RasterBuilder::<SGray8>::new()
+            .try_with_iter(3, 2, vec![SGray8::new(1); 5]);
+        assert_eq!(r, mismatch(6, 5));
+        let it = (0..5).map(SGray8::new).filter(|_| true);
+        let r = RasterBuilder::new().try_with_iter(3, 2, it);
+        assert_eq!(r, mismatch(6, 5));
+        // Too long, with and without an exact size hint
+        let r = RasterBuilder::<SGray8>::new()
+            .try_with_iter(3, 2, vec![SGray8::new(1); 8]);
+        assert_eq!(r, mismatch(6, 8));
+        let it = (0..8).map(SGray8::new).filter(|_| true);
+        let r = RasterBuilder::new().try_with_iter(3, 2, it);
+        assert_eq!(r, mismatch(6, 7));
+        let r = RasterBuilder::new().try_with_iter(3, 2, SGray8::new(1));
+        assert_eq!(r, mismatch(6, 7));
+        // Too short for huge dimensions, without an upper size hint
+        let it = (0..5).map(|_| SRgba32::default());
+        let it = it.chain(std::iter::from_fn(|| None));
+        let r = RasterBuilder::new().try_with_iter(u32::MAX, 0xFFFF, it);
+        let e = RasterError::LengthMismatch {
+            expected: 0xFFFF_FFFF * 0xFFFF,
+            found: 5,
+        };
+        assert_eq!(r.err(), Some(e));
+    }
+    #[test]
+    #[should_panic(expected = "pixel data length 2 does not match expected 4")]
+    fn with_iter_short() {
+        let it = vec![SGray8::new(0); 2];
+        let _r = RasterBuilder::new().with_iter(2, 2, it);
+    }
+    #[test]
+    fn into_iter() {
+        let mut r = RasterBuilder::<SGray8>::new()
+            .with_fn(3, 2, |x, y| SGray8::new((y * 10 + x) as u8));
+        let mut v = vec![];
+        for p in &r {
+            v.push(u8::from(p.value()));
+        }
+        assert_eq!(v, [0, 1, 2, 10, 11, 12]);
+        assert_eq!(r.pixels().len(), 6);
+        for p in &mut r {
+            *p = SGray8::new(u8::from(p.value()) + 1);
+        }
+        r.pixels_mut().rev().take(1).for_each(|p| *p = SGray8::new(0));
+        let ptr = r.as_slice().as_ptr();
+        let it = r.into_iter();
+        // Pixels are not re-allocated
+        assert_eq!(it.as_slice().as_ptr(), ptr);
+        let v: Vec<u8> = it.map(|p| u8::from(p.value())).collect();
+        assert_eq!(v, [1, 2, 3, 11, 12, 0]);
+    }
+    #[test]
+    fn region_iter_sizes() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(5, 4, |x, y| SGray8::new((y * 10 + x) as u8));
+        fn v(it: impl Iterator<Item = SGray8>) -> Vec<u8> {
+            it.map(|p| u8::from(p.value())).collect()
+        }
+        let mut it = r.region_iter((3, 1, 4, 2));
+        assert_eq!(it.size_hint(), (4, Some(4)));
+        assert_eq!(it.len(), 4);
+        it.next();
+        assert_eq!(it.len(), 3);
+        assert_eq!(v(it.rev()), [24, 23, 14]);
+        let reg = (-1, -1, 3, 4);
+        assert_eq!(v(r.region_iter(reg).rev()), [21, 20, 11, 10, 1, 0]);
+        let mut it = r.region_iter(reg);
+        assert_eq!(it.next_back(), Some(SGray8::new(21)));
+        assert_eq!(it.next(), Some(SGray8::new(0)));
+        assert_eq!(it.next_back(), Some(SGray8::new(20)));
+        assert_eq!(it.len(), 3);
+        assert_eq!(v(it), [1, 10, 11]);
+        assert_eq!(r.region_iter(r.region()).rev().count(), 20);
+        // Empty regions, including zero width with nonzero height
+        for reg in [(1, 1, 0, 3), (2, 0, 3, 0), (5, 0, 2, 2), (0, -3, 2, 2)]
+            .iter()
+        {
+            let mut it = r.region_iter(*reg);
+            assert_eq!(it.len(), 0);
+            assert_eq!(it.next(), None);
+            assert_eq!(it.next(), None);
+            assert_eq!(it.next_back(), None);
+            assert_eq!(it.len(), 0);
+        }
+    }
+    #[test]
+    fn region_iter_enumerated() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(5, 4);
+        for (i, p) in r.as_slice_mut().iter_mut().enumerate() {
+            *p = SGray8::new(i as u8);
+        }
+        for reg in &[(1, 1, 3, 2), (-2, -1, 4, 4), (3, 2, 9, 9), (6, 0, 2, 2)] {
+            let e: Vec<_> = r.region_iter_enumerated(*reg).collect();
+            let p: Vec<_> = r.region_iter(*reg).collect();
+            assert_eq!(e.len(), p.len());
+            for ((x, y, pe), pi) in e.into_iter().zip(p) {
+                assert_eq!(pe, pi);
+                assert_eq!(pe, r.pixel(x, y));
+            }
+        }
+        let e: Vec<_> = r
+            .region_iter_enumerated((3, 2, 9, 9))
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(e, vec![(3, 2), (4, 2), (3, 3), (4, 3)]);
+    }
+    #[test]
+    fn with_fn() {
+        let mut calls = 0;
+        let r = RasterBuilder::<SGray8>::new().with_fn(3, 2, |x, y| {
+            assert_eq!(calls, y * 3 + x);
+            calls += 1;
+            SGray8::new((y * 0x10 + x) as u8)
+        });
+        assert_eq!(calls, 6);
+        let v = vec![
+            SGray8::new(0x00), SGray8::new(0x01), SGray8::new(0x02),
+            SGray8::new(0x10), SGray8::new(0x11), SGray8::new(0x12),
+        ];
+        let p = RasterBuilder::<SGray8>::new().with_pixels(3, 2, v);
+        assert_eq!(r.as_slice(), p.as_slice());
+    }
+    #[test]
+    fn set_region_fn() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 3);
+        r.set_region_fn((-1, 1, 3, 5), |x, y| {
+            SGray8::new((y * 0x10 + x) as u8)
+        });
+        r.set_region_fn((4, 0, 3, 3), |_, _| unreachable!());
+        let v = vec![
+            0x00, 0x00, 0x00, 0x00,
+            0x10, 0x11, 0x00, 0x00,
+            0x20, 0x21, 0x00, 0x00,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn with_raster_same() {
+        let r = RasterBuilder::<Hsv8>::new().with_u8_buffer(
+            4,
+            1,
+            vec![0x10, 0x80, 0x40, 0xFF, 0x00, 0xFF, 0x55, 0x01, 0xC0, 3, 2, 1],
+        );
+        let s = RasterBuilder::<Hsv8>::new().with_raster(&r);
+        assert_eq!(r, s);
+        let mut t = RasterBuilder::<Hsv8>::new().with_clear(4, 1);
+        t.set_region(t.region(), r.region_iter(r.region()));
+        assert_eq!(r, t);
+    }
+
+    #[test]
+    fn threshold() {
+        let r = RasterBuilder::<SRgba16>::new().with_fn(3, 2, |x, y| {
+            let v = (x + y * 3) as u16 * 0x3333;
+            SRgba16::with_alpha(v, v / 2, 0, 0x1234)
+        });
+        let m = r.threshold(Ch16::new(0x6666));
+        assert_eq!(m.as_u8_slice(), &[0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        let m = r.threshold(Ch16::MIN);
+        assert!(m.as_u8_slice().iter().all(|v| *v == 0xFF));
+        let m = r.threshold(Ch16::MAX);
+        assert!(m.as_u8_slice().iter().all(|v| *v == 0));
+        let m = r.threshold_map(|p| p.alpha() > Ch16::new(0x2000));
+        assert!(m.as_u8_slice().iter().all(|v| *v == 0));
+        let r = RasterBuilder::<Rgba8p>::new().with_u8_buffer(
+            2,
+            1,
+            vec![0x40, 0x40, 0x40, 0x80, 0x20, 0x20, 0x20, 0x80],
+        );
+        let m = r.threshold(Ch8::new(0x60));
+        assert_eq!(m.as_u8_slice(), &[0xFF, 0x00]);
+        let r = RasterBuilder::<SGray8>::new().with_u8_buffer(1, 1, vec![0xFF]);
+        assert_eq!(r.threshold(Ch8::MAX).as_u8_slice(), &[0x00]);
+    }
+
+    #[test]
+    fn nonzero_region() {
+        let mut r = RasterBuilder::<SRgba8>::new().with_clear(8, 6);
+        assert_eq!(r.nonzero_region(), None);
+        r.set_pixel(5, 1, SRgba8::with_alpha(0, 0, 0, 0x10));
+        assert_eq!(r.nonzero_region(), Some(Region::new(5, 1, 1, 1)));
+        r.set_pixel(2, 4, SRgba8::with_alpha(0, 0, 0, 0x80));
+        assert_eq!(r.nonzero_region(), Some(Region::new(2, 1, 4, 4)));
+        r.set_pixel(7, 3, SRgba8::with_alpha(0, 0, 0, 0x01));
+        assert_eq!(r.nonzero_region(), Some(Region::new(2, 1, 6, 4)));
+        let reg = r.nonzero_region_above(Ch8::new(0x10));
+        assert_eq!(reg, Some(Region::new(2, 4, 1, 1)));
+        assert_eq!(r.nonzero_region_above(Ch8::MAX), None);
+        let r = RasterBuilder::<SRgb8>::new().with_clear(3, 2);
+        assert_eq!(r.nonzero_region(), Some(r.region()));
+        let r = RasterBuilder::<Mask8>::new().with_clear(0, 0);
+        assert_eq!(r.nonzero_region(), None);
+    }
+
+    #[test]
+    fn alpha_mask() {
+        let mut r = RasterBuilder::<SRgba16p>::new().with_fn(3, 1, |x, _| {
+            SRgba16p::with_alpha(0x1000, 0x2000, 0x3000, 0x4000 * x as u16)
+        });
+        let m: Raster<Mask8> = r.extract_alpha();
+        assert_eq!(m.as_u8_slice(), &[0x00, 0x40, 0x80]);
+        let m = m.mask_not();
+        r.set_alpha(&m);
+        let rgb = |a| SRgba16p::with_alpha(0x1000, 0x2000, 0x3000, a);
+        assert_eq!(r.pixel(0, 0), rgb(0xFFFF));
+        assert_eq!(r.pixel(2, 0), rgb(0x7F7F));
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(3, 1);
+        r.set_alpha(&m);
+        assert_eq!(r.as_u8_slice(), &[0; 9]);
+        let m: Raster<Mask32> = r.extract_alpha();
+        assert_eq!(m.pixel(1, 0), Mask32::new(1.0));
+    }
+
+    #[test]
+    fn split_channels() {
+        let r = RasterBuilder::<SBgra8>::new().with_u8_buffer(
+            3,
+            2,
+            (0..24).collect::<Vec<u8>>(),
+        );
+        let channels = r.split_channels();
+        assert_eq!(channels.len(), 4);
+        assert_eq!(channels[0].as_u8_slice(), &[0, 4, 8, 12, 16, 20]);
+        assert_eq!(channels[3].as_u8_slice(), &[3, 7, 11, 15, 19, 23]);
+        let s = RasterBuilder::<SBgra8>::new().with_channels(&channels);
+        assert_eq!(r.as_u8_slice(), s.as_u8_slice());
+        let r = RasterBuilder::<Rgb16>::new()
+            .with_fn(5, 7, |x, y| Rgb16::new(x as u16, y as u16, 0xFFFF));
+        let channels = r.split_channels();
+        assert_eq!(channels.len(), 3);
+        let s = RasterBuilder::<Rgb16>::new().with_channels(&channels);
+        assert_eq!(r, s);
+        let m = RasterBuilder::<Mask8>::new().with_clear(2, 2);
+        assert_eq!(m.split_channels().len(), 1);
+    }
+
+    #[test]
+    fn try_with_channels() {
+        let g = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+        let h = RasterBuilder::<SGray8>::new().with_clear(4, 3);
+        let r = RasterBuilder::<SRgb8>::new()
+            .try_with_channels(&[g.clone(), g.clone(), h]);
+        assert_eq!(r, Err(RasterError::SizeMismatch));
+        let r = RasterBuilder::<SRgb8>::new().try_with_channels(&[g]);
+        let err = RasterError::ChannelMismatch {
+            expected: 3,
+            found: 1,
+        };
+        assert_eq!(r, Err(err));
+        let msg = "channel count 1 does not match expected 3";
+        assert_eq!(err.to_string(), msg);
+    }
+
+    #[test]
+    fn fill_region() {
+        let clr = SRgba8::with_alpha(0x40, 0x80, 0xC0, 0x80);
+        for reg in [
+            Region::new(0, 0, 7, 5),
+            Region::new(2, 1, 3, 2),
+            Region::new(4, 3, 10, 10),
+            Region::new(-1, 2, 4, 4),
+            Region::new(2, -1, 4, 4),
+            Region::new(7, 0, 1, 1),
+            Region::new(0, 0, 0, 3),
+        ]
+        .iter()
+        {
+            let mut r = RasterBuilder::<SRgb16>::new().with_clear(7, 5);
+            let mut s = r.clone();
+            r.fill_region(*reg, clr);
+            s.set_region(*reg, clr);
+            assert_eq!(r, s);
+        }
+    }
+
+    #[test]
+    fn try_set_region() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+        let s = RasterBuilder::<SGray8>::new().with_color(4, 4, 0x11.into());
+        let it = s.region_iter((0, 0, 2, 2));
+        assert_eq!(r.try_set_region((0, 0, 2, 2), it), Ok(()));
+        let it = s.region_iter((0, 0, 1, 1));
+        assert_eq!(r.try_set_region((3, 3, 2, 2), it), Ok(()));
+        assert_eq!(
+            r.try_set_region((0, 0, 3, 2), s.region_iter((0, 0, 2, 2))),
+            Err(RegionMismatch { expected: 6, found: 4 })
+        );
+        assert_eq!(
+            r.try_set_region((2, 2, 1, 2), s.region_iter((0, 0, 2, 2))),
+            Err(RegionMismatch { expected: 2, found: 4 })
+        );
+        assert_eq!(
+            r.try_set_region((2, 2, 1, 2), vec![SGray8::new(1)].into_iter()),
+            Err(RegionMismatch { expected: 2, found: 1 })
+        );
+        assert_eq!(
+            r.try_set_region((2, 2, 1, 1), SGray8::new(0x22)),
+            Err(RegionMismatch { expected: 1, found: 2 })
+        );
+        let it = std::iter::empty::<SGray8>();
+        assert_eq!(r.try_set_region((-1, 0, 2, 2), it), Ok(()));
+        // Mismatched iterators without an exact size hint cause partial
+        // writes, but region iterators are rejected up front
+        let v = vec![
+            0x11, 0x11, 0x00, 0x00,
+            0x11, 0x11, 0x00, 0x00,
+            0x00, 0x00, 0x22, 0x00,
+            0x00, 0x00, 0x00, 0x11,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn map() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(3, 2);
+        r.set_region((1, 0, 2, 2), SGray8::new(0x40));
+        let m = r.map(|p| Mask8::new(u8::from(p.value()) * 2));
+        assert_eq!((m.width(), m.height()), (3, 2));
+        assert_eq!(m.as_u8_slice(), &[0x00, 0x80, 0x80, 0x00, 0x80, 0x80]);
+        r.map_in_place(|p| *p = SGray8::new(0xFF - u8::from(p.value())));
+        assert_eq!(r.as_u8_slice(), &[0xFF, 0xBF, 0xBF, 0xFF, 0xBF, 0xBF]);
+    }
+    #[test]
+    fn zip_with() {
+        let mut a = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+        a.set_region((0, 0, 1, 2), SGray8::new(0x80));
+        let mut b = RasterBuilder::<Mask8>::new().with_clear(2, 2);
+        b.set_region((0, 0, 2, 1), Mask8::new(0x40));
+        let c = a.zip_with(&b, |p, m| {
+            Mask8::new(u8::from(p.value()) / 2 + u8::from(m.alpha()))
+        });
+        assert_eq!(c.as_u8_slice(), &[0x80, 0x40, 0x40, 0x00]);
+        a.zip_with_in_place(&c, |p, m| *p = SGray8::new(m.alpha()));
+        assert_eq!(a.as_u8_slice(), c.as_u8_slice());
+    }
+    #[test]
+    #[should_panic]
+    fn zip_with_mismatch() {
+        let a = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+        let b = RasterBuilder::<SGray8>::new().with_clear(2, 3);
+        a.zip_with(&b, |p, _| p);
+    }
+    #[test]
+    fn composite_raster() {
+        let mut d = RasterBuilder::<SRgba8>::new()
+            .with_color(3, 2, SRgba8::with_alpha(0x00, 0x00, 0xFF, 0xFF));
+        let mut s = RasterBuilder::<SRgba8>::new().with_clear(2, 2);
+        s.set_pixel(0, 0, SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80));
+        s.set_pixel(1, 0, SRgba8::with_alpha(0x00, 0xFF, 0x00, 0xFF));
+        s.set_pixel(1, 1, SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0x40));
+        d.composite_raster((-1, 0, 3, 3), &s, s.region());
+        d.composite_raster((1, 0, 3, 3), &s, (-1, -1, 3, 3));
+        let v = vec![
+            0x00,0xFF,0x00,0xFF, 0x00,0x00,0xFF,0xFF, 0x00,0x00,0xFF,0xFF,
+            0x40,0x40,0xFF,0xFF, 0x00,0x00,0xFF,0xFF, 0x80,0x00,0x7F,0xFF,
+        ];
+        assert_eq!(d.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn composite_raster_op() {
+        let mut d = RasterBuilder::<SRgba8>::new()
+            .with_color(2, 1, SRgba8::with_alpha(0x00, 0x00, 0xFF, 0xC0));
+        let s = RasterBuilder::<SRgba8>::new()
+            .with_color(1, 1, SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80));
+        d.composite_raster_op((1, 0, 1, 1), &s, s.region(), PorterDuff::Xor);
+        let v = [0x00,0x00,0xFF,0xC0, 0x40,0x00,0xBF,0x80];
+        assert_eq!(d.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn composite_color() {
+        let mut r = RasterBuilder::<SRgba8>::new()
+            .with_color(3, 2, SRgba8::with_alpha(0x00, 0x00, 0xFF, 0xFF));
+        let clr = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80);
+        r.composite_color((-1, 1, 3, 3), clr);
+        let v = [
+            0x00,0x00,0xFF,0xFF, 0x00,0x00,0xFF,0xFF, 0x00,0x00,0xFF,0xFF,
+            0x80,0x00,0x7F,0xFF, 0x80,0x00,0x7F,0xFF, 0x00,0x00,0xFF,0xFF,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        let mut s = RasterBuilder::<SRgba8>::new().with_raster(&r);
+        let clr = SRgba8::with_alpha(0x12, 0x34, 0x56, 0xFF);
+        r.composite_color((1, 0, 2, 2), clr);
+        s.set_region((1, 0, 2, 2), clr);
+        assert_eq!(r.as_u8_slice(), s.as_u8_slice());
+        let mut r = RasterBuilder::<SRgb8>::new()
+            .with_color(2, 1, SRgb8::new(0x00, 0x00, 0xFF));
+        let clr = SRgba8::with_alpha(0xFF, 0x00, 0x00, 0x80);
+        r.composite_color((1, 0, 1, 1), clr);
+        assert_eq!(r.as_u8_slice(), &[0x00, 0x00, 0xFF, 0x80, 0x00, 0x7F]);
+        let mut r = RasterBuilder::<Rgb16>::new()
+            .with_color(1, 1, Rgb16::new(0, 0, 0));
+        r.composite_color(r.region(), Rgba8p::with_alpha(0x40, 0, 0, 0x80));
+        assert_eq!(r.pixel(0, 0), Rgb16::new(0x4040, 0, 0));
+        let mut r = RasterBuilder::<SRgb8>::new()
+            .with_color(1, 1, SRgb8::new(1, 2, 3));
+        r.composite_color(r.region(), SRgba8::with_alpha(9, 9, 9, 0));
+        assert_eq!(r.pixel(0, 0), SRgb8::new(1, 2, 3));
+    }
+    #[test]
+    fn composite_matte() {
+        let mut r = RasterBuilder::<SRgb8>::new()
+            .with_color(3, 2, SRgb8::new(0x00, 0x00, 0xFF));
+        let mut m = RasterBuilder::<Mask16>::new().with_clear(2, 2);
+        m.set_pixel(0, 0, Mask16::new(0xFFFF));
+        m.set_pixel(1, 0, Mask16::new(0x8080));
+        m.set_pixel(1, 1, Mask16::new(0x4040));
+        let clr = SRgb8::new(0xFF, 0x00, 0x00);
+        r.composite_matte((1, 0, 3, 3), &m, (-1, -1, 3, 3), clr,
+            PorterDuff::SrcOver);
+        let v = [
+            0x00,0x00,0xFF, 0x00,0x00,0xFF, 0x00,0x00,0xFF,
+            0x00,0x00,0xFF, 0x00,0x00,0xFF, 0xFF,0x00,0x00,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        r.composite_matte((0, 0, 2, 1), &m, (0, 0, 2, 1), clr,
+            PorterDuff::SrcOver);
+        let v = [
+            0xFF,0x00,0x00, 0x80,0x00,0x7F, 0x00,0x00,0xFF,
+            0x00,0x00,0xFF, 0x00,0x00,0xFF, 0xFF,0x00,0x00,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn blend_raster() {
+        let mut d = RasterBuilder::<SRgb8>::new()
+            .with_color(2, 2, SRgb8::new(0x80, 0x80, 0x80));
+        let s = RasterBuilder::<SRgb8>::new()
+            .with_color(2, 2, SRgb8::new(0x80, 0x80, 0x80));
+        d.blend_raster((1, 1, 2, 2), &s, s.region(), BlendMode::Multiply);
+        let v = [
+            0x80,0x80,0x80, 0x80,0x80,0x80,
+            0x80,0x80,0x80, 0x40,0x40,0x40,
+        ];
+        assert_eq!(d.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn with_raster_premultiplied() {
+        let mut r = RasterBuilder::<SRgba16>::new().with_clear(3, 1);
+        r.set_pixel(0, 0, SRgba16::with_alpha(0xFFFF, 0x8000, 0x0000, 0xFFFF));
+        r.set_pixel(1, 0, SRgba16::with_alpha(0x1234, 0x5678, 0x9ABC, 0x0000));
+        r.set_pixel(2, 0, SRgba16::with_alpha(0x0000, 0x0000, 0x0000, 0x8080));
+        let p = RasterBuilder::<Rgba8p>::new().with_raster(&r);
+        let v = [
+            0xFF,0x37,0x00,0xFF, 0x00,0x00,0x00,0x00, 0x00,0x00,0x00,0x80,
+        ];
+        assert_eq!(p.as_u8_slice(), &v[..]);
+        let s = RasterBuilder::<Rgba8>::new().with_raster(&p);
+        let v = [
+            0xFF,0x37,0x00,0xFF, 0x00,0x00,0x00,0x00, 0x00,0x00,0x00,0x80,
+        ];
+        assert_eq!(s.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn premultiply_in_place() {
+        let mut r = RasterBuilder::<SRgba8>::new().with_clear(2, 2);
+        r.set_pixel(0, 0, SRgba8::with_alpha(0xFF, 0x80, 0x40, 0x80));
+        r.set_pixel(1, 0, SRgba8::with_alpha(0x12, 0x34, 0x56, 0xFF));
+        r.set_pixel(0, 1, SRgba8::with_alpha(0x12, 0x34, 0x56, 0x00));
+        r.set_pixel(1, 1, SRgba8::with_alpha(0xFF, 0x80, 0x40, 0x40));
+        let r = r.premultiply_in_place();
+        let v = [
+            0x80,0x40,0x20,0x80, 0x12,0x34,0x56,0xFF,
+            0x00,0x00,0x00,0x00, 0x40,0x20,0x10,0x40,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        let r = r.unpremultiply_in_place();
+        let v = [
+            0xFF,0x80,0x40,0x80, 0x12,0x34,0x56,0xFF,
+            0x00,0x00,0x00,0x00, 0xFF,0x80,0x40,0x40,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        let mut r = RasterBuilder::<GrayAlpha16>::new().with_clear(2, 1);
+        r.set_pixel(0, 0, GrayAlpha16::with_alpha(0x8000, 0x8000));
+        let r = r.premultiply_in_place();
+        assert_eq!(r.pixel(0, 0), GrayAlpha16p::with_alpha(0x4000, 0x8000));
+        assert_eq!(r.pixel(1, 0), GrayAlpha16p::with_alpha(0x0000, 0x0000));
+        let r = RasterBuilder::<SGrayAlpha8p>::new()
+            .with_color(1, 1, SGrayAlpha8p::with_alpha(0x20, 0x40));
+        let r = r.unpremultiply_in_place();
+        assert_eq!(r.pixel(0, 0), SGrayAlpha8::with_alpha(0x80, 0x40));
+    }
+    #[test]
+    fn swizzle_in_place() {
+        let mut r = RasterBuilder::<SRgba8>::new().with_clear(2, 1);
+        r.set_pixel(0, 0, SRgba8::with_alpha(0x01, 0x02, 0x03, 0x04));
+        r.set_pixel(1, 0, SRgba8::with_alpha(0x05, 0x06, 0x07, 0x08));
+        let r = r.into_bgr();
+        let v = [0x03,0x02,0x01,0x04, 0x07,0x06,0x05,0x08];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        let r = r.into_rgb();
+        let v = [0x01,0x02,0x03,0x04, 0x05,0x06,0x07,0x08];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        let r = RasterBuilder::<Bgr32>::new()
+            .with_color(1, 1, Bgr32::new(0.25, 0.5, 1.0));
+        assert_eq!(r.into_rgb().pixel(0, 0), Rgb32::new(0.25, 0.5, 1.0));
+    }
+    #[test]
+    fn with_f64_buffer() {
+        let v = 0.1 + 1e-12;
+        let buf = vec![v, 0.5, 0.25, 1.0];
+        let r = RasterBuilder::<Rgba64>::new().with_f64_buffer(1, 1, buf);
+        assert_eq!(r.pixel(0, 0), Rgba64::with_alpha(v, 0.5, 0.25, 1.0));
+        let g = RasterBuilder::<Gray64>::new().with_f64_buffer(1, 1, vec![v]);
+        let r = RasterBuilder::<Rgba64>::new().with_raster(&g);
+        assert_eq!(r.pixel(0, 0), Rgba64::with_alpha(v, v, v, 1.0));
+        let g = RasterBuilder::<Gray64>::new().with_raster(&r);
+        assert_eq!(g.pixel(0, 0), Gray64::new(v));
+        let m = RasterBuilder::<Mask64>::new().with_raster(&r);
+        assert_eq!(m.pixel(0, 0), Mask64::new(1.0));
+        let g = RasterBuilder::<Gray16>::new()
+            .with_color(1, 1, Gray16::new(0x1234));
+        let r = RasterBuilder::<Rgb64>::new().with_raster(&g);
+        let g = RasterBuilder::<Gray16>::new().with_raster(&r);
+        assert_eq!(g.pixel(0, 0), Gray16::new(0x1234));
+    }
+    #[test]
+    fn with_f16_buffer() {
+        let buf = vec![0x3C00, 0x7E00, 0xBC00, 0x7C00, 0x3555, 0x4000];
+        let r = RasterBuilder::<Gray16f>::new().with_f16_buffer(3, 2, buf);
+        let v: Vec<u16> =
+            r.as_slice().iter().map(|p| p.value().to_bits()).collect();
+        assert_eq!(v, [0x3C00, 0x0000, 0x0000, 0x3C00, 0x3555, 0x3C00]);
+        let r = RasterBuilder::<Rgba16f>::new().with_raster(&r);
+        let v = [0x00,0x3C, 0x00,0x3C, 0x00,0x3C, 0x00,0x3C];
+        assert_eq!(r.as_u8_slice()[..8], v[..]);
+        let s = RasterBuilder::<Rgba8>::new().with_raster(&r);
+        assert_eq!(s.pixel(1, 1), Rgba8::with_alpha(0x55, 0x55, 0x55, 0xFF));
+    }
+    #[test]
+    fn rows() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(3, 4);
+        for (y, row) in r.rows_mut().enumerate() {
+            for p in row.iter_mut() {
+                *p = SGray8::new(y as u8);
+            }
+        }
+        assert_eq!(r.rows().len(), 4);
+        assert_eq!(r.rows().next_back(), Some(&[SGray8::new(3); 3][..]));
+        for (y, row) in r.rows().enumerate() {
+            assert_eq!(row, r.as_slice_row(y as u32));
+        }
+        {
+            let mut rows = r.region_rows((-1, 1, 3, 2));
+            assert_eq!(rows.len(), 2);
+            assert_eq!(rows.next(), Some(&[SGray8::new(1); 2][..]));
+            assert_eq!(rows.next_back(), Some(&[SGray8::new(2); 2][..]));
+            assert_eq!(rows.next(), None);
+        }
+        assert_eq!(r.region_rows((3, 0, 2, 2)).len(), 0);
+        for row in r.region_rows_mut((2, 2, 5, 5)).rev() {
+            row[0] = SGray8::new(0xFF);
+        }
+        let v = vec![
+            0x00, 0x00, 0x00,
+            0x01, 0x01, 0x01,
+            0x02, 0x02, 0xFF,
+            0x03, 0x03, 0xFF,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+        {
+            let mut rows = r.rows_mut();
+            assert_eq!(rows.len(), 4);
+            rows.next_back().unwrap()[0] = SGray8::new(0x33);
+            rows.next().unwrap()[0] = SGray8::new(0x11);
+            rows.next_back().unwrap()[1] = SGray8::new(0x22);
+            assert_eq!(rows.len(), 1);
+            assert_eq!(rows.next().unwrap()[0], SGray8::new(0x01));
+            assert!(rows.next().is_none());
+        }
+        assert_eq!(r.pixel(0, 0), SGray8::new(0x11));
+        assert_eq!(r.pixel(1, 2), SGray8::new(0x22));
+        assert_eq!(r.pixel(0, 3), SGray8::new(0x33));
+    }
+    #[test]
+    fn rows_zero_width() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(0, 3);
+        assert_eq!(r.rows().len(), 3);
+        assert!(r.rows().all(|row| row.is_empty()));
+        assert_eq!(r.rows_mut().len(), 3);
+        assert_eq!(r.rows_mut().rev().filter(|row| row.is_empty()).count(), 3);
+        let r = RasterBuilder::<SGray8>::new().with_clear(3, 0);
+        assert_eq!(r.rows().len(), 0);
+    }
+    #[test]
+    fn copy_raster() {
+        let mut s = RasterBuilder::<SGray8>::new().with_clear(3, 3);
+        for (i, p) in s.as_slice_mut().iter_mut().enumerate() {
+            *p = SGray8::new(i as u8 + 1);
+        }
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+        r.copy_raster((2, 2, 5, 5), &s, (0, 0, 3, 3));
+        r.copy_raster((-1, 0, 2, 1), &s, (0, 0, 2, 2));
+        r.copy_raster((0, 3, 2, 2), &s, (-1, 1, 3, 3));
+        r.copy_raster((1, 1, 1, 1), &s, (5, 5, 1, 1));
+        r.copy_raster((5, 0, 2, 2), &s, (0, 0, 2, 2));
+        let v = vec![
+            0x02, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x01, 0x02,
+            0x00, 0x04, 0x04, 0x05,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn copy_raster_convert() {
+        let mut s = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+        s.set_region((0, 0, 1, 2), SGray8::new(0x45));
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(3, 2);
+        r.copy_raster((1, 0, 3, 3), &s, s.region());
+        let v = vec![
+            0x00,0x00,0x00, 0x45,0x45,0x45, 0x00,0x00,0x00,
+            0x00,0x00,0x00, 0x45,0x45,0x45, 0x00,0x00,0x00,
+        ];
+        assert_eq!(r.as_u8_slice(), &v[..]);
+    }
+    #[test]
     fn region_size() {
         assert_eq!(std::mem::size_of::<Region>(), 16);
     }
@@ -764,4 +4359,600 @@ mod test {
         );
         Ok(())
     }
+    #[test]
+    fn intersect_disjoint() {
+        let r = Region::new(0, 0, 5, 5);
+        let e = r.intersection(Region::new(10, 10, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        let e = r.intersection(Region::new(10, 0, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        let e = r.intersection(Region::new(0, 10, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        let e = r.intersection(Region::new(-10, -10, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        // Touching at an edge
+        let e = r.intersection(Region::new(5, 0, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        let e = r.intersection(Region::new(0, 5, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        let e = r.intersection(Region::new(5, 5, 5, 5));
+        assert_eq!((e.width, e.height), (0, 0));
+        assert_eq!(
+            Region::new(i32::MAX - 5, 0, 5, 5),
+            Region::new(i32::MAX - 5, 0, u32::MAX, 5)
+                .intersection(Region::new(0, 0, i32::MAX as u32, 10))
+        );
+    }
+    #[test]
+    fn disjoint_noop() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+        let reg = r.region().intersection((10, 10, 5, 5));
+        r.set_region(reg, SGray8::new(0xFF));
+        let reg = r.region().intersection((-5, 0, 5, 5));
+        r.set_region(reg, SGray8::new(0xFF));
+        assert_eq!(r.region_iter((4, 0, 5, 5)).count(), 0);
+        assert_eq!(r.region_iter((0, 4, 5, 5)).count(), 0);
+        assert_eq!(r.region_iter((1, 1, 0, 3)).count(), 0);
+        assert_eq!(r.region_iter((1, 1, 3, 0)).count(), 0);
+        assert_eq!(r.region_iter((-2, -2, 4, 4)).count(), 4);
+        assert_eq!(r.as_u8_slice(), &[0; 16][..]);
+    }
+    #[test]
+    fn union() {
+        let r = Region::new(0, 0, 5, 5);
+        assert_eq!(r, r.union(Region::new(1, 1, 2, 2)));
+        assert_eq!(r, r.union(Region::new(20, 20, 0, 0)));
+        assert_eq!(r, Region::new(20, 20, 0, 3).union(r));
+        assert_eq!(
+            Region::new(-5, 0, 10, 8),
+            r.union(Region::new(-5, 3, 2, 5))
+        );
+        assert_eq!(
+            Region::new(0, 0, i32::MAX as u32 + 9, 5),
+            r.union(Region::new(i32::MAX - 1, 0, 10, 1))
+        );
+        assert_eq!(
+            Region::new(i32::MIN, 0, u32::MAX, 5),
+            r.union(Region::new(i32::MIN, 0, 1, 1))
+                .union(Region::new(i32::MAX, 0, u32::MAX, 1))
+        );
+    }
+    #[test]
+    fn tiles() {
+        let r = RasterBuilder::<SGray8>::new().with_clear(10, 10);
+        let tiles: Vec<_> = r.tiles(4, 4).collect();
+        assert_eq!(tiles.len(), 9);
+        let sizes: Vec<_> =
+            tiles.iter().map(|t| (t.width(), t.height())).collect();
+        assert_eq!(
+            sizes,
+            vec![
+                (4, 4), (4, 4), (2, 4), //
+                (4, 4), (4, 4), (2, 4), //
+                (4, 2), (4, 2), (2, 2),
+            ]
+        );
+        assert_eq!(tiles[5], Region::new(8, 4, 2, 4));
+        let area: u32 = tiles.iter().map(|t| t.width() * t.height()).sum();
+        assert_eq!(area, 100);
+        assert_eq!(r.tiles(1, 20).count(), 10);
+        assert_eq!(r.tiles(20, 20).collect::<Vec<_>>(), vec![r.region()]);
+        let e = RasterBuilder::<SGray8>::new().with_clear(0, 5);
+        assert_eq!(e.tiles(4, 4).count(), 0);
+    }
+    #[test]
+    fn region_tiles() {
+        let tiles: Vec<_> = Region::new(-3, 5, 6, 2).tiles(3, 1).collect();
+        assert_eq!(
+            tiles,
+            vec![
+                Region::new(-3, 5, 3, 1),
+                Region::new(0, 5, 3, 1),
+                Region::new(-3, 6, 3, 1),
+                Region::new(0, 6, 3, 1),
+            ]
+        );
+        let r = Region::new(i32::MAX - 1, 0, 4, 1);
+        assert_eq!(r.tiles(2, 1).count(), 1);
+    }
+    #[test]
+    #[should_panic]
+    fn tiles_zero() {
+        let _ = Region::new(0, 0, 4, 4).tiles(0, 4);
+    }
+    #[test]
+    fn intersects() {
+        let r = Region::new(0, 0, 5, 5);
+        assert!(r.intersects(r));
+        assert!(r.intersects(Region::new(4, 4, 5, 5)));
+        assert!(r.intersects(Region::new(-5, -5, 6, 6)));
+        assert!(!r.intersects(Region::new(5, 0, 5, 5)));
+        assert!(!r.intersects(Region::new(0, 5, 5, 5)));
+        assert!(!r.intersects(Region::new(2, 2, 0, 1)));
+        assert!(!r.intersects(Region::new(i32::MAX, 0, u32::MAX, 5)));
+    }
+    #[test]
+    fn try_overflow() {
+        use RasterError::Overflow;
+        let m = u32::MAX;
+        let r = RasterBuilder::<SRgb8>::new().try_with_clear(m, m);
+        assert_eq!(r.err(), Some(Overflow));
+        let b = RasterBuilder::<Mask8>::new;
+        assert_eq!(b().try_with_clear(m, m).err(), Some(Overflow));
+        let r = b().try_with_fn(m, m, |_, _| unreachable!());
+        assert_eq!(r.err(), Some(Overflow));
+        let r = b().try_with_u8_buffer(m, m, vec![]);
+        assert_eq!(r.err(), Some(Overflow));
+        let r = RasterBuilder::<Rgba64>::new()
+            .try_with_f64_buffer(m, 1 << 28, vec![]);
+        assert_eq!(r.err(), Some(Overflow));
+        assert!(b().try_with_clear(0, m).is_ok());
+    }
+    #[test]
+    #[should_panic(expected = "raster size overflow")]
+    fn with_clear_overflow() {
+        RasterBuilder::<SRgba16>::new().with_clear(u32::MAX, u32::MAX);
+    }
+    #[test]
+    fn try_mismatch() {
+        use RasterError::LengthMismatch;
+        let r = RasterBuilder::<SRgb8>::new()
+            .try_with_pixels(2, 2, vec![SRgb8::default(); 3]);
+        assert_eq!(r.err(), Some(LengthMismatch { expected: 4, found: 3 }));
+        let r = RasterBuilder::<SRgb8>::new()
+            .try_with_u8_buffer(2, 2, vec![0; 13]);
+        assert_eq!(r.err(), Some(LengthMismatch { expected: 12, found: 13 }));
+        let r = RasterBuilder::<SGrayAlpha16>::new()
+            .try_with_u16_buffer(3, 1, vec![0; 5]);
+        assert_eq!(r.err(), Some(LengthMismatch { expected: 6, found: 5 }));
+        let r = RasterBuilder::<Rgba16f>::new()
+            .try_with_f16_buffer(1, 1, vec![0; 8]);
+        assert_eq!(r.err(), Some(LengthMismatch { expected: 4, found: 8 }));
+        let r = RasterBuilder::<Gray64>::new()
+            .try_with_f64_buffer(1, 2, vec![0.0]);
+        assert_eq!(r.err(), Some(LengthMismatch { expected: 2, found: 1 }));
+        let r = RasterBuilder::<Gray64>::new()
+            .try_with_f64_buffer(3, 1, vec![0.0, 1.5, f64::NAN]);
+        assert_eq!(r.err(), Some(RasterError::OutOfRange { index: 1 }));
+        let r = RasterBuilder::<Gray64>::new()
+            .try_with_f64_buffer(2, 1, vec![0.5, f64::NAN]);
+        assert_eq!(r.err(), Some(RasterError::OutOfRange { index: 1 }));
+        let r = RasterBuilder::<SRgb8>::new()
+            .try_with_u8_buffer(2, 2, vec![0; 12])
+            .unwrap();
+        assert_eq!(r.as_u8_slice(), &[0; 12]);
+    }
+    #[test]
+    #[should_panic(expected = "pixel data length 3 does not match expected 4")]
+    fn with_pixels_mismatch() {
+        let p = vec![Mask8::new(0); 3];
+        RasterBuilder::<Mask8>::new().with_pixels(2, 2, p);
+    }
+    #[test]
+    fn buffer_reuse() {
+        let buf = vec![0x10, 0x20, 0x30, 0x40, 0x50, 0x60];
+        let ptr = buf.as_ptr();
+        let r = RasterBuilder::<SRgb8>::new().with_u8_buffer(2, 1, buf);
+        assert_eq!(r.as_u8_slice().as_ptr(), ptr);
+        assert_eq!(r.pixel(1, 0), SRgb8::new(0x40, 0x50, 0x60));
+        let v: Vec<SRgb8> = r.into();
+        assert_eq!(v.as_ptr() as *const u8, ptr);
+        let buf = vec![0x1000, 0x2000, 0x3000, 0x4000];
+        let ptr = buf.as_ptr();
+        let r = RasterBuilder::<SGrayAlpha16>::new().with_u16_buffer(2, 1, buf);
+        assert_eq!(r.as_slice().as_ptr() as *const u16, ptr);
+        assert_eq!(r.pixel(1, 0), SGrayAlpha16::with_alpha(0x3000, 0x4000));
+    }
+    #[test]
+    fn buffer_copy() {
+        let clr = SRgb16::new(0x1234, 0x5678, 0x9ABC);
+        let mut buf = vec![0];
+        for c in [0x1234u16, 0x5678, 0x9ABC].iter() {
+            buf.extend_from_slice(&c.to_ne_bytes());
+        }
+        let buf: Box<[u8]> = buf[1..].into();
+        let pixels: Box<[SRgb16]> = buffer_pixels(buf, 1);
+        assert_eq!(&pixels[..], &[clr]);
+    }
+    #[test]
+    fn u16_buffer_endian() {
+        let v: Vec<u16> = vec![0x0102, 0x0304, 0x0506, 0xA0B0, 0xC0D0, 0xE0F0];
+        let mut le = vec![];
+        let mut be = vec![];
+        for c in v.iter() {
+            le.push((c & 0xFF) as u8);
+            le.push((c >> 8) as u8);
+            be.push((c >> 8) as u8);
+            be.push((c & 0xFF) as u8);
+        }
+        let r = RasterBuilder::<SRgb16>::new().with_u16_buffer(2, 1, v);
+        let r0 = RasterBuilder::<SRgb16>::new().with_u16_buffer_le(2, 1, &le);
+        let r1 = RasterBuilder::<SRgb16>::new().with_u16_buffer_be(2, 1, &be);
+        assert_eq!(r0.as_slice(), r.as_slice());
+        assert_eq!(r1.as_slice(), r.as_slice());
+        assert_eq!(r.pixel(1, 0), SRgb16::new(0xA0B0, 0xC0D0, 0xE0F0));
+        assert_eq!(r.to_le_bytes(), le);
+        assert_eq!(r.to_be_bytes(), be);
+        let e = RasterBuilder::<SRgb16>::new()
+            .try_with_u16_buffer_le(2, 1, &le[1..]);
+        let found = 11;
+        let mismatch = RasterError::LengthMismatch { expected: 12, found };
+        assert_eq!(e.err(), Some(mismatch));
+    }
+    #[test]
+    fn ch32_bytes_endian() {
+        let r = RasterBuilder::<Gray32>::new()
+            .with_color(1, 1, Gray32::new(0.5));
+        let bits = 0.5f32.to_bits();
+        let le: Vec<u8> = (0..4).map(|i| (bits >> (i * 8)) as u8).collect();
+        let be: Vec<u8> = le.iter().rev().cloned().collect();
+        assert_eq!(r.to_le_bytes(), le);
+        assert_eq!(r.to_be_bytes(), be);
+        let r = RasterBuilder::<SRgb8>::new()
+            .with_color(1, 1, SRgb8::new(1, 2, 3));
+        assert_eq!(r.to_le_bytes(), r.to_be_bytes());
+    }
+    #[test]
+    fn get_pixel() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(3, 2);
+        assert!(r.try_set_pixel(2, 1, SGray8::new(0x80)));
+        assert!(!r.try_set_pixel(3, 1, SGray8::new(0x80)));
+        assert!(!r.try_set_pixel(0, 2, SGray8::new(0x80)));
+        assert_eq!(r.get_pixel(2, 1), Some(SGray8::new(0x80)));
+        assert_eq!(r.get_pixel(3, 0), None);
+        assert_eq!(r.get_pixel(0, 2), None);
+        assert_eq!(r.get_pixel(u32::MAX, u32::MAX), None);
+        *r.get_pixel_mut(0, 1).unwrap() = SGray8::new(0x40);
+        assert!(r.get_pixel_mut(0, 5).is_none());
+        assert_eq!(r.as_u8_slice(), &[0x00, 0x00, 0x00, 0x40, 0x00, 0x80]);
+    }
+    #[test]
+    #[should_panic(expected = "pixel (3, 0) out of bounds for 3x2 raster")]
+    fn pixel_x_out_of_bounds() {
+        let r = RasterBuilder::<SGray8>::new().with_clear(3, 2);
+        r.pixel(3, 0);
+    }
+    #[test]
+    #[should_panic(expected = "pixel (0, 2) out of bounds for 3x2 raster")]
+    fn set_pixel_y_out_of_bounds() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(3, 2);
+        r.set_pixel(0, 2, SGray8::new(0xFF));
+    }
+    #[test]
+    fn index() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(3, 2);
+        r[(1, 0)] = SGray8::new(0x10);
+        r[(2, 1)] = SGray8::new(0x20);
+        r[3] = SGray8::new(0x30);
+        assert_eq!(r[(1, 0)], r.pixel(1, 0));
+        assert_eq!(r[5], SGray8::new(0x20));
+        assert_eq!(r[(0, 1)], SGray8::new(0x30));
+        assert_eq!(r.as_u8_slice(), &[0x00, 0x10, 0x00, 0x30, 0x00, 0x20]);
+        for i in 0..6 {
+            r[i] = SGray8::new(i as u8);
+        }
+        assert_eq!(r[(2, 1)], SGray8::new(5));
+    }
+    #[test]
+    #[should_panic(expected = "pixel (1, 3) out of bounds for 3x2 raster")]
+    fn index_out_of_bounds() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(3, 2);
+        r[(1, 3)] = SGray8::new(0xFF);
+    }
+    #[test]
+    fn clone_eq() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(2, 2);
+        r.set_pixel(1, 1, SRgb8::new(0x10, 0x20, 0x30));
+        let c = r.clone();
+        assert_eq!(r, c);
+        r.set_pixel(0, 0, SRgb8::new(0xFF, 0xFF, 0xFF));
+        assert_ne!(r, c);
+        assert_eq!(c.pixel(0, 0), SRgb8::default());
+        let a = RasterBuilder::<Mask8>::new().with_clear(2, 3);
+        let b = RasterBuilder::<Mask8>::new().with_clear(3, 2);
+        assert_ne!(a, b);
+    }
+    #[test]
+    fn debug() {
+        let r = RasterBuilder::<Mask8>::new().with_clear(3, 2);
+        let d = format!("{:?}", r);
+        assert!(d.starts_with("Raster { pixel: Mask8, "), "{}", d);
+        assert!(d.contains("width: 3, height: 2"), "{}", d);
+        assert!(d.ends_with("... 2 more] }"), "{}", d);
+        assert_eq!(d.matches("Mask8(0)").count(), 4);
+        let r = RasterBuilder::<Gray32>::new().with_clear(1, 1);
+        assert!(!format!("{:?}", r).contains("more"));
+    }
+    #[test]
+    fn hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let hash = |r: &Raster<SGray8>| {
+            let mut h = DefaultHasher::new();
+            r.hash(&mut h);
+            h.finish()
+        };
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+        let h = hash(&r);
+        assert_eq!(h, hash(&r.clone()));
+        r.set_pixel(3, 3, SGray8::new(1));
+        assert_ne!(h, hash(&r));
+    }
+    #[test]
+    fn scale_nearest_integer() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(3, 2, |x, y| SGray8::new((y * 3 + x) as u8));
+        let r2 = r.scale_nearest(6, 4);
+        let v = [
+            0,0,1,1,2,2,
+            0,0,1,1,2,2,
+            3,3,4,4,5,5,
+            3,3,4,4,5,5,
+        ];
+        assert_eq!(r2.as_u8_slice(), &v[..]);
+        assert_eq!(r2.scale_nearest(3, 2), r);
+        assert_eq!(r.scale_nearest(3, 2), r);
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(6, 1, |x, _| SGray8::new(x as u8));
+        assert_eq!(r.scale_nearest(3, 1).as_u8_slice(), &[1, 3, 5]);
+        assert_eq!(r.scale_nearest(4, 1).as_u8_slice(), &[0, 2, 3, 5]);
+    }
+    #[test]
+    fn scale_nearest_extreme() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(1, 3, |_, y| SGray8::new(y as u8));
+        let r2 = r.scale_nearest(100, 1);
+        assert_eq!(r2.width(), 100);
+        assert!(r2.as_u8_slice().iter().all(|v| *v == 1));
+        let r2 = r.scale_nearest(2, 7);
+        let v = [0,0, 0,0, 1,1, 1,1, 1,1, 2,2, 2,2];
+        assert_eq!(r2.as_u8_slice(), &v[..]);
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(1000, 1, |x, _| SGray8::new((x / 4) as u8));
+        assert_eq!(r.scale_nearest(1, 1).pixel(0, 0), SGray8::new(125));
+        assert_eq!(r.scale_nearest(0, 5).height(), 5);
+        let e = RasterBuilder::<SGray8>::new().with_clear(0, 0);
+        let r2 = RasterBuilder::new().with_clear(2, 2);
+        assert_eq!(e.scale_nearest(2, 2), r2);
+    }
+    #[test]
+    fn scale_linear() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(2, 2, |x, y| SGray8::new((x * 0x40 + y * 0x80) as u8));
+        let r2 = r.scale_linear(4, 4);
+        let v = [
+            0x00,0x10,0x30,0x40,
+            0x20,0x30,0x50,0x60,
+            0x60,0x70,0x90,0xA0,
+            0x80,0x90,0xB0,0xC0,
+        ];
+        assert_eq!(r2.as_u8_slice(), &v[..]);
+        let r = RasterBuilder::<SRgb16>::new().with_fn(3, 3, |x, y| {
+            SRgb16::new(x as u16 * 0x1234, y as u16 * 0x4321, 0x5555)
+        });
+        assert_eq!(r.scale_linear(3, 3), r);
+        let r = RasterBuilder::<Gray32>::new()
+            .with_fn(3, 1, |x, _| Gray32::new(x as f32 * 0.5));
+        let r2 = r.scale_linear(2, 1);
+        assert_eq!(r2.pixel(0, 0), Gray32::new(0.125));
+        assert_eq!(r2.pixel(1, 0), Gray32::new(0.875));
+        let r2 = r.scale_linear(1, 5);
+        assert!(r2.as_slice().iter().all(|p| *p == Gray32::new(0.5)));
+    }
+    #[test]
+    fn scale_linear_alpha() {
+        let mut r = RasterBuilder::<SRgba8>::new().with_clear(2, 1);
+        r.set_pixel(0, 0, SRgba8::with_alpha(0xFF, 0x00, 0x00, 0xFF));
+        let r2 = r.scale_linear(4, 1);
+        let v = [
+            0xFF,0x00,0x00,0xFF, 0xFF,0x00,0x00,0xBF,
+            0xFF,0x00,0x00,0x40, 0x00,0x00,0x00,0x00,
+        ];
+        assert_eq!(r2.as_u8_slice(), &v[..]);
+        let r = RasterBuilder::<SRgba8p>::new().with_raster(&r);
+        let r2 = r.scale_linear(4, 1);
+        let v = [
+            0xFF,0x00,0x00,0xFF, 0xBF,0x00,0x00,0xBF,
+            0x40,0x00,0x00,0x40, 0x00,0x00,0x00,0x00,
+        ];
+        assert_eq!(r2.as_u8_slice(), &v[..]);
+    }
+    #[test]
+    fn sample() {
+        let r = RasterBuilder::<Gray32>::new()
+            .with_fn(2, 2, |x, y| Gray32::new((x + 2 * y) as f32 / 4.0));
+        assert_eq!(r.sample_nearest(0.0, 0.0), Gray32::new(0.0));
+        assert_eq!(r.sample_nearest(1.0, 0.999), Gray32::new(0.25));
+        assert_eq!(r.sample_nearest(-1.0, 2.0), Gray32::new(0.5));
+        assert_eq!(r.sample_nearest(9e9, f32::NAN), Gray32::new(0.25));
+        assert_eq!(r.sample_linear(0.5, 0.5), Gray32::new(0.0));
+        assert_eq!(r.sample_linear(1.5, 1.5), Gray32::new(0.75));
+        assert_eq!(r.sample_linear(1.0, 0.5), Gray32::new(0.125));
+        assert_eq!(r.sample_linear(1.0, 1.0), Gray32::new(0.375));
+        assert_eq!(r.sample_linear(0.75, 1.25), Gray32::new(0.4375));
+        assert_eq!(r.sample_linear(-5.0, 1.0), Gray32::new(0.25));
+        assert_eq!(r.sample_linear(1.0, 9.0), Gray32::new(0.625));
+        assert_eq!(r.sample_linear(f32::NAN, 0.0), Gray32::new(0.0));
+        let e = RasterBuilder::<Gray32>::new().with_clear(0, 3);
+        assert_eq!(e.sample_linear(0.0, 0.0), Gray32::default());
+        assert_eq!(e.sample_nearest(0.0, 0.0), Gray32::default());
+        // weights are not rounded to 8 bits: 0xD8 * 0.01 is 2.16
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 1);
+        r.set_pixel(1, 0, SGray8::new(0xD8));
+        assert_eq!(r.sample_linear(0.51, 0.5), SGray8::new(2));
+    }
+    #[test]
+    fn crop() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(4, 3, |x, y| SGray8::new((y * 4 + x) as u8));
+        let c = r.crop((1, 1, 2, 2));
+        assert_eq!((c.width(), c.height()), (2, 2));
+        assert_eq!(c.as_u8_slice(), &[5, 6, 9, 10]);
+        let c = r.crop((-2, 1, 4, 10));
+        assert_eq!((c.width(), c.height()), (2, 2));
+        assert_eq!(c.as_u8_slice(), &[4, 5, 8, 9]);
+        assert_eq!(r.crop(r.region()), r);
+        let c = r.crop((4, 0, 2, 2));
+        assert_eq!((c.width(), c.height()), (0, 0));
+        let c = r.crop((1, 1, 0, 5));
+        assert_eq!((c.width(), c.height()), (0, 0));
+    }
+    #[test]
+    fn flip() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(3, 3, |x, y| SGray8::new((y * 3 + x) as u8));
+        let v = r.flip_vertical();
+        assert_eq!(v.as_u8_slice(), &[6, 7, 8, 3, 4, 5, 0, 1, 2]);
+        let h = r.flip_horizontal();
+        assert_eq!(h.as_u8_slice(), &[2, 1, 0, 5, 4, 3, 8, 7, 6]);
+        let mut f = r.clone();
+        f.flip_vertical_in_place();
+        f.flip_horizontal_in_place();
+        assert_eq!(f.as_u8_slice(), &[8, 7, 6, 5, 4, 3, 2, 1, 0]);
+        f.flip_horizontal_in_place();
+        f.flip_vertical_in_place();
+        assert_eq!(f, r);
+        let r = RasterBuilder::<SRgb8>::new().with_fn(4, 2, |x, y| {
+            SRgb8::new(x as u8, y as u8, 0xFF)
+        });
+        assert_eq!(r.flip_vertical().flip_vertical(), r);
+        assert_eq!(r.flip_horizontal().flip_horizontal(), r);
+        assert_eq!(r.flip_vertical().pixel(3, 0), SRgb8::new(3, 1, 0xFF));
+        let mut e = RasterBuilder::<SRgb8>::new().with_clear(0, 5);
+        e.flip_vertical_in_place();
+        e.flip_horizontal_in_place();
+        let mut e = RasterBuilder::<SRgb8>::new().with_clear(5, 1);
+        e.flip_vertical_in_place();
+    }
+    #[test]
+    fn rotate() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(3, 2, |x, y| SGray8::new((y * 3 + x) as u8));
+        let r90 = r.rotate90();
+        assert_eq!((r90.width(), r90.height()), (2, 3));
+        assert_eq!(r90.as_u8_slice(), &[3, 0, 4, 1, 5, 2]);
+        let r180 = r.rotate180();
+        assert_eq!((r180.width(), r180.height()), (3, 2));
+        assert_eq!(r180.as_u8_slice(), &[5, 4, 3, 2, 1, 0]);
+        let r270 = r.rotate270();
+        assert_eq!((r270.width(), r270.height()), (2, 3));
+        assert_eq!(r270.as_u8_slice(), &[2, 5, 1, 4, 0, 3]);
+        assert_eq!(r90.rotate90(), r180);
+        assert_eq!(r180.rotate90(), r270);
+        assert_eq!(r270.rotate90(), r);
+        assert_eq!(r90.rotate270(), r);
+        let e = RasterBuilder::<SGray8>::new().with_clear(0, 4).rotate90();
+        assert_eq!((e.width(), e.height()), (4, 0));
+    }
+    #[test]
+    fn rotate_blocks() {
+        let r = RasterBuilder::<SRgb16>::new().with_fn(70, 33, |x, y| {
+            SRgb16::new(x as u16, y as u16, (x * y) as u16)
+        });
+        let r90 = r.rotate90();
+        assert_eq!((r90.width(), r90.height()), (33, 70));
+        assert_eq!(r90.pixel(32, 0), r.pixel(0, 0));
+        assert_eq!(r90.pixel(0, 69), r.pixel(69, 32));
+        assert_eq!(r90.pixel(12, 40), r.pixel(40, 20));
+        assert_eq!(r90.rotate90().rotate90().rotate90(), r);
+        assert_eq!(r.rotate270().rotate270(), r.rotate180());
+    }
+    #[test]
+    fn transpose() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(1, 5, |_, y| SGray8::new(y as u8));
+        let t = r.transpose();
+        assert_eq!((t.width(), t.height()), (5, 1));
+        assert_eq!(t.as_u8_slice(), &[0, 1, 2, 3, 4]);
+        assert_eq!(t.transpose(), r);
+        let r = RasterBuilder::<SRgba8>::new().with_fn(67, 35, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 0, 0xFF)
+        });
+        let t = r.transpose();
+        assert_eq!((t.width(), t.height()), (35, 67));
+        assert_eq!(t.pixel(34, 66), r.pixel(66, 34));
+        assert_eq!(t.pixel(3, 40), r.pixel(40, 3));
+        assert_eq!(t.transpose(), r);
+    }
+    #[test]
+    fn transpose_in_place() {
+        for n in [0, 1, 3, 32, 33, 70].iter() {
+            let r = RasterBuilder::<SGray16>::new()
+                .with_fn(*n, *n, |x, y| SGray16::new((y * 100 + x) as u16));
+            let mut t = r.clone();
+            t.transpose_in_place();
+            assert_eq!(t, r.transpose());
+            t.transpose_in_place();
+            assert_eq!(t, r);
+        }
+    }
+    #[test]
+    #[should_panic(expected = "raster is not square")]
+    fn transpose_in_place_not_square() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 3);
+        r.transpose_in_place();
+    }
+    #[test]
+    fn into_vec() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(3, 2, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 7, 0xFF)
+        });
+        let bytes = r.as_u8_slice().to_vec();
+        let ptr = r.as_slice().as_ptr() as *const u8;
+        let v = r.into_u8_vec();
+        assert_eq!(v.len(), 3 * 2 * 4);
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v, bytes);
+        let r = RasterBuilder::<SGrayAlpha16>::new()
+            .with_color(2, 1, SGrayAlpha16::with_alpha(0x1234, 0xABCD));
+        let ptr = r.as_slice().as_ptr() as *const u16;
+        let v = r.into_u16_vec();
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v, vec![0x1234, 0xABCD, 0x1234, 0xABCD]);
+        let r = RasterBuilder::<SGray8>::new().with_clear(0, 3);
+        assert!(r.into_u8_vec().is_empty());
+    }
+    #[test]
+    fn raw_parts() {
+        let r = RasterBuilder::<SRgba16>::new().with_fn(5, 3, |x, y| {
+            SRgba16::with_alpha(x as u16, y as u16, 0x1234, 0xFFFF)
+        });
+        let copy = r.clone();
+        let (width, height, ptr, len) = r.into_raw_parts();
+        assert_eq!((width, height, len), (5, 3, 15));
+        let r = unsafe { Raster::from_raw_parts(width, height, ptr, len) };
+        assert_eq!(r.as_slice().as_ptr(), ptr as *const SRgba16);
+        assert_eq!(r, copy);
+        let mut v = std::mem::ManuallyDrop::new(vec![SGray8::new(9); 6]);
+        assert_eq!(v.capacity(), v.len());
+        let r: Raster<SGray8> =
+            unsafe { Raster::from_raw_parts(2, 3, v.as_mut_ptr(), v.len()) };
+        assert_eq!(r.pixel(1, 2), SGray8::new(9));
+        let r = RasterBuilder::<SGray8>::new().with_clear(0, 7);
+        let (width, height, ptr, len) = r.into_raw_parts();
+        let r: Raster<SGray8> =
+            unsafe { Raster::from_raw_parts(width, height, ptr, len) };
+        assert_eq!((r.width(), r.height()), (0, 7));
+    }
+    #[test]
+    #[should_panic(expected = "pixel length 5 does not match 2x3 raster")]
+    fn raw_parts_mismatch() {
+        // Panics before taking ownership, so the Vec still frees its data
+        let mut v = vec![SGray8::new(0); 5];
+        let _r: Raster<SGray8> =
+            unsafe { Raster::from_raw_parts(2, 3, v.as_mut_ptr(), v.len()) };
+    }
+    #[test]
+    fn approx_eq() {
+        let a = RasterBuilder::<Gray32>::new()
+            .with_fn(3, 2, |x, y| Gray32::new(x as f32 * 0.1 + y as f32 * 0.3));
+        let b = a.map(|p| Gray32::new(f32::from(p.value()) * 3.0 / 3.0));
+        assert!(a.approx_eq(&b, 1e-6));
+        let mut c = b.clone();
+        c.set_pixel(2, 1, Gray32::new(0.0));
+        assert!(!a.approx_eq(&c, 1e-6));
+        let d = RasterBuilder::<Gray32>::new().with_clear(2, 3);
+        assert!(!d.approx_eq(&RasterBuilder::new().with_clear(3, 2), 1.0));
+        assert!(d.approx_eq(&RasterBuilder::new().with_clear(2, 3), 0.0));
+    }
 }
@@ -7,7 +7,11 @@ use crate::alpha::{
     self, AChannel, Mode as _, Opaque, Premultiplied, Straight, Translucent,
 };
 use crate::gamma::{self, Linear};
-use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, Pixel};
+use crate::pixel::fmt_pixel;
+use crate::{
+    Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel,
+};
+use std::fmt;
 use std::marker::PhantomData;
 use std::ops::Mul;
 
@@ -18,7 +22,7 @@ use std::ops::Mul;
 /// [alpha]: alpha/trait.AChannel.html
 /// [channel]: trait.Channel.html
 /// [color model]: trait.ColorModel.html
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct Rgb<C, A, M, G>
 where
@@ -52,6 +56,11 @@ where
         self.alpha.value()
     }
 
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
     /// Convert to *red*, *green*, *blue* and *alpha* components
     fn to_rgba(self) -> [Self::Chan; 4] {
         [self.red(), self.green(), self.blue(), self.alpha()]
@@ -100,6 +109,19 @@ where
     }
 }
 
+impl<C, A, M, G> fmt::Debug for Rgb<C, A, M, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    M: alpha::Mode,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
 impl<C, A, M, G> Pixel for Rgb<C, A, M, G>
 where
     C: Channel,
@@ -107,6 +129,27 @@ where
     M: alpha::Mode,
     G: gamma::Mode,
 {
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Rgb);
+
+    const WHITE: Self = Rgb {
+        components: [C::MAX; 3],
+        alpha: A::OPAQUE,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = Rgb {
+        components: [C::MIN; 3],
+        alpha: A::OPAQUE,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = Rgb {
+        components: [C::MIN; 3],
+        alpha: A::TRANSPARENT,
+        mode: PhantomData,
+        gamma: PhantomData,
+    };
+
     type Alpha = M;
     type Gamma = G;
 }
@@ -287,17 +330,71 @@ where
         }
     }
     /// Get the red component.
-    pub fn red(self) -> C {
+    pub const fn red(self) -> C {
         self.components[0]
     }
     /// Get the green component.
-    pub fn green(self) -> C {
+    pub const fn green(self) -> C {
         self.components[1]
     }
     /// Get the blue component.
-    pub fn blue(self) -> C {
+    pub const fn blue(self) -> C {
         self.components[2]
     }
+    /// Set the red component.
+    pub fn set_red<H>(&mut self, red: H)
+    where
+        C: From<H>,
+    {
+        self.components[0] = C::from(red);
+    }
+    /// Set the green component.
+    pub fn set_green<H>(&mut self, green: H)
+    where
+        C: From<H>,
+    {
+        self.components[1] = C::from(green);
+    }
+    /// Set the blue component.
+    pub fn set_blue<H>(&mut self, blue: H)
+    where
+        C: From<H>,
+    {
+        self.components[2] = C::from(blue);
+    }
+    /// Get the *alpha* value.
+    pub fn alpha(self) -> C {
+        self.alpha.value()
+    }
+}
+
+impl<C, A, G> Rgb<C, A, Straight, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Premultiply *red*, *green* and *blue* components with *alpha*.
+    ///
+    /// Components are multiplied in the gamma encoding of the pixel.
+    pub fn premultiply(self) -> Rgb<C, A, Premultiplied, G> {
+        self.into()
+    }
+}
+
+impl<C, A, G> Rgb<C, A, Premultiplied, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    /// Divide *red*, *green* and *blue* components by *alpha*.
+    ///
+    /// Components are divided in the gamma encoding of the pixel.  If
+    /// *alpha* is zero, all components are zero.
+    pub fn unpremultiply(self) -> Rgb<C, A, Straight, G> {
+        self.into()
+    }
 }
 
 /// [Rgb](struct.Rgb.html) 8-bit [opaque](alpha/struct.Opaque.html) (no alpha)
@@ -309,6 +406,9 @@ pub type Rgb16 = Rgb<Ch16, Opaque<Ch16>, Straight, Linear>;
 /// [Rgb](struct.Rgb.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
 /// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
 pub type Rgb32 = Rgb<Ch32, Opaque<Ch32>, Straight, Linear>;
+/// [Rgb](struct.Rgb.html) 64-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Rgb64 = Rgb<Ch64, Opaque<Ch64>, Straight, Linear>;
 
 /// [Rgb](struct.Rgb.html) 8-bit [straight](alpha/struct.Straight.html) alpha
 /// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
@@ -316,9 +416,16 @@ pub type Rgba8 = Rgb<Ch8, Translucent<Ch8>, Straight, Linear>;
 /// [Rgb](struct.Rgb.html) 16-bit [straight](alpha/struct.Straight.html) alpha
 /// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
 pub type Rgba16 = Rgb<Ch16, Translucent<Ch16>, Straight, Linear>;
+/// [Rgb](struct.Rgb.html) 16-bit floating-point
+/// [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Rgba16f = Rgb<Ch16f, Translucent<Ch16f>, Straight, Linear>;
 /// [Rgb](struct.Rgb.html) 32-bit [straight](alpha/struct.Straight.html) alpha
 /// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
 pub type Rgba32 = Rgb<Ch32, Translucent<Ch32>, Straight, Linear>;
+/// [Rgb](struct.Rgb.html) 64-bit [straight](alpha/struct.Straight.html) alpha
+/// [linear](gamma/struct.Linear.html) gamma [pixel](trait.Pixel.html) format.
+pub type Rgba64 = Rgb<Ch64, Translucent<Ch64>, Straight, Linear>;
 
 /// [Rgb](struct.Rgb.html) 8-bit
 /// [premultiplied](alpha/struct.Premultiplied.html) alpha
@@ -401,4 +508,43 @@ mod test {
         let b = SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0x10);
         assert_eq!(a * b, SRgba8::with_alpha(0xFF, 0x00, 0x80, 0x10));
     }
+
+    #[test]
+    fn premultiply() {
+        let a = SRgba8::with_alpha(0xFF, 0x80, 0x00, 0x80);
+        let b = SRgba8p::with_alpha(0x80, 0x40, 0x00, 0x80);
+        assert_eq!(a.premultiply(), b);
+        assert_eq!(b.unpremultiply(), a);
+        let a = Rgba32p::with_alpha(0.5, 0.25, 0.0, 0.0);
+        assert_eq!(a.unpremultiply(), Rgba32::with_alpha(0.0, 0.0, 0.0, 0.0));
+        for a in 0..=255 {
+            for c in 0..=a {
+                let p = Rgba8p::with_alpha(c, c, c, a);
+                assert_eq!(p.unpremultiply().premultiply(), p);
+            }
+        }
+        for i in 1..=64 {
+            let a = i as f32 / 64.0;
+            let p = Rgba32::with_alpha(0.25, 0.5, 1.0, a);
+            let q = p.premultiply().unpremultiply();
+            for (c, d) in p.components().iter().zip(q.components()) {
+                let (c, d) = (f32::from(*c), f32::from(*d));
+                assert!((c - d).abs() <= f32::EPSILON);
+            }
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        let mut clr = SRgba8::with_alpha(0x10, 0x20, 0x30, 0x40);
+        assert_eq!(clr.red(), Ch8::new(0x10));
+        assert_eq!(clr.green(), Ch8::new(0x20));
+        assert_eq!(clr.blue(), Ch8::new(0x30));
+        assert_eq!(clr.alpha(), Ch8::new(0x40));
+        clr.set_red(0xA0);
+        clr.set_green(Ch8::new(0xB0));
+        clr.set_blue(0xC0);
+        assert_eq!(clr, SRgba8::with_alpha(0xA0, 0xB0, 0xC0, 0x40));
+        assert_eq!(SRgb16::new(1, 2, 3).alpha(), Ch16::MAX);
+    }
 }
@@ -0,0 +1,407 @@
+// serialize.rs   Serde support.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{self, AChannel};
+use crate::gamma;
+use crate::raster::pixel_count;
+use crate::{
+    Bgr, Ch16, Ch16f, Ch32, Ch64, Ch8, Channel, Cmyk, ColorModel, Gray, Hsl,
+    Hsv, Idx8, Lab, Mask, Oklab, Pixel, Raster, RasterBuilder, RasterError,
+    Rgb, Xyz, YCbCr,
+};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, SerializeTuple, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+impl Serialize for Ch8 {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u8(u8::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Ch8 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        u8::deserialize(d).map(Ch8::new)
+    }
+}
+
+impl Serialize for Ch16 {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u16(u16::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Ch16 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        u16::deserialize(d).map(Ch16::new)
+    }
+}
+
+impl Serialize for Ch16f {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u16(self.to_bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for Ch16f {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        u16::deserialize(d).map(Ch16f::from_bits)
+    }
+}
+
+impl Serialize for Ch32 {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f32(f32::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Ch32 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        f32::deserialize(d).map(Ch32::new)
+    }
+}
+
+impl Serialize for Ch64 {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(f64::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Ch64 {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        f64::deserialize(d).map(Ch64::new)
+    }
+}
+
+/// Serialize a pixel as a tuple of channels, including alpha
+fn serialize_pixel<P, S>(p: &P, s: S) -> Result<S::Ok, S::Error>
+where
+    P: Pixel,
+    P::Chan: Serialize,
+    S: Serializer,
+{
+    let chans = p.channels();
+    let mut tup = s.serialize_tuple(chans.len())?;
+    for c in chans {
+        tup.serialize_element(c)?;
+    }
+    tup.end()
+}
+
+/// Visitor for a tuple of pixel channels
+struct PixelVisitor<P>(PhantomData<P>);
+
+impl<'de, P> Visitor<'de> for PixelVisitor<P>
+where
+    P: Pixel,
+    P::Chan: Deserialize<'de>,
+{
+    type Value = P;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a tuple of {} channels", P::CHANNELS)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<P, A::Error> {
+        let mut p = P::default();
+        for (i, c) in p.channels_mut().iter_mut().enumerate() {
+            *c = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        Ok(p)
+    }
+}
+
+/// Deserialize a pixel from a tuple of channels, including alpha
+fn deserialize_pixel<'de, P, D>(d: D) -> Result<P, D::Error>
+where
+    P: Pixel,
+    P::Chan: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    d.deserialize_tuple(P::CHANNELS, PixelVisitor(PhantomData))
+}
+
+/// Implement `Serialize` and `Deserialize` for a pixel type
+macro_rules! impl_serde_pixel {
+    ($pix:ident, [$($gen:ident),*], [$($bound:tt)*]) => {
+        impl<$($gen),*> Serialize for $pix<$($gen),*>
+        where
+            Self: Pixel,
+            <Self as ColorModel>::Chan: Serialize,
+            $($bound)*
+        {
+            fn serialize<S: Serializer>(
+                &self,
+                s: S,
+            ) -> Result<S::Ok, S::Error> {
+                serialize_pixel(self, s)
+            }
+        }
+
+        impl<'de, $($gen),*> Deserialize<'de> for $pix<$($gen),*>
+        where
+            Self: Pixel,
+            <Self as ColorModel>::Chan: Deserialize<'de>,
+            $($bound)*
+        {
+            fn deserialize<D: Deserializer<'de>>(
+                d: D,
+            ) -> Result<Self, D::Error> {
+                deserialize_pixel(d)
+            }
+        }
+    };
+}
+
+impl_serde_pixel!(Idx8, [], []);
+impl_serde_pixel!(Mask, [C], [C: Channel]);
+impl_serde_pixel!(Lab, [C, A], [C: Channel, A: AChannel<Chan = C>]);
+impl_serde_pixel!(Oklab, [C, A], [C: Channel, A: AChannel<Chan = C>]);
+impl_serde_pixel!(Xyz, [C, A], [C: Channel, A: AChannel<Chan = C>]);
+impl_serde_pixel!(
+    Cmyk,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_serde_pixel!(
+    Hsl,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_serde_pixel!(
+    Hsv,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_serde_pixel!(
+    YCbCr,
+    [C, A, G],
+    [C: Channel, A: AChannel<Chan = C>, G: gamma::Mode]
+);
+impl_serde_pixel!(
+    Bgr,
+    [C, A, M, G],
+    [C: Channel, A: AChannel<Chan = C>, M: alpha::Mode, G: gamma::Mode]
+);
+impl_serde_pixel!(
+    Gray,
+    [C, A, M, G],
+    [C: Channel, A: AChannel<Chan = C>, M: alpha::Mode, G: gamma::Mode]
+);
+impl_serde_pixel!(
+    Rgb,
+    [C, A, M, G],
+    [C: Channel, A: AChannel<Chan = C>, M: alpha::Mode, G: gamma::Mode]
+);
+
+/// Pixel data as a byte buffer
+struct PixelBytes<'a>(&'a [u8]);
+
+impl Serialize for PixelBytes<'_> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bytes(self.0)
+    }
+}
+
+/// Owned pixel data, deserialized from bytes or a sequence
+struct PixelBuf(Vec<u8>);
+
+/// Visitor for pixel data
+struct PixelBufVisitor;
+
+impl<'de> Visitor<'de> for PixelBufVisitor {
+    type Value = PixelBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pixel data bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<PixelBuf, E> {
+        Ok(PixelBuf(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<PixelBuf, E> {
+        Ok(PixelBuf(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<PixelBuf, A::Error> {
+        // Size hint is untrusted, so limit preallocation
+        let cap = seq.size_hint().unwrap_or(0).min(4096);
+        let mut v = Vec::with_capacity(cap);
+        while let Some(b) = seq.next_element()? {
+            v.push(b);
+        }
+        Ok(PixelBuf(v))
+    }
+}
+
+impl<'de> Deserialize<'de> for PixelBuf {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_byte_buf(PixelBufVisitor)
+    }
+}
+
+/// Serialized form of a `Raster`
+#[derive(serde::Deserialize)]
+#[serde(rename = "Raster")]
+struct RasterData {
+    width: u32,
+    height: u32,
+    pixels: PixelBuf,
+}
+
+impl RasterData {
+    /// Build a `Raster` from serialized data
+    fn into_raster<P: Pixel>(self) -> Result<Raster<P>, RasterError> {
+        // Check length before allocating, since the header is untrusted
+        let len = pixel_count::<P>(self.width, self.height)?;
+        let expected = len * std::mem::size_of::<P>();
+        let found = self.pixels.0.len();
+        if found != expected {
+            return Err(RasterError::LengthMismatch { expected, found });
+        }
+        let mut raster =
+            RasterBuilder::<P>::new().try_with_clear(self.width, self.height)?;
+        let bytes = raster.as_u8_slice_mut();
+        bytes.copy_from_slice(&self.pixels.0);
+        if cfg!(target_endian = "big") {
+            let n = std::mem::size_of::<P::Chan>();
+            for chan in bytes.chunks_exact_mut(n) {
+                chan.reverse();
+            }
+        }
+        raster.clamp_float_channels();
+        Ok(raster)
+    }
+}
+
+impl<P: Pixel> Serialize for Raster<P> {
+    /// Serialize width, height and pixel data bytes.
+    ///
+    /// Channels are in little-endian order on any host.
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.to_le_bytes();
+        let mut st = s.serialize_struct("Raster", 3)?;
+        st.serialize_field("width", &self.width())?;
+        st.serialize_field("height", &self.height())?;
+        st.serialize_field("pixels", &PixelBytes(&bytes))?;
+        st.end()
+    }
+}
+
+impl<'de, P: Pixel> Deserialize<'de> for Raster<P> {
+    /// Deserialize width, height and pixel data bytes.
+    ///
+    /// Returns an error if the length of pixel data does not match.
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        RasterData::deserialize(d)?
+            .into_raster()
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn round_trip<T>(value: &T) -> (T, T)
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let bin = bincode::serialize(value).unwrap();
+        let json = serde_json::to_string(value).unwrap();
+        (
+            bincode::deserialize(&bin).unwrap(),
+            serde_json::from_str(&json).unwrap(),
+        )
+    }
+
+    #[test]
+    fn rasters() {
+        let r = RasterBuilder::<SRgba8>::new().with_fn(5, 3, |x, y| {
+            SRgba8::with_alpha(x as u8, y as u8, 0x80, 0xFF - x as u8)
+        });
+        assert_eq!(round_trip(&r), (r.clone(), r));
+        let r = RasterBuilder::<Rgb16>::new().with_fn(4, 4, |x, y| {
+            Rgb16::new(x as u16 * 0x1111, y as u16 * 0x2222, 0xABCD)
+        });
+        assert_eq!(round_trip(&r), (r.clone(), r));
+        let r = RasterBuilder::<Gray32>::new().with_fn(3, 2, |x, y| {
+            Gray32::new(x as f32 * 0.25 + y as f32 * 0.1)
+        });
+        assert_eq!(round_trip(&r), (r.clone(), r));
+        let r = RasterBuilder::<Rgba16f>::new()
+            .with_color(2, 2, Rgba16f::with_alpha(0.25, 0.5, 1.0, 0.75));
+        assert_eq!(round_trip(&r), (r.clone(), r));
+        let r = RasterBuilder::<Mask8>::new().with_clear(0, 0);
+        assert_eq!(round_trip(&r), (r.clone(), r));
+    }
+
+    #[test]
+    fn raster_json() {
+        let r = RasterBuilder::<Gray16>::new().with_fn(2, 1, |x, _y| {
+            Gray16::new(0x0102 + x as u16)
+        });
+        let json = serde_json::to_string(&r).unwrap();
+        assert_eq!(json, r#"{"width":2,"height":1,"pixels":[2,1,3,1]}"#);
+    }
+
+    #[test]
+    fn length_mismatch() {
+        let json = r#"{"width":2,"height":2,"pixels":[1,2,3]}"#;
+        let r: Result<Raster<Mask8>, _> = serde_json::from_str(json);
+        let err = r.unwrap_err().to_string();
+        assert!(err.contains("does not match"), "{}", err);
+        let json = r#"{"width":4294967295,"height":4294967295,"pixels":[]}"#;
+        assert!(serde_json::from_str::<Raster<SRgb8>>(json).is_err());
+        // Must not allocate the pixels of a huge raster
+        let json = r#"{"width":65535,"height":65535,"pixels":[0,0,0,0]}"#;
+        let err = serde_json::from_str::<Raster<Rgba64>>(json).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+    }
+
+    #[test]
+    fn clamp_floats() {
+        let mut bytes = vec![];
+        for v in [-1.0f32, 0.5, 2.0, f32::NAN].iter() {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        let bin = bincode::serialize(&(4u32, 1u32, bytes)).unwrap();
+        let r: Raster<Mask32> = bincode::deserialize(&bin).unwrap();
+        let v: Vec<f32> =
+            r.as_slice().iter().map(|p| f32::from(p.alpha())).collect();
+        assert_eq!(v, vec![0.0, 0.5, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn regions() {
+        let reg = Region::new(-5, 10, 20, 30);
+        assert_eq!(round_trip(&reg), (reg, reg));
+        let json = serde_json::to_string(&reg).unwrap();
+        assert_eq!(json, r#"{"x":-5,"y":10,"width":20,"height":30}"#);
+    }
+
+    #[test]
+    fn pixels() {
+        let p = SRgba16::with_alpha(0x1234, 0x5678, 0x9ABC, 0xDEF0);
+        assert_eq!(round_trip(&p), (p, p));
+        let p = Hsl32::new(0.25, 0.5, 0.75);
+        assert_eq!(round_trip(&p), (p, p));
+        let p = Idx8::new(7);
+        assert_eq!(round_trip(&p), (p, p));
+        let p = Mask::<Ch16f>::new(0.5);
+        assert_eq!(round_trip(&p), (p, p));
+        let json = serde_json::to_string(&SRgb8::new(1, 2, 3)).unwrap();
+        assert_eq!(json, "[1,2,3]");
+        let p: Gray32 = serde_json::from_str("[1.5]").unwrap();
+        assert_eq!(p, Gray32::new(1.0));
+        assert!(serde_json::from_str::<SRgb8>("[1,2]").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+// shared.rs    Shared rasters.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{AsRasterView, Pixel, Raster, RasterView};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// Immutable [Raster](struct.Raster.html) with shared ownership.
+///
+/// Cloning a `SharedRaster` is cheap: pixel data is reference counted, not
+/// copied.  All read-only `Raster` methods are available through `Deref`.
+///
+/// To modify pixels, use
+/// [make_mut](struct.SharedRaster.html#method.make_mut) or
+/// [into_raster](struct.SharedRaster.html#method.into_raster).  The pixel
+/// data is copied only if other clones still share it.
+///
+/// ### Share a raster
+/// ```
+/// # use pix::*;
+/// let r = RasterBuilder::<SRgb8>::new().with_color_bars(64, 64);
+/// let shared = SharedRaster::from(r);
+/// let other = shared.clone();
+/// assert!(SharedRaster::ptr_eq(&shared, &other));
+/// assert_eq!(other.pixel(0, 0), shared.pixel(0, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SharedRaster<P: Pixel> {
+    raster: Arc<Raster<P>>,
+}
+
+impl<P: Pixel> From<Raster<P>> for SharedRaster<P> {
+    /// Share a `Raster`, without copying pixel data.
+    fn from(raster: Raster<P>) -> Self {
+        SharedRaster {
+            raster: Arc::new(raster),
+        }
+    }
+}
+
+impl<P: Pixel> From<SharedRaster<P>> for Raster<P> {
+    /// Get a `Raster`, copying pixel data only if it is still shared.
+    fn from(shared: SharedRaster<P>) -> Self {
+        shared.into_raster()
+    }
+}
+
+impl<P: Pixel> Deref for SharedRaster<P> {
+    type Target = Raster<P>;
+
+    fn deref(&self) -> &Raster<P> {
+        &self.raster
+    }
+}
+
+impl<P: Pixel> AsRef<Raster<P>> for SharedRaster<P> {
+    fn as_ref(&self) -> &Raster<P> {
+        &self.raster
+    }
+}
+
+impl<P: Pixel> AsRasterView<P> for SharedRaster<P> {
+    fn as_view(&self) -> RasterView<'_, P> {
+        self.raster.as_view()
+    }
+}
+
+impl<P: Pixel> SharedRaster<P> {
+    /// Check if two `SharedRaster`s share the same pixel data.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.raster, &other.raster)
+    }
+    /// Get a mutable reference to the `Raster`.
+    ///
+    /// If the pixel data is shared with any clones, it is copied first
+    /// (copy-on-write).  Other clones are not affected.
+    ///
+    /// ### Modify a copy
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+    /// let shared = SharedRaster::from(r);
+    /// let mut copy = shared.clone();
+    /// copy.make_mut().set_pixel(1, 1, SGray8::new(0xFF));
+    /// assert_eq!(copy.pixel(1, 1), SGray8::new(0xFF));
+    /// assert_eq!(shared.pixel(1, 1), SGray8::new(0));
+    /// ```
+    pub fn make_mut(&mut self) -> &mut Raster<P> {
+        Arc::make_mut(&mut self.raster)
+    }
+    /// Convert into a `Raster`.
+    ///
+    /// If the pixel data is shared with any clones, it is copied.
+    pub fn into_raster(self) -> Raster<P> {
+        Arc::try_unwrap(self.raster).unwrap_or_else(|r| (*r).clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn numbered() -> Raster<SGray8> {
+        RasterBuilder::new()
+            .with_fn(5, 4, |x, y| SGray8::new((y * 5 + x) as u8))
+    }
+
+    #[test]
+    fn unique_no_copy() {
+        let r = numbered();
+        let ptr = r.as_slice().as_ptr();
+        let mut shared = SharedRaster::from(r);
+        assert_eq!(shared.as_slice().as_ptr(), ptr);
+        shared.make_mut().set_pixel(0, 0, SGray8::new(0xFF));
+        assert_eq!(shared.as_slice().as_ptr(), ptr);
+        let r = Raster::from(shared);
+        assert_eq!(r.as_slice().as_ptr(), ptr);
+        assert_eq!(r.pixel(0, 0), SGray8::new(0xFF));
+    }
+
+    #[test]
+    fn copy_on_write() {
+        let shared = SharedRaster::from(numbered());
+        let ptr = shared.as_slice().as_ptr();
+        let mut copy = shared.clone();
+        assert!(SharedRaster::ptr_eq(&shared, &copy));
+        assert_eq!(copy.as_slice().as_ptr(), ptr);
+        copy.make_mut().set_pixel(4, 3, SGray8::new(0));
+        assert!(!SharedRaster::ptr_eq(&shared, &copy));
+        assert_ne!(copy.as_slice().as_ptr(), ptr);
+        assert_eq!(shared.pixel(4, 3), SGray8::new(19));
+        assert_eq!(copy.pixel(4, 3), SGray8::new(0));
+        let r = shared.clone().into_raster();
+        assert_ne!(r.as_slice().as_ptr(), ptr);
+        assert_eq!(r, *shared);
+        // Now unique again
+        assert_eq!(shared.into_raster().as_slice().as_ptr(), ptr);
+    }
+
+    #[test]
+    fn read_only() {
+        let shared = SharedRaster::from(numbered());
+        assert_eq!((shared.width(), shared.height()), (5, 4));
+        assert_eq!(shared.get_pixel(2, 1), Some(SGray8::new(7)));
+        let v: Vec<u8> = shared
+            .region_iter((3, 2, 2, 2))
+            .map(|p| u8::from(p.value()))
+            .collect();
+        assert_eq!(v, [13, 14, 18, 19]);
+        assert_eq!(shared.as_u8_slice()[6], 6);
+        assert_eq!(shared.as_view().pixel(1, 1), SGray8::new(6));
+    }
+}
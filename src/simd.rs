@@ -0,0 +1,229 @@
+// simd.rs      SIMD pixel conversions (simd feature).
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{
+    Gray16, Gray8, GrayAlpha16, GrayAlpha8, Mask16, Mask8, Pixel, Rgb16,
+    Rgb8, Rgba16, Rgba16p, Rgba8, Rgba8p, SGray16, SGray8, SGrayAlpha16,
+    SGrayAlpha8, SRgb16, SRgb8, SRgba16, SRgba16p, SRgba8, SRgba8p,
+};
+use std::any::TypeId;
+use std::arch::x86_64::*;
+use std::mem::size_of_val;
+use std::slice;
+
+/// Kind of SIMD conversion
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    /// Widen 8-bit channels to 16 bits
+    Widen,
+    /// Drop alpha from 8-bit RGBA
+    DropAlpha,
+    /// Expand 8-bit gray to RGB
+    GrayToRgb,
+}
+
+/// Get the `TypeId`s of a pair of pixel formats
+fn pair<S: Pixel, D: Pixel>() -> (TypeId, TypeId) {
+    (TypeId::of::<S>(), TypeId::of::<D>())
+}
+
+/// Get the kind of SIMD conversion between two pixel formats.
+///
+/// Only pairs with the same alpha mode and gamma are included, so the
+/// result matches `Pixel::convert` exactly.
+fn kind<S: Pixel, D: Pixel>() -> Option<Kind> {
+    let p = pair::<S, D>();
+    if [
+        pair::<Gray8, Gray16>(),
+        pair::<SGray8, SGray16>(),
+        pair::<GrayAlpha8, GrayAlpha16>(),
+        pair::<SGrayAlpha8, SGrayAlpha16>(),
+        pair::<Mask8, Mask16>(),
+        pair::<Rgb8, Rgb16>(),
+        pair::<SRgb8, SRgb16>(),
+        pair::<Rgba8, Rgba16>(),
+        pair::<SRgba8, SRgba16>(),
+        pair::<Rgba8p, Rgba16p>(),
+        pair::<SRgba8p, SRgba16p>(),
+    ]
+    .contains(&p)
+    {
+        Some(Kind::Widen)
+    } else if p == pair::<Rgba8, Rgb8>() || p == pair::<SRgba8, SRgb8>() {
+        Some(Kind::DropAlpha)
+    } else if p == pair::<Gray8, Rgb8>() || p == pair::<SGray8, SRgb8>() {
+        Some(Kind::GrayToRgb)
+    } else {
+        None
+    }
+}
+
+/// Convert a row of pixels using SIMD instructions.
+///
+/// * `dst` Destination pixels.
+/// * `src` Source pixels (same length as `dst`).
+///
+/// Returns `false` if there is no SIMD path for the pixel formats, or the
+/// CPU does not support it.
+pub(crate) fn convert_row<S, D>(dst: &mut [D], src: &[S]) -> bool
+where
+    S: Pixel,
+    D: Pixel,
+{
+    debug_assert_eq!(dst.len(), src.len());
+    let kind = match kind::<S, D>() {
+        Some(kind) => kind,
+        None => return false,
+    };
+    if kind != Kind::Widen && !is_x86_feature_detected!("ssse3") {
+        return false;
+    }
+    // Safety: all pixel formats above are plain 8/16-bit channels
+    let src = unsafe {
+        slice::from_raw_parts(src.as_ptr() as *const u8, size_of_val(src))
+    };
+    let dst = unsafe {
+        slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut u8, size_of_val(dst))
+    };
+    match kind {
+        Kind::Widen => widen(dst, src),
+        Kind::DropAlpha => unsafe { drop_alpha(dst, src) },
+        Kind::GrayToRgb => unsafe { gray_to_rgb(dst, src) },
+    }
+    true
+}
+
+/// Widen 8-bit channels to little-endian 16 bits (`v * 257`).
+fn widen(dst: &mut [u8], src: &[u8]) {
+    let n = src.len() / 16 * 16;
+    let (src, tail) = src.split_at(n);
+    let (dst, dtail) = dst.split_at_mut(n * 2);
+    for (d, s) in dst.chunks_exact_mut(32).zip(src.chunks_exact(16)) {
+        // SSE2 is always available on x86_64
+        unsafe {
+            let v = _mm_loadu_si128(s.as_ptr() as *const __m128i);
+            let d = d.as_mut_ptr() as *mut __m128i;
+            _mm_storeu_si128(d, _mm_unpacklo_epi8(v, v));
+            _mm_storeu_si128(d.add(1), _mm_unpackhi_epi8(v, v));
+        }
+    }
+    for (d, s) in dtail.chunks_exact_mut(2).zip(tail) {
+        d[0] = *s;
+        d[1] = *s;
+    }
+}
+
+/// Drop the alpha channel from 8-bit RGBA, leaving RGB.
+#[target_feature(enable = "ssse3")]
+unsafe fn drop_alpha(dst: &mut [u8], src: &[u8]) {
+    let shuf =
+        _mm_setr_epi8(0, 1, 2, 4, 5, 6, 8, 9, 10, 12, 13, 14, -1, -1, -1, -1);
+    let len = src.len() / 4;
+    let mut i = 0;
+    // Each store writes 16 bytes, but only 12 are used
+    while i + 6 <= len {
+        let v = _mm_loadu_si128(src.as_ptr().add(i * 4) as *const __m128i);
+        let d = dst.as_mut_ptr().add(i * 3) as *mut __m128i;
+        _mm_storeu_si128(d, _mm_shuffle_epi8(v, shuf));
+        i += 4;
+    }
+    let dst = dst[i * 3..].chunks_exact_mut(3);
+    for (d, s) in dst.zip(src[i * 4..].chunks_exact(4)) {
+        d.copy_from_slice(&s[..3]);
+    }
+}
+
+/// Expand 8-bit gray to RGB.
+#[target_feature(enable = "ssse3")]
+unsafe fn gray_to_rgb(dst: &mut [u8], src: &[u8]) {
+    let m0 = _mm_setr_epi8(0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 4, 5);
+    let m1 = _mm_setr_epi8(5, 5, 6, 6, 6, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10, 10);
+    let m2 = _mm_setr_epi8(
+        10, 11, 11, 11, 12, 12, 12, 13, 13, 13, 14, 14, 14, 15, 15, 15,
+    );
+    let n = src.len() / 16 * 16;
+    let (src, tail) = src.split_at(n);
+    let (dst, dtail) = dst.split_at_mut(n * 3);
+    for (d, s) in dst.chunks_exact_mut(48).zip(src.chunks_exact(16)) {
+        let v = _mm_loadu_si128(s.as_ptr() as *const __m128i);
+        let d = d.as_mut_ptr() as *mut __m128i;
+        _mm_storeu_si128(d, _mm_shuffle_epi8(v, m0));
+        _mm_storeu_si128(d.add(1), _mm_shuffle_epi8(v, m1));
+        _mm_storeu_si128(d.add(2), _mm_shuffle_epi8(v, m2));
+    }
+    for (d, s) in dtail.chunks_exact_mut(3).zip(tail) {
+        d.copy_from_slice(&[*s; 3]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+    use super::*;
+
+    /// Simple xorshift random bytes
+    fn random_bytes(len: usize, seed: &mut u32) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                *seed ^= *seed << 13;
+                *seed ^= *seed >> 17;
+                *seed ^= *seed << 5;
+                *seed as u8
+            })
+            .collect()
+    }
+
+    fn check<S, D>(kind: Kind)
+    where
+        S: Pixel<Chan = Ch8>,
+        D: Pixel,
+        D::Chan: From<Ch8>,
+    {
+        assert_eq!(super::kind::<S, D>(), Some(kind));
+        let mut seed = 0x1234_5678;
+        for width in (0..40).chain(1000..1003) {
+            let len = width * std::mem::size_of::<S>();
+            let buf = random_bytes(len * 3, &mut seed);
+            let w = width as u32;
+            let src = RasterBuilder::<S>::new().with_u8_buffer(w, 3, buf);
+            let mut dst = RasterBuilder::<D>::new().with_clear(w, 3);
+            let mut scalar = dst.clone();
+            for (d, s) in dst.rows_mut().zip(src.rows()) {
+                assert!(convert_row(d, s));
+            }
+            let pixels = scalar.as_slice_mut().iter_mut();
+            for (d, s) in pixels.zip(src.as_slice()) {
+                *d = s.convert();
+            }
+            assert_eq!(dst.as_u8_slice(), scalar.as_u8_slice());
+        }
+    }
+
+    #[test]
+    fn widen() {
+        check::<Gray8, Gray16>(Kind::Widen);
+        check::<SGrayAlpha8, SGrayAlpha16>(Kind::Widen);
+        check::<Mask8, Mask16>(Kind::Widen);
+        check::<SRgb8, SRgb16>(Kind::Widen);
+        check::<Rgba8p, Rgba16p>(Kind::Widen);
+    }
+
+    #[test]
+    fn drop_alpha() {
+        check::<Rgba8, Rgb8>(Kind::DropAlpha);
+        check::<SRgba8, SRgb8>(Kind::DropAlpha);
+    }
+
+    #[test]
+    fn gray_to_rgb() {
+        check::<Gray8, Rgb8>(Kind::GrayToRgb);
+        check::<SGray8, SRgb8>(Kind::GrayToRgb);
+    }
+
+    #[test]
+    fn no_simd() {
+        assert_eq!(kind::<SRgba8p, SRgb8>(), None);
+        assert_eq!(kind::<SGray8, Rgb8>(), None);
+    }
+}
@@ -0,0 +1,340 @@
+// strided.rs   Rasters with padded rows.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::raster::pixel_count;
+use crate::{Ch8, Pixel, Raster, RasterBuilder, RasterError, Region};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Image with padding at the end of each row.
+///
+/// Buffers from GPU readbacks, DMA and window system surfaces often have
+/// rows aligned to a multiple of some number of bytes.  A `StridedRaster`
+/// wraps such a buffer without repacking it.
+///
+/// Use [RasterBuilder](struct.RasterBuilder.html)::`with_u8_buffer_stride`
+/// to create.  To get a tightly packed `Raster`, use
+/// [into_raster](struct.StridedRaster.html#method.into_raster).
+///
+/// ### Wrap a padded buffer
+/// ```
+/// # use pix::*;
+/// // 3 pixels per row, padded to 16 bytes
+/// let buffer = vec![0x80; 16 * 4];
+/// let r = RasterBuilder::<SRgb8>::new()
+///     .with_u8_buffer_stride(3, 4, 16, buffer);
+/// assert_eq!(r.stride(), 16);
+/// assert_eq!(r.pixel(2, 3), SRgb8::new(0x80, 0x80, 0x80));
+/// let packed = r.into_raster();
+/// assert_eq!(packed.as_u8_slice().len(), 3 * 4 * 3);
+/// ```
+#[derive(Clone)]
+pub struct StridedRaster<P: Pixel> {
+    width: u32,
+    height: u32,
+    stride: usize,
+    buffer: Box<[u8]>,
+    _pixel: PhantomData<P>,
+}
+
+impl<P: Pixel> fmt::Debug for StridedRaster<P> {
+    /// Format dimensions, pixel type and stride.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StridedRaster")
+            .field("pixel", &format_args!("{}", std::any::type_name::<P>()))
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("stride", &self.stride)
+            .finish()
+    }
+}
+
+impl<P: Pixel<Chan = Ch8>> From<StridedRaster<P>> for Raster<P> {
+    /// Get a tightly packed `Raster`.
+    fn from(raster: StridedRaster<P>) -> Self {
+        raster.into_raster()
+    }
+}
+
+impl<P: Pixel> RasterBuilder<P> {
+    /// Build a [StridedRaster](struct.StridedRaster.html) from a `u8`
+    /// buffer with padded rows.
+    ///
+    /// The buffer is reused without copying.
+    ///
+    /// * `B` Owned pixed type (`Vec` or boxed slice).
+    /// * `width` Width of `Raster`.
+    /// * `height` Height of `Raster`.
+    /// * `stride` Number of bytes from the start of one row to the next.
+    /// * `buffer` Buffer of pixel data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stride` is less than `width` * `std::mem::size_of::<P>()`,
+    /// or if `buffer` is too short to hold `height` rows.
+    pub fn with_u8_buffer_stride<B>(
+        self,
+        width: u32,
+        height: u32,
+        stride: usize,
+        buffer: B,
+    ) -> StridedRaster<P>
+    where
+        B: Into<Box<[u8]>>,
+        P: Pixel<Chan = Ch8>,
+    {
+        self.try_with_u8_buffer_stride(width, height, stride, buffer)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to build a [StridedRaster](struct.StridedRaster.html) from a `u8`
+    /// buffer with padded rows.
+    ///
+    /// The last row does not need padding, so a buffer of
+    /// `stride` * (`height` - 1) + `width` * `std::mem::size_of::<P>()`
+    /// bytes is long enough.
+    ///
+    /// ### Stride too small
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgba8>::new()
+    ///     .try_with_u8_buffer_stride(4, 2, 12, vec![0; 32]);
+    /// let e = RasterError::StrideTooSmall { minimum: 16, found: 12 };
+    /// assert_eq!(r.err(), Some(e));
+    /// ```
+    pub fn try_with_u8_buffer_stride<B>(
+        self,
+        width: u32,
+        height: u32,
+        stride: usize,
+        buffer: B,
+    ) -> Result<StridedRaster<P>, RasterError>
+    where
+        B: Into<Box<[u8]>>,
+        P: Pixel<Chan = Ch8>,
+    {
+        let buffer = buffer.into();
+        let row_len = pixel_count::<P>(width, 1)? * std::mem::size_of::<P>();
+        if stride < row_len {
+            return Err(RasterError::StrideTooSmall {
+                minimum: row_len,
+                found: stride,
+            });
+        }
+        let expected = match height {
+            0 => 0,
+            _ => stride
+                .checked_mul(height as usize - 1)
+                .and_then(|n| n.checked_add(row_len))
+                .ok_or(RasterError::Overflow)?,
+        };
+        if buffer.len() < expected {
+            let found = buffer.len();
+            return Err(RasterError::LengthMismatch { expected, found });
+        }
+        Ok(StridedRaster {
+            width,
+            height,
+            stride,
+            buffer,
+            _pixel: PhantomData,
+        })
+    }
+}
+
+impl<P: Pixel<Chan = Ch8>> StridedRaster<P> {
+    /// Get width of `StridedRaster`.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of `StridedRaster`.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get number of bytes from the start of one row to the next.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+    /// Get `Region` of entire `StridedRaster`.
+    pub fn region(&self) -> Region {
+        Region::new(0, 0, self.width, self.height)
+    }
+    /// Get one pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is outside of the `StridedRaster`.
+    pub fn pixel(&self, x: u32, y: u32) -> P {
+        match self.get_pixel(x, y) {
+            Some(p) => p,
+            None => panic!(
+                "pixel ({}, {}) out of bounds for {}x{} raster",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+    /// Get one pixel, or `None` if `x` or `y` is outside of the
+    /// `StridedRaster`.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<P> {
+        if x < self.width && y < self.height {
+            Some(self.as_slice_row(y)[x as usize])
+        } else {
+            None
+        }
+    }
+    /// Get view of a row of pixels as a slice, without padding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is outside of the `StridedRaster`.
+    pub fn as_slice_row(&self, y: u32) -> &[P] {
+        let row = self.as_u8_slice_row(y);
+        // Pixels with 8-bit channels have an alignment of 1
+        let (head, pixels, tail) = unsafe { row.align_to::<P>() };
+        debug_assert!(head.is_empty() && tail.is_empty());
+        pixels
+    }
+    /// Get view of a row of pixels as a `u8` slice, without padding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `y` is outside of the `StridedRaster`.
+    pub fn as_u8_slice_row(&self, y: u32) -> &[u8] {
+        assert!(y < self.height, "row {} out of bounds", y);
+        let s = y as usize * self.stride;
+        let t = s + self.width as usize * std::mem::size_of::<P>();
+        &self.buffer[s..t]
+    }
+    /// Get view of the entire buffer as a `u8` slice.
+    ///
+    /// This *includes* the padding at the end of each row.  Use
+    /// [as_u8_slice_row](struct.StridedRaster.html#method.as_u8_slice_row)
+    /// to get rows without padding.
+    pub fn as_u8_slice(&self) -> &[u8] {
+        &self.buffer
+    }
+    /// Get an `Iterator` of rows, from top to bottom.
+    ///
+    /// Each row is a slice of pixels, without padding.
+    pub fn rows(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &[P]> + ExactSizeIterator {
+        (0..self.height).map(move |y| self.as_slice_row(y))
+    }
+    /// Get an `Iterator` of pixels within a `Region`, in row-major order.
+    ///
+    /// * `reg` Region within `StridedRaster`.
+    ///
+    /// The region is clipped to the `StridedRaster` bounds.
+    pub fn region_iter<R>(&self, reg: R) -> impl Iterator<Item = P> + '_
+    where
+        R: Into<Region>,
+    {
+        let reg = reg.into().intersection(self.region());
+        // Empty intersections have zero width and height
+        let (x0, y0) = (reg.x() as usize, reg.y() as u32);
+        let (x1, y1) = (x0 + reg.width() as usize, y0 + reg.height());
+        (y0..y1).flat_map(move |y| {
+            self.as_slice_row(y)[x0..x1].iter().cloned()
+        })
+    }
+    /// Convert into a tightly packed `Raster`.
+    ///
+    /// If there is no padding, the buffer is reused without copying.
+    pub fn into_raster(self) -> Raster<P> {
+        let (width, height) = (self.width, self.height);
+        let len = width as usize * height as usize * std::mem::size_of::<P>();
+        if self.buffer.len() == len {
+            RasterBuilder::new().with_u8_buffer(width, height, self.buffer)
+        } else {
+            self.to_raster()
+        }
+    }
+    /// Copy into a tightly packed `Raster`.
+    pub fn to_raster(&self) -> Raster<P> {
+        let len = self.width as usize * self.height as usize;
+        let mut pixels = Vec::with_capacity(len);
+        for row in self.rows() {
+            pixels.extend_from_slice(row);
+        }
+        RasterBuilder::new().with_pixels(self.width, self.height, pixels)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    /// Build a buffer of numbered pixels, with padding bytes of 0xEE
+    fn padded(width: u32, height: u32, stride: usize) -> Vec<u8> {
+        let mut buf = vec![0xEE; stride * height as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let v = (y * 10 + x) as u8;
+                let i = y as usize * stride + x as usize * 3;
+                buf[i..i + 3].copy_from_slice(&[v, v, 0xFF - v]);
+            }
+        }
+        buf
+    }
+
+    #[test]
+    fn padded_rows() {
+        let buf = padded(5, 3, 32);
+        let r = RasterBuilder::<SRgb8>::new()
+            .with_u8_buffer_stride(5, 3, 32, buf);
+        assert!(32 > 5 * std::mem::size_of::<SRgb8>());
+        assert_eq!((r.width(), r.height(), r.stride()), (5, 3, 32));
+        assert_eq!(r.pixel(4, 2), SRgb8::new(24, 24, 0xFF - 24));
+        assert_eq!(r.get_pixel(5, 0), None);
+        assert_eq!(r.get_pixel(0, 3), None);
+        assert_eq!(r.rows().len(), 3);
+        assert!(r.rows().all(|row| row.len() == 5));
+        assert_eq!(r.as_u8_slice_row(1).len(), 15);
+        assert_eq!(r.as_u8_slice().len(), 96);
+        assert_eq!(r.as_u8_slice()[15], 0xEE);
+        let v: Vec<u8> = r
+            .region_iter((3, 1, 4, 4))
+            .map(|p| u8::from(p.red()))
+            .collect();
+        assert_eq!(v, [13, 14, 23, 24]);
+        let packed = r.into_raster();
+        let expected = RasterBuilder::<SRgb8>::new().with_fn(5, 3, |x, y| {
+            let v = (y * 10 + x) as u8;
+            SRgb8::new(v, v, 0xFF - v)
+        });
+        assert_eq!(packed, expected);
+    }
+
+    #[test]
+    fn unpadded_last_row() {
+        let mut buf = padded(2, 2, 8);
+        buf.truncate(14);
+        let r = RasterBuilder::<SRgb8>::new()
+            .try_with_u8_buffer_stride(2, 2, 8, buf)
+            .unwrap();
+        assert_eq!(r.rows().next_back().unwrap().len(), 2);
+        assert_eq!(r.to_raster().pixel(1, 1), SRgb8::new(11, 11, 0xF4));
+        let e = RasterBuilder::<SRgb8>::new()
+            .try_with_u8_buffer_stride(2, 2, 8, vec![0; 13]);
+        let mismatch = RasterError::LengthMismatch {
+            expected: 14,
+            found: 13,
+        };
+        assert_eq!(e.err(), Some(mismatch));
+    }
+
+    #[test]
+    fn no_padding() {
+        let buf = vec![7; 4 * 3 * 2];
+        let ptr = buf.as_ptr();
+        let r = RasterBuilder::<SRgba8>::new()
+            .with_u8_buffer_stride(3, 2, 12, buf);
+        let packed = r.into_raster();
+        assert_eq!(packed.as_u8_slice().as_ptr(), ptr);
+        let r = RasterBuilder::<SGray8>::new()
+            .with_u8_buffer_stride(0, 0, 0, vec![]);
+        assert_eq!(r.rows().count(), 0);
+        assert_eq!(r.into_raster().width(), 0);
+    }
+}
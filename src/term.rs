@@ -0,0 +1,177 @@
+// term.rs      Terminal rendering.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Ch8, Pixel, Raster, SRgb8, SRgba8};
+use std::fmt::Write;
+
+/// Upper half block character
+const UPPER_HALF: char = '\u{2580}';
+
+/// ASCII shades, from dark to light
+const SHADES: &[u8] = b" .:-=+*#%@";
+
+/// Get the luma of an sRGB color, from 0 to 255
+fn luma(clr: SRgb8) -> u32 {
+    let r = u32::from(u8::from(clr.red()));
+    let g = u32::from(u8::from(clr.green()));
+    let b = u32::from(u8::from(clr.blue()));
+    (r * 299 + g * 587 + b * 114 + 500) / 1000
+}
+
+/// Composite an sRGB color over black
+fn over_black(clr: SRgba8) -> SRgb8 {
+    let a = u32::from(u8::from(clr.alpha()));
+    let blend = |c: Ch8| ((u32::from(u8::from(c)) * a + 127) / 255) as u8;
+    SRgb8::new(blend(clr.red()), blend(clr.green()), blend(clr.blue()))
+}
+
+/// Write an ANSI 24-bit color escape sequence.
+///
+/// * `layer` 38 for foreground, or 48 for background.
+fn write_color(s: &mut String, layer: u8, clr: SRgb8) {
+    let r = u8::from(clr.red());
+    let g = u8::from(clr.green());
+    let b = u8::from(clr.blue());
+    let _ = write!(s, "\x1B[{};2;{};{};{}m", layer, r, g, b);
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Render as a string of ANSI 24-bit color escape sequences.
+    ///
+    /// * `max_width` Maximum width, in terminal columns.
+    ///
+    /// Same as
+    /// [to_ansi_string_with](struct.Raster.html#method.to_ansi_string_with),
+    /// with color.
+    ///
+    /// ### Print a raster
+    /// ```
+    /// # use pix::*;
+    /// let r = RasterBuilder::<SRgb8>::new().with_color_bars(14, 4);
+    /// print!("{}", r.to_ansi_string(80));
+    /// ```
+    pub fn to_ansi_string(&self, max_width: u32) -> String
+    where
+        Ch8: From<P::Chan>,
+    {
+        self.to_ansi_string_with(max_width, true)
+    }
+    /// Render as a string for printing to a terminal.
+    ///
+    /// * `max_width` Maximum width, in terminal columns.
+    /// * `color` If true, use ANSI 24-bit color escape sequences; otherwise,
+    ///   use plain ASCII shades.
+    ///
+    /// Each character cell covers two rows of pixels.  With color, the upper
+    /// half block `▀` is drawn with the top pixel as foreground and the
+    /// bottom pixel as background.  Translucent pixels are composited over
+    /// black, so [Mask](struct.Mask.html) formats are rendered as gray.
+    ///
+    /// A `Raster` wider than `max_width` is downscaled to fit.  An empty
+    /// `Raster` (or zero `max_width`) produces an empty string.
+    ///
+    /// ### Print a mask as ASCII
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<Mask8>::new().with_clear(4, 2);
+    /// r.set_pixel(1, 0, Mask8::new(0xFF));
+    /// r.set_pixel(1, 1, Mask8::new(0xFF));
+    /// assert_eq!(r.to_ansi_string_with(80, false), " @  \n");
+    /// ```
+    pub fn to_ansi_string_with(&self, max_width: u32, color: bool) -> String
+    where
+        Ch8: From<P::Chan>,
+    {
+        let mut s = String::new();
+        if self.width() == 0 || self.height() == 0 || max_width == 0 {
+            return s;
+        }
+        let mut rgb = self.map(|p| over_black(p.convert()));
+        if rgb.width() > max_width {
+            let factor = rgb.width().div_ceil(max_width);
+            rgb = rgb.downscale_by(factor, factor);
+        }
+        let mut rows = rgb.rows();
+        while let Some(top) = rows.next() {
+            let bottom = rows.next();
+            for (x, t) in top.iter().enumerate() {
+                let b = bottom.map(|row| row[x]);
+                if color {
+                    write_color(&mut s, 38, *t);
+                    match b {
+                        Some(b) => write_color(&mut s, 48, b),
+                        None => s.push_str("\x1B[49m"),
+                    }
+                    s.push(UPPER_HALF);
+                } else {
+                    let v = match b {
+                        Some(b) => (luma(*t) + luma(b)).div_ceil(2),
+                        None => luma(*t),
+                    };
+                    let i = v as usize * (SHADES.len() - 1) / 255;
+                    s.push(char::from(SHADES[i]));
+                }
+            }
+            if color {
+                s.push_str("\x1B[0m");
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn ansi() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(2, 3);
+        r.set_pixel(0, 0, SRgb8::new(1, 2, 3));
+        r.set_pixel(1, 1, SRgb8::new(4, 5, 6));
+        r.set_pixel(1, 2, SRgb8::new(7, 8, 9));
+        assert_eq!(
+            r.to_ansi_string(80),
+            "\x1B[38;2;1;2;3m\x1B[48;2;0;0;0m▀\
+             \x1B[38;2;0;0;0m\x1B[48;2;4;5;6m▀\x1B[0m\n\
+             \x1B[38;2;0;0;0m\x1B[49m▀\
+             \x1B[38;2;7;8;9m\x1B[49m▀\x1B[0m\n"
+        );
+    }
+
+    #[test]
+    fn gray() {
+        let r = RasterBuilder::<SGray8>::new()
+            .with_fn(10, 2, |x, _y| SGray8::new((x * 255).div_ceil(9) as u8));
+        assert_eq!(r.to_ansi_string_with(80, false), " .:-=+*#%@\n");
+        let r = RasterBuilder::new().with_color(1, 1, Mask8::new(0x80));
+        assert_eq!(
+            r.to_ansi_string(80),
+            "\x1B[38;2;128;128;128m\x1B[49m▀\x1B[0m\n"
+        );
+    }
+
+    #[test]
+    fn downsample() {
+        let r = RasterBuilder::<SRgba8>::new()
+            .with_color(100, 40, SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0xFF));
+        let s = r.to_ansi_string_with(30, false);
+        let lines: Vec<_> = s.lines().collect();
+        // Downscaled by 4 to 25 x 10
+        assert_eq!(lines.len(), 5);
+        assert!(lines.iter().all(|l| *l == "@".repeat(25)));
+        assert_eq!(r.to_ansi_string(30).matches('▀').count(), 25 * 5);
+    }
+
+    #[test]
+    fn empty() {
+        let r = RasterBuilder::<SRgb8>::new().with_clear(0, 5);
+        assert_eq!(r.to_ansi_string(80), "");
+        let r = RasterBuilder::<SRgb8>::new().with_clear(5, 0);
+        assert_eq!(r.to_ansi_string_with(80, false), "");
+        let r = RasterBuilder::<SRgb8>::new().with_clear(5, 5);
+        assert_eq!(r.to_ansi_string(0), "");
+    }
+}
@@ -0,0 +1,680 @@
+// view.rs      Borrowed views of rasters.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Pixel, Raster, Region};
+use std::ops::Range;
+
+/// Borrowed view of a rectangular part of a [Raster](struct.Raster.html).
+///
+/// Use `Raster`::[view](struct.Raster.html#method.view) to create.  All
+/// coordinates are relative to the top-left corner of the view.
+///
+/// ### Process one tile
+/// ```
+/// # use pix::*;
+/// let mut r = RasterBuilder::<SGray8>::new().with_clear(64, 64);
+/// r.set_region((16, 0, 16, 16), SGray8::new(0xFF));
+/// let tile = r.view((16, 0, 16, 16));
+/// assert_eq!((tile.width(), tile.height()), (16, 16));
+/// let white = SGray8::new(0xFF);
+/// assert!(tile.rows().all(|row| row.iter().all(|p| *p == white)));
+/// ```
+#[derive(Clone, Copy)]
+pub struct RasterView<'a, P: Pixel> {
+    pixels: &'a [P],
+    stride: usize,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Mutable borrowed view of a rectangular part of a
+/// [Raster](struct.Raster.html).
+///
+/// Use `Raster`::[view_mut](struct.Raster.html#method.view_mut) to create.
+/// All coordinates are relative to the top-left corner of the view.
+///
+/// ### Fill a tile
+/// ```
+/// # use pix::*;
+/// let mut r = RasterBuilder::<SGray8>::new().with_clear(4, 4);
+/// let mut tile = r.view_mut((2, 2, 2, 2));
+/// tile.set_region(tile.region(), SGray8::new(0x80));
+/// tile.set_pixel(0, 0, SGray8::new(0xFF));
+/// assert_eq!(r.pixel(2, 2), SGray8::new(0xFF));
+/// assert_eq!(r.pixel(3, 3), SGray8::new(0x80));
+/// ```
+pub struct RasterViewMut<'a, P: Pixel> {
+    pixels: &'a mut [P],
+    stride: usize,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Trait for borrowing a [RasterView](struct.RasterView.html).
+///
+/// This is implemented by [Raster](struct.Raster.html), `RasterView` and
+/// [RasterViewMut](struct.RasterViewMut.html), so functions can accept any
+/// of them.
+///
+/// ### Count opaque pixels in a `Raster` or view
+/// ```
+/// # use pix::*;
+/// fn count_opaque<V: AsRasterView<Mask8>>(v: &V) -> usize {
+///     let v = v.as_view();
+///     v.rows().flatten().filter(|p| p.alpha() == Ch8::MAX).count()
+/// }
+/// let mut r = RasterBuilder::<Mask8>::new().with_clear(8, 8);
+/// r.set_region((0, 0, 4, 8), Mask8::new(0xFF));
+/// assert_eq!(count_opaque(&r), 32);
+/// assert_eq!(count_opaque(&r.view((2, 2, 4, 4))), 8);
+/// ```
+pub trait AsRasterView<P: Pixel> {
+    /// Borrow as a `RasterView`.
+    fn as_view(&self) -> RasterView<'_, P>;
+}
+
+/// Clip a region to a view, getting the clipped region and pixel range.
+///
+/// * `reg` Region relative to view.
+/// * `width` Width of view.
+/// * `height` Height of view.
+/// * `stride` Number of pixels between rows of view.
+fn clip(
+    reg: Region,
+    width: u32,
+    height: u32,
+    stride: usize,
+) -> (Region, Range<usize>) {
+    let reg = reg.intersection((0, 0, width, height));
+    if reg.width() == 0 || reg.height() == 0 {
+        return (Region::new(0, 0, 0, 0), 0..0);
+    }
+    let (x, y) = (reg.x() as usize, reg.y() as usize);
+    let start = y * stride + x;
+    let end = start + (reg.height() as usize - 1) * stride;
+    (reg, start..end + reg.width() as usize)
+}
+
+/// Panic for pixel coordinates outside of a view.
+fn out_of_bounds(x: u32, y: u32, width: u32, height: u32) -> ! {
+    panic!(
+        "pixel ({}, {}) out of bounds for {}x{} view",
+        x, y, width, height
+    )
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Borrow a `Region` of the `Raster` as a
+    /// [RasterView](struct.RasterView.html).
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds.
+    pub fn view<R>(&self, reg: R) -> RasterView<'_, P>
+    where
+        R: Into<Region>,
+    {
+        let stride = self.width() as usize;
+        let (width, height) = (self.width(), self.height());
+        let (reg, range) = clip(reg.into(), width, height, stride);
+        RasterView {
+            pixels: &self.as_slice()[range],
+            stride,
+            x: reg.x() as u32,
+            y: reg.y() as u32,
+            width: reg.width(),
+            height: reg.height(),
+        }
+    }
+    /// Borrow a `Region` of the `Raster` as a mutable
+    /// [RasterViewMut](struct.RasterViewMut.html).
+    ///
+    /// * `reg` Region within `Raster`.
+    ///
+    /// The region is clipped to the `Raster` bounds.
+    pub fn view_mut<R>(&mut self, reg: R) -> RasterViewMut<'_, P>
+    where
+        R: Into<Region>,
+    {
+        let stride = self.width() as usize;
+        let (width, height) = (self.width(), self.height());
+        let (reg, range) = clip(reg.into(), width, height, stride);
+        RasterViewMut {
+            pixels: &mut self.as_slice_mut()[range],
+            stride,
+            x: reg.x() as u32,
+            y: reg.y() as u32,
+            width: reg.width(),
+            height: reg.height(),
+        }
+    }
+    /// Split the `Raster` into disjoint mutable bands of consecutive rows.
+    ///
+    /// * `n` Number of bands.
+    ///
+    /// Rows are divided as evenly as possible, with any remainder going to
+    /// the first bands.  If there are fewer than `n` rows, one band is
+    /// returned for each row.  Each [RasterViewMut](struct.RasterViewMut.html)
+    /// borrows a separate part of the pixel data, so bands can be processed
+    /// on different threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    ///
+    /// ### Clear two halves concurrently
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(64, 64);
+    /// std::thread::scope(|s| {
+    ///     for mut band in r.split_rows_mut(2) {
+    ///         s.spawn(move || {
+    ///             let v = if band.y() == 0 { 0x40 } else { 0x80 };
+    ///             band.set_region(band.region(), SGray8::new(v));
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(r.pixel(0, 31), SGray8::new(0x40));
+    /// assert_eq!(r.pixel(0, 32), SGray8::new(0x80));
+    /// ```
+    pub fn split_rows_mut(&mut self, n: usize) -> Vec<RasterViewMut<'_, P>> {
+        assert!(n > 0, "number of bands must be positive");
+        let width = self.width();
+        let height = self.height() as usize;
+        let n = n.min(height);
+        let mut bands = Vec::with_capacity(n);
+        let mut pixels = self.as_slice_mut();
+        let mut y = 0;
+        for i in 0..n {
+            let rows = height / n + usize::from(i < height % n);
+            let (band, rest) = pixels.split_at_mut(rows * width as usize);
+            bands.push(RasterViewMut {
+                pixels: band,
+                stride: width as usize,
+                x: 0,
+                y: y as u32,
+                width,
+                height: rows as u32,
+            });
+            pixels = rest;
+            y += rows;
+        }
+        bands
+    }
+    /// Call a function with a mutable view of each tile.
+    ///
+    /// * `tw` Tile width.
+    /// * `th` Tile height.
+    /// * `f` Function called with a mutable view of each tile.
+    ///
+    /// Tiles are visited in the same order as
+    /// [tiles](struct.Raster.html#method.tiles), with edge tiles clipped to
+    /// the `Raster`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tw` or `th` is zero.
+    ///
+    /// ### Fill tiles in a grid
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new().with_clear(6, 4);
+    /// let mut v = 0;
+    /// r.for_each_tile_mut(4, 4, |mut tile| {
+    ///     v += 0x40;
+    ///     tile.set_region(tile.region(), SGray8::new(v));
+    /// });
+    /// assert_eq!(r.pixel(3, 3), SGray8::new(0x40));
+    /// assert_eq!(r.pixel(4, 0), SGray8::new(0x80));
+    /// ```
+    pub fn for_each_tile_mut<F>(&mut self, tw: u32, th: u32, mut f: F)
+    where
+        F: FnMut(RasterViewMut<'_, P>),
+    {
+        for reg in self.tiles(tw, th) {
+            f(self.view_mut(reg));
+        }
+    }
+}
+
+impl<P: Pixel> AsRasterView<P> for Raster<P> {
+    fn as_view(&self) -> RasterView<'_, P> {
+        self.view(self.region())
+    }
+}
+
+impl<P: Pixel> AsRasterView<P> for RasterView<'_, P> {
+    fn as_view(&self) -> RasterView<'_, P> {
+        *self
+    }
+}
+
+impl<P: Pixel> AsRasterView<P> for RasterViewMut<'_, P> {
+    fn as_view(&self) -> RasterView<'_, P> {
+        RasterView {
+            pixels: self.pixels,
+            stride: self.stride,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<'a, P: Pixel> RasterView<'a, P> {
+    /// Borrow pixel data from a raw pointer as a view.
+    ///
+    /// Unlike the [Raster](struct.Raster.html) method of the same name, this
+    /// does not take ownership, so the pixel data can come from any
+    /// allocator, or a memory-mapped buffer.
+    ///
+    /// * `width` Width of view.
+    /// * `height` Height of view.
+    /// * `ptr` Pointer to first pixel, with rows packed together.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null, aligned for `P`, and valid for reads of
+    /// `width` * `height` pixels.  The pixel data must not be modified or
+    /// freed for the lifetime `'a`.
+    ///
+    /// ### Borrow a C buffer
+    /// ```
+    /// # use pix::*;
+    /// let buf = [SGray8::new(1), SGray8::new(2), SGray8::new(3)];
+    /// let ptr = buf.as_ptr();
+    /// // ... get the pointer through FFI
+    /// let v = unsafe { RasterView::from_raw_parts(3, 1, ptr) };
+    /// assert_eq!(v.pixel(2, 0), SGray8::new(3));
+    /// ```
+    #[must_use]
+    pub unsafe fn from_raw_parts(
+        width: u32,
+        height: u32,
+        ptr: *const P,
+    ) -> Self {
+        let len = width as usize * height as usize;
+        RasterView {
+            pixels: std::slice::from_raw_parts(ptr, len),
+            stride: width as usize,
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+    /// Get width of view.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of view.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get horizontal offset of view within its `Raster`.
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+    /// Get vertical offset of view within its `Raster`.
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+    /// Get `Region` of entire view.
+    pub fn region(&self) -> Region {
+        Region::new(0, 0, self.width, self.height)
+    }
+    /// Get one pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is outside of the view.
+    pub fn pixel(&self, x: u32, y: u32) -> P {
+        match self.get_pixel(x, y) {
+            Some(p) => p,
+            None => out_of_bounds(x, y, self.width, self.height),
+        }
+    }
+    /// Get one pixel, or `None` if `x` or `y` is outside of the view.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<P> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y as usize * self.stride + x as usize])
+        } else {
+            None
+        }
+    }
+    /// Get an `Iterator` of rows, from top to bottom.
+    pub fn rows(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &'a [P]> + ExactSizeIterator {
+        let width = self.width as usize;
+        self.pixels
+            .chunks(self.stride.max(1))
+            .take(self.height as usize)
+            .map(move |row| &row[..width])
+    }
+    /// Get an `Iterator` of pixels within a `Region`, in row-major order.
+    ///
+    /// * `reg` Region within view.
+    ///
+    /// The region is clipped to the view bounds.
+    pub fn region_iter<R>(&self, reg: R) -> impl Iterator<Item = P> + 'a
+    where
+        R: Into<Region>,
+    {
+        self.view(reg).rows().flat_map(|row| row.iter().cloned())
+    }
+    /// Borrow a `Region` of the view as another view.
+    ///
+    /// * `reg` Region within view.
+    ///
+    /// The region is clipped to the view bounds.
+    pub fn view<R>(&self, reg: R) -> RasterView<'a, P>
+    where
+        R: Into<Region>,
+    {
+        let (reg, range) =
+            clip(reg.into(), self.width, self.height, self.stride);
+        RasterView {
+            pixels: &self.pixels[range],
+            stride: self.stride,
+            x: self.x + reg.x() as u32,
+            y: self.y + reg.y() as u32,
+            width: reg.width(),
+            height: reg.height(),
+        }
+    }
+}
+
+impl<P: Pixel> RasterViewMut<'_, P> {
+    /// Get width of view.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of view.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get horizontal offset of view within its `Raster`.
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+    /// Get vertical offset of view within its `Raster`.
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+    /// Get `Region` of entire view.
+    pub fn region(&self) -> Region {
+        Region::new(0, 0, self.width, self.height)
+    }
+    /// Get one pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is outside of the view.
+    pub fn pixel(&self, x: u32, y: u32) -> P {
+        self.as_view().pixel(x, y)
+    }
+    /// Get one pixel, or `None` if `x` or `y` is outside of the view.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<P> {
+        self.as_view().get_pixel(x, y)
+    }
+    /// Set one pixel value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `x` or `y` is outside of the view.
+    pub fn set_pixel<S>(&mut self, x: u32, y: u32, p: S)
+    where
+        P: From<S>,
+    {
+        if x < self.width && y < self.height {
+            self.pixels[y as usize * self.stride + x as usize] = p.into();
+        } else {
+            out_of_bounds(x, y, self.width, self.height)
+        }
+    }
+    /// Set a `Region` using a pixel `Iterator`.
+    ///
+    /// * `reg` Region within view.
+    /// * `it` `Iterator` of pixels in `Region`.
+    ///
+    /// The region is clipped to the view bounds.  As with
+    /// `Raster`::[set_region](struct.Raster.html#method.set_region), a
+    /// single pixel can be used to fill with a solid color.
+    pub fn set_region<R, S, I>(&mut self, reg: R, mut it: I)
+    where
+        R: Into<Region>,
+        S: Pixel,
+        P::Chan: From<S::Chan>,
+        I: Iterator<Item = S>,
+    {
+        for row in self.view_mut(reg).rows_mut() {
+            for p in row {
+                if let Some(s) = it.next() {
+                    *p = s.convert();
+                }
+            }
+        }
+    }
+    /// Get an `Iterator` of rows, from top to bottom.
+    pub fn rows(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &[P]> + ExactSizeIterator {
+        let width = self.width as usize;
+        self.pixels
+            .chunks(self.stride.max(1))
+            .take(self.height as usize)
+            .map(move |row| &row[..width])
+    }
+    /// Get an `Iterator` of mutable rows, from top to bottom.
+    pub fn rows_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut [P]> + ExactSizeIterator {
+        let width = self.width as usize;
+        self.pixels
+            .chunks_mut(self.stride.max(1))
+            .take(self.height as usize)
+            .map(move |row| &mut row[..width])
+    }
+    /// Borrow a `Region` of the view as a `RasterView`.
+    ///
+    /// * `reg` Region within view.
+    ///
+    /// The region is clipped to the view bounds.
+    pub fn view<R>(&self, reg: R) -> RasterView<'_, P>
+    where
+        R: Into<Region>,
+    {
+        self.as_view().view(reg)
+    }
+    /// Borrow a `Region` of the view as another mutable view.
+    ///
+    /// * `reg` Region within view.
+    ///
+    /// The region is clipped to the view bounds.
+    pub fn view_mut<R>(&mut self, reg: R) -> RasterViewMut<'_, P>
+    where
+        R: Into<Region>,
+    {
+        let (reg, range) =
+            clip(reg.into(), self.width, self.height, self.stride);
+        RasterViewMut {
+            pixels: &mut self.pixels[range],
+            stride: self.stride,
+            x: self.x + reg.x() as u32,
+            y: self.y + reg.y() as u32,
+            width: reg.width(),
+            height: reg.height(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn numbered(width: u32, height: u32) -> Raster<SGray8> {
+        RasterBuilder::new()
+            .with_fn(width, height, |x, y| SGray8::new((y * 10 + x) as u8))
+    }
+
+    fn values(v: RasterView<SGray8>) -> Vec<u8> {
+        v.rows().flatten().map(|p| u8::from(p.value())).collect()
+    }
+
+    #[test]
+    fn offset_view() {
+        let r = numbered(5, 4);
+        let v = r.view((2, 1, 2, 3));
+        assert_eq!((v.width(), v.height()), (2, 3));
+        assert_eq!((v.x(), v.y()), (2, 1));
+        assert_eq!(v.pixel(0, 0), SGray8::new(12));
+        assert_eq!(v.pixel(1, 2), SGray8::new(33));
+        assert_eq!(v.get_pixel(2, 0), None);
+        assert_eq!(v.get_pixel(0, 3), None);
+        assert_eq!(v.rows().len(), 3);
+        assert_eq!(values(v), vec![12, 13, 22, 23, 32, 33]);
+        let it: Vec<_> = v.region_iter((1, 1, 5, 5)).collect();
+        assert_eq!(it, vec![SGray8::new(23), SGray8::new(33)]);
+        let last = v.rows().next_back().unwrap();
+        assert_eq!(last, &[SGray8::new(32), SGray8::new(33)]);
+    }
+
+    #[test]
+    fn clipped_view() {
+        let r = numbered(5, 4);
+        let v = r.view((3, -1, 10, 3));
+        assert_eq!((v.width(), v.height()), (2, 2));
+        assert_eq!((v.x(), v.y()), (3, 0));
+        assert_eq!(values(v), vec![3, 4, 13, 14]);
+        let v = r.view((5, 0, 2, 2));
+        assert_eq!((v.width(), v.height()), (0, 0));
+        assert_eq!(v.rows().count(), 0);
+        let e = RasterBuilder::<SGray8>::new().with_clear(0, 3);
+        assert_eq!(e.view(e.region()).rows().count(), 0);
+    }
+
+    #[test]
+    fn view_of_view() {
+        let r = numbered(6, 6);
+        let v = r.view((1, 1, 4, 4));
+        let vv = v.view((1, 2, 5, 5));
+        assert_eq!((vv.x(), vv.y()), (2, 3));
+        assert_eq!((vv.width(), vv.height()), (3, 2));
+        assert_eq!(values(vv), vec![32, 33, 34, 42, 43, 44]);
+        let vvv = vv.view((2, 1, 1, 1));
+        assert_eq!(values(vvv), vec![44]);
+        assert_eq!((vvv.x(), vvv.y()), (4, 4));
+    }
+
+    #[test]
+    fn view_mut() {
+        let mut r = numbered(5, 4);
+        let mut v = r.view_mut((1, 1, 3, 2));
+        assert_eq!(v.pixel(2, 1), SGray8::new(23));
+        v.set_pixel(0, 0, SGray8::new(0xFF));
+        for row in v.rows_mut() {
+            row[2] = SGray8::new(0xEE);
+        }
+        let mut vv = v.view_mut((1, 0, 9, 9));
+        assert_eq!((vv.x(), vv.y()), (2, 1));
+        vv.set_region((0, 1, 1, 1), SGray8::new(0xDD));
+        assert_eq!(values(v.as_view()), vec![0xFF, 12, 0xEE, 21, 0xDD, 0xEE]);
+        let rows: Vec<_> = r.rows().map(|row| row.to_vec()).collect();
+        let v: Vec<u8> = rows
+            .iter()
+            .flatten()
+            .map(|p| u8::from(p.value()))
+            .collect();
+        assert_eq!(
+            v,
+            vec![
+                0, 1, 2, 3, 4,
+                10, 0xFF, 12, 0xEE, 14,
+                20, 21, 0xDD, 0xEE, 24,
+                30, 31, 32, 33, 34,
+            ]
+        );
+    }
+
+    #[test]
+    fn set_region_convert() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(4, 2);
+        let g = numbered(2, 2);
+        let mut v = r.view_mut((2, 0, 2, 2));
+        v.set_region(v.region(), g.region_iter(g.region()));
+        assert_eq!(r.pixel(3, 1), SRgb8::new(11, 11, 11));
+        assert_eq!(r.pixel(1, 1), SRgb8::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "pixel (2, 0) out of bounds for 2x2 view")]
+    fn view_out_of_bounds() {
+        let r = numbered(5, 4);
+        r.view((3, 0, 2, 2)).pixel(2, 0);
+    }
+
+    #[test]
+    fn raw_parts() {
+        let r = numbered(4, 3);
+        let ptr = r.as_slice().as_ptr();
+        let v = unsafe { RasterView::from_raw_parts(4, 3, ptr) };
+        assert_eq!(values(v), values(r.as_view()));
+        assert_eq!(values(v.view((1, 1, 2, 2))), [11, 12, 21, 22]);
+        let ptr = std::ptr::NonNull::<SGray8>::dangling().as_ptr();
+        let v = unsafe { RasterView::from_raw_parts(0, 0, ptr) };
+        assert_eq!(v.rows().len(), 0);
+    }
+
+    #[test]
+    fn split_rows() {
+        let mut r = numbered(3, 7);
+        let bands = r.split_rows_mut(3);
+        let dims: Vec<_> = bands.iter().map(|b| (b.y(), b.height())).collect();
+        assert_eq!(dims, vec![(0, 3), (3, 2), (5, 2)]);
+        assert!(bands.iter().all(|b| b.width() == 3 && b.x() == 0));
+        assert_eq!(bands[1].pixel(2, 1), SGray8::new(42));
+        for mut band in bands {
+            let v = SGray8::new(band.y() as u8);
+            band.set_region(band.region(), v);
+        }
+        let v = r.rows().map(|row| u8::from(row[1].value()));
+        assert_eq!(v.collect::<Vec<_>>(), vec![0, 0, 0, 3, 3, 5, 5]);
+        assert_eq!(r.split_rows_mut(10).len(), 7);
+        assert_eq!(r.split_rows_mut(1)[0].height(), 7);
+        let mut e = RasterBuilder::<SGray8>::new().with_clear(4, 0);
+        assert!(e.split_rows_mut(4).is_empty());
+    }
+
+    #[test]
+    fn tiles_mut() {
+        let mut r = numbered(5, 3);
+        let mut regions = vec![];
+        r.for_each_tile_mut(2, 2, |mut tile| {
+            regions.push((tile.x(), tile.y(), tile.width(), tile.height()));
+            let v = SGray8::new(regions.len() as u8);
+            tile.set_region(tile.region(), v);
+        });
+        let tiles: Vec<_> = r.tiles(2, 2).collect();
+        let regions: Vec<Region> = regions
+            .iter()
+            .map(|&(x, y, w, h)| Region::new(x as i32, y as i32, w, h))
+            .collect();
+        assert_eq!(regions, tiles);
+        assert_eq!(
+            values(r.view(r.region())),
+            vec![
+                1, 1, 2, 2, 3, //
+                1, 1, 2, 2, 3, //
+                4, 4, 5, 5, 6,
+            ]
+        );
+    }
+}
+
@@ -0,0 +1,195 @@
+// window.rs    Neighborhood filters.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::{Pixel, Raster, RasterBuilder};
+
+/// Square neighborhood of one pixel within a [Raster](struct.Raster.html).
+///
+/// Use `Raster`::[filter_window](struct.Raster.html#method.filter_window)
+/// to visit each window.  Neighbors beyond the edges of the `Raster` are
+/// clamped to the nearest edge pixel.
+pub struct Window<'a, P: Pixel> {
+    raster: &'a Raster<P>,
+    x: u32,
+    y: u32,
+    size: u32,
+}
+
+impl<'a, P: Pixel> Window<'a, P> {
+    /// Get width and height of window.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+    /// Get X-position of center pixel within its `Raster`.
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+    /// Get Y-position of center pixel within its `Raster`.
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+    /// Get the center pixel.
+    pub fn center(&self) -> P {
+        self.raster.pixel(self.x, self.y)
+    }
+    /// Get a pixel relative to the center.
+    ///
+    /// * `dx` Horizontal offset from center.
+    /// * `dy` Vertical offset from center.
+    ///
+    /// Positions beyond the edges of the `Raster` are clamped.
+    pub fn get(&self, dx: i32, dy: i32) -> P {
+        let clamp = |v: u32, d: i32, len: u32| {
+            (i64::from(v) + i64::from(d)).clamp(0, i64::from(len) - 1) as u32
+        };
+        let x = clamp(self.x, dx, self.raster.width());
+        let y = clamp(self.y, dy, self.raster.height());
+        self.raster.pixel(x, y)
+    }
+    /// Get an `Iterator` of all pixels in the window, in row-major order.
+    pub fn pixels(&self) -> impl Iterator<Item = P> + '_ {
+        let r = (self.size / 2) as i32;
+        (-r..=r).flat_map(move |dy| (-r..=r).map(move |dx| self.get(dx, dy)))
+    }
+}
+
+impl<P: Pixel> Raster<P> {
+    /// Create a new `Raster` by applying a function to each neighborhood.
+    ///
+    /// * `n` Width and height of each [Window](struct.Window.html).
+    /// * `f` Function returning the new value of the center pixel.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not odd.
+    ///
+    /// ### Dilate a mask
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<Mask8>::new().with_clear(5, 5);
+    /// r.set_pixel(2, 2, Mask8::new(0xFF));
+    /// let d = r.filter_window(3, |w| {
+    ///     w.pixels().max_by_key(|p| p.alpha()).unwrap()
+    /// });
+    /// assert_eq!(d.pixel(1, 1), Mask8::new(0xFF));
+    /// assert_eq!(d.pixel(0, 0), Mask8::new(0));
+    /// ```
+    pub fn filter_window<F>(&self, n: u32, mut f: F) -> Raster<P>
+    where
+        F: FnMut(&Window<P>) -> P,
+    {
+        assert!(n % 2 == 1, "window size must be odd");
+        RasterBuilder::new().with_fn(self.width(), self.height(), |x, y| {
+            f(&Window {
+                raster: self,
+                x,
+                y,
+                size: n,
+            })
+        })
+    }
+    /// Create a new `Raster` with a median filter.
+    ///
+    /// * `n` Width and height of neighborhood.
+    ///
+    /// Each channel (including *alpha*) is replaced with the median value of
+    /// that channel within an `n` by `n` neighborhood, clamped at the edges.
+    /// This removes *salt and pepper* noise while preserving edges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is not odd.
+    ///
+    /// ### Remove noise
+    /// ```
+    /// # use pix::*;
+    /// let mut r = RasterBuilder::<SGray8>::new()
+    ///     .with_color(8, 8, SGray8::new(0x80));
+    /// r.set_pixel(3, 4, SGray8::new(0xFF));
+    /// r.set_pixel(6, 1, SGray8::new(0x00));
+    /// let m = r.median_filter(3);
+    /// assert!(m.as_slice().iter().all(|p| *p == SGray8::new(0x80)));
+    /// ```
+    pub fn median_filter(&self, n: u32) -> Raster<P> {
+        let count = (n as usize).pow(2);
+        let mut pixels = Vec::with_capacity(count);
+        let mut values = Vec::with_capacity(count);
+        self.filter_window(n, |w| {
+            pixels.clear();
+            pixels.extend(w.pixels());
+            let mut p = w.center();
+            for c in 0..P::CHANNELS {
+                values.clear();
+                values.extend(pixels.iter().map(|q| q.channels()[c]));
+                let (_, median, _) = values.select_nth_unstable(count / 2);
+                p.channels_mut()[c] = *median;
+            }
+            p
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn numbered() -> Raster<SGray8> {
+        RasterBuilder::new()
+            .with_fn(4, 3, |x, y| SGray8::new((y * 10 + x) as u8))
+    }
+
+    #[test]
+    fn clamped() {
+        let r = numbered();
+        let w = r.filter_window(3, |w| {
+            let v: Vec<_> = w.pixels().map(|p| u8::from(p.value())).collect();
+            if (w.x(), w.y()) == (0, 0) {
+                assert_eq!(v, vec![0, 0, 1, 0, 0, 1, 10, 10, 11]);
+            }
+            if (w.x(), w.y()) == (3, 2) {
+                assert_eq!(v, vec![12, 13, 13, 22, 23, 23, 22, 23, 23]);
+            }
+            assert_eq!(v.len(), 9);
+            assert_eq!(w.size(), 3);
+            w.get(-100, 100)
+        });
+        assert!(w.rows().all(|row| row == [SGray8::new(20); 4]));
+        assert_eq!(r.filter_window(1, |w| w.center()), r);
+        assert_eq!(r.filter_window(5, |w| w.get(0, 0)), r);
+    }
+
+    #[test]
+    fn median() {
+        let mut r = RasterBuilder::<SRgba8>::new()
+            .with_color(6, 6, SRgba8::with_alpha(0x10, 0x20, 0x30, 0xFF));
+        r.set_pixel(0, 0, SRgba8::with_alpha(0xFF, 0xFF, 0xFF, 0x00));
+        r.set_pixel(5, 3, SRgba8::with_alpha(0x00, 0x00, 0x00, 0x00));
+        let m = r.median_filter(3);
+        let clr = SRgba8::with_alpha(0x10, 0x20, 0x30, 0xFF);
+        assert!(m.as_slice().iter().all(|p| *p == clr));
+        // Channels are filtered separately
+        let r = RasterBuilder::<SRgb8>::new().with_fn(3, 1, |x, _y| {
+            SRgb8::new(x as u8, 2 - x as u8, 7)
+        });
+        let m = r.median_filter(3);
+        assert_eq!(m.pixel(1, 0), SRgb8::new(1, 1, 7));
+        assert_eq!(m.pixel(0, 0), SRgb8::new(0, 2, 7));
+    }
+
+    #[test]
+    fn median_edge() {
+        // A step edge is preserved
+        let r = RasterBuilder::<SGray8>::new().with_fn(8, 8, |x, _y| {
+            SGray8::new(if x < 4 { 0x00 } else { 0xFF })
+        });
+        assert_eq!(r.median_filter(3), r);
+        assert_eq!(r.median_filter(5), r);
+    }
+
+    #[test]
+    #[should_panic]
+    fn even_size() {
+        numbered().median_filter(2);
+    }
+}
@@ -0,0 +1,259 @@
+// xyz.rs       CIE XYZ color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight, Translucent};
+use crate::gamma::Linear;
+use crate::hsv::channel_from_f32;
+use crate::pixel::fmt_pixel;
+use crate::{Ch32, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+
+/// D65 white point, X component
+pub(crate) const WHITE_X: f32 = 0.950_47;
+
+/// D65 white point, Z component
+pub(crate) const WHITE_Z: f32 = 1.088_83;
+
+/// CIE 1931 XYZ [color model], with optional [alpha channel].
+///
+/// The components are *X*, *Y* (relative luminance) and *Z*, converted from
+/// linear sRGB primaries with a D65 white point.  Each component is scaled
+/// by the white point, so that D65 *white* is 1.0 in all three channels.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Xyz<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    components: [C; 3],
+    alpha: A,
+}
+
+impl<C, A> Xyz<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying *X*,
+    /// *Y* and *Z*, scaled by the white point.
+    pub fn new<H>(x: H, y: H, z: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(x, y, z, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying *X*, *Y*, *Z* and *alpha*.
+    pub fn with_alpha<H, B>(x: H, y: H, z: H, alpha: B) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let components = [C::from(x), C::from(y), C::from(z)];
+        let alpha = A::from(alpha);
+        Xyz { components, alpha }
+    }
+    /// Get the *X* component.
+    pub fn x(self) -> C {
+        self.components[0]
+    }
+    /// Get the *Y* (relative luminance) component.
+    pub fn y(self) -> C {
+        self.components[1]
+    }
+    /// Get the *Z* component.
+    pub fn z(self) -> C {
+        self.components[2]
+    }
+}
+
+impl<C, A> ColorModel for Xyz<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let x = f32::from(Ch32::from(self.x())) * WHITE_X;
+        let y = f32::from(Ch32::from(self.y()));
+        let z = f32::from(Ch32::from(self.z())) * WHITE_Z;
+        let rgb = xyz_to_rgb([x, y, z]);
+        [
+            channel_from_f32(rgb[0]),
+            channel_from_f32(rgb[1]),
+            channel_from_f32(rgb[2]),
+            self.alpha(),
+        ]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let red = f32::from(Ch32::from(rgba[0]));
+        let green = f32::from(Ch32::from(rgba[1]));
+        let blue = f32::from(Ch32::from(rgba[2]));
+        let xyz = rgb_to_xyz([red, green, blue]);
+        let x = channel_from_f32::<C>(xyz[0] / WHITE_X);
+        let y = channel_from_f32::<C>(xyz[1]);
+        let z = channel_from_f32::<C>(xyz[2] / WHITE_Z);
+        Xyz::with_alpha(x, y, z, rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let diff = |a: C, b: C| a.max(b) - a.min(b);
+        Xyz::with_alpha(
+            diff(self.x(), rhs.x()),
+            diff(self.y(), rhs.y()),
+            diff(self.z(), rhs.z()),
+            diff(self.alpha(), rhs.alpha()),
+        )
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.x() <= rhs.x()
+            && self.y() <= rhs.y()
+            && self.z() <= rhs.z()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A> fmt::Debug for Xyz<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A> Pixel for Xyz<C, A>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::Xyz);
+
+    const WHITE: Self = Xyz {
+        components: [C::MAX; 3],
+        alpha: A::OPAQUE,
+    };
+    const BLACK: Self = Xyz {
+        components: [C::MIN; 3],
+        alpha: A::OPAQUE,
+    };
+    const TRANSPARENT: Self = Xyz {
+        components: [C::MIN; 3],
+        alpha: A::TRANSPARENT,
+    };
+
+    type Alpha = Straight;
+    type Gamma = Linear;
+}
+
+impl<C, A> Iterator for Xyz<C, A>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// Convert linear *red*, *green* and *blue* to XYZ (D65).
+pub(crate) fn rgb_to_xyz(rgb: [f32; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb;
+    [
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+        0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+    ]
+}
+
+/// Convert XYZ (D65) to linear *red*, *green* and *blue*.
+pub(crate) fn xyz_to_rgb(xyz: [f32; 3]) -> [f32; 3] {
+    let [x, y, z] = xyz;
+    [
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    ]
+}
+
+/// [Xyz](struct.Xyz.html) 32-bit [opaque](alpha/struct.Opaque.html) (no alpha)
+/// [pixel](trait.Pixel.html) format.
+pub type Xyz32 = Xyz<Ch32, Opaque<Ch32>>;
+/// [Xyz](struct.Xyz.html) 32-bit [straight](alpha/struct.Straight.html) alpha
+/// [pixel](trait.Pixel.html) format.
+pub type Xyza32 = Xyz<Ch32, Translucent<Ch32>>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    fn assert_near(a: Xyz32, b: Xyz32) {
+        assert!(a.approx_eq(&b, 1e-4), "{:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<Xyz32>(), 12);
+        assert_eq!(std::mem::size_of::<Xyza32>(), 16);
+    }
+
+    #[test]
+    fn rgb_to_xyz() {
+        let white = Rgb32::new(1.0, 1.0, 1.0);
+        assert_near(white.convert(), Xyz32::new(1.0, 1.0, 1.0));
+        let black = Rgb32::new(0.0, 0.0, 0.0);
+        assert_near(black.convert(), Xyz32::new(0.0, 0.0, 0.0));
+        assert_near(
+            Rgb32::new(1.0, 0.0, 0.0).convert(),
+            Xyz32::new(0.433_953, 0.212_673, 0.017_756),
+        );
+        assert_near(
+            SRgb8::new(0x00, 0x00, 0xFF).convert(),
+            Xyz32::new(0.189_839, 0.072_175, 0.872_766),
+        );
+    }
+
+    #[test]
+    fn xyz_to_rgb() {
+        let rgb: SRgb8 = Xyz32::new(1.0, 1.0, 1.0).convert();
+        assert_eq!(rgb, SRgb8::new(0xFF, 0xFF, 0xFF));
+        let xyz = Xyz32::new(0.433_953, 0.212_673, 0.017_756);
+        let rgb: SRgb8 = xyz.convert();
+        assert_eq!(rgb, SRgb8::new(0xFF, 0x00, 0x00));
+    }
+}
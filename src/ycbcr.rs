@@ -0,0 +1,290 @@
+// ycbcr.rs     YCbCr color model.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::alpha::{AChannel, Opaque, Straight};
+use crate::gamma::{self, Srgb};
+use crate::hsv::channel_from_f32;
+use crate::pixel::fmt_pixel;
+use crate::{Ch16, Ch32, Ch8, Channel, ColorModel, PixFmt, PixModel, Pixel};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Y′CbCr [color model], with optional [alpha channel].
+///
+/// The components are *luma* (Y′), *blue-difference chroma* (Cb) and
+/// *red-difference chroma* (Cr), using full-range [BT.601] coefficients, as in
+/// JPEG (JFIF).  Chroma is stored offset by half of the channel range, so that
+/// neutral colors have the same Cb and Cr as `Channel::from(0.5)`.
+///
+/// Conversions are done on gamma-encoded [Rgb](struct.Rgb.html) components,
+/// and out-of-gamut results are clamped.
+///
+/// [alpha channel]: alpha/trait.AChannel.html
+/// [BT.601]: https://en.wikipedia.org/wiki/YCbCr#JPEG_conversion
+/// [color model]: trait.ColorModel.html
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct YCbCr<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    components: [C; 3],
+    alpha: A,
+    gamma: PhantomData<G>,
+}
+
+impl<C, A, G> YCbCr<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    /// Create an [Opaque](alpha/struct.Opaque.html) color by specifying
+    /// *luma*, *blue-difference* and *red-difference* components.
+    pub fn new<H>(y: H, cb: H, cr: H) -> Self
+    where
+        C: From<H>,
+        A: From<Opaque<C>>,
+    {
+        Self::with_alpha(y, cb, cr, Opaque::default())
+    }
+    /// Create a [Translucent](alpha/struct.Translucent.html) color by
+    /// specifying *luma*, *blue-difference*, *red-difference* and *alpha*.
+    pub fn with_alpha<H, B>(y: H, cb: H, cr: H, alpha: B) -> Self
+    where
+        C: From<H>,
+        A: From<B>,
+    {
+        let components = [C::from(y), C::from(cb), C::from(cr)];
+        let alpha = A::from(alpha);
+        YCbCr {
+            components,
+            alpha,
+            gamma: PhantomData,
+        }
+    }
+    /// Get the *luma* (Y′) component.
+    pub fn y(self) -> C {
+        self.components[0]
+    }
+    /// Get the *blue-difference chroma* (Cb) component.
+    pub fn cb(self) -> C {
+        self.components[1]
+    }
+    /// Get the *red-difference chroma* (Cr) component.
+    pub fn cr(self) -> C {
+        self.components[2]
+    }
+}
+
+impl<C, A, G> ColorModel for YCbCr<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    type Chan = C;
+
+    /// Get all non-alpha components
+    fn components(&self) -> &[Self::Chan] {
+        &self.components
+    }
+
+    /// Get the *alpha* component
+    fn alpha(self) -> Self::Chan {
+        self.alpha.value()
+    }
+
+    /// Set the *alpha* component
+    fn set_alpha(&mut self, alpha: Self::Chan) {
+        self.alpha = A::from(alpha);
+    }
+
+    /// Convert to *red*, *green*, *blue* and *alpha* components
+    fn to_rgba(self) -> [Self::Chan; 4] {
+        let half = chroma_offset::<C>();
+        let y = f32::from(Ch32::from(self.y()));
+        let cb = f32::from(Ch32::from(self.cb())) - half;
+        let cr = f32::from(Ch32::from(self.cr())) - half;
+        let red = y + 1.402 * cr;
+        let green = y - 0.344_136 * cb - 0.714_136 * cr;
+        let blue = y + 1.772 * cb;
+        [
+            channel_from_f32(red),
+            channel_from_f32(green),
+            channel_from_f32(blue),
+            self.alpha(),
+        ]
+    }
+
+    /// Convert from *red*, *green*, *blue* and *alpha* components
+    fn with_rgba(rgba: [Self::Chan; 4]) -> Self {
+        let half = chroma_offset::<C>();
+        let red = f32::from(Ch32::from(rgba[0]));
+        let green = f32::from(Ch32::from(rgba[1]));
+        let blue = f32::from(Ch32::from(rgba[2]));
+        let y = 0.299 * red + 0.587 * green + 0.114 * blue;
+        let cb = half - 0.168_736 * red - 0.331_264 * green + 0.5 * blue;
+        let cr = half + 0.5 * red - 0.418_688 * green - 0.081_312 * blue;
+        let y = channel_from_f32::<C>(y);
+        let cb = channel_from_f32::<C>(cb);
+        let cr = channel_from_f32::<C>(cr);
+        YCbCr::with_alpha(y, cb, cr, rgba[3])
+    }
+
+    /// Get channel-wise difference
+    fn difference(self, rhs: Self) -> Self {
+        let y = self.y().max(rhs.y()) - self.y().min(rhs.y());
+        let cb = self.cb().max(rhs.cb()) - self.cb().min(rhs.cb());
+        let cr = self.cr().max(rhs.cr()) - self.cr().min(rhs.cr());
+        let a = self.alpha().max(rhs.alpha()) - self.alpha().min(rhs.alpha());
+        YCbCr::with_alpha(y, cb, cr, a)
+    }
+
+    /// Check if all `Channel`s are within threshold
+    fn within_threshold(self, rhs: Self) -> bool {
+        self.y() <= rhs.y()
+            && self.cb() <= rhs.cb()
+            && self.cr() <= rhs.cr()
+            && self.alpha() <= rhs.alpha()
+    }
+}
+
+impl<C, A, G> fmt::Debug for YCbCr<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+    Ch32: From<C>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_pixel(self, f)
+    }
+}
+
+impl<C, A, G> Pixel for YCbCr<C, A, G>
+where
+    C: Channel,
+    Ch32: From<C>,
+    A: AChannel<Chan = C> + From<C>,
+    G: gamma::Mode,
+{
+    const PIX_FMT: PixFmt = PixFmt::new::<Self>(PixModel::YCbCr);
+
+    const WHITE: Self = YCbCr {
+        components: [C::MAX, C::MID, C::MID],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const BLACK: Self = YCbCr {
+        components: [C::MIN, C::MID, C::MID],
+        alpha: A::OPAQUE,
+        gamma: PhantomData,
+    };
+    const TRANSPARENT: Self = YCbCr {
+        components: [C::MIN, C::MID, C::MID],
+        alpha: A::TRANSPARENT,
+        gamma: PhantomData,
+    };
+
+    type Alpha = Straight;
+    type Gamma = G;
+}
+
+impl<C, A, G> Iterator for YCbCr<C, A, G>
+where
+    C: Channel,
+    A: AChannel<Chan = C>,
+    G: gamma::Mode,
+{
+    type Item = Self;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(*self)
+    }
+}
+
+/// Get offset of chroma components, which is the nearest value to one half.
+fn chroma_offset<C: Channel>() -> f32
+where
+    Ch32: From<C>,
+{
+    f32::from(Ch32::from(C::from(0.5)))
+}
+
+/// [YCbCr](struct.YCbCr.html) 8-bit [opaque](alpha/struct.Opaque.html)
+/// (no alpha) [sRGB](gamma/struct.Srgb.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type YCbCr8 = YCbCr<Ch8, Opaque<Ch8>, Srgb>;
+/// [YCbCr](struct.YCbCr.html) 16-bit [opaque](alpha/struct.Opaque.html)
+/// (no alpha) [sRGB](gamma/struct.Srgb.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type YCbCr16 = YCbCr<Ch16, Opaque<Ch16>, Srgb>;
+/// [YCbCr](struct.YCbCr.html) 32-bit [opaque](alpha/struct.Opaque.html)
+/// (no alpha) [sRGB](gamma/struct.Srgb.html) gamma
+/// [pixel](trait.Pixel.html) format.
+pub type YCbCr32 = YCbCr<Ch32, Opaque<Ch32>, Srgb>;
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn check_sizes() {
+        assert_eq!(std::mem::size_of::<YCbCr8>(), 3);
+        assert_eq!(std::mem::size_of::<YCbCr16>(), 6);
+        assert_eq!(std::mem::size_of::<YCbCr32>(), 12);
+    }
+
+    #[test]
+    fn rgb_to_ycbcr() {
+        let ycc: YCbCr8 = SRgb8::new(0xFF, 0x00, 0x00).convert();
+        assert_eq!(ycc, YCbCr8::new(76, 85, 255));
+        let ycc: YCbCr8 = SRgb8::new(0x00, 0xFF, 0x00).convert();
+        assert_eq!(ycc, YCbCr8::new(150, 44, 21));
+        let ycc: YCbCr8 = SRgb8::new(0x00, 0x00, 0xFF).convert();
+        assert_eq!(ycc, YCbCr8::new(29, 255, 107));
+        let ycc: YCbCr8 = SRgb8::new(0x80, 0x80, 0x80).convert();
+        assert_eq!(ycc, YCbCr8::new(128, 128, 128));
+        let ycc: YCbCr16 = SRgb16::new(0xFFFF, 0xFFFF, 0xFFFF).convert();
+        assert_eq!(ycc, YCbCr16::new(0xFFFF, 0x8000, 0x8000));
+        let ycc: YCbCr32 = SRgb32::new(0.0, 0.0, 0.0).convert();
+        assert_eq!(ycc, YCbCr32::new(0.0, 0.5, 0.5));
+    }
+
+    #[test]
+    fn ycbcr_to_rgb() {
+        let rgb: SRgb8 = YCbCr8::new(76, 85, 255).convert();
+        assert_eq!(rgb, SRgb8::new(0xFE, 0x00, 0x00));
+        let rgb: SRgb8 = YCbCr8::new(150, 44, 21).convert();
+        assert_eq!(rgb, SRgb8::new(0x00, 0xFF, 0x01));
+        let rgb: SRgb8 = YCbCr8::new(29, 255, 107).convert();
+        assert_eq!(rgb, SRgb8::new(0x00, 0x00, 0xFE));
+        // Out of gamut values are clamped
+        let rgb: SRgb8 = YCbCr8::new(255, 255, 255).convert();
+        assert_eq!(rgb, SRgb8::new(0xFF, 0x79, 0xFF));
+        let rgb: SRgb8 = YCbCr8::new(0, 0, 0).convert();
+        assert_eq!(rgb, SRgb8::new(0x00, 0x87, 0x00));
+    }
+
+    #[test]
+    fn round_trip_32() {
+        for r in 0..=8 {
+            for g in 0..=8 {
+                for b in 0..=8 {
+                    let (r, g, b) = (r as f32, g as f32, b as f32);
+                    let rgb = SRgb32::new(r / 8.0, g / 8.0, b / 8.0);
+                    let ycc: YCbCr32 = rgb.convert();
+                    let diff = rgb.difference(ycc.convert());
+                    let max = Ch32::new(1e-5);
+                    assert!(diff.components().iter().all(|c| *c <= max));
+                }
+            }
+        }
+    }
+}
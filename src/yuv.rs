@@ -0,0 +1,462 @@
+// yuv.rs       Chroma-subsampled YUV frames.
+//
+// Copyright (c) 2020  Douglas P Lau
+//
+use crate::hsv::channel_from_f32;
+use crate::{Ch32, Ch8, Pixel, Raster, RasterBuilder, RasterError, SRgb8};
+
+/// Matrix coefficients for converting between YUV and RGB.
+///
+/// Both matrices use full-range (0-255) luma and chroma, with chroma offset
+/// by 128.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum YuvMatrix {
+    /// [BT.601] coefficients, as used by JPEG and SD video
+    ///
+    /// [BT.601]: https://en.wikipedia.org/wiki/Rec._601
+    Bt601,
+    /// [BT.709] coefficients, as used by HD video
+    ///
+    /// [BT.709]: https://en.wikipedia.org/wiki/Rec._709
+    Bt709,
+}
+
+/// YUV frame with 4:2:0 chroma subsampling.
+///
+/// The *Y* (luma) plane has one byte for each pixel.  The *U* (Cb) and *V*
+/// (Cr) planes have one byte for each 2x2 block of pixels.  When the width
+/// or height is odd, the chroma planes are rounded up, so that the last
+/// column or row of pixels has its own chroma samples.
+///
+/// Planes are stored without padding between rows.
+///
+/// ### Convert an I420 frame to RGB
+/// ```
+/// # use pix::*;
+/// let y = [0x80; 16];
+/// let u = [0x80; 4];
+/// let v = [0x80; 4];
+/// let frame = Yuv420Frame::with_planes(4, 4, (&y, 4), (&u, 2), (&v, 2));
+/// let r = frame.to_raster::<SRgb8>(YuvMatrix::Bt709);
+/// assert_eq!(r.pixel(3, 3), SRgb8::new(0x80, 0x80, 0x80));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Yuv420Frame {
+    width: u32,
+    height: u32,
+    y: Vec<u8>,
+    u: Vec<u8>,
+    v: Vec<u8>,
+}
+
+impl YuvMatrix {
+    /// Get the *red* and *blue* luma coefficients (Kr, Kb).
+    fn coefficients(self) -> (f32, f32) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+
+    /// Convert Y, U and V to *red*, *green* and *blue*.
+    fn to_rgb(self, y: u8, u: u8, v: u8) -> SRgb8 {
+        let (kr, kb) = self.coefficients();
+        let kg = 1.0 - kr - kb;
+        let half = chroma_offset();
+        let y = unit(y);
+        let u = unit(u) - half;
+        let v = unit(v) - half;
+        let red = y + 2.0 * (1.0 - kr) * v;
+        let green = y
+            - 2.0 * kb * (1.0 - kb) / kg * u
+            - 2.0 * kr * (1.0 - kr) / kg * v;
+        let blue = y + 2.0 * (1.0 - kb) * u;
+        SRgb8::new(
+            channel_from_f32::<Ch8>(red),
+            channel_from_f32::<Ch8>(green),
+            channel_from_f32::<Ch8>(blue),
+        )
+    }
+
+    /// Convert *red*, *green* and *blue* to Y, U and V (unclamped).
+    fn to_yuv(self, rgb: SRgb8) -> [f32; 3] {
+        let (kr, kb) = self.coefficients();
+        let kg = 1.0 - kr - kb;
+        let half = chroma_offset();
+        let red = unit(u8::from(rgb.red()));
+        let green = unit(u8::from(rgb.green()));
+        let blue = unit(u8::from(rgb.blue()));
+        let y = kr * red + kg * green + kb * blue;
+        let u = half + (blue - y) / (2.0 * (1.0 - kb));
+        let v = half + (red - y) / (2.0 * (1.0 - kr));
+        [y, u, v]
+    }
+}
+
+/// Get a byte as a value from 0 to 1.
+fn unit(v: u8) -> f32 {
+    f32::from(Ch32::from(Ch8::new(v)))
+}
+
+/// Get the chroma offset, as a value from 0 to 1.
+fn chroma_offset() -> f32 {
+    f32::from(Ch32::from(Ch8::from(0.5)))
+}
+
+/// Check the size of a strided plane.
+///
+/// * `width` Width of plane, in bytes.
+/// * `height` Height of plane.
+/// * `data` Plane data.
+/// * `stride` Number of bytes from the start of one row to the next.
+fn check_plane(
+    width: usize,
+    height: usize,
+    data: &[u8],
+    stride: usize,
+) -> Result<(), RasterError> {
+    if stride < width {
+        return Err(RasterError::StrideTooSmall {
+            minimum: width,
+            found: stride,
+        });
+    }
+    let expected = match height {
+        0 => 0,
+        _ => stride
+            .checked_mul(height - 1)
+            .and_then(|n| n.checked_add(width))
+            .ok_or(RasterError::Overflow)?,
+    };
+    if data.len() < expected {
+        let found = data.len();
+        return Err(RasterError::LengthMismatch { expected, found });
+    }
+    Ok(())
+}
+
+/// Copy a strided plane, removing padding between rows.
+///
+/// * `width` Width of plane, in bytes.
+/// * `height` Height of plane.
+/// * `data` Plane data.
+/// * `stride` Number of bytes from the start of one row to the next.
+fn copy_plane(
+    width: usize,
+    height: usize,
+    data: &[u8],
+    stride: usize,
+) -> Vec<u8> {
+    let mut plane = Vec::with_capacity(width * height);
+    for row in 0..height {
+        let s = row * stride;
+        plane.extend_from_slice(&data[s..s + width]);
+    }
+    plane
+}
+
+impl Yuv420Frame {
+    /// Create a `Yuv420Frame` by copying separate Y, U and V planes (I420).
+    ///
+    /// * `width` Width of frame.
+    /// * `height` Height of frame.
+    /// * `y` Luma plane data and stride, in bytes.
+    /// * `u` Cb plane data and stride, in bytes.
+    /// * `v` Cr plane data and stride, in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any stride is smaller than its plane width, or if any plane
+    /// is too short.
+    pub fn with_planes(
+        width: u32,
+        height: u32,
+        y: (&[u8], usize),
+        u: (&[u8], usize),
+        v: (&[u8], usize),
+    ) -> Self {
+        Self::try_with_planes(width, height, y, u, v)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to create a `Yuv420Frame` by copying separate Y, U and V planes
+    /// (I420).
+    ///
+    /// * `width` Width of frame.
+    /// * `height` Height of frame.
+    /// * `y` Luma plane data and stride, in bytes.
+    /// * `u` Cb plane data and stride, in bytes.
+    /// * `v` Cr plane data and stride, in bytes.
+    ///
+    /// ### Stride smaller than plane width
+    /// ```
+    /// # use pix::*;
+    /// let y = [0; 16];
+    /// let c = [0; 4];
+    /// let f = Yuv420Frame::try_with_planes(4, 4, (&y, 4), (&c, 1), (&c, 2));
+    /// assert_eq!(
+    ///     f,
+    ///     Err(RasterError::StrideTooSmall { minimum: 2, found: 1 })
+    /// );
+    /// ```
+    pub fn try_with_planes(
+        width: u32,
+        height: u32,
+        y: (&[u8], usize),
+        u: (&[u8], usize),
+        v: (&[u8], usize),
+    ) -> Result<Self, RasterError> {
+        let (w, h) = (width as usize, height as usize);
+        let (cw, ch) = (chroma_size(width), chroma_size(height));
+        check_plane(w, h, y.0, y.1)?;
+        check_plane(cw, ch, u.0, u.1)?;
+        check_plane(cw, ch, v.0, v.1)?;
+        Ok(Yuv420Frame {
+            width,
+            height,
+            y: copy_plane(w, h, y.0, y.1),
+            u: copy_plane(cw, ch, u.0, u.1),
+            v: copy_plane(cw, ch, v.0, v.1),
+        })
+    }
+    /// Create a `Yuv420Frame` by copying a Y plane and an interleaved UV
+    /// plane (NV12).
+    ///
+    /// * `width` Width of frame.
+    /// * `height` Height of frame.
+    /// * `y` Luma plane data and stride, in bytes.
+    /// * `uv` Interleaved Cb / Cr plane data and stride, in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either stride is smaller than its plane width, or if either
+    /// plane is too short.
+    pub fn with_nv12(
+        width: u32,
+        height: u32,
+        y: (&[u8], usize),
+        uv: (&[u8], usize),
+    ) -> Self {
+        Self::try_with_nv12(width, height, y, uv)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Try to create a `Yuv420Frame` by copying a Y plane and an interleaved
+    /// UV plane (NV12).
+    ///
+    /// * `width` Width of frame.
+    /// * `height` Height of frame.
+    /// * `y` Luma plane data and stride, in bytes.
+    /// * `uv` Interleaved Cb / Cr plane data and stride, in bytes.
+    pub fn try_with_nv12(
+        width: u32,
+        height: u32,
+        y: (&[u8], usize),
+        uv: (&[u8], usize),
+    ) -> Result<Self, RasterError> {
+        let (w, h) = (width as usize, height as usize);
+        let (cw, ch) = (chroma_size(width), chroma_size(height));
+        check_plane(w, h, y.0, y.1)?;
+        check_plane(cw * 2, ch, uv.0, uv.1)?;
+        let uv = copy_plane(cw * 2, ch, uv.0, uv.1);
+        Ok(Yuv420Frame {
+            width,
+            height,
+            y: copy_plane(w, h, y.0, y.1),
+            u: uv.iter().step_by(2).copied().collect(),
+            v: uv.iter().skip(1).step_by(2).copied().collect(),
+        })
+    }
+    /// Create a `Yuv420Frame` from a `Raster`.
+    ///
+    /// * `raster` Source raster; converted to sRGB before encoding.
+    /// * `matrix` Matrix coefficients.
+    ///
+    /// Each chroma sample is the average of a 2x2 block of pixels.  On the
+    /// last column or row of a frame with odd width or height, only the
+    /// pixels within the frame are averaged.
+    pub fn from_raster<P>(raster: &Raster<P>, matrix: YuvMatrix) -> Self
+    where
+        P: Pixel,
+        Ch8: From<P::Chan>,
+    {
+        let rgb = RasterBuilder::<SRgb8>::new().with_raster(raster);
+        let (width, height) = (rgb.width(), rgb.height());
+        let (w, h) = (width as usize, height as usize);
+        let (cw, ch) = (chroma_size(width), chroma_size(height));
+        let yuv: Vec<[f32; 3]> =
+            rgb.as_slice().iter().map(|p| matrix.to_yuv(*p)).collect();
+        let y = yuv.iter().map(|c| u8::from(channel_from_f32::<Ch8>(c[0])));
+        let mut u = Vec::with_capacity(cw * ch);
+        let mut v = Vec::with_capacity(cw * ch);
+        for cy in 0..ch {
+            let rows = (cy * 2)..(cy * 2 + 2).min(h);
+            for cx in 0..cw {
+                let cols = (cx * 2)..(cx * 2 + 2).min(w);
+                let mut sum = [0.0; 2];
+                let mut n = 0.0;
+                for row in rows.clone() {
+                    for c in &yuv[row * w + cols.start..row * w + cols.end] {
+                        sum[0] += c[1];
+                        sum[1] += c[2];
+                        n += 1.0;
+                    }
+                }
+                u.push(u8::from(channel_from_f32::<Ch8>(sum[0] / n)));
+                v.push(u8::from(channel_from_f32::<Ch8>(sum[1] / n)));
+            }
+        }
+        Yuv420Frame {
+            width,
+            height,
+            y: y.collect(),
+            u,
+            v,
+        }
+    }
+    /// Convert to a `Raster`.
+    ///
+    /// * `matrix` Matrix coefficients.
+    ///
+    /// Chroma is upsampled by nearest neighbor, so each chroma sample applies
+    /// to a 2x2 block of pixels.
+    pub fn to_raster<P>(&self, matrix: YuvMatrix) -> Raster<P>
+    where
+        P: Pixel,
+        P::Chan: From<Ch8>,
+    {
+        let cw = chroma_size(self.width);
+        let rgb = RasterBuilder::<SRgb8>::new().with_fn(
+            self.width,
+            self.height,
+            |x, y| {
+                let i = (y * self.width + x) as usize;
+                let c = (y as usize / 2) * cw + x as usize / 2;
+                matrix.to_rgb(self.y[i], self.u[c], self.v[c])
+            },
+        );
+        RasterBuilder::new().with_raster(&rgb)
+    }
+    /// Get width of frame.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Get height of frame.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Get width of chroma planes (rounded up).
+    pub fn chroma_width(&self) -> u32 {
+        chroma_size(self.width) as u32
+    }
+    /// Get height of chroma planes (rounded up).
+    pub fn chroma_height(&self) -> u32 {
+        chroma_size(self.height) as u32
+    }
+    /// Get the luma (Y) plane.
+    pub fn y_plane(&self) -> &[u8] {
+        &self.y
+    }
+    /// Get the Cb (U) plane.
+    pub fn u_plane(&self) -> &[u8] {
+        &self.u
+    }
+    /// Get the Cr (V) plane.
+    pub fn v_plane(&self) -> &[u8] {
+        &self.v
+    }
+}
+
+/// Get the chroma plane size for a frame dimension.
+fn chroma_size(len: u32) -> usize {
+    len.div_ceil(2) as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::*;
+
+    #[test]
+    fn strided_planes() {
+        let y = [
+            1, 2, 3, 0, //
+            4, 5, 6, 0, //
+            7, 8, 9,
+        ];
+        let u = [10, 11, 0, 12, 13];
+        let v = [20, 21, 0, 0, 22, 23];
+        let f = Yuv420Frame::with_planes(3, 3, (&y, 4), (&u, 3), (&v, 4));
+        assert_eq!(f.chroma_width(), 2);
+        assert_eq!(f.chroma_height(), 2);
+        assert_eq!(f.y_plane(), &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(f.u_plane(), &[10, 11, 12, 13]);
+        assert_eq!(f.v_plane(), &[20, 21, 22, 23]);
+        let uv = [10, 20, 11, 21, 0, 12, 22, 13, 23];
+        let nv12 = Yuv420Frame::with_nv12(3, 3, (&y, 4), (&uv, 5));
+        assert_eq!(f, nv12);
+    }
+
+    #[test]
+    fn short_plane() {
+        let y = [0; 15];
+        let c = [0; 4];
+        assert_eq!(
+            Yuv420Frame::try_with_planes(4, 4, (&y, 4), (&c, 2), (&c, 2)),
+            Err(RasterError::LengthMismatch {
+                expected: 16,
+                found: 15
+            })
+        );
+    }
+
+    #[test]
+    fn bt601_matches_ycbcr() {
+        let y = [0x20, 0x60, 0xA0, 0xE0];
+        let u = [0x50];
+        let v = [0xB0];
+        let f = Yuv420Frame::with_planes(2, 2, (&y, 2), (&u, 1), (&v, 1));
+        let r = f.to_raster::<SRgb8>(YuvMatrix::Bt601);
+        for (i, p) in r.as_slice().iter().enumerate() {
+            let c: SRgb8 = YCbCr8::new(y[i], 0x50, 0xB0).convert();
+            assert_eq!(*p, c);
+        }
+    }
+
+    #[test]
+    fn bt709_primaries() {
+        let r = RasterBuilder::<SRgb8>::new()
+            .with_color(2, 2, SRgb8::new(0xFF, 0, 0));
+        let f = Yuv420Frame::from_raster(&r, YuvMatrix::Bt709);
+        assert_eq!(f.y_plane(), &[54; 4]);
+        assert_eq!(f.u_plane(), &[99]);
+        assert_eq!(f.v_plane(), &[255]);
+        let r601 = Yuv420Frame::from_raster(&r, YuvMatrix::Bt601);
+        assert_eq!(r601.y_plane(), &[76; 4]);
+    }
+
+    #[test]
+    fn odd_size() {
+        let mut r = RasterBuilder::<SRgb8>::new().with_clear(3, 3);
+        r.set_region((0, 0, 2, 2), SRgb8::new(0x40, 0x80, 0xC0));
+        r.set_region((2, 0, 1, 3), SRgb8::new(0xFF, 0, 0));
+        r.set_region((0, 2, 2, 1), SRgb8::new(0, 0xFF, 0));
+        let f = Yuv420Frame::from_raster(&r, YuvMatrix::Bt709);
+        assert_eq!(f.u_plane().len(), 4);
+        let back = f.to_raster::<SRgb8>(YuvMatrix::Bt709);
+        // Each block is a single color, so only rounding error remains
+        for (a, b) in r.as_slice().iter().zip(back.as_slice()) {
+            let d = a.difference(*b);
+            assert!(u8::from(d.red()) <= 2, "{:?} {:?}", a, b);
+            assert!(u8::from(d.green()) <= 2, "{:?} {:?}", a, b);
+            assert!(u8::from(d.blue()) <= 2, "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn chroma_average() {
+        let mut r = RasterBuilder::<SGray8>::new().with_clear(2, 2);
+        r.set_pixel(0, 0, SGray8::new(0xFF));
+        let f = Yuv420Frame::from_raster(&r, YuvMatrix::Bt601);
+        assert_eq!(f.y_plane(), &[0xFF, 0, 0, 0]);
+        assert_eq!(f.u_plane(), &[0x80]);
+        assert_eq!(f.v_plane(), &[0x80]);
+    }
+}